@@ -29,7 +29,7 @@ path = "src/main_console.rs"
 
 [dependencies]
 # Core dependencies (platform-agnostic)
-nix = { version = "0.27", features = ["signal", "process"] }
+nix = { version = "0.27", features = ["signal", "process", "fs"] }
 crossbeam-channel = "0.5"
 tokio = { version = "1.0", features = ["full"] }
 serde = { version = "1.0", features = ["derive"] }
@@ -1,10 +1,18 @@
 use crate::{
-    process_monitor::ProcessMonitor,
+    port_scanner::{LsofScanner, PortScanner},
+    command_runner::LocalRunner,
+    error::PortKillError,
+    history::{KillHistory, KillRecord},
+    hooks::Hooks,
+    process_monitor::{check_kill_limit, get_processes_with_scanner, is_within_max_age, jitter_sample, jittered_interval, kill_batch, kill_processes, newest_process, process_started_at, retain_not_ignored, snapshot_kill_targets, IdleBackoff, KillLimitExceeded, KillPolicy, KillSignal, ProcessMonitor, KILL_BATCH_CONCURRENCY, KILL_BATCH_TIMEOUT},
     tray_menu::TrayMenu,
-    types::{ProcessUpdate, StatusBarInfo},
+    types::{docker_process_count, menu_label, menu_overflow_label, menu_ports, process_count, process_names, ProcessDiff, ProcessInfo, ProcessUpdate, spare_marker},
+    webhook::{KillOutcome, Webhook},
     cli::Args,
 };
 use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use crate::process_monitor::is_process_excluded;
 use anyhow::Result;
 use crossbeam_channel::{bounded, Receiver};
 use log::{error, info, warn};
@@ -20,10 +28,25 @@ use tray_icon::{
 #[cfg(target_os = "macos")]
 use winit::event_loop::EventLoop;
 
+/// How often the tray rescans for its menu and icon, before jitter and idle backoff
+#[cfg(target_os = "macos")]
+const TRAY_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The session's kills for the "Recently Killed" menu, newest first
+fn recent_kills(history: &StdMutex<KillHistory>) -> Vec<KillRecord> {
+    history.lock().map(|history| history.recent().cloned().collect()).unwrap_or_default()
+}
+
 #[derive(Debug, Clone)]
 enum MenuAction {
     KillAll,
     KillProcess(u16), // port number
+    SignalProcess(u16, KillSignal), // port number, signal to send instead of the kill escalation
+    ToggleSpare(u16), // port number
+    KillAllExcept,
+    KillSnapshot, // the processes listed by the last scan, without rescanning
+    KillNewest, // the most recently started process of the last scan (--compact-menu)
+    TogglePause, // stop or restart scanning and kills for this session
     Quit,
     Unknown,
 }
@@ -37,25 +60,33 @@ pub struct PortKillApp {
     update_receiver: Receiver<ProcessUpdate>,
     tray_menu: TrayMenu,
     args: Args,
-    current_processes: Arc<StdMutex<HashMap<u16, crate::types::ProcessInfo>>>,
+    current_processes: Arc<StdMutex<HashMap<u16, Vec<ProcessInfo>>>>,
     // Add state tracking for better stability
     last_menu_update: Arc<StdMutex<std::time::Instant>>,
     is_killing_processes: Arc<AtomicBool>,
     menu_update_cooldown: std::time::Duration,
+    config: crate::config::Config,
 }
 
 #[cfg(target_os = "macos")]
 impl PortKillApp {
-    pub fn new(args: Args, _config: crate::config::Config) -> Result<Self> {
+    pub fn new(args: Args, config: crate::config::Config) -> Result<Self> {
         // Create channels for communication
         let (update_sender, update_receiver) = bounded(100);
         let (menu_sender, menu_event_receiver) = bounded(100);
 
         // Create process monitor with configurable ports
-        let process_monitor = Arc::new(Mutex::new(ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), args.docker, args.discover_all)?));
+        let mut monitor = ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), args.docker, args.discover_all, args.command_runner())?;
+        monitor.set_kill_policy(config.app.kill_policy().with_dump(args.dump_wait()));
+        monitor.set_watch_processes(args.watch_process.clone());
+        monitor.set_lsof_timeout(args.lsof_timeout);
+        monitor.set_poll_jitter_percent(config.app.poll_jitter_percent);
+        monitor.set_docker_retries(config.app.docker_retries);
+        monitor.set_idle_backoff(args.interval_backoff_when_idle.then(|| config.app.idle_backoff_max()));
+        let process_monitor = Arc::new(Mutex::new(monitor));
 
         // Create tray menu
-        let tray_menu = TrayMenu::new(menu_sender)?;
+        let tray_menu = TrayMenu::new(menu_sender, config.labels.clone(), config.app.max_processes_in_menu, config.app.use_emoji)?;
 
         Ok(Self {
             tray_icon: Arc::new(StdMutex::new(None)),
@@ -68,6 +99,7 @@ impl PortKillApp {
             last_menu_update: Arc::new(StdMutex::new(std::time::Instant::now())),
             is_killing_processes: Arc::new(AtomicBool::new(false)),
             menu_update_cooldown: std::time::Duration::from_secs(3), // Reduced to 3s since we're more selective
+            config,
         })
     }
 
@@ -79,7 +111,17 @@ impl PortKillApp {
 
         // Now create the tray icon after the event loop is created
         info!("Creating tray icon...");
-        let initial_menu = Self::create_static_config_menu(&HashMap::new())?;
+        let labels = self.config.labels.clone();
+        let use_emoji = self.config.app.use_emoji;
+        // Kills of this session, shared with the console through the history file
+        let history = Arc::new(StdMutex::new(KillHistory::session(self.config.app.history_size)));
+        // --compact-menu: a fixed three-item menu that is never rebuilt
+        let compact_menu = self.args.compact_menu;
+        let initial_menu = if compact_menu {
+            Self::create_compact_menu(use_emoji)?
+        } else {
+            Self::create_static_config_menu(&HashMap::new(), &std::collections::BTreeSet::new(), &labels, &recent_kills(&history), use_emoji)?
+        };
         let tray_icon = TrayIconBuilder::new()
             .with_tooltip("Port Kill - Static Config Menu (Crash-Safe)")
             .with_menu(Box::new(initial_menu))
@@ -96,6 +138,12 @@ impl PortKillApp {
         // For now, let's manually check for processes every 5 seconds in the event loop
         let tray_icon = self.tray_icon.clone();
         let mut last_check = std::time::Instant::now();
+        // The menu's own scan is spread out by poll_jitter_percent like the monitor's
+        let poll_jitter_percent = self.config.app.poll_jitter_percent;
+        let check_interval = move |base| jittered_interval(base, poll_jitter_percent, jitter_sample());
+        // --interval-backoff-when-idle stretches it too while the menu's processes don't change
+        let mut idle_backoff = self.args.interval_backoff_when_idle.then(|| IdleBackoff::new(self.config.app.idle_backoff_max()));
+        let mut next_check = check_interval(TRAY_CHECK_INTERVAL);
         let mut last_process_count = 0;
         let mut last_ports: std::collections::BTreeSet<u16> = std::collections::BTreeSet::new();
         let is_killing_processes = self.is_killing_processes.clone();
@@ -112,9 +160,110 @@ impl PortKillApp {
         let menu_event_receiver = self.menu_event_receiver.clone();
         let current_processes = self.current_processes.clone();
         let args = self.args.clone();
+        let status_icon = self.config.app.status_icon.clone();
+        let tooltip_template = self.config.app.tooltip_template();
+        let kill_policy = self.config.app.kill_policy().with_dump(self.args.dump_wait());
+        let max_kill = self.config.app.max_kill_without_confirm;
+        let kill_cooldown = Arc::new(self.config.app.kill_cooldown());
+        let webhook = Webhook::from_config(&self.config.app);
+        let hooks = Hooks::from_config(&self.config.app);
+
+        // Ports spared by "Kill All Except Selected" (session-only, never written to the config)
+        let spared_ports: Arc<StdMutex<std::collections::BTreeSet<u16>>> = Arc::new(StdMutex::new(std::collections::BTreeSet::new()));
+        let spared_changed = Arc::new(AtomicBool::new(false));
+
+        // "Pause Monitoring": no scans and no kills until resumed (session-only, like spared ports)
+        let paused = Arc::new(AtomicBool::new(false));
+        let pause_changed = Arc::new(AtomicBool::new(false));
+
+        // Quit, Ctrl+C and SIGTERM all end the event loop the same way so main() can clean up
+        let quit_requested = Arc::new(AtomicBool::new(false));
+        if let Err(e) = crate::shutdown::request_on_signal(quit_requested.clone()) {
+            warn!("Failed to install shutdown signal handler: {}", e);
+        }
+
+        // `port-kill --refresh` (SIGUSR1) rescans right away instead of waiting for the next check
+        let refresh_requested = Arc::new(AtomicBool::new(false));
+        let refresh_flag = refresh_requested.clone();
+        let event_loop_proxy = event_loop.create_proxy();
+        if let Err(e) = crate::refresh::on_signal(move || {
+            refresh_flag.store(true, Ordering::Relaxed);
+            // Wake the event loop, it only runs when an event arrives
+            let _ = event_loop_proxy.send_event(());
+        }) {
+            warn!("Failed to install refresh signal handler: {}", e);
+        }
 
         // Run the event loop
-        event_loop.run(move |_event, _elwt| {
+        event_loop.run(move |_event, elwt| {
+            if quit_requested.load(Ordering::Relaxed) {
+                info!("Shutting down Port Kill...");
+                elwt.exit();
+                return;
+            }
+
+            // Swap the icon and menu as soon as monitoring is paused or resumed
+            if pause_changed.swap(false, Ordering::Relaxed) {
+                let is_paused = paused.load(Ordering::Relaxed);
+                let processes = current_processes.lock().map(|processes| processes.clone()).unwrap_or_default();
+                if let Ok(tray_icon_guard) = tray_icon.lock() {
+                    if let Some(ref icon) = *tray_icon_guard {
+                        let new_menu = if is_paused {
+                            Self::create_paused_menu(use_emoji)
+                        } else if compact_menu {
+                            Self::create_compact_menu(use_emoji)
+                        } else {
+                            let spared = spared_ports.lock().map(|spared| spared.clone()).unwrap_or_default();
+                            Self::create_static_config_menu(&processes, &spared, &labels, &recent_kills(&history), use_emoji)
+                        };
+                        match new_menu {
+                            Ok(new_menu) => icon.set_menu(Some(Box::new(new_menu))),
+                            Err(e) => error!("Failed to rebuild menu: {}", e),
+                        }
+
+                        let new_icon = if is_paused {
+                            TrayMenu::create_paused_icon()
+                        } else {
+                            TrayMenu::create_status_icon(&status_icon.status_for(process_count(&processes)), &status_icon)
+                        };
+                        match new_icon {
+                            Ok(new_icon) => {
+                                if let Err(e) = icon.set_icon(Some(new_icon)) {
+                                    error!("Failed to update icon: {}", e);
+                                }
+                            }
+                            Err(e) => error!("Failed to create icon: {}", e),
+                        }
+                        let tooltip = if is_paused { "Port Kill - Monitoring paused" } else { "Port Kill - Click for actions" };
+                        if let Err(e) = icon.set_tooltip(Some(tooltip)) {
+                            error!("Failed to update tooltip: {}", e);
+                        }
+                    }
+                }
+
+                if is_paused {
+                    println!("⏸️  Monitoring paused, no scans or kills until resumed");
+                } else {
+                    println!("▶️  Monitoring resumed");
+                    // Rescan right away instead of showing the counts from before the pause
+                    refresh_requested.store(true, Ordering::Relaxed);
+                }
+            }
+
+            // Reflect a spared-port toggle right away instead of waiting for the next scan (the paused menu has no ports)
+            if spared_changed.swap(false, Ordering::Relaxed) && !paused.load(Ordering::Relaxed) {
+                let processes = current_processes.lock().map(|processes| processes.clone()).unwrap_or_default();
+                let spared = spared_ports.lock().map(|spared| spared.clone()).unwrap_or_default();
+                if let Ok(tray_icon_guard) = tray_icon.lock() {
+                    if let Some(ref icon) = *tray_icon_guard {
+                        match Self::create_static_config_menu(&processes, &spared, &labels, &recent_kills(&history), use_emoji) {
+                            Ok(new_menu) => icon.set_menu(Some(Box::new(new_menu))),
+                            Err(e) => error!("Failed to rebuild menu: {}", e),
+                        }
+                    }
+                }
+            }
+
             // Handle menu events with improved crash-safe approach
             if let Ok(event) = menu_event_receiver.try_recv() {
                 info!("Menu event received: {:?}", event);
@@ -127,13 +276,24 @@ impl PortKillApp {
                     // Get current processes for menu handling
                     let current_processes_clone = current_processes.clone();
                     let is_killing_clone = is_killing_processes.clone();
+                    let quit_requested_clone = quit_requested.clone();
+                    let kill_cooldown_clone = kill_cooldown.clone();
+                    let webhook_clone = webhook.clone();
+                    let hooks_clone = hooks.clone();
+                    let history_clone = history.clone();
+                    let spared_ports_clone = spared_ports.clone();
+                    let spared_changed_clone = spared_changed.clone();
+                    let paused_clone = paused.clone();
+                    let pause_changed_clone = pause_changed.clone();
                     let args_clone = args.clone();
+                    let kill_policy = kill_policy.clone();
 
                     std::thread::spawn(move || {
                         // Add a delay to ensure the menu system is stable
                         std::thread::sleep(std::time::Duration::from_millis(200)); // Increased delay
 
                         // Handle different menu actions based on event
+                        let mut ran_kill = false;
                         let result = if let Ok(current_processes_guard) = current_processes_clone.lock() {
                             let processes = &*current_processes_guard;
 
@@ -150,45 +310,118 @@ impl PortKillApp {
                             info!("========================");
 
                             let menu_action = Self::map_menu_id_to_action(&menu_id_str, processes);
+                            let cooldown_remaining = kill_cooldown_clone.remaining(std::time::Instant::now());
+                            let is_paused = paused_clone.load(Ordering::Relaxed);
+                            ran_kill = !matches!(menu_action, MenuAction::Quit | MenuAction::ToggleSpare(_) | MenuAction::TogglePause)
+                                && cooldown_remaining.is_none()
+                                && !is_paused;
 
                             match menu_action {
+                                MenuAction::Quit => {
+                                    info!("Quit clicked (ID: {})", menu_id_str);
+                                    quit_requested_clone.store(true, Ordering::Relaxed);
+                                    Ok(Vec::new())
+                                }
+                                MenuAction::ToggleSpare(port) => {
+                                    if let Ok(mut spared) = spared_ports_clone.lock() {
+                                        if spared.remove(&port) {
+                                            info!("Port {} no longer spared from Kill All Except (ID: {})", port, menu_id_str);
+                                        } else {
+                                            spared.insert(port);
+                                            info!("Port {} spared from Kill All Except (ID: {})", port, menu_id_str);
+                                        }
+                                    }
+                                    spared_changed_clone.store(true, Ordering::Relaxed);
+                                    Ok(Vec::new())
+                                }
+                                MenuAction::TogglePause => {
+                                    let now_paused = !paused_clone.fetch_xor(true, Ordering::Relaxed);
+                                    info!("Monitoring {} (ID: {})", if now_paused { "paused" } else { "resumed" }, menu_id_str);
+                                    pause_changed_clone.store(true, Ordering::Relaxed);
+                                    Ok(Vec::new())
+                                }
+                                _ if is_paused => {
+                                    // The paused menu has no kill items, but a click may have raced the menu swap
+                                    info!("Ignoring menu click (ID: {}): monitoring is paused", menu_id_str);
+                                    Ok(Vec::new())
+                                }
+                                _ if cooldown_remaining.is_some() => {
+                                    // Rapid repeat clicks would otherwise kill processes that just restarted
+                                    info!("Ignoring menu click (ID: {}): kill cooldown active for another {:?}",
+                                          menu_id_str, cooldown_remaining.unwrap_or_default());
+                                    Ok(Vec::new())
+                                }
                                 MenuAction::KillAll => {
                                     info!("Kill All Processes clicked (ID: {})", menu_id_str);
                                     // Always use auto-discovery - kill ALL discovered processes!
-                                    Self::kill_all_discovered_processes(&args_clone)
-                                }
-                                MenuAction::Quit => {
-                                    info!("Quit clicked (ID: {})", menu_id_str);
-                                    std::process::exit(0);
+                                    Self::kill_all_discovered_processes(&args_clone, &kill_policy, max_kill)
                                 }
                                 MenuAction::KillProcess(port) => {
                                     info!("Kill process on port {} clicked (ID: {})", port, menu_id_str);
-                                    Self::kill_processes_on_port(port, &args_clone)
+                                    Self::kill_processes_on_port(port, &args_clone, &kill_policy, None)
+                                }
+                                MenuAction::SignalProcess(port, signal) => {
+                                    info!("{} on port {} clicked (ID: {})", signal.label(), port, menu_id_str);
+                                    Self::kill_processes_on_port(port, &args_clone, &kill_policy, Some(signal))
+                                }
+                                MenuAction::KillAllExcept => {
+                                    let spared = spared_ports_clone.lock().map(|spared| spared.clone()).unwrap_or_default();
+                                    info!("Kill All Except {:?} clicked (ID: {})", spared, menu_id_str);
+                                    // Treat spared ports like --ignore-ports for this one kill
+                                    let mut except_args = args_clone.clone();
+                                    except_args.ignore_ports.get_or_insert_with(Vec::new).extend(spared);
+                                    Self::kill_all_discovered_processes(&except_args, &kill_policy, max_kill)
+                                }
+                                MenuAction::KillSnapshot => {
+                                    info!("Kill Monitored clicked (ID: {}), killing the last snapshot", menu_id_str);
+                                    Self::kill_snapshot(processes, &kill_policy, max_kill)
+                                }
+                                MenuAction::KillNewest => {
+                                    info!("Kill Newest Process clicked (ID: {})", menu_id_str);
+                                    Self::kill_newest(processes, &kill_policy)
                                 }
                                 MenuAction::Unknown => {
                                     info!("Unknown menu item clicked: {}, defaulting to kill all", menu_id_str);
-                                    if args_clone.discover_all {
-                                        Self::kill_all_discovered_processes(&args_clone)
+                                    if args_clone.discover_all || !args_clone.watch_process.is_empty() {
+                                        Self::kill_all_discovered_processes(&args_clone, &kill_policy, max_kill)
                                     } else {
                                         let ports_to_kill = args_clone.get_ports_to_monitor();
-                                        Self::kill_all_processes(&ports_to_kill, &args_clone)
+                                        Self::kill_all_processes(&ports_to_kill, &args_clone, &kill_policy, max_kill)
                                     }
                                 }
                             }
                         } else {
                             error!("Failed to access current processes");
-                            Ok(())
+                            Ok(Vec::new())
                         };
 
+                        // Start the cooldown only once a kill actually ran
+                        if ran_kill {
+                            kill_cooldown_clone.finished(std::time::Instant::now());
+                        }
+
+                        if let (Ok(mut history), Ok(outcomes)) = (history_clone.lock(), &result) {
+                            history.record(outcomes);
+                        }
+                        if let (Some(hooks), Ok(outcomes)) = (&hooks_clone, &result) {
+                            hooks.on_kill(outcomes);
+                        }
+                        if let (Some(webhook), Ok(outcomes)) = (&webhook_clone, &result) {
+                            webhook.notify(outcomes.clone());
+                        }
+
                         match result {
                             Ok(_) => {
                                 info!("Process killing completed successfully");
-                                // Reset the flag after a longer delay to allow menu updates again
-                                std::thread::sleep(std::time::Duration::from_secs(2)); // Increased delay
                                 is_killing_clone.store(false, Ordering::Relaxed);
                             }
                             Err(e) => {
                                 error!("Failed to kill processes: {}", e);
+                                if let Some(exceeded) = e.downcast_ref::<KillLimitExceeded>() {
+                                    Self::show_warning_notification(&format!(
+                                        "{}. Kill ports one by one, or raise max_kill_without_confirm in the config.", exceeded
+                                    ));
+                                }
                                 is_killing_clone.store(false, Ordering::Relaxed);
                             }
                         }
@@ -198,32 +431,56 @@ impl PortKillApp {
                 }
             }
 
-            // Check for processes every 10 seconds (like other successful tray apps)
-            if last_check.elapsed() >= std::time::Duration::from_secs(10) {
+            // Check for processes every 10 seconds (like other successful tray apps, ±poll_jitter_percent), or when asked to refresh
+            if paused.load(Ordering::Relaxed) {
+                return;
+            }
+            let refresh = refresh_requested.swap(false, Ordering::Relaxed);
+            if refresh || last_check.elapsed() >= next_check {
                 last_check = std::time::Instant::now();
+                if refresh {
+                    if let Some(backoff) = idle_backoff.as_mut() {
+                        backoff.reset();
+                    }
+                }
 
                 // Get detailed process information for CONFIGURED PORTS ONLY (static approach)
                 let configured_ports = vec![3000, 3001, 3002, 3003, 5173, 8080, 8081, 8082, 5137, 5138];
-                let (process_count, processes) = Self::get_processes_on_configured_ports(&configured_ports, &args);
-
-                let status_info = StatusBarInfo::from_process_count(process_count);
+                let (_, mut processes) = Self::get_processes_on_configured_ports(&configured_ports, &args);
+                // --show-range narrows the menu and icon; "Kill All" still covers every port
+                processes.retain(|port, _| args.is_port_shown(*port));
+                let process_count = process_count(&processes);
+
+                let mut status_info = status_icon.status_for(process_count).with_docker_count(docker_process_count(&processes));
+                if let Some(template) = &tooltip_template {
+                    status_info.tooltip = template.render(&processes);
+                }
                 println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
 
-                // Update current processes
+                // Update current processes, running on_detect_command for the ones that just appeared
                 if let Ok(mut current_processes_guard) = current_processes.lock() {
+                    let diff = ProcessDiff::between(&current_processes_guard, &processes);
+                    if let Some(ref hooks) = hooks {
+                        hooks.on_detect(&diff.added);
+                    }
+                    if let Some(backoff) = idle_backoff.as_mut() {
+                        backoff.record(!diff.is_empty());
+                    }
                     *current_processes_guard = processes.clone();
                 }
+                next_check = check_interval(idle_backoff.map_or(TRAY_CHECK_INTERVAL, |backoff| backoff.interval(TRAY_CHECK_INTERVAL)));
 
                 // Print detected processes
                 if process_count > 0 {
                     println!("📋 Detected Processes:");
-                    for (port, process_info) in &processes {
+                    for process_info in processes.values().flatten() {
+                        let port = &process_info.port;
                         if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
-                            println!("   • Port {}: {} [Docker: {}]", port, process_info.name, container_name);
+                            println!("   • {}: {} [Docker: {}]", labels.display(*port), process_info.name, container_name);
                         } else if args.show_pid {
-                            println!("   • Port {}: {} (PID {})", port, process_info.name, process_info.pid);
+                            println!("   • {}: {} (PID {})", labels.display(*port), process_info.name, process_info.pid);
                         } else {
-                            println!("   • Port {}: {}", port, process_info.name);
+                            println!("   • {}: {}", labels.display(*port), process_info.name);
                         }
                     }
                 } else {
@@ -239,7 +496,7 @@ impl PortKillApp {
                         }
 
                         // Update icon with new status (force update every time to fix hover-only issue)
-                        if let Ok(new_icon) = TrayMenu::create_icon(&status_info.text) {
+                        if let Ok(new_icon) = TrayMenu::create_status_icon(&status_info, &status_icon) {
                             // Try setting icon to None first, then to the new icon to force refresh
                             let _ = icon.set_icon(None);
                             std::thread::sleep(std::time::Duration::from_millis(50));
@@ -247,14 +504,14 @@ impl PortKillApp {
                             if let Err(e) = icon.set_icon(Some(new_icon)) {
                                 error!("Failed to update icon: {}", e);
                             } else {
-                                info!("Icon forcefully updated to show {} processes (color: {})",
+                                info!("Icon forcefully updated to show {} processes (tier: {})",
                                       process_count,
-                                      if process_count == 0 { "green" } else { "orange" });
+                                      status_info.tier.as_str());
                             }
                         }
 
                         // SMART MENU REBUILD: Only rebuild when process count changes (much safer than constant rebuilding)
-                        if process_count != last_process_count {
+                        if process_count != last_process_count && !compact_menu {
                             info!("Process count changed from {} to {} - rebuilding menu once",
                                   last_process_count, process_count);
                             
@@ -266,7 +523,8 @@ impl PortKillApp {
                             }
                             
                             // Rebuild menu with current state (only when count changes!)
-                            match Self::create_static_config_menu(&processes) {
+                            let spared = spared_ports.lock().map(|spared| spared.clone()).unwrap_or_default();
+                            match Self::create_static_config_menu(&processes, &spared, &labels, &recent_kills(&history), use_emoji) {
                                 Ok(new_menu) => {
                                     icon.set_menu(Some(Box::new(new_menu)));
                                     info!("✅ Menu rebuilt successfully with {} processes (count changed)", process_count);
@@ -280,7 +538,6 @@ impl PortKillApp {
                         }
 
                         // Update tooltip as well (for both count and port changes)
-                        let status_info = StatusBarInfo::from_process_count(process_count);
                         if let Err(e) = icon.set_tooltip(Some(&format!("{} - Click for actions", status_info.tooltip))) {
                             error!("Failed to update tooltip: {}", e);
                         }
@@ -293,113 +550,17 @@ impl PortKillApp {
     }
 
     /// Scan only configured ports - much faster and more stable than auto-discovery
-    pub fn get_processes_on_configured_ports(configured_ports: &[u16], args: &Args) -> (usize, HashMap<u16, crate::types::ProcessInfo>) {
-        let mut processes = HashMap::new();
-
-        // Scan each configured port individually
-        for &port in configured_ports {
-            if let Ok(process_info) = Self::get_single_port_process(port) {
-                // Check if this process should be ignored
-                let ignore_ports = args.get_ignore_ports_set();
-                let ignore_processes = args.get_ignore_processes_set();
-                
-                let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&process_info.name);
-                
-                if !should_ignore {
-                    processes.insert(port, process_info);
-                }
-            }
-        }
-
-        (processes.len(), processes)
+    pub fn get_processes_on_configured_ports(configured_ports: &[u16], args: &Args) -> (usize, HashMap<u16, Vec<ProcessInfo>>) {
+        // One lsof run covers every configured port; protected and ignored processes are skipped (same filter as the console)
+        get_processes_with_scanner(&LsofScanner::from_args(args), configured_ports, args)
     }
 
-    /// Get process info for a single port
-    fn get_single_port_process(port: u16) -> Result<crate::types::ProcessInfo> {
-        let output = std::process::Command::new("lsof")
-            .args(&["-ti", &format!(":{}", port), "-sTCP:LISTEN"])
-            .output()?;
-
-        if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                let pid_str = line.trim();
-                if !pid_str.is_empty() {
-                    if let Ok(pid) = pid_str.parse::<i32>() {
-                        // Get process name
-                        let name_output = std::process::Command::new("ps")
-                            .args(&["-p", &pid.to_string(), "-o", "comm="])
-                            .output()?;
-                            
-                        let name = String::from_utf8_lossy(&name_output.stdout)
-                            .trim()
-                            .to_string();
-                            
-                        return Ok(crate::types::ProcessInfo {
-                            pid,
-                            port,
-                            command: name.clone(),
-                            name,
-                            container_id: None,
-                            container_name: None,
-                        });
-                    }
-                }
-            }
-        }
-
-        Err(anyhow::anyhow!("No process found on port {}", port))
-    }
-
-    pub fn discover_all_listening_processes(args: &Args) -> (usize, HashMap<u16, crate::types::ProcessInfo>) {
+    pub fn discover_all_listening_processes(args: &Args) -> (usize, HashMap<u16, Vec<ProcessInfo>>) {
         #[cfg(not(target_os = "windows"))]
         {
-            // Use lsof to find ALL listening processes on ALL ports
-            let output = std::process::Command::new("lsof")
-                .args(&["-i", "-P", "-n", "-sTCP:LISTEN"])
-                .output();
-
-            match output {
-                Ok(output) => {
-                    let stdout = String::from_utf8_lossy(&output.stdout);
-                    let mut processes = HashMap::new();
-
-                    // Get ignore sets for efficient lookup
-                    let ignore_ports = args.get_ignore_ports_set();
-                    let ignore_processes = args.get_ignore_processes_set();
-
-                    for line in stdout.lines().skip(1) { // Skip header
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        if parts.len() >= 9 {
-                            if let (Ok(pid), Some(port_str)) = (parts[1].parse::<i32>(), parts[8].split(':').last()) {
-                                if let Ok(port) = port_str.parse::<u16>() {
-                                    let command = parts[0].to_string();
-                                    let name = parts[0].to_string();
-
-                                    // Check if this process should be ignored
-                                    let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
-
-                                    if !should_ignore {
-                                        processes.insert(port, crate::types::ProcessInfo {
-                                            pid,
-                                            port,
-                                            command,
-                                            name,
-                                            container_id: None,
-                                            container_name: None,
-                                        });
-                                    } else {
-                                        info!("Ignoring process {} (PID {}) on port {} (ignored by user configuration)", name, pid, port);
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    (processes.len(), processes)
-                }
-                Err(_) => (0, HashMap::new())
-            }
+            // Scanning with no port filter lists ALL listening processes on ALL ports, minus protected
+            // and ignored ones (same filter as the console)
+            get_processes_with_scanner(&LsofScanner::from_args(args), &[], args)
         }
 
         #[cfg(target_os = "windows")]
@@ -412,11 +573,7 @@ impl PortKillApp {
             match output {
                 Ok(output) => {
                     let stdout = String::from_utf8_lossy(&output.stdout);
-                    let mut processes = HashMap::new();
-
-                    // Get ignore sets for efficient lookup
-                    let ignore_ports = args.get_ignore_ports_set();
-                    let ignore_processes = args.get_ignore_processes_set();
+                    let mut processes: HashMap<u16, Vec<ProcessInfo>> = HashMap::new();
 
                     for line in stdout.lines() {
                         if line.contains("LISTENING") {
@@ -426,6 +583,11 @@ impl PortKillApp {
                                 if let Some(port_str) = parts[1].split(':').last() {
                                     if let Ok(port) = port_str.parse::<u16>() {
                                         if let Ok(pid) = parts[4].parse::<i32>() {
+                                            // netstat lists a process once per address family
+                                            if processes.get(&port).is_some_and(|on_port| on_port.iter().any(|process_info| process_info.pid == pid)) {
+                                                continue;
+                                            }
+
                                             // Get process name from tasklist
                                             let name_output = std::process::Command::new("tasklist")
                                                 .args(&["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
@@ -444,20 +606,17 @@ impl PortKillApp {
 
                                             let name = command.strip_suffix(".exe").unwrap_or(&command).to_string();
 
-                                            // Check if this process should be ignored
-                                            let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
-
-                                            if !should_ignore {
-                                                processes.insert(port, crate::types::ProcessInfo {
+                                            // Skip protected and ignored processes (same filter as the console)
+                                            let bind_address = parts[1].rsplit_once(':').map(|(address, _)| address.to_string());
+                                            if !is_process_excluded(args, pid, port, &name, bind_address.as_deref(), None) {
+                                                processes.entry(port).or_default().push(ProcessInfo {
                                                     pid,
                                                     port,
                                                     command: command.clone(),
                                                     name,
-                                                    container_id: None,
-                                                    container_name: None,
+                                                    bind_address,
+                                                    ..Default::default()
                                                 });
-                                            } else {
-                                                info!("Ignoring process {} (PID {}) on port {} (ignored by user configuration)", name, pid, port);
                                             }
                                         }
                                     }
@@ -466,7 +625,7 @@ impl PortKillApp {
                         }
                     }
 
-                    (processes.len(), processes)
+                    (process_count(&processes), processes)
                 }
                 Err(_) => (0, HashMap::new())
             }
@@ -474,64 +633,49 @@ impl PortKillApp {
     }
 
 
-    pub fn kill_all_discovered_processes(args: &Args) -> Result<()> {
+    /// Kill every discovered listener; refuses with [`KillLimitExceeded`] above `max_kill` processes
+    pub fn kill_all_discovered_processes(args: &Args, kill_policy: &KillPolicy, max_kill: usize) -> Result<Vec<KillOutcome>> {
         info!("Killing ALL discovered listening processes...");
+        let mut outcomes = Vec::new();
 
         #[cfg(not(target_os = "windows"))]
         {
-            // Get all listening processes using lsof
-            let output = match std::process::Command::new("lsof")
-                .args(&["-i", "-P", "-n", "-sTCP:LISTEN"])
-                .output() {
-                Ok(output) => output,
-                Err(e) => {
-                    error!("Failed to run lsof command: {}", e);
-                    return Err(anyhow::anyhow!("Failed to run lsof: {}", e));
-                }
-            };
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            // Get ignore sets for efficient lookup
-            let ignore_ports = args.get_ignore_ports_set();
-            let ignore_processes = args.get_ignore_processes_set();
-
-            // Use HashSet to automatically deduplicate PIDs
-            let mut pids_to_kill = std::collections::HashSet::new();
-
-            for line in stdout.lines().skip(1) { // Skip header
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 9 {
-                    if let (Ok(pid), Some(port_str)) = (parts[1].parse::<i32>(), parts[8].split(':').last()) {
-                        if let Ok(port) = port_str.parse::<u16>() {
-                            let name = parts[0].to_string();
-
-                            // Check if this process should be ignored
-                            let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
-
-                            if !should_ignore {
-                                pids_to_kill.insert(pid); // insert() instead of push() - automatically deduplicates
-                            } else {
-                                info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
-                            }
-                        }
-                    }
+            // Get all listening processes (only the watched ones with --watch-process)
+            let found = LsofScanner::from_args(args).scan(&[]).map_err(|e| {
+                error!("Failed to scan for listening processes: {}", e);
+                e
+            })?;
+
+            // Keyed by PID to deduplicate processes listening on several sockets
+            let mut pids_to_kill: HashMap<i32, (u16, String)> = HashMap::new();
+
+            // Skip protected, ignored and (with --max-age) long-running processes
+            for process_info in retain_not_ignored(found, args) {
+                if is_within_max_age(args, &LocalRunner, process_info.pid, process_info.port, &process_info.name) {
+                    pids_to_kill.entry(process_info.pid).or_insert((process_info.port, process_info.name));
                 }
             }
 
             if pids_to_kill.is_empty() {
                 info!("No processes found to kill (all were ignored or none found)");
-                return Ok(());
+                return Ok(outcomes);
             }
 
             info!("Found {} processes to kill (after filtering ignored processes)", pids_to_kill.len());
+            check_kill_limit(pids_to_kill.len(), max_kill)?;
 
-            for pid in pids_to_kill {
+            let pids: Vec<i32> = pids_to_kill.keys().copied().collect();
+            let results = kill_batch(&pids, KILL_BATCH_CONCURRENCY, KILL_BATCH_TIMEOUT, &|pid| {
                 info!("Attempting to kill process PID: {}", pid);
-                match Self::kill_process(pid) {
+                Self::kill_process(pid, kill_policy, None)
+            });
+            for (pid, result) in pids.into_iter().zip(results) {
+                match result {
                     Ok(_) => info!("Successfully killed process PID: {}", pid),
-                    Err(e) => error!("Failed to kill process {}: {}", pid, e),
+                    Err(ref e) => error!("Failed to kill process {}: {}", pid, e),
                 }
+                let (port, name) = &pids_to_kill[&pid];
+                outcomes.push(KillOutcome::new(pid, Some(*port), Some(name), &result));
             }
         }
 
@@ -550,10 +694,6 @@ impl PortKillApp {
 
             let stdout = String::from_utf8_lossy(&output.stdout);
 
-            // Get ignore sets for efficient lookup
-            let ignore_ports = args.get_ignore_ports_set();
-            let ignore_processes = args.get_ignore_processes_set();
-
             let mut pids_to_kill = Vec::new();
 
             for line in stdout.lines() {
@@ -580,13 +720,12 @@ impl PortKillApp {
                                         "unknown".to_string()
                                     };
 
-                                    // Check if this process should be ignored
-                                    let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
-
-                                    if !should_ignore {
-                                        pids_to_kill.push(pid);
-                                    } else {
-                                        info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
+                                    // Skip protected, ignored and (with --max-age) long-running processes
+                                    let bind_address = parts[1].rsplit_once(':').map(|(address, _)| address.to_string());
+                                    if !is_process_excluded(args, pid, port, &name, bind_address.as_deref(), None)
+                                        && is_within_max_age(args, &LocalRunner, pid, port, &name)
+                                    {
+                                        pids_to_kill.push((pid, port, name));
                                     }
                                 }
                             }
@@ -597,167 +736,137 @@ impl PortKillApp {
 
             if pids_to_kill.is_empty() {
                 info!("No processes found to kill (all were ignored or none found)");
-                return Ok(());
+                return Ok(outcomes);
             }
 
             info!("Found {} processes to kill (after filtering ignored processes)", pids_to_kill.len());
+            check_kill_limit(pids_to_kill.len(), max_kill)?;
 
-            for pid in pids_to_kill {
+            for (pid, port, name) in pids_to_kill {
                 info!("Attempting to kill process PID: {}", pid);
-                match Self::kill_process(pid) {
+                let result = Self::kill_process(pid, kill_policy, None);
+                match result {
                     Ok(_) => info!("Successfully killed process PID: {}", pid),
-                    Err(e) => error!("Failed to kill process {}: {}", pid, e),
+                    Err(ref e) => error!("Failed to kill process {}: {}", pid, e),
                 }
+                outcomes.push(KillOutcome::new(pid, Some(port), Some(&name), &result));
             }
         }
 
         info!("Finished killing all discovered processes");
-        Ok(())
+        Ok(outcomes)
     }
 
-    pub fn kill_all_processes(ports: &[u16], args: &Args) -> Result<()> {
-        // Build port range string for lsof
-        let port_range = if ports.len() <= 10 {
-            // For small number of ports, list them individually
-            ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
-        } else {
-            // For large ranges, use range format
-            format!("{}-{}", ports.first().unwrap_or(&0), ports.last().unwrap_or(&0))
-        };
+    /// Kill exactly the processes of the last scan (`current_processes`) without rescanning, so the
+    /// result matches what the menu showed; refuses with [`KillLimitExceeded`] above `max_kill` processes
+    pub fn kill_snapshot(processes: &HashMap<u16, Vec<ProcessInfo>>, kill_policy: &KillPolicy, max_kill: usize) -> Result<Vec<KillOutcome>> {
+        let targets = snapshot_kill_targets(processes);
+        if targets.is_empty() {
+            info!("No processes in the last snapshot to kill");
+            return Ok(Vec::new());
+        }
 
-        info!("Killing all processes on ports {}...", port_range);
+        info!("Killing {} process(es) from the last snapshot", targets.len());
+        check_kill_limit(targets.len(), max_kill)?;
+        let outcomes = kill_processes(&targets, &LocalRunner, kill_policy);
+        info!("Finished killing the last snapshot");
+        Ok(outcomes)
+    }
 
-        // Get all PIDs on the monitored ports
-        let output = match std::process::Command::new("lsof")
-            .args(&["-i", &format!(":{}", port_range), "-sTCP:LISTEN", "-P", "-n"])
-            .output() {
-            Ok(output) => output,
-            Err(e) => {
-                error!("Failed to run lsof command: {}", e);
-                return Err(anyhow::anyhow!("Failed to run lsof: {}", e));
-            }
+    /// Kill the most recently started process of the last scan ("Kill Newest Process" in --compact-menu)
+    pub fn kill_newest(processes: &HashMap<u16, Vec<ProcessInfo>>, kill_policy: &KillPolicy) -> Result<Vec<KillOutcome>> {
+        // The tray's scan doesn't look up uptime, so only do it when asked
+        let mut processes = processes.clone();
+        for process_info in processes.values_mut().flatten() {
+            process_info.started_at = process_started_at(&LocalRunner, process_info.pid);
+        }
+        let Some(newest) = newest_process(&processes) else {
+            info!("No process with a known start time in the last scan to kill");
+            return Ok(Vec::new());
         };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let lines: Vec<&str> = stdout.lines().collect();
-
-        // Get ignore sets for efficient lookup
-        let ignore_ports = args.get_ignore_ports_set();
-        let ignore_processes = args.get_ignore_processes_set();
+        info!("Killing the newest process: {} (PID {}) on port {}", newest.name, newest.pid, newest.port);
+        Ok(kill_processes(std::slice::from_ref(newest), &LocalRunner, kill_policy))
+    }
 
-        let mut pids_to_kill = Vec::new();
+    /// Kill every listener on `ports`; refuses with [`KillLimitExceeded`] above `max_kill` processes
+    pub fn kill_all_processes(ports: &[u16], args: &Args, kill_policy: &KillPolicy, max_kill: usize) -> Result<Vec<KillOutcome>> {
+        info!("Killing all processes on {} port(s)...", ports.len());
 
-        for line in lines {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 9 {
-                if let (Ok(pid), Ok(port)) = (parts[1].parse::<i32>(), parts[8].split(':').last().unwrap_or("0").parse::<u16>()) {
-                    let name = parts[0].to_string();
+        // Get all PIDs on the monitored ports
+        let found = LsofScanner::from_args(args).scan(ports).map_err(|e| {
+            error!("Failed to scan ports: {}", e);
+            e
+        })?;
 
-                    // Check if this process should be ignored
-                    let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
+        let mut pids_to_kill = Vec::new();
 
-                    if !should_ignore {
-                        pids_to_kill.push(pid);
-                    } else {
-                        info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
-                    }
-                }
+        // Skip protected, ignored and (with --max-age) long-running processes
+        for process_info in retain_not_ignored(found, args) {
+            if is_within_max_age(args, &LocalRunner, process_info.pid, process_info.port, &process_info.name) {
+                pids_to_kill.push((process_info.pid, process_info.port, process_info.name));
             }
         }
 
         if pids_to_kill.is_empty() {
             info!("No processes found to kill (all were ignored or none found)");
-            return Ok(());
+            return Ok(Vec::new());
         }
 
+        // A process listening on IPv4 and IPv6 shows up twice but is only killed once
+        let mut distinct_pids = std::collections::HashSet::new();
+        pids_to_kill.retain(|(pid, _, _)| distinct_pids.insert(*pid));
         info!("Found {} processes to kill (after filtering ignored processes)", pids_to_kill.len());
+        check_kill_limit(pids_to_kill.len(), max_kill)?;
 
-        for pid in pids_to_kill {
+        let pids: Vec<i32> = pids_to_kill.iter().map(|(pid, _, _)| *pid).collect();
+        let results = kill_batch(&pids, KILL_BATCH_CONCURRENCY, KILL_BATCH_TIMEOUT, &|pid| {
             info!("Attempting to kill process PID: {}", pid);
-            match Self::kill_process(pid) {
+            Self::kill_process(pid, kill_policy, None)
+        });
+        let mut outcomes = Vec::new();
+        for ((pid, port, name), result) in pids_to_kill.into_iter().zip(results) {
+            match result {
                 Ok(_) => info!("Successfully killed process PID: {}", pid),
-                Err(e) => error!("Failed to kill process {}: {}", pid, e),
+                Err(ref e) => error!("Failed to kill process {}: {}", pid, e),
             }
+            outcomes.push(KillOutcome::new(pid, Some(port), Some(&name), &result));
         }
 
         info!("Finished killing all processes");
-        Ok(())
+        Ok(outcomes)
     }
 
 
-    #[cfg(not(target_os = "windows"))]
-    fn kill_process(pid: i32) -> Result<()> {
-        use nix::sys::signal::{kill, Signal};
-        use nix::unistd::Pid;
-
-        info!("Killing process PID: {} with SIGTERM", pid);
-
-        // First try SIGTERM (graceful termination)
-        match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-            Ok(_) => info!("SIGTERM sent to PID: {}", pid),
-            Err(e) => {
-                // Don't fail immediately, just log the error and continue
-                warn!("Failed to send SIGTERM to PID {}: {} (process may already be terminated)", pid, e);
-            }
+    /// Surface a refused action as a macOS notification, since the status bar has no other way to report it
+    fn show_warning_notification(message: &str) {
+        let script = format!("display notification {:?} with title \"Port Kill\"", message);
+        if let Err(e) = std::process::Command::new("osascript").args(["-e", &script]).output() {
+            warn!("Failed to show notification: {}", e);
         }
+    }
 
-        // Wait a bit for graceful termination
-        std::thread::sleep(std::time::Duration::from_millis(500));
-
-        // Check if process is still running
-        let still_running = std::process::Command::new("ps")
-            .args(&["-p", &pid.to_string()])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false);
-
-        if still_running {
-            // Process still running, send SIGKILL
-            info!("Process {} still running, sending SIGKILL", pid);
-            match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                Ok(_) => info!("SIGKILL sent to PID: {}", pid),
-                Err(e) => {
-                    // Log error but don't fail the entire operation
-                    warn!("Failed to send SIGKILL to PID {}: {} (process may be protected)", pid, e);
-                }
-            }
-        } else {
-            info!("Process {} terminated gracefully", pid);
+    fn kill_process(pid: i32, kill_policy: &KillPolicy, signal: Option<KillSignal>) -> Result<(), PortKillError> {
+        match signal {
+            // Only the chosen signal, no escalation: the user picked exactly what to send
+            Some(signal) => crate::process_monitor::signal_process(pid, &crate::command_runner::LocalRunner, signal),
+            // SIGTERM, then SIGKILL with retries for processes that refuse to die
+            None => crate::process_monitor::kill_process(pid, &crate::command_runner::LocalRunner, kill_policy),
         }
-
-        Ok(())
     }
 
-    #[cfg(target_os = "windows")]
-    fn kill_process(pid: i32) -> Result<()> {
-        use std::process::Command;
-
-        info!("Killing process PID: {} on Windows", pid);
-
-        // Use taskkill to terminate the process
-        let output = Command::new("taskkill")
-            .args(&["/PID", &pid.to_string(), "/F"])
-            .output();
+            pub fn kill_single_process(pid: i32, args: &Args, kill_policy: &KillPolicy, signal: Option<KillSignal>) -> Result<()> {
+        info!("Killing single process PID: {}", pid);
 
-        match output {
-            Ok(output) => {
-                if output.status.success() {
-                    info!("Successfully killed process PID: {}", pid);
-                } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    warn!("Failed to kill process PID {}: {}", pid, stderr);
-                }
-            }
-            Err(e) => {
-                warn!("Failed to execute taskkill for PID {}: {}", pid, e);
-            }
+        if args.get_ignore_pids_set().contains(&pid) {
+            info!("Ignoring PID {} - PID is in ignore list", pid);
+            return Ok(());
         }
 
-        Ok(())
-    }
-
-            pub fn kill_single_process(pid: i32, args: &Args) -> Result<()> {
-        info!("Killing single process PID: {}", pid);
+        if crate::process_monitor::has_ignored_executable(args, pid) {
+            info!("Ignoring PID {} - its executable is under an ignored path prefix", pid);
+            return Ok(());
+        }
 
         // Check if this process should be ignored
         let ignore_ports = args.get_ignore_ports_set();
@@ -799,10 +908,10 @@ impl PortKillApp {
         }
 
         // Process is not ignored, proceed with killing
-        Self::kill_process(pid)
+        Ok(Self::kill_process(pid, kill_policy, signal)?)
     }
 
-    pub fn kill_processes_on_port(port: u16, args: &Args) -> Result<()> {
+    pub fn kill_processes_on_port(port: u16, args: &Args, kill_policy: &KillPolicy, signal: Option<KillSignal>) -> Result<Vec<KillOutcome>> {
         info!("Killing processes on port {}...", port);
 
         // Use lsof to get PIDs on the specific port
@@ -815,21 +924,24 @@ impl PortKillApp {
                 if output.status.success() {
                     let stdout = String::from_utf8_lossy(&output.stdout);
                     let mut pids_killed = 0;
+                    let mut outcomes = Vec::new();
 
                     for line in stdout.lines() {
                         let pid_str = line.trim();
                         if !pid_str.is_empty() {
                             if let Ok(pid) = pid_str.parse::<i32>() {
                                 info!("Attempting to kill process PID: {} on port {}", pid, port);
-                                match Self::kill_process(pid) {
+                                let result = Self::kill_process(pid, kill_policy, signal);
+                                match result {
                                     Ok(_) => {
                                         info!("Successfully killed process PID: {} on port {}", pid, port);
                                         pids_killed += 1;
                                     }
-                                    Err(e) => {
+                                    Err(ref e) => {
                                         error!("Failed to kill process {} on port {}: {}", pid, port, e);
                                     }
                                 }
+                                outcomes.push(KillOutcome::new(pid, Some(port), None, &result));
                             }
                         }
                     }
@@ -840,10 +952,10 @@ impl PortKillApp {
                         info!("Killed {} process(es) on port {}", pids_killed, port);
                     }
 
-                    Ok(())
+                    Ok(outcomes)
                 } else {
                     info!("No processes found on port {}", port);
-                    Ok(())
+                    Ok(Vec::new())
                 }
             }
             Err(e) => {
@@ -879,9 +991,9 @@ impl PortKillApp {
         }
     }
 
-    /// Create config-based menu with current process state (rebuilt only when needed)
-    fn create_static_config_menu(processes: &HashMap<u16, crate::types::ProcessInfo>) -> Result<tray_icon::menu::Menu> {
-        use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem, MenuId};
+    /// Create config-based menu with current process state (rebuilt only when needed); `use_emoji` off gives plain-text labels
+    fn create_static_config_menu(processes: &HashMap<u16, Vec<ProcessInfo>>, spared_ports: &std::collections::BTreeSet<u16>, labels: &crate::config::PortLabels, recent_kills: &[KillRecord], use_emoji: bool) -> Result<tray_icon::menu::Menu> {
+        use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem, MenuId, Submenu};
 
         let menu = Menu::new();
         
@@ -892,12 +1004,57 @@ impl PortKillApp {
             // Kill All option (always first)
             let kill_all_item = MenuItem::with_id(
                 MenuId("kill_all".to_string()),
-                "🔪 Kill All Active Processes",
+                &menu_label("🔪", "Kill All Active Processes", use_emoji),
                 true,
                 None
             );
             menu.append(&kill_all_item)?;
-            
+
+            // Kill exactly what the menu lists below, unlike "Kill All", which rescans every port
+            let snapshot_count = snapshot_kill_targets(processes).len();
+            let kill_snapshot_item = MenuItem::with_id(
+                MenuId("kill_snapshot".to_string()),
+                &menu_label("🎯", &format!("Kill Monitored ({} in this snapshot)", snapshot_count), use_emoji),
+                snapshot_count > 0,
+                None
+            );
+            menu.append(&kill_snapshot_item)?;
+
+            // Kill All Except: mark detected ports to spare, then kill the rest
+            let except_menu = Submenu::with_id(
+                MenuId("kill_all_except_menu".to_string()),
+                &menu_label("🛡", "Kill All Except...", use_emoji),
+                true
+            );
+            let mut spare_candidates: std::collections::BTreeSet<u16> = processes.keys().copied().collect();
+            spare_candidates.extend(spared_ports.iter().copied());
+            if spare_candidates.is_empty() {
+                except_menu.append(&MenuItem::with_id(
+                    MenuId("no_processes".to_string()),
+                    "No processes detected",
+                    false,
+                    None
+                ))?;
+            }
+            for port in spare_candidates {
+                let marker = spare_marker(spared_ports.contains(&port), use_emoji);
+                let names = processes.get(&port).map(|on_port| process_names(on_port)).unwrap_or_else(|| "not running".to_string());
+                except_menu.append(&MenuItem::with_id(
+                    MenuId(format!("spare_{}", port)),
+                    &format!("{} {} ({})", marker, labels.display(port), names),
+                    true,
+                    None
+                ))?;
+            }
+            except_menu.append(&PredefinedMenuItem::separator())?;
+            except_menu.append(&MenuItem::with_id(
+                MenuId("kill_all_except".to_string()),
+                &menu_label("🔪", &format!("Kill All Except Selected ({} spared)", spared_ports.len()), use_emoji),
+                !spared_ports.is_empty(),
+                None
+            ))?;
+            menu.append(&except_menu)?;
+
             menu.append(&PredefinedMenuItem::separator())?;
             
             // Config-based port items with current state
@@ -905,43 +1062,100 @@ impl PortKillApp {
                 let menu_id = format!("port_{}", port);
                 
                 // Show current state with appropriate emoji
-                let (emoji, status) = if let Some(process_info) = processes.get(&port) {
-                    let emoji = if process_info.name.starts_with("docker-proxy") {
+                let (emoji, status) = if let Some(on_port) = processes.get(&port) {
+                    let emoji = if on_port.iter().any(|process_info| process_info.name.starts_with("docker-proxy")) {
                         "🔴" // Red for Docker
                     } else {
                         "🟠" // Orange for regular processes
                     };
-                    (emoji, format!("({})", process_info.name))
+                    (emoji, format!("({})", process_names(on_port)))
                 } else {
                     ("🟢", "(available)".to_string())
                 };
                 
-                let menu_text = format!("{} Port {} {}", emoji, port, status);
-                
-                let port_item = MenuItem::with_id(
-                    MenuId(menu_id),
+                let menu_text = menu_label(emoji, &format!("{} {}", labels.display(port), status), use_emoji);
+
+                if !processes.contains_key(&port) {
+                    let port_item = MenuItem::with_id(
+                        MenuId(menu_id),
+                        &menu_text,
+                        true,
+                        None
+                    );
+                    menu.append(&port_item)?;
+                    continue;
+                }
+
+                // Detected ports get a submenu so a specific signal can be sent instead of the usual escalation
+                let port_menu = Submenu::with_id(
+                    MenuId(format!("port_menu_{}", port)),
                     &menu_text,
+                    true
+                );
+                port_menu.append(&MenuItem::with_id(
+                    MenuId(menu_id),
+                    &menu_label("🔪", "Kill (SIGTERM, then SIGKILL)", use_emoji),
                     true,
                     None
-                );
-                menu.append(&port_item)?;
+                ))?;
+                port_menu.append(&PredefinedMenuItem::separator())?;
+                for signal in KillSignal::ALL {
+                    port_menu.append(&MenuItem::with_id(
+                        MenuId(format!("signal_{}_{}", signal.id(), port)),
+                        signal.label(),
+                        true,
+                        None
+                    ))?;
+                }
+                menu.append(&port_menu)?;
             }
             
             menu.append(&PredefinedMenuItem::separator())?;
         }
+
+        // Read-only session history; the items are disabled since unknown IDs are treated as Kill All
+        let history_menu = Submenu::with_id(
+            MenuId("recently_killed".to_string()),
+            &menu_label("🕘", "Recently Killed", use_emoji),
+            true
+        );
+        if recent_kills.is_empty() {
+            history_menu.append(&MenuItem::with_id(
+                MenuId("recently_killed_none".to_string()),
+                "Nothing killed yet",
+                false,
+                None
+            ))?;
+        }
+        for (index, record) in recent_kills.iter().enumerate() {
+            history_menu.append(&MenuItem::with_id(
+                MenuId(format!("recently_killed_{}", index)),
+                &record.label(),
+                false,
+                None
+            ))?;
+        }
+        menu.append(&history_menu)?;
         
         // Settings and Quit (always present)
         let settings_item = MenuItem::with_id(
             MenuId("settings".to_string()),
-            "⚙️ Settings",
+            &menu_label("⚙️", "Settings", use_emoji),
             false, // Not implemented yet
             None
         );
         menu.append(&settings_item)?;
+
+        menu.append(&MenuItem::with_id(
+            MenuId("toggle_pause".to_string()),
+            &menu_label("⏸️", "Pause Monitoring", use_emoji),
+            true,
+            None
+        ))?;
         
         let quit_item = MenuItem::with_id(
             MenuId("quit".to_string()),
-            "❌ Quit",
+            &menu_label("❌", "Quit", use_emoji),
             true,
             None
         );
@@ -951,18 +1165,74 @@ impl PortKillApp {
         Ok(menu)
     }
 
-    /// Create a crash-safe menu with limited items (prevents segfaults with many processes)
-    fn create_crash_resistant_dynamic_menu(processes: &HashMap<u16, crate::types::ProcessInfo>, _max_items: usize) -> Result<tray_icon::menu::Menu> {
+    /// Create the --compact-menu: Kill All, Kill Newest Process, Pause Monitoring and Quit, with no
+    /// per-process items, so it never needs rebuilding however many processes are running
+    fn create_compact_menu(use_emoji: bool) -> Result<tray_icon::menu::Menu> {
+        use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem, MenuId};
+
+        let menu = Menu::new();
+        menu.append(&MenuItem::with_id(
+            MenuId("kill_all".to_string()),
+            &menu_label("🔪", "Kill All Processes", use_emoji),
+            true,
+            None
+        ))?;
+        menu.append(&MenuItem::with_id(
+            MenuId("kill_newest".to_string()),
+            &menu_label("🆕", "Kill Newest Process", use_emoji),
+            true,
+            None
+        ))?;
+        menu.append(&PredefinedMenuItem::separator())?;
+        menu.append(&MenuItem::with_id(
+            MenuId("toggle_pause".to_string()),
+            &menu_label("⏸️", "Pause Monitoring", use_emoji),
+            true,
+            None
+        ))?;
+        menu.append(&MenuItem::with_id(
+            MenuId("quit".to_string()),
+            &menu_label("❌", "Quit", use_emoji),
+            true,
+            None
+        ))?;
+
+        Ok(menu)
+    }
+
+    /// Create the menu shown while monitoring is paused: only Resume Monitoring and Quit, so nothing can be killed
+    fn create_paused_menu(use_emoji: bool) -> Result<tray_icon::menu::Menu> {
+        use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem, MenuId};
+
+        let menu = Menu::new();
+        menu.append(&MenuItem::with_id(
+            MenuId("toggle_pause".to_string()),
+            &menu_label("▶️", "Resume Monitoring", use_emoji),
+            true,
+            None
+        ))?;
+        menu.append(&PredefinedMenuItem::separator())?;
+        menu.append(&MenuItem::with_id(
+            MenuId("quit".to_string()),
+            &menu_label("❌", "Quit", use_emoji),
+            true,
+            None
+        ))?;
+
+        Ok(menu)
+    }
+
+    /// Create a crash-safe menu listing at most `max_items` ports (`max_processes_in_menu`; prevents segfaults with many processes)
+    fn create_crash_resistant_dynamic_menu(processes: &HashMap<u16, Vec<ProcessInfo>>, max_items: usize, use_emoji: bool) -> Result<tray_icon::menu::Menu> {
         use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem, MenuId};
 
         let menu = Menu::new();
-        const MAX_MENU_ITEMS: usize = 8; // Limit to prevent crashes
 
         if !processes.is_empty() {
             // Kill All option (always first)
             let kill_all_item = MenuItem::with_id(
                 MenuId("kill_all".to_string()),
-                &format!("🔪 Kill All {} Processes", processes.len()),
+                &menu_label("🔪", &format!("Kill All {} Processes", process_count(processes)), use_emoji),
                 true,
                 None
             );
@@ -971,20 +1241,18 @@ impl PortKillApp {
             menu.append(&PredefinedMenuItem::separator())?;
 
             // Add individual process items - LIMITED to prevent crashes
-            let mut sorted_ports: Vec<_> = processes.keys().collect();
-            sorted_ports.sort();
-
-            let mut items_added = 0;
-            for &port in sorted_ports.iter().take(MAX_MENU_ITEMS) {
-                if let Some(process_info) = processes.get(&port) {
+            let (shown_ports, hidden) = menu_ports(processes, max_items);
+            for port in shown_ports {
+                if let Some(on_port) = processes.get(&port) {
                     let menu_id = format!("kill_{}", port);
                     // Truncate long process names to prevent menu width issues
-                    let display_name = if process_info.name.len() > 15 {
-                        format!("{}...", &process_info.name[..12])
+                    let names = process_names(on_port);
+                    let display_name = if names.len() > 15 {
+                        format!("{}...", &names[..12])
                     } else {
-                        process_info.name.clone()
+                        names
                     };
-                    let menu_text = format!("🎯 Kill Port {} ({})", port, display_name);
+                    let menu_text = menu_label("🎯", &format!("Kill Port {} ({})", port, display_name), use_emoji);
 
                     let kill_item = MenuItem::with_id(
                         MenuId(menu_id),
@@ -993,15 +1261,14 @@ impl PortKillApp {
                         None
                     );
                     menu.append(&kill_item)?;
-                    items_added += 1;
                 }
             }
 
-            // Show "more processes" if we have more than the limit
-            if processes.len() > MAX_MENU_ITEMS {
+            // The ports that didn't fit are only listed in the console
+            if hidden > 0 {
                 let more_item = MenuItem::with_id(
                     MenuId("more_processes".to_string()),
-                    &format!("📊 {} more processes... (use Kill All)", processes.len() - items_added),
+                    &menu_overflow_label(hidden),
                     false,
                     None
                 );
@@ -1013,7 +1280,7 @@ impl PortKillApp {
             // No processes running
             let no_processes_item = MenuItem::with_id(
                 MenuId("no_processes".to_string()),
-                "✅ No processes running",
+                &menu_label("✅", "No processes running", use_emoji),
                 false,
                 None
             );
@@ -1025,7 +1292,7 @@ impl PortKillApp {
         // Always show quit
         let quit_item = MenuItem::with_id(
             MenuId("quit".to_string()),
-            "❌ Quit",
+            &menu_label("❌", "Quit", use_emoji),
             true,
             None
         );
@@ -1036,7 +1303,7 @@ impl PortKillApp {
 
     /// Create a stable, simplified menu that's less likely to cause crashes (DEPRECATED - causes crashes)
     #[allow(dead_code)]
-    fn create_stable_menu(processes: &HashMap<u16, crate::types::ProcessInfo>, show_pid: bool) -> Result<tray_icon::menu::Menu> {
+    fn create_stable_menu(processes: &HashMap<u16, Vec<ProcessInfo>>, show_pid: bool, max_items: usize) -> Result<tray_icon::menu::Menu> {
         use tray_icon::menu::{Menu, MenuItem, PredefinedMenuItem};
 
         let menu = Menu::new();
@@ -1048,24 +1315,24 @@ impl PortKillApp {
         // Add separator
         menu.append(&PredefinedMenuItem::separator())?;
 
-        // Add up to 4 individual processes (to keep menu stable)
-        let mut process_entries: Vec<_> = processes.iter().collect();
-        process_entries.sort_by_key(|(port, _)| **port);
+        // Add up to `max_items` individual ports (to keep menu stable)
+        let (shown_ports, hidden) = menu_ports(processes, max_items);
 
-        for (_index, (port, process_info)) in process_entries.iter().take(4).enumerate() {
+        for (port, on_port) in shown_ports.iter().filter_map(|port| processes.get(port).map(|on_port| (port, on_port))) {
             let menu_text = if show_pid {
-                format!("🎯 Kill Port {} (PID {})", port, process_info.pid)
+                let pids: Vec<String> = on_port.iter().map(|process_info| process_info.pid.to_string()).collect();
+                format!("🎯 Kill Port {} (PID {})", port, pids.join(", "))
             } else {
-                format!("🎯 Kill Port {} ({})", port, process_info.name)
+                format!("🎯 Kill Port {} ({})", port, process_names(on_port))
             };
 
             let process_item = MenuItem::new(&menu_text, true, None);
             menu.append(&process_item)?;
         }
 
-        // Show count if more than 4 processes
-        if processes.len() > 4 {
-            let more_item = MenuItem::new(&format!("📊 {} more processes...", processes.len() - 4), false, None);
+        // Show count if more ports than fit
+        if hidden > 0 {
+            let more_item = MenuItem::new(&menu_overflow_label(hidden), false, None);
             menu.append(&more_item)?;
         }
 
@@ -1078,7 +1345,7 @@ impl PortKillApp {
     }
 
                         /// Map menu ID to action using dynamic string IDs (fully dynamic!)
-    fn map_menu_id_to_action(menu_id: &str, processes: &HashMap<u16, crate::types::ProcessInfo>) -> MenuAction {
+    fn map_menu_id_to_action(menu_id: &str, processes: &HashMap<u16, Vec<ProcessInfo>>) -> MenuAction {
         match menu_id {
             "kill_all" => {
                 info!("Kill All action triggered (ID: {})", menu_id);
@@ -1088,13 +1355,38 @@ impl PortKillApp {
                 info!("Quit action triggered (ID: {})", menu_id);
                 MenuAction::Quit
             }
+            "kill_all_except" => {
+                info!("Kill All Except action triggered (ID: {})", menu_id);
+                MenuAction::KillAllExcept
+            }
+            "kill_snapshot" => {
+                info!("Kill Monitored action triggered (ID: {})", menu_id);
+                MenuAction::KillSnapshot
+            }
+            "kill_newest" => {
+                info!("Kill Newest Process action triggered (ID: {})", menu_id);
+                MenuAction::KillNewest
+            }
+            "toggle_pause" => {
+                info!("Pause/Resume Monitoring action triggered (ID: {})", menu_id);
+                MenuAction::TogglePause
+            }
             "no_processes" => {
                 info!("No processes item clicked (ID: {})", menu_id);
                 MenuAction::KillAll // Safe no-op
             }
             _ => {
                 // Handle both old kill_PORT and new port_PORT IDs
-                if menu_id.starts_with("kill_") {
+                if let Some(port) = menu_id.strip_prefix("spare_").and_then(|port| port.parse::<u16>().ok()) {
+                    info!("Toggle spare for port {} triggered (ID: {})", port, menu_id);
+                    MenuAction::ToggleSpare(port)
+                } else if let Some((signal, port)) = menu_id.strip_prefix("signal_")
+                    .and_then(|rest| rest.split_once('_'))
+                    .and_then(|(signal, port)| Some((KillSignal::from_id(signal)?, port.parse::<u16>().ok()?)))
+                {
+                    info!("{} on port {} triggered (ID: {})", signal.label(), port, menu_id);
+                    MenuAction::SignalProcess(port, signal)
+                } else if menu_id.starts_with("kill_") {
                     if let Ok(port) = menu_id.strip_prefix("kill_").unwrap_or("").parse::<u16>() {
                         // Verify this port actually has a running process
                         if processes.contains_key(&port) {
@@ -0,0 +1,288 @@
+use crate::{
+    cli::Args,
+    command_runner::CommandRunner,
+    error::PortKillError,
+    history::KillHistory,
+    event_socket::EventPublisher,
+    hooks::Hooks,
+    session_summary::SessionStats,
+    port_scanner::{is_lsof_missing, LsofScanner, PortScanner},
+    process_monitor::{is_process_excluded, kill_process, KillPolicy},
+    types::ProcessInfo,
+    webhook::{KillOutcome, Webhook},
+};
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Kills of the same port within this window count towards the back-off limit
+pub const RESPAWN_WINDOW: Duration = Duration::from_secs(60);
+/// Kills of one port within `RESPAWN_WINDOW` before auto-kill backs off from it
+pub const MAX_KILLS_PER_WINDOW: usize = 3;
+/// How long a port whose process keeps respawning is left alone
+pub const BACKOFF: Duration = Duration::from_secs(300);
+
+/// Stops auto-kill from fighting a supervisor that restarts a process as soon as it dies
+#[derive(Debug, Default)]
+pub struct KillLoopGuard {
+    recent_kills: HashMap<u16, VecDeque<Instant>>,
+    backed_off_until: HashMap<u16, Instant>,
+}
+
+impl KillLoopGuard {
+    /// Time left before `port` may be auto-killed again, or `None` if it may be killed now
+    pub fn backoff_remaining(&self, port: u16, now: Instant) -> Option<Duration> {
+        self.backed_off_until
+            .get(&port)
+            .and_then(|until| until.checked_duration_since(now))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Record a kill on `port`; returns true when it starts a back-off
+    pub fn record_kill(&mut self, port: u16, now: Instant) -> bool {
+        let kills = self.recent_kills.entry(port).or_default();
+        while kills.front().is_some_and(|killed_at| now.saturating_duration_since(*killed_at) > RESPAWN_WINDOW) {
+            kills.pop_front();
+        }
+        kills.push_back(now);
+
+        if kills.len() < MAX_KILLS_PER_WINDOW {
+            return false;
+        }
+        kills.clear();
+        self.backed_off_until.insert(port, now + BACKOFF);
+        true
+    }
+}
+
+/// Kills whatever appears on the --auto-kill ports, once per monitoring cycle
+pub struct AutoKiller {
+    ports: Vec<u16>,
+    args: Args,
+    runner: Arc<dyn CommandRunner>,
+    kill_policy: KillPolicy,
+    guard: KillLoopGuard,
+    /// Processes already reported by --dry-run, so each is only logged once
+    dry_run_reported: HashSet<(u16, i32)>,
+    webhook: Option<Webhook>,
+    hooks: Option<Hooks>,
+    events: Option<EventPublisher>,
+    stats: Option<SessionStats>,
+    history: KillHistory,
+}
+
+impl AutoKiller {
+    pub fn new(ports: Vec<u16>, args: &Args, kill_policy: KillPolicy, webhook: Option<Webhook>) -> Self {
+        Self {
+            ports,
+            args: args.clone(),
+            runner: args.command_runner(),
+            kill_policy,
+            guard: KillLoopGuard::default(),
+            dry_run_reported: HashSet::new(),
+            webhook,
+            hooks: None,
+            events: None,
+            stats: None,
+            history: KillHistory::new(0, None),
+        }
+    }
+
+    /// Also add the auto-kills to `history`
+    pub fn with_history(mut self, history: KillHistory) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Also run the config's `on_kill_command` for the auto-kills
+    pub fn with_hooks(mut self, hooks: Option<Hooks>) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Also publish the auto-kills on the --event-socket
+    pub fn with_events(mut self, events: Option<EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Also count the auto-kills towards the --summary-on-exit report
+    pub fn with_stats(mut self, stats: Option<SessionStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Scan the auto-kill ports and kill every process found on them
+    pub fn run_cycle(&mut self) {
+        // Not `from_args`: --watch-process must not widen the scan beyond the auto-kill ports
+        let scanner = LsofScanner::new(self.runner.clone()).with_timeout(self.args.lsof_timeout).with_interface(self.args.interface).with_network_namespaces(self.args.netns);
+        let runner = self.runner.clone();
+        let kill_policy = self.kill_policy.clone();
+        let outcomes = self.cycle(&scanner, Instant::now(), &mut |process_info| {
+            kill_process(process_info.pid, runner.as_ref(), &kill_policy)
+        });
+
+        self.history.record(&outcomes);
+        if let Some(hooks) = &self.hooks {
+            hooks.on_kill(&outcomes);
+        }
+        if let Some(events) = &self.events {
+            events.kills(&outcomes);
+        }
+        if let Some(stats) = &self.stats {
+            stats.record(&outcomes);
+        }
+        if let Some(webhook) = &self.webhook {
+            webhook.notify(outcomes);
+        }
+    }
+
+    /// One cycle against `scanner`, killing through `kill`; returns the kills that were attempted
+    pub fn cycle(
+        &mut self,
+        scanner: &dyn PortScanner,
+        now: Instant,
+        kill: &mut dyn FnMut(&ProcessInfo) -> Result<(), PortKillError>,
+    ) -> Vec<KillOutcome> {
+        let found = match scanner.scan(&self.ports) {
+            Ok(found) => found,
+            // Already reported once by the scanner
+            Err(e) if is_lsof_missing(&e) => return Vec::new(),
+            Err(e) => {
+                warn!("Auto-kill scan failed: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut outcomes = Vec::new();
+        let mut seen_pids = HashSet::new();
+        for process_info in found {
+            // lsof may report other ports for a multi-socket process; only the auto-kill ports count
+            if !self.ports.contains(&process_info.port) || !seen_pids.insert(process_info.pid) {
+                continue;
+            }
+            let (pid, port, name) = (process_info.pid, process_info.port, process_info.name.as_str());
+            if is_process_excluded(&self.args, pid, port, name, process_info.bind_address.as_deref(), process_info.owner.as_deref()) {
+                continue;
+            }
+
+            if self.args.dry_run {
+                if self.dry_run_reported.insert((port, pid)) {
+                    info!("Dry run: would auto-kill {} (PID {}) on port {}", name, pid, port);
+                }
+                continue;
+            }
+
+            if let Some(remaining) = self.guard.backoff_remaining(port, now) {
+                info!("Not auto-killing {} (PID {}) on port {}: backing off for another {:?}", name, pid, port, remaining);
+                continue;
+            }
+
+            let result = kill(&process_info);
+            match result {
+                Ok(()) => info!("Auto-killed {} (PID {}) on port {}", name, pid, port),
+                Err(ref e) => warn!("Failed to auto-kill {} (PID {}) on port {}: {}", name, pid, port, e),
+            }
+            if self.guard.record_kill(port, now) {
+                warn!("Port {} was auto-killed {} times within {:?}; it keeps respawning, backing off for {:?}",
+                      port, MAX_KILLS_PER_WINDOW, RESPAWN_WINDOW, BACKOFF);
+            }
+            outcomes.push(KillOutcome::new(pid, Some(port), Some(name), &result));
+        }
+
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    struct FakeScanner(Vec<ProcessInfo>);
+
+    impl PortScanner for FakeScanner {
+        fn scan(&self, ports: &[u16]) -> Result<Vec<ProcessInfo>> {
+            Ok(self.0.iter().filter(|p| ports.contains(&p.port)).cloned().collect())
+        }
+    }
+
+    #[test]
+    fn test_guard_backs_off_after_repeated_kills() {
+        let mut guard = KillLoopGuard::default();
+        let start = Instant::now();
+
+        assert!(!guard.record_kill(3000, start));
+        assert!(!guard.record_kill(3000, start + Duration::from_secs(10)));
+        assert_eq!(guard.backoff_remaining(3000, start + Duration::from_secs(10)), None);
+        assert!(guard.record_kill(3000, start + Duration::from_secs(20)));
+
+        assert_eq!(guard.backoff_remaining(3000, start + Duration::from_secs(20)), Some(BACKOFF));
+        assert_eq!(guard.backoff_remaining(8080, start + Duration::from_secs(20)), None);
+        assert_eq!(guard.backoff_remaining(3000, start + Duration::from_secs(20) + BACKOFF), None);
+    }
+
+    #[test]
+    fn test_guard_forgets_kills_outside_the_window() {
+        let mut guard = KillLoopGuard::default();
+        let start = Instant::now();
+
+        for minute in 0..5 {
+            assert!(!guard.record_kill(3000, start + Duration::from_secs(61 * minute)));
+        }
+    }
+
+    #[test]
+    fn test_cycle_kills_auto_kill_ports_only() {
+        let args = Args::parse_from(["port-kill", "--auto-kill", "3000,8080", "--ignore-processes", "postgres"]);
+        let scanner = FakeScanner(vec![
+            ProcessInfo::fixture(3000, 10, "node"),
+            ProcessInfo::fixture(3000, 10, "node"),
+            ProcessInfo::fixture(5173, 11, "vite"),
+            ProcessInfo::fixture(8080, 12, "postgres"),
+        ]);
+        let mut killer = AutoKiller::new(vec![3000, 8080], &args, KillPolicy::default(), None);
+
+        let mut killed = Vec::new();
+        let outcomes = killer.cycle(&scanner, Instant::now(), &mut |process_info| {
+            killed.push(process_info.pid);
+            Ok(())
+        });
+
+        assert_eq!(killed, vec![10]);
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].success);
+    }
+
+    #[test]
+    fn test_cycle_stops_killing_a_respawning_process() {
+        let args = Args::parse_from(["port-kill", "--auto-kill", "3000"]);
+        let scanner = FakeScanner(vec![ProcessInfo::fixture(3000, 10, "node")]);
+        let mut killer = AutoKiller::new(vec![3000], &args, KillPolicy::default(), None);
+        let start = Instant::now();
+
+        let mut kills = 0;
+        for cycle in 0..10 {
+            killer.cycle(&scanner, start + Duration::from_secs(2 * cycle), &mut |_| {
+                kills += 1;
+                Ok(())
+            });
+        }
+        assert_eq!(kills, MAX_KILLS_PER_WINDOW);
+    }
+
+    #[test]
+    fn test_dry_run_never_kills() {
+        let args = Args::parse_from(["port-kill", "--auto-kill", "3000", "--dry-run"]);
+        let scanner = FakeScanner(vec![ProcessInfo::fixture(3000, 10, "node")]);
+        let mut killer = AutoKiller::new(vec![3000], &args, KillPolicy::default(), None);
+
+        for _ in 0..3 {
+            let outcomes = killer.cycle(&scanner, Instant::now(), &mut |_| panic!("dry run must not kill"));
+            assert!(outcomes.is_empty());
+        }
+        assert_eq!(killer.dry_run_reported.len(), 1);
+    }
+}
@@ -0,0 +1,238 @@
+/// Highest count rendered exactly; anything above shows as "99+"
+pub const MAX_BADGE_COUNT: usize = 99;
+
+/// Docker's brand blue, used for the container marker
+pub const DOCKER_COLOR: [u8; 3] = [36, 150, 237];
+
+/// Neutral grey of the icon while monitoring is paused
+pub const PAUSED_COLOR: [u8; 3] = [142, 142, 147];
+
+/// Side of the plain fallback icon; every tray backend accepts 16x16
+pub const FALLBACK_ICON_SIZE: usize = 16;
+
+/// Glyph cell size of the built-in bitmap font
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+
+/// 3x5 bitmap glyphs, one row per byte using the low three bits (MSB = leftmost pixel)
+fn glyph(c: char) -> Option<[u8; GLYPH_HEIGHT]> {
+    let rows = match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Text shown on the icon for a process count
+pub fn badge_text(count: usize) -> String {
+    if count > MAX_BADGE_COUNT {
+        format!("{}+", MAX_BADGE_COUNT)
+    } else {
+        count.to_string()
+    }
+}
+
+/// Pick black or white digits, whichever contrasts better with the background color
+pub fn text_color_for(background: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = background.map(|c| c as u32);
+    // Rec. 601 luma, scaled by 1000
+    let luma = 299 * r + 587 * g + 114 * b;
+    if luma > 128 * 1000 {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    }
+}
+
+/// Draw `text` centered onto an RGBA bitmap, scaled as large as the icon allows.
+///
+/// Digits use the color that contrasts with `background` and get a one pixel
+/// outline in the opposite color so they stay readable on any icon artwork.
+pub fn draw_badge(rgba: &mut [u8], width: usize, height: usize, text: &str, background: [u8; 3]) {
+    let glyphs: Vec<[u8; GLYPH_HEIGHT]> = text.chars().filter_map(glyph).collect();
+    if glyphs.is_empty() || rgba.len() < width * height * 4 {
+        return;
+    }
+
+    // Unscaled text size with one pixel spacing between glyphs
+    let text_width = glyphs.len() * (GLYPH_WIDTH + 1) - 1;
+    let scale = ((width * 3 / 4) / text_width)
+        .min((height / 2) / GLYPH_HEIGHT)
+        .max(1);
+
+    let scaled_width = text_width * scale;
+    let scaled_height = GLYPH_HEIGHT * scale;
+    if scaled_width > width || scaled_height > height {
+        return;
+    }
+    let origin_x = (width - scaled_width) / 2;
+    let origin_y = (height - scaled_height) / 2;
+
+    // Collect the text pixels first so the outline can be drawn underneath them
+    let mut mask = vec![false; width * height];
+    for (index, rows) in glyphs.iter().enumerate() {
+        let glyph_x = origin_x + index * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        mask[(origin_y + row * scale + dy) * width + glyph_x + col * scale + dx] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    let text_color = text_color_for(background);
+    let outline_color = text_color.map(|c| 255 - c);
+
+    for y in 0..height {
+        for x in 0..width {
+            let color = if mask[y * width + x] {
+                text_color
+            } else if has_neighbor(&mask, width, height, x, y) {
+                outline_color
+            } else {
+                continue;
+            };
+
+            let offset = (y * width + x) * 4;
+            rgba[offset..offset + 3].copy_from_slice(&color);
+            rgba[offset + 3] = 255;
+        }
+    }
+}
+
+/// A fully opaque `size`x`size` RGBA square in `color`, for when no icon artwork can be produced
+pub fn solid_icon(size: usize, color: [u8; 3]) -> Vec<u8> {
+    [color[0], color[1], color[2], 255].repeat(size * size)
+}
+
+/// Draw a small Docker-blue square with a white border in the top-right corner,
+/// clear of the centered digits, to mark that some processes are containerized.
+pub fn draw_docker_marker(rgba: &mut [u8], width: usize, height: usize) {
+    let size = (width.min(height) / 4).max(4);
+    if rgba.len() < width * height * 4 || size > width || size > height {
+        return;
+    }
+
+    for y in 0..size {
+        for x in width - size..width {
+            let on_border = y == 0 || y == size - 1 || x == width - size || x == width - 1;
+            let offset = (y * width + x) * 4;
+            rgba[offset..offset + 3].copy_from_slice(if on_border { &[255, 255, 255] } else { &DOCKER_COLOR });
+            rgba[offset + 3] = 255;
+        }
+    }
+}
+
+/// Draw two centered pause bars ("⏸") in place of the process count while monitoring is paused,
+/// in the color that contrasts with `background`
+pub fn draw_pause_bars(rgba: &mut [u8], width: usize, height: usize, background: [u8; 3]) {
+    let bar_width = (width / 6).max(1);
+    let gap = bar_width;
+    let bar_height = height / 2;
+    if rgba.len() < width * height * 4 || bar_height == 0 || bar_width * 2 + gap > width {
+        return;
+    }
+    let origin_x = (width - (bar_width * 2 + gap)) / 2;
+    let origin_y = (height - bar_height) / 2;
+    let color = text_color_for(background);
+
+    for y in origin_y..origin_y + bar_height {
+        for x in (origin_x..origin_x + bar_width).chain(origin_x + bar_width + gap..origin_x + bar_width * 2 + gap) {
+            let offset = (y * width + x) * 4;
+            rgba[offset..offset + 3].copy_from_slice(&color);
+            rgba[offset + 3] = 255;
+        }
+    }
+}
+
+fn has_neighbor(mask: &[bool], width: usize, height: usize, x: usize, y: usize) -> bool {
+    (y.saturating_sub(1)..=(y + 1).min(height - 1))
+        .any(|ny| (x.saturating_sub(1)..=(x + 1).min(width - 1)).any(|nx| mask[ny * width + nx]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_badge_text_overflow() {
+        assert_eq!(badge_text(0), "0");
+        assert_eq!(badge_text(42), "42");
+        assert_eq!(badge_text(99), "99");
+        assert_eq!(badge_text(100), "99+");
+    }
+
+    #[test]
+    fn test_text_color_contrasts_with_status_colors() {
+        // Default idle green and busy orange are both bright, so digits are dark
+        assert_eq!(text_color_for([95, 249, 57]), [0, 0, 0]);
+        assert_eq!(text_color_for([255, 165, 0]), [0, 0, 0]);
+        assert_eq!(text_color_for([40, 40, 120]), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_draw_badge_fits_icon() {
+        for text in ["0", "42", "99+"] {
+            let mut rgba = vec![0u8; 22 * 22 * 4];
+            draw_badge(&mut rgba, 22, 22, text, [255, 165, 0]);
+
+            let dark_pixels = rgba.chunks(4).filter(|p| p[3] == 255 && p[..3] == [0, 0, 0]).count();
+            assert!(dark_pixels > 0, "no digits drawn for {}", text);
+        }
+    }
+
+    #[test]
+    fn test_solid_icon() {
+        let rgba = solid_icon(FALLBACK_ICON_SIZE, [255, 165, 0]);
+        assert_eq!(rgba.len(), FALLBACK_ICON_SIZE * FALLBACK_ICON_SIZE * 4);
+        assert!(rgba.chunks(4).all(|p| p == [255, 165, 0, 255]));
+    }
+
+    #[test]
+    fn test_pause_bars() {
+        for size in [16, 22, 36] {
+            let mut rgba = vec![0u8; size * size * 4];
+            draw_pause_bars(&mut rgba, size, size, PAUSED_COLOR);
+
+            // Two bars, so the middle column between them stays clear
+            let drawn = |x: usize, y: usize| rgba[(y * size + x) * 4 + 3] == 255;
+            let middle_row: Vec<bool> = (0..size).map(|x| drawn(x, size / 2)).collect();
+            let bars = middle_row.windows(2).filter(|pair| !pair[0] && pair[1]).count();
+            assert_eq!(bars, 2, "expected two pause bars on a {}px icon", size);
+            assert!(!drawn(size / 2, 0) && !drawn(size / 2, size - 1));
+        }
+    }
+
+    #[test]
+    fn test_docker_marker_stays_clear_of_the_digits() {
+        for (size, text) in [(22, "0"), (22, "99+"), (36, "8")] {
+            let mut with_marker = vec![0u8; size * size * 4];
+            draw_badge(&mut with_marker, size, size, text, [255, 165, 0]);
+            let digits = with_marker.clone();
+            draw_docker_marker(&mut with_marker, size, size);
+
+            let docker_pixels = with_marker.chunks(4).filter(|p| p[..3] == DOCKER_COLOR).count();
+            assert!(docker_pixels > 0, "no marker drawn on a {}px icon", size);
+            // Only transparent pixels were painted over
+            for (before, after) in digits.chunks(4).zip(with_marker.chunks(4)) {
+                assert!(before[3] == 0 || before == after, "marker covers the {:?} badge on a {}px icon", text, size);
+            }
+        }
+    }
+}
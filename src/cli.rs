@@ -1,5 +1,47 @@
+use crate::command_runner::{CommandRunner, LocalRunner, SshRunner};
+use crate::config::AppConfig;
+use crate::process_monitor::KillPolicy;
+use crate::types::{AddressFamily, ProcessInfo};
 use clap::Parser;
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Well-known development ports monitored by `--common`
+pub const COMMON_DEV_PORTS: &[u16] = &[
+    3000,  // React, Next.js, Express, Rails
+    3001,  // Second dev server / API
+    3306,  // MySQL
+    4000,  // Phoenix, Jekyll, Gatsby
+    4200,  // Angular
+    5000,  // Flask, .NET
+    5173,  // Vite
+    5432,  // PostgreSQL
+    6379,  // Redis
+    8000,  // Django, FastAPI, python -m http.server
+    8080,  // Generic HTTP / Java servers
+    8081,  // Metro (React Native), secondary HTTP
+    8888,  // Jupyter
+    9000,  // PHP-FPM, SonarQube, MinIO
+    9229,  // Node.js inspector
+    27017, // MongoDB
+];
+
+/// System services never touched in all-ports mode unless --no-safety is given
+pub const PROTECTED_PROCESSES: &[&str] = &[
+    "sshd",
+    "systemd",
+    "systemd-resolved",
+    "launchd",
+    "loginwindow",
+    "WindowServer",
+];
+
+/// Default --status-format, e.g. "ports: 3000,5173 (2)"
+pub const DEFAULT_STATUS_FORMAT: &str = "ports: {ports} ({count})";
+
+/// lsof truncates the COMMAND column to this many characters
+const LSOF_COMMAND_WIDTH: usize = 9;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum LogLevel {
@@ -13,8 +55,236 @@ pub enum LogLevel {
     None,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable log lines
+    Text,
+    /// One JSON object per line (for log ingestion)
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BindFilter {
+    /// Include processes regardless of the address they listen on
+    All,
+    /// Only processes bound to a loopback address (127.0.0.1, ::1)
+    Localhost,
+    /// Only processes reachable from other machines (0.0.0.0, ::, a LAN address)
+    External,
+}
+
+impl BindFilter {
+    /// Whether a process listening on `bind_address` passes this filter.
+    ///
+    /// Processes whose bind address could not be determined only pass `All`.
+    pub fn matches(&self, bind_address: Option<&str>) -> bool {
+        match (self, bind_address) {
+            (BindFilter::All, _) => true,
+            (_, None) => false,
+            (BindFilter::Localhost, Some(address)) => is_loopback_address(address),
+            (BindFilter::External, Some(address)) => !is_loopback_address(address),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Ascending port number (matches the menu)
+    Port,
+    /// Ascending process ID
+    Pid,
+    /// Process name, alphabetically
+    Name,
+    /// Highest CPU usage first (needs --show-resources)
+    Cpu,
+    /// Highest memory usage first (needs --show-resources)
+    Memory,
+}
+
+impl SortKey {
+    /// Sort processes in place; ties (and unknown CPU/memory usage) fall back to port order
+    pub fn sort(&self, processes: &mut [&ProcessInfo]) {
+        processes.sort_by(|a, b| {
+            let ordering = match self {
+                SortKey::Port => std::cmp::Ordering::Equal,
+                SortKey::Pid => a.pid.cmp(&b.pid),
+                SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                SortKey::Cpu => b.cpu_percent.unwrap_or(-1.0).total_cmp(&a.cpu_percent.unwrap_or(-1.0)),
+                SortKey::Memory => b.memory_bytes.cmp(&a.memory_bytes),
+            };
+            ordering.then(a.port.cmp(&b.port))
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GroupBy {
+    /// Project directory: the nearest ancestor of the working directory with a .git, package.json, Cargo.toml, ...
+    Dir,
+}
+
+/// Inclusive port range given as "start-end" (used by --show-range and --ports)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+
+    pub fn ports(&self) -> std::ops::RangeInclusive<u16> {
+        self.start..=self.end
+    }
+}
+
+impl From<u16> for PortRange {
+    fn from(port: u16) -> Self {
+        Self { start: port, end: port }
+    }
+}
+
+impl std::str::FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once('-')
+            .ok_or_else(|| format!("Invalid port range '{}', expected START-END (e.g. 3000-3100)", s))?;
+        let start = start.trim().parse::<u16>().map_err(|_| format!("Invalid start port in range '{}'", s))?;
+        let end = end.trim().parse::<u16>().map_err(|_| format!("Invalid end port in range '{}'", s))?;
+
+        if start > end {
+            return Err(format!("Invalid port range '{}': start port is greater than end port", s));
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl std::fmt::Display for PortRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+/// Parse one --ports entry: a single port ("3000"), a service name ("postgres") or an inclusive range ("8000-8010")
+pub fn parse_port_spec(s: &str) -> Result<PortRange, String> {
+    // Config aliases may contain dashes ("my-api"), so names go first
+    if let Some(port) = crate::port_aliases::lookup(s) {
+        return Ok(PortRange::from(port));
+    }
+    if s.contains('-') {
+        return s.parse();
+    }
+    s.trim().parse::<u16>().map(PortRange::from).map_err(|_| {
+        format!("Invalid port '{}', expected a port (3000), a range (8000-8010) or a service name ({})", s, crate::port_aliases::known_names())
+    })
+}
+
+/// Parse a single port given as a number ("5432") or a service name ("postgres")
+pub fn parse_port(s: &str) -> Result<u16, String> {
+    s.trim()
+        .parse::<u16>()
+        .ok()
+        .or_else(|| crate::port_aliases::lookup(s))
+        .ok_or_else(|| format!("Invalid port '{}', expected a number or a service name ({})", s, crate::port_aliases::known_names()))
+}
+
+/// Parse a duration like "90s", "30m", "2h", "1d" or "1h30m" (a bare number means seconds)
+pub fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let s = s.trim();
+    if let Ok(seconds) = s.parse::<u64>() {
+        return Ok(std::time::Duration::from_secs(seconds));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            _ => return Err(format!("Invalid duration '{}': unknown unit '{}' (use s, m, h or d)", s, c)),
+        };
+        let value = digits
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid duration '{}': expected a number before '{}'", s, c))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(|| format!("Invalid duration '{}': too large", s))?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total == 0 {
+        return Err(format!("Invalid duration '{}', expected e.g. 30m, 2h or 1h30m", s));
+    }
+
+    Ok(std::time::Duration::from_secs(total))
+}
+
+/// Parse an --interface address: "127.0.0.1", "::1" or "[::1]", "0.0.0.0" for IPv4 wildcard binds
+pub fn parse_interface(s: &str) -> Result<std::net::IpAddr, String> {
+    let address = s.trim();
+    let address = address.strip_prefix('[').and_then(|a| a.strip_suffix(']')).unwrap_or(address);
+    address
+        .parse()
+        .map_err(|_| format!("Invalid interface address '{}', expected an IP address such as 127.0.0.1 or ::1", s))
+}
+
+/// Whether a socket of `family` bound to the lsof listen address `bind_address` ("127.0.0.1",
+/// "[::1]", "*") is bound to `interface`. lsof shows both wildcard binds as "*", so the family
+/// tells 0.0.0.0 from ::; an IPv4-mapped IPv6 address counts as its IPv4 address.
+pub fn socket_matches_interface(bind_address: &str, family: Option<AddressFamily>, interface: std::net::IpAddr) -> bool {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let address = bind_address.trim_start_matches('[').trim_end_matches(']');
+    // Drop an IPv6 zone ("fe80::1%en0")
+    let address = address.split('%').next().unwrap_or(address);
+    let bound = match (address, family) {
+        ("*", Some(AddressFamily::Ipv4)) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        ("*", Some(AddressFamily::Ipv6)) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        ("*", None) => return interface.is_unspecified(),
+        (address, _) => match address.parse::<IpAddr>() {
+            Ok(ip) => ip,
+            Err(_) => return false,
+        },
+    };
+    bound.to_canonical() == interface.to_canonical()
+}
+
+/// Whether an lsof/netstat listen address ("127.0.0.1", "[::1]", "localhost", "*") is loopback-only
+pub fn is_loopback_address(address: &str) -> bool {
+    let address = address.trim_start_matches('[').trim_end_matches(']');
+    address.eq_ignore_ascii_case("localhost")
+        || address
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(
+    group(clap::ArgGroup::new("printed_output").args(["once", "export_config", "wait_open", "history", "who", "list_ignored"])),
+    group(clap::ArgGroup::new("killing_mode").args(["auto_kill", "kill_pid", "kill_oldest", "kill_newest"])),
+    group(clap::ArgGroup::new("one_shot_kill").args(["kill_name", "kill_pid", "kill_oldest", "kill_newest"])),
+    group(clap::ArgGroup::new("one_shot_mode").multiple(false).args([
+        "stop", "kill_name", "kill_pid", "kill_oldest", "kill_newest", "wait_open", "once", "pick", "export_config", "doctor",
+        "config_check", "refresh", "stdio_rpc", "history", "status_line", "who", "list_ignored",
+    ])),
     name = "port-kill",
     about = "A lightweight macOS status bar app that monitors and manages development processes",
     version,
@@ -29,22 +299,38 @@ pub struct Args {
     #[arg(short, long, default_value = "6000")]
     pub end_port: u16,
 
-    /// Specific ports to monitor (comma-separated, overrides start/end port range)
-    #[arg(short, long, value_delimiter = ',')]
-    pub ports: Option<Vec<u16>>,
+    /// Specific ports, ranges and service names to monitor (comma-separated, e.g. 3000,8000-8010,postgres; overrides start/end port range)
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_port_spec)]
+    pub ports: Option<Vec<PortRange>>,
+
+    /// Monitor a preset of common development ports (combines with --ports, overrides start/end port range)
+    #[arg(long)]
+    pub common: bool,
 
     /// Ports to ignore (comma-separated, e.g., 5353,5000,7000 for Chromecast/AirDrop)
-    #[arg(long, value_delimiter = ',')]
+    #[arg(long, value_delimiter = ',', value_parser = parse_port)]
     pub ignore_ports: Option<Vec<u16>>,
 
     /// Process names to ignore (comma-separated, e.g., Chrome,ControlCe)
     #[arg(long, value_delimiter = ',')]
     pub ignore_processes: Option<Vec<String>>,
 
+    /// PIDs to ignore (comma-separated, e.g., 4242 to spare one process without ignoring its name)
+    #[arg(long = "ignore-pid", value_delimiter = ',')]
+    pub ignore_pids: Option<Vec<i32>>,
+
+    /// Ignore processes whose executable lies under one of these directories (comma-separated, e.g., /usr/bin,/opt/homebrew)
+    #[arg(long = "ignore-path-prefix", value_delimiter = ',')]
+    pub ignore_path_prefixes: Option<Vec<String>>,
+
     /// Run in console mode instead of status bar mode
     #[arg(short, long)]
     pub console: bool,
 
+    /// Keep the status bar menu to Kill All, Kill Newest Process, Pause Monitoring and Quit; the console lists every process
+    #[arg(long, conflicts_with = "console")]
+    pub compact_menu: bool,
+
     /// Enable verbose logging
     #[arg(short, long)]
     pub verbose: bool,
@@ -57,6 +343,47 @@ pub struct Args {
     #[arg(short = 'P', long)]
     pub show_pid: bool,
 
+    /// Show how long each process has been running (console mode)
+    #[arg(long)]
+    pub show_uptime: bool,
+
+    /// Show CPU and memory usage for each process (console mode)
+    #[arg(long)]
+    pub show_resources: bool,
+
+    /// Show the user owning each process (console mode)
+    #[arg(long)]
+    pub show_user: bool,
+
+    /// Show the child processes of each listening process as a tree (console mode)
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Order of the console process listing (port, pid, name, cpu, memory)
+    #[arg(long, default_value = "port", value_enum)]
+    pub sort: SortKey,
+
+    /// Group the console listing under a header per project directory (dir)
+    #[arg(long, value_enum)]
+    pub group_by: Option<GroupBy>,
+
+    /// Print each console process row from this template instead, e.g. "{port} {pid} {name} {cmd} {user}"
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "json")]
+    pub format: Option<String>,
+
+    /// Print the console process rows (and --once) as CSV: a header row, then "port,pid,name,command,user,container"
+    /// per process on every scan; the other console messages go to stderr
+    #[arg(long, conflicts_with_all = ["json", "format", "tree", "group_by", "quiet", "auto_kill"])]
+    pub csv: bool,
+
+    /// Only print when detected processes appear or disappear (console mode)
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Disable colored console output (also honored: NO_COLOR env var, non-TTY stdout)
+    #[arg(long)]
+    pub no_color: bool,
+
     /// Log level (info, warn, error, none)
     #[arg(long, default_value = "info", value_enum)]
     pub log_level: LogLevel,
@@ -65,17 +392,343 @@ pub struct Args {
     #[arg(long)]
     pub discover_all: bool,
 
+    /// Monitor processes whose name starts with NAME on whatever ports they hold (repeatable, e.g. --watch-process node)
+    #[arg(long, value_name = "NAME", value_delimiter = ',', conflicts_with_all = ["discover_all", "ports", "common"])]
+    pub watch_process: Vec<String>,
+
+    /// Also monitor the ports declared in this project directory (.env files, vite.config, package.json scripts)
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["discover_all", "watch_process"])]
+    pub from_project: Option<String>,
+
+    /// Also monitor the ports listed in this file, one port, range or service name per line (# starts a comment);
+    /// re-read when it changes with --watch-config
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["discover_all", "watch_process"])]
+    pub ports_file: Option<String>,
+
+    /// The entries read from --ports-file, monitored on top of --ports
+    #[arg(skip)]
+    pub file_ports: Vec<PortRange>,
+
+    /// Also monitor the processes holding this Unix socket, e.g. a database or docker.sock (repeatable, Unix only)
+    #[arg(long, value_name = "PATH", value_delimiter = ',')]
+    pub socket: Vec<String>,
+
+    /// Also report processes holding a monitored port without listening on it (ESTABLISHED, CLOSE_WAIT, ...),
+    /// to explain "address already in use"
+    #[arg(long, conflicts_with_all = ["discover_all", "watch_process"])]
+    pub include_established: bool,
+
+    /// Also scan inside other network namespaces (Docker/Podman containers, `ip netns`), whose listeners a plain
+    /// lsof can't see (Linux, needs root and nsenter)
+    #[arg(long)]
+    pub netns: bool,
+
+    /// Allow --discover-all to include protected system services (sshd, systemd, launchd, ...)
+    #[arg(long)]
+    pub no_safety: bool,
+
+    /// Only include processes by listen address (all, localhost, external)
+    #[arg(long, default_value = "all", value_enum)]
+    pub bind_filter: BindFilter,
+
+    /// Only include sockets bound to this local address, e.g. 127.0.0.1, ::1 or 172.17.0.1 (the Docker bridge);
+    /// 0.0.0.0 and :: select wildcard binds
+    #[arg(long, value_name = "ADDR", value_parser = parse_interface)]
+    pub interface: Option<std::net::IpAddr>,
+
+    /// Only include processes owned by this user, for display and killing
+    #[arg(long, value_name = "NAME")]
+    pub only_user: Option<String>,
+
+    /// Only include processes owned by the current user (--only-user with your own name)
+    #[arg(long, conflicts_with = "only_user")]
+    pub mine: bool,
+
+    /// Only kill processes that started within this long (e.g. 30m, 2h); older ones are spared
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub max_age: Option<std::time::Duration>,
+
+    /// Only display processes on ports in START-END; monitoring and "Kill All" are unaffected
+    #[arg(long, value_name = "START-END")]
+    pub show_range: Option<PortRange>,
+
     /// Path to configuration file (default: ./port-kill.toml)
-    #[arg(short = 'c', long, default_value = "port-kill.toml")]
+    #[arg(long, default_value = "port-kill.toml")]
     pub config: String,
+
+    /// Take monitored ports and ignore lists from --config and reload them when the file changes (console mode)
+    #[arg(long)]
+    pub watch_config: bool,
+
+    /// Also write logs to this file (rotated to <PATH>.1 when it reaches 10 MB)
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<String>,
+
+    /// Log output format (text, json)
+    #[arg(long, default_value = "text", value_enum)]
+    pub log_format: LogFormat,
+
+    /// Log every step of each kill (signal sent, liveness check, escalation, final state) with timings, at the kill_trace log target
+    #[arg(long)]
+    pub trace_kill: bool,
+
+    /// Send SIGQUIT before killing, so a hung JVM or Go server prints a thread dump, then kill as usual (Unix only)
+    #[arg(long)]
+    pub dump_before_kill: bool,
+
+    /// How long --dump-before-kill waits for the dump before the usual kill
+    #[arg(long, value_name = "DURATION", default_value = "2s", value_parser = parse_duration, requires = "dump_before_kill")]
+    pub dump_wait: std::time::Duration,
+
+    /// Run the console monitor detached in the background (Unix only)
+    #[arg(long, conflicts_with = "one_shot_mode")]
+    pub daemon: bool,
+
+    /// Stop a running daemon using its PID file
+    #[arg(long)]
+    pub stop: bool,
+
+    /// Kill every process with this name on the monitored ports and exit (repeatable, e.g. --kill-name node)
+    #[arg(long, value_name = "NAME", value_delimiter = ',')]
+    pub kill_name: Vec<String>,
+
+    /// Kill these PIDs (SIGTERM, then SIGKILL) unless ignored, and exit (repeatable, e.g. --kill-pid 1234)
+    #[arg(long, value_name = "PID", value_delimiter = ',', value_parser = clap::value_parser!(i32).range(1..))]
+    pub kill_pid: Vec<i32>,
+
+    /// Kill the monitored process that has been running the longest, report which one, and exit
+    #[arg(long)]
+    pub kill_oldest: bool,
+
+    /// Kill the most recently started monitored process (e.g. a duplicate dev server), report which one, and exit
+    #[arg(long)]
+    pub kill_newest: bool,
+
+    /// After --kill-name, --kill-pid, --kill-oldest or --kill-newest, wait until nothing listens on these ports any more (e.g. --wait-free 3000)
+    #[arg(long, value_name = "PORTS", value_delimiter = ',', value_parser = parse_port, requires = "one_shot_kill")]
+    pub wait_free: Vec<u16>,
+
+    /// With --wait-free, also wait for sockets lingering in TIME_WAIT or CLOSE_WAIT on those ports (uses netstat)
+    #[arg(long, requires = "wait_free")]
+    pub wait_lingering: bool,
+
+    /// Wait until something listens on this port, print it and exit (a readiness probe, e.g. --wait-open 3000)
+    #[arg(long, value_name = "PORT", value_parser = parse_port)]
+    pub wait_open: Option<u16>,
+
+    /// How long --wait-free and --wait-open wait (e.g. 30s, 1m)
+    #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = parse_duration)]
+    pub wait_timeout: std::time::Duration,
+
+    /// Print the current processes once and exit instead of monitoring
+    #[arg(long)]
+    pub once: bool,
+
+    /// Interactively fuzzy-search the current processes by name or port and kill the chosen one
+    #[arg(long)]
+    pub pick: bool,
+
+    /// Print the effective configuration (--config file merged with these options) and exit
+    #[arg(long)]
+    pub export_config: bool,
+
+    /// Check prerequisites (lsof, permissions, docker with --docker, the config file) and exit
+    #[arg(long)]
+    pub doctor: bool,
+
+    /// Load and fully validate a config file (default: --config) without running anything, print "OK" or the error, and exit
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub config_check: Option<Option<String>>,
+
+    /// Make the running status bar app or console daemon rescan right away, and exit (Unix only)
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Serve JSON-RPC 2.0 on stdin/stdout for editor integrations, until stdin closes
+    #[arg(long)]
+    pub stdio_rpc: bool,
+
+    /// Print the processes killed this session (by the status bar app or the console), newest first, and exit
+    #[arg(long)]
+    pub history: bool,
+
+    /// Scan once and print a single compact line (e.g. "ports: 3000,5173 (2)") for tmux or shell prompts, then exit
+    #[arg(long)]
+    pub status_line: bool,
+
+    /// Format of --status-line, with {count}, {ports} and {names} placeholders
+    #[arg(long, value_name = "FORMAT", default_value = DEFAULT_STATUS_FORMAT, requires = "status_line")]
+    pub status_format: String,
+
+    /// Show what holds this port (PID, name, full command, working directory, uptime) and how to kill it, then exit
+    #[arg(long, value_name = "PORT", value_parser = parse_port)]
+    pub who: Option<u16>,
+
+    /// Scan once and print the processes the safety list, ignore lists and filters leave out, each with the reason, then exit
+    #[arg(long)]
+    pub list_ignored: bool,
+
+    /// Print --once, --export-config, --wait-open, --history, --who or --list-ignored output as JSON
+    #[arg(long, requires = "printed_output")]
+    pub json: bool,
+
+    /// Print --export-config output as YAML
+    #[arg(long, requires = "export_config", conflicts_with_all = ["json", "once"])]
+    pub yaml: bool,
+
+    /// PID file used by --daemon, --stop and --refresh (default: $XDG_RUNTIME_DIR or temp dir /port-kill.pid)
+    #[arg(long, value_name = "PATH")]
+    pub pid_file: Option<String>,
+
+    /// Monitor a remote host over SSH instead of this machine (e.g. user@host, requires lsof on the remote)
+    #[arg(long, value_name = "USER@HOST")]
+    pub remote: Option<String>,
+
+    /// Serve Prometheus metrics on http://127.0.0.1:<PORT>/metrics (console mode)
+    #[arg(long, value_name = "PORT")]
+    pub metrics_port: Option<u16>,
+
+    /// Stream process added/removed/killed events as newline-delimited JSON to clients of this Unix socket (console mode)
+    #[arg(long, value_name = "PATH")]
+    pub event_socket: Option<String>,
+
+    /// Stop monitoring and exit cleanly after this long, e.g. 30m for a CI job (console mode)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub max_runtime: Option<std::time::Duration>,
+
+    /// On exit, print the session's runtime, kills per port and failed kills; skipped with --quiet (console mode)
+    #[arg(long)]
+    pub summary_on_exit: bool,
+
+    /// On exit, write a JSON report of the run (start and end time, monitored config, every kill and its outcome)
+    /// to this file (console mode, --kill-name, --kill-pid, --kill-oldest, --kill-newest)
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<PathBuf>,
+
+    /// Automatically kill any process that appears on these ports, every monitoring cycle (console mode, e.g. --auto-kill 3000,8080)
+    #[arg(long, value_name = "PORTS", value_delimiter = ',', value_parser = parse_port, conflicts_with = "one_shot_mode")]
+    pub auto_kill: Option<Vec<u16>>,
+
+    /// With --auto-kill, --kill-pid, --kill-oldest or --kill-newest, only log what would be killed instead of killing it
+    #[arg(long, requires = "killing_mode")]
+    pub dry_run: bool,
+
+    /// With --kill-name, kill more processes than the config's max_kill_without_confirm without asking
+    #[arg(short = 'y', long, requires = "kill_name")]
+    pub yes: bool,
+
+    /// With --kill-name, also kill processes that take a freed port right after the kill
+    /// (a supervisor respawning them), a few times per port
+    #[arg(long, requires = "kill_name")]
+    pub kill_respawns: bool,
+
+    /// Scan less often after a few scans without changes, up to the config's idle_backoff_max_seconds (30s by default);
+    /// any change returns to the normal interval
+    #[arg(long)]
+    pub interval_backoff_when_idle: bool,
+
+    /// Kill an lsof scan that takes longer than this (e.g. 5s, 1m)
+    #[arg(long, value_name = "DURATION", default_value = "10s", value_parser = parse_duration)]
+    pub lsof_timeout: std::time::Duration,
 }
 
 impl Args {
-    /// Get the list of ports to monitor
+    /// Whether a process owned by `owner` passes --only-user; unknown owners don't
+    pub fn matches_owner(&self, owner: Option<&str>) -> bool {
+        match self.only_user {
+            Some(ref user) => owner == Some(user.as_str()),
+            None => true,
+        }
+    }
+
+    /// Turn --mine into --only-user with the name of the user running port-kill (on the --remote host, if any)
+    pub fn resolve_mine(&mut self) -> Result<(), String> {
+        if !self.mine {
+            return Ok(());
+        }
+        let output = self.command_runner()
+            .run("id", &["-un"])
+            .map_err(|e| format!("--mine: failed to determine the current user: {}", e))?;
+        let user = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || user.is_empty() {
+            return Err("--mine: failed to determine the current user (`id -un` failed)".to_string());
+        }
+        self.only_user = Some(user);
+        Ok(())
+    }
+
+    /// Add the ports declared in the --from-project directory to --ports
+    pub fn resolve_project_ports(&mut self) -> Result<(), String> {
+        let Some(ref dir) = self.from_project else {
+            return Ok(());
+        };
+        let found = crate::project_ports::discover(std::path::Path::new(dir)).map_err(|e| format!("--from-project: {:#}", e))?;
+        if found.is_empty() {
+            return Err(format!("--from-project: no ports declared in {} (looked at .env files, vite.config and package.json scripts)", dir));
+        }
+
+        let ports = self.ports.get_or_insert_with(Vec::new);
+        for project_port in found {
+            if !ports.iter().any(|range| range.contains(project_port.port)) {
+                ports.push(PortRange::from(project_port.port));
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the --ports-file entries into `file_ports`
+    pub fn resolve_ports_file(&mut self) -> Result<(), String> {
+        let Some(ref path) = self.ports_file else {
+            return Ok(());
+        };
+        let ports = crate::ports_file::load(std::path::Path::new(path)).map_err(|e| format!("--ports-file: {:#}", e))?;
+        if ports.is_empty() {
+            return Err(format!("--ports-file: no ports listed in {}", path));
+        }
+        self.file_ports = ports;
+        Ok(())
+    }
+
+    /// --ports followed by the --ports-file entries; `None` if neither was given
+    fn port_specs(&self) -> Option<Vec<PortRange>> {
+        if self.ports.is_none() && self.file_ports.is_empty() {
+            return None;
+        }
+        Some(self.ports.iter().flatten().chain(&self.file_ports).copied().collect())
+    }
+
+    /// Whether a process on `port` should be displayed (--show-range only narrows the view)
+    pub fn is_port_shown(&self, port: u16) -> bool {
+        self.show_range.is_none_or(|range| range.contains(port))
+    }
+
+    /// The --ports and --ports-file entries with ranges expanded, in the order given and without duplicates
+    pub fn specific_ports(&self) -> Option<Vec<u16>> {
+        let mut seen = HashSet::new();
+        self.port_specs().map(|ranges| {
+            ranges
+                .iter()
+                .flat_map(PortRange::ports)
+                .filter(|port| seen.insert(*port))
+                .collect()
+        })
+    }
+
+    /// Get the list of ports to monitor (empty with --discover-all, meaning every port)
     pub fn get_ports_to_monitor(&self) -> Vec<u16> {
-        if let Some(ref specific_ports) = self.ports {
+        if self.discover_all {
+            // Auto-discovery scans every listening port
+            Vec::new()
+        } else if self.common {
+            // Use the preset, plus any specific ports
+            let mut ports = COMMON_DEV_PORTS.to_vec();
+            ports.extend(self.specific_ports().into_iter().flatten());
+            ports.sort_unstable();
+            ports.dedup();
+            ports
+        } else if let Some(specific_ports) = self.specific_ports() {
             // Use specific ports if provided
-            specific_ports.clone()
+            specific_ports
         } else {
             // Use port range
             (self.start_port..=self.end_port).collect()
@@ -97,11 +750,87 @@ impl Args {
         self.ignore_processes.clone().unwrap_or_default().into_iter().collect()
     }
 
+    /// Get a HashSet of PIDs to ignore for efficient lookup
+    pub fn get_ignore_pids_set(&self) -> HashSet<i32> {
+        self.ignore_pids.clone().unwrap_or_default().into_iter().collect()
+    }
+
+    /// Whether the all-ports safety list shields this process (PID 1 or a known system service)
+    pub fn is_protected_process(&self, pid: i32, name: &str) -> bool {
+        if !self.discover_all || self.no_safety {
+            return false;
+        }
+
+        pid == 1
+            || PROTECTED_PROCESSES.iter().any(|protected| {
+                *protected == name
+                    // Match lsof's truncated COMMAND column (e.g. "WindowSer")
+                    || (name.len() == LSOF_COMMAND_WIDTH && protected.starts_with(name))
+            })
+    }
+
+    /// Whether a process name matches one of the --kill-name names (case-insensitive)
+    pub fn matches_kill_name(&self, name: &str) -> bool {
+        self.kill_name.iter().any(|wanted| {
+            wanted.eq_ignore_ascii_case(name)
+                // Match lsof's truncated COMMAND column (e.g. "com.docke" for "com.docker.backend")
+                || (name.len() == LSOF_COMMAND_WIDTH
+                    && wanted.get(..LSOF_COMMAND_WIDTH).is_some_and(|prefix| prefix.eq_ignore_ascii_case(name)))
+        })
+    }
+
+    /// The --dump-before-kill wait, or `None` without a dump
+    pub fn dump_wait(&self) -> Option<std::time::Duration> {
+        self.dump_before_kill.then_some(self.dump_wait)
+    }
+
+    /// The config's kill escalation and retries with --dump-before-kill applied
+    pub fn kill_policy(&self, app: &AppConfig) -> KillPolicy {
+        app.kill_policy().with_dump(self.dump_wait())
+    }
+
+    /// Get the runner used to execute discovery and kill commands
+    pub fn command_runner(&self) -> Arc<dyn CommandRunner> {
+        match self.remote {
+            Some(ref target) => Arc::new(SshRunner::new(target)),
+            None => Arc::new(LocalRunner),
+        }
+    }
+
+    /// Whether `executable` lies under one of --ignore-path-prefix; whole path components
+    /// must match, so /usr/bin doesn't cover /usr/bin2
+    pub fn is_ignored_executable(&self, executable: &std::path::Path) -> bool {
+        self.ignore_path_prefixes.iter().flatten().any(|prefix| executable.starts_with(prefix))
+    }
+
+    /// Get the PID file path used by the daemon
+    pub fn get_pid_file(&self) -> PathBuf {
+        self.pid_file
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(crate::daemon::default_pid_file)
+    }
+
+    /// The file --config-check validates: its own PATH, or the --config file when given without one
+    pub fn config_check_path(&self) -> Option<String> {
+        self.config_check.as_ref().map(|path| path.clone().unwrap_or_else(|| self.config.clone()))
+    }
+
     /// Get a description of the port configuration
     pub fn get_port_description(&self) -> String {
         let mut description = if self.discover_all {
             "auto-discovering ALL listening processes on ANY port".to_string()
-        } else if let Some(ref specific_ports) = self.ports {
+        } else if !self.watch_process.is_empty() {
+            format!("processes named {} on any port", self.watch_process.join(", "))
+        } else if self.common {
+            match self.port_specs() {
+                Some(specific_ports) if !specific_ports.is_empty() => format!(
+                    "common dev ports plus specific ports: {}",
+                    specific_ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", ")
+                ),
+                _ => "common dev ports".to_string(),
+            }
+        } else if let Some(specific_ports) = self.port_specs() {
             format!("specific ports: {}", specific_ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "))
         } else {
             format!("port range: {}-{}", self.start_port, self.end_port)
@@ -122,10 +851,44 @@ impl Args {
             }
         }
 
+        if let Some(ref ignore_pids) = self.ignore_pids {
+            if !ignore_pids.is_empty() {
+                ignore_info.push(format!("ignoring PIDs: {}", ignore_pids.iter().map(|pid| pid.to_string()).collect::<Vec<_>>().join(", ")));
+            }
+        }
+
+        if let Some(ref prefixes) = self.ignore_path_prefixes {
+            if !prefixes.is_empty() {
+                ignore_info.push(format!("ignoring executables under: {}", prefixes.join(", ")));
+            }
+        }
+
         if !ignore_info.is_empty() {
             description.push_str(&format!(" ({})", ignore_info.join(", ")));
         }
 
+        if !self.socket.is_empty() {
+            description.push_str(&format!(" plus sockets: {}", self.socket.join(", ")));
+        }
+
+        match self.bind_filter {
+            BindFilter::All => {}
+            BindFilter::Localhost => description.push_str(" [localhost binds only]"),
+            BindFilter::External => description.push_str(" [external binds only]"),
+        }
+
+        if let Some(interface) = self.interface {
+            description.push_str(&format!(" [interface {} only]", interface));
+        }
+
+        if let Some(ref remote) = self.remote {
+            description.push_str(&format!(" on {}", remote));
+        }
+
+        if let Some(range) = self.show_range {
+            description.push_str(&format!(" [showing ports {} only]", range));
+        }
+
         description
     }
 
@@ -140,12 +903,12 @@ impl Args {
 
             // Validate specific ports if provided
             if let Some(ref specific_ports) = self.ports {
-                if specific_ports.is_empty() {
+                if specific_ports.is_empty() && !self.common {
                     return Err("At least one port must be specified".to_string());
                 }
 
-                for &port in specific_ports {
-                    if port == 0 {
+                for range in specific_ports {
+                    if range.contains(0) {
                         return Err("Port 0 is not valid".to_string());
                     }
                 }
@@ -161,6 +924,67 @@ impl Args {
             }
         }
 
+        if self.kill_name.iter().any(|name| name.trim().is_empty()) {
+            return Err("--kill-name cannot be empty".to_string());
+        }
+
+        if self.watch_process.iter().any(|name| name.trim().is_empty()) {
+            return Err("--watch-process cannot be empty".to_string());
+        }
+
+        if let Some(ref auto_kill) = self.auto_kill {
+            if auto_kill.contains(&0) {
+                return Err("Auto-kill port 0 is not valid".to_string());
+            }
+        }
+
+        if self.wait_free.contains(&0) {
+            return Err("--wait-free port 0 is not valid".to_string());
+        }
+
+        if self.only_user.as_deref().is_some_and(|user| user.trim().is_empty()) {
+            return Err("--only-user cannot be empty".to_string());
+        }
+
+        if cfg!(target_os = "windows") && (self.only_user.is_some() || self.mine) {
+            return Err("--only-user and --mine are not supported on Windows".to_string());
+        }
+
+        if let Some(path) = self.socket.iter().find(|path| !path.starts_with('/')) {
+            return Err(format!("--socket needs an absolute path, got '{}'", path));
+        }
+
+        if cfg!(target_os = "windows") && !self.socket.is_empty() {
+            return Err("--socket is not supported on Windows".to_string());
+        }
+
+        if self.who == Some(0) {
+            return Err("--who port 0 is not valid".to_string());
+        }
+
+        if self.wait_open == Some(0) {
+            return Err("--wait-open port 0 is not valid".to_string());
+        }
+
+        if self.lsof_timeout.is_zero() {
+            return Err("--lsof-timeout must be greater than zero".to_string());
+        }
+
+        if let Some(ref format) = self.format {
+            crate::row_format::RowTemplate::parse(format).map_err(|e| format!("Invalid --format: {}", e))?;
+        }
+
+        if self.max_runtime.is_some_and(|max_runtime| max_runtime.is_zero()) {
+            return Err("--max-runtime must be greater than zero".to_string());
+        }
+
+        // Validate remote target if provided
+        if let Some(ref remote) = self.remote {
+            if remote.trim().is_empty() || remote.starts_with('-') {
+                return Err(format!("Invalid remote host: '{}'", remote));
+            }
+        }
+
         // Validate ignore processes if provided
         if let Some(ref ignore_processes) = self.ignore_processes {
             for process_name in ignore_processes {
@@ -170,6 +994,15 @@ impl Args {
             }
         }
 
+        if let Some(pid) = self.ignore_pids.iter().flatten().find(|pid| **pid <= 0) {
+            return Err(format!("Ignore PID {} is not valid", pid));
+        }
+
+        // A relative prefix would depend on where port-kill was started
+        if let Some(prefix) = self.ignore_path_prefixes.iter().flatten().find(|prefix| !std::path::Path::new(prefix).is_absolute()) {
+            return Err(format!("Ignore path prefix '{}' must be an absolute path", prefix));
+        }
+
         Ok(())
     }
 }
@@ -205,20 +1038,329 @@ impl LogLevel {
 mod tests {
     use super::*;
 
+    fn default_args() -> Args {
+        Args::parse_from(["port-kill"])
+    }
+
+    #[test]
+    fn test_matches_kill_name() {
+        let args = Args::parse_from(["port-kill", "--kill-name", "node", "--kill-name", "com.docker.backend"]);
+
+        assert_eq!(args.kill_name, vec!["node", "com.docker.backend"]);
+        assert!(args.matches_kill_name("node"));
+        assert!(args.matches_kill_name("Node"));
+        assert!(args.matches_kill_name("com.docke"));
+        assert!(!args.matches_kill_name("nodemon"));
+        assert!(!default_args().matches_kill_name("node"));
+
+        assert!(Args::parse_from(["port-kill", "--kill-name", "node", "-y"]).yes);
+        assert!(Args::try_parse_from(["port-kill", "--yes"]).is_err());
+
+        assert!(Args::parse_from(["port-kill", "--kill-name", "node", "--kill-respawns"]).kill_respawns);
+        assert!(Args::try_parse_from(["port-kill", "--kill-respawns"]).is_err());
+    }
+
+    #[test]
+    fn test_once_flags() {
+        let args = Args::parse_from(["port-kill", "--once", "--json"]);
+        assert!(args.once && args.json);
+
+        assert!(Args::try_parse_from(["port-kill", "--json"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--once", "--daemon"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--once", "--kill-name", "node"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--pick", "--once"]).is_err());
+    }
+
+    #[test]
+    fn test_export_config_flags() {
+        let args = Args::parse_from(["port-kill", "--export-config", "--json"]);
+        assert!(args.export_config && args.json);
+        assert!(Args::parse_from(["port-kill", "--export-config", "--yaml"]).yaml);
+
+        assert!(Args::try_parse_from(["port-kill", "--yaml"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--once", "--yaml"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--export-config", "--json", "--yaml"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--export-config", "--once"]).is_err());
+    }
+
+    #[test]
+    fn test_kill_oldest_and_newest_flags() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000,3001", "--kill-newest", "--dry-run", "--wait-free", "3001"]);
+        assert!(args.kill_newest && args.dry_run);
+        assert!(Args::parse_from(["port-kill", "--kill-oldest"]).kill_oldest);
+
+        assert!(Args::try_parse_from(["port-kill", "--kill-oldest", "--kill-newest"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-newest", "--kill-pid", "42"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-oldest", "--once"]).is_err());
+    }
+
+    #[test]
+    fn test_kill_pid_flags() {
+        let args = Args::parse_from(["port-kill", "--kill-pid", "1234,5678", "--kill-pid", "42", "--dry-run"]);
+        assert_eq!(args.kill_pid, vec![1234, 5678, 42]);
+        assert!(args.dry_run);
+        assert!(Args::parse_from(["port-kill", "--auto-kill", "3000", "--dry-run"]).dry_run);
+
+        assert!(Args::try_parse_from(["port-kill", "--dry-run"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-pid", "0"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-pid", "-1"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-pid", "1234", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--kill-pid", "1234", "--auto-kill", "3000"]).is_err());
+    }
+
+    #[test]
+    fn test_wait_free_flags() {
+        let args = Args::parse_from(["port-kill", "--kill-name", "node", "--wait-free", "3000,3001", "--wait-timeout", "30s"]);
+        assert_eq!(args.wait_free, vec![3000, 3001]);
+        assert_eq!(args.wait_timeout, std::time::Duration::from_secs(30));
+        assert!(Args::parse_from(["port-kill", "--kill-pid", "1234", "--wait-free", "3000"]).wait_free == vec![3000]);
+
+        assert!(Args::try_parse_from(["port-kill", "--wait-free", "3000"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--once", "--wait-free", "3000"]).is_err());
+
+        assert!(Args::parse_from(["port-kill", "--kill-name", "node", "--wait-free", "3000", "--wait-lingering"]).wait_lingering);
+        assert!(Args::try_parse_from(["port-kill", "--kill-name", "node", "--wait-lingering"]).is_err());
+    }
+
+    #[test]
+    fn test_wait_open_flags() {
+        let args = Args::parse_from(["port-kill", "--wait-open", "3000", "--json", "--wait-timeout", "1m"]);
+        assert_eq!(args.wait_open, Some(3000));
+        assert!(args.json);
+
+        assert!(Args::try_parse_from(["port-kill", "--wait-open", "3000", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--wait-open", "3000", "--kill-name", "node"]).is_err());
+        assert_eq!(Args::parse_from(["port-kill", "--wait-open", "0"]).validate(), Err("--wait-open port 0 is not valid".to_string()));
+    }
+
+    #[test]
+    fn test_owner_filter() {
+        let args = Args::parse_from(["port-kill", "--only-user", "dev"]);
+        assert!(args.matches_owner(Some("dev")));
+        assert!(!args.matches_owner(Some("root")));
+        assert!(!args.matches_owner(None));
+        assert!(default_args().matches_owner(Some("root")));
+        assert!(default_args().matches_owner(None));
+
+        assert!(Args::try_parse_from(["port-kill", "--mine", "--only-user", "dev"]).is_err());
+        assert!(Args::parse_from(["port-kill", "--only-user", " "]).validate().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_mine() {
+        let mut args = Args::parse_from(["port-kill", "--mine"]);
+        args.resolve_mine().unwrap();
+
+        let expected = std::process::Command::new("id").arg("-un").output().unwrap();
+        assert_eq!(args.only_user.as_deref(), Some(String::from_utf8_lossy(&expected.stdout).trim()));
+    }
+
+    #[test]
+    fn test_resolve_project_ports() {
+        let dir = std::env::temp_dir().join(format!("port-kill-cli-project-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "PORT=3000\nAPI_PORT=8080\n").unwrap();
+        let dir_arg = dir.to_string_lossy().to_string();
+
+        let mut args = Args::parse_from(["port-kill", "--from-project", &dir_arg]);
+        args.resolve_project_ports().unwrap();
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 8080]);
+
+        // Added to --ports, without repeating ports already covered
+        let mut args = Args::parse_from(["port-kill", "--ports", "8000-8080", "--from-project", &dir_arg]);
+        args.resolve_project_ports().unwrap();
+        assert_eq!(args.ports.as_ref().unwrap().len(), 2);
+        assert_eq!(args.get_ports_to_monitor().len(), 82);
+
+        std::fs::write(dir.join(".env"), "DEBUG=1\n").unwrap();
+        assert!(Args::parse_from(["port-kill", "--from-project", &dir_arg]).resolve_project_ports().unwrap_err().contains("no ports declared"));
+        assert!(Args::try_parse_from(["port-kill", "--from-project", &dir_arg, "--discover-all"]).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_history_flag() {
+        assert!(Args::parse_from(["port-kill", "--history", "--json"]).history);
+
+        assert!(Args::try_parse_from(["port-kill", "--history", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--history", "--kill-name", "node"]).is_err());
+    }
+
+    #[test]
+    fn test_status_line_flags() {
+        let args = Args::parse_from(["port-kill", "--status-line"]);
+        assert!(args.status_line);
+        assert_eq!(args.status_format, DEFAULT_STATUS_FORMAT);
+        assert_eq!(Args::parse_from(["port-kill", "--status-line", "--status-format", "{count}"]).status_format, "{count}");
+
+        assert!(Args::try_parse_from(["port-kill", "--status-format", "{count}"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--status-line", "--once"]).is_err());
+    }
+
+    #[test]
+    fn test_who_flags() {
+        let args = Args::parse_from(["port-kill", "--who", "3000", "--json"]);
+        assert_eq!(args.who, Some(3000));
+        assert!(args.json);
+
+        assert!(Args::try_parse_from(["port-kill", "--who", "3000", "--once"]).is_err());
+        assert!(Args::parse_from(["port-kill", "--who", "0"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_list_ignored_flag() {
+        let args = Args::parse_from(["port-kill", "--list-ignored", "--json", "--ignore-ports", "5353"]);
+        assert!(args.list_ignored && args.json);
+
+        assert!(Args::try_parse_from(["port-kill", "--list-ignored", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--list-ignored", "--who", "3000"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_ports_file() {
+        let path = std::env::temp_dir().join(format!("port-kill-cli-ports-{}.txt", std::process::id()));
+        std::fs::write(&path, "# Written by the dev proxy
+3001
+5173 # vite
+
+8000-8002
+").unwrap();
+        let path_arg = path.to_string_lossy().to_string();
+
+        let mut args = Args::parse_from(["port-kill", "--ports-file", &path_arg]);
+        args.resolve_ports_file().unwrap();
+        assert_eq!(args.get_ports_to_monitor(), vec![3001, 5173, 8000, 8001, 8002]);
+        assert_eq!(args.get_port_description(), "specific ports: 3001, 5173, 8000-8002");
+
+        // Added to --ports, each port once
+        let mut args = Args::parse_from(["port-kill", "--ports", "3000,3001", "--ports-file", &path_arg]);
+        args.resolve_ports_file().unwrap();
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 3001, 5173, 8000, 8001, 8002]);
+
+        std::fs::write(&path, "# nothing yet
+").unwrap();
+        assert!(Args::parse_from(["port-kill", "--ports-file", &path_arg]).resolve_ports_file().unwrap_err().contains("no ports listed"));
+        std::fs::write(&path, "3000
+localhost:3000
+").unwrap();
+        assert!(Args::parse_from(["port-kill", "--ports-file", &path_arg]).resolve_ports_file().unwrap_err().contains("line 2"));
+        assert!(Args::try_parse_from(["port-kill", "--ports-file", &path_arg, "--discover-all"]).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_config_check_flag() {
+        let args = Args::parse_from(["port-kill", "--config-check", "other.toml"]);
+        assert_eq!(args.config_check_path().as_deref(), Some("other.toml"));
+        let args = Args::parse_from(["port-kill", "--config", "mine.toml", "--config-check"]);
+        assert_eq!(args.config_check_path().as_deref(), Some("mine.toml"));
+        assert_eq!(Args::parse_from(["port-kill"]).config_check_path(), None);
+
+        assert!(Args::try_parse_from(["port-kill", "--config-check", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--config-check", "--doctor"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--config-check", "--refresh"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--config-check", "--daemon"]).is_err());
+    }
+
+    #[test]
+    fn test_one_shot_modes_exclude_each_other() {
+        assert!(Args::try_parse_from(["port-kill", "--kill-oldest", "--wait-open", "3000"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--status-line", "--stdio-rpc"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--auto-kill", "3000", "--list-ignored"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--daemon", "--auto-kill", "3000"]).is_ok());
+    }
+
+    #[test]
+    fn test_doctor_flag() {
+        assert!(Args::parse_from(["port-kill", "--doctor", "--docker"]).doctor);
+
+        assert!(Args::try_parse_from(["port-kill", "--doctor", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--doctor", "--json"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--doctor", "--daemon"]).is_err());
+    }
+
+    #[test]
+    fn test_refresh_flag() {
+        assert!(Args::parse_from(["port-kill", "--refresh", "--pid-file", "/tmp/port-kill.pid"]).refresh);
+
+        assert!(Args::try_parse_from(["port-kill", "--refresh", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--refresh", "--daemon"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--refresh", "--kill-pid", "1234"]).is_err());
+    }
+
+    #[test]
+    fn test_stdio_rpc_flag() {
+        assert!(Args::parse_from(["port-kill", "--stdio-rpc", "--ports", "3000,8080"]).stdio_rpc);
+
+        assert!(Args::try_parse_from(["port-kill", "--stdio-rpc", "--once"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--stdio-rpc", "--daemon"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--stdio-rpc", "--json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(2 * 86_400)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("5m3").is_err());
+    }
+
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!("3000-3100".parse::<PortRange>(), Ok(PortRange { start: 3000, end: 3100 }));
+        assert_eq!("8080-8080".parse::<PortRange>(), Ok(PortRange { start: 8080, end: 8080 }));
+        assert!("3100-3000".parse::<PortRange>().is_err());
+        assert!("3000".parse::<PortRange>().is_err());
+        assert!("3000-70000".parse::<PortRange>().is_err());
+    }
+
+    #[test]
+    fn test_show_range_only_narrows_display() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000,5173,8080", "--show-range", "3000-5999"]);
+
+        assert!(args.is_port_shown(3000));
+        assert!(args.is_port_shown(5173));
+        assert!(!args.is_port_shown(8080));
+        // Monitoring still covers every configured port
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 5173, 8080]);
+        assert!(default_args().is_port_shown(8080));
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let vite = ProcessInfo { memory_bytes: Some(200), ..ProcessInfo::fixture(5173, 300, "node") };
+        let django = ProcessInfo::fixture(8000, 100, "Python");
+        let rails = ProcessInfo { memory_bytes: Some(900), ..ProcessInfo::fixture(3000, 200, "ruby") };
+
+        let sorted_ports = |key: SortKey| {
+            let mut processes = vec![&vite, &django, &rails];
+            key.sort(&mut processes);
+            processes.iter().map(|p| p.port).collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted_ports(SortKey::Port), vec![3000, 5173, 8000]);
+        assert_eq!(sorted_ports(SortKey::Pid), vec![8000, 3000, 5173]);
+        assert_eq!(sorted_ports(SortKey::Name), vec![5173, 8000, 3000]);
+        // Unknown usage sorts last
+        assert_eq!(sorted_ports(SortKey::Memory), vec![3000, 5173, 8000]);
+        assert_eq!(sorted_ports(SortKey::Cpu), vec![3000, 5173, 8000]);
+    }
+
     #[test]
     fn test_get_ports_to_monitor_range() {
         let args = Args {
             start_port: 3000,
             end_port: 3005,
-            ports: None,
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         let ports = args.get_ports_to_monitor();
@@ -228,37 +1370,91 @@ mod tests {
     #[test]
     fn test_get_ports_to_monitor_specific() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: Some(vec![3000, 8000, 8080]),
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ports: Some(vec![3000.into(), 8000.into(), 8080.into()]),
+            ..default_args()
         };
 
         let ports = args.get_ports_to_monitor();
         assert_eq!(ports, vec![3000, 8000, 8080]);
     }
 
+    #[test]
+    fn test_mixed_port_specs() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000,3001,8000-8003,9229,3001"]);
+
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 3001, 8000, 8001, 8002, 8003, 9229]);
+        assert_eq!(args.get_port_description(), "specific ports: 3000, 3001, 8000-8003, 9229, 3001");
+        assert!(args.validate().is_ok());
+
+        let error = Args::try_parse_from(["port-kill", "--ports", "3000,8010-8000"]).unwrap_err().to_string();
+        assert!(error.contains("start port is greater than end port"), "{}", error);
+        let error = Args::try_parse_from(["port-kill", "--ports", "3000,web"]).unwrap_err().to_string();
+        assert!(error.contains("Invalid port 'web'"), "{}", error);
+        assert!(Args::try_parse_from(["port-kill", "--ports", "8000-"]).is_err());
+        assert!(Args::parse_from(["port-kill", "--ports", "0-10"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_service_name_ports() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000,postgres,Redis", "--ignore-ports", "mysql"]);
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 5432, 6379]);
+        assert_eq!(args.ignore_ports, Some(vec![3306]));
+
+        let args = Args::parse_from(["port-kill", "--console", "--auto-kill", "mongodb,8080"]);
+        assert_eq!(args.auto_kill, Some(vec![27017, 8080]));
+        assert_eq!(Args::parse_from(["port-kill", "--who", "postgres"]).who, Some(5432));
+
+        let error = Args::try_parse_from(["port-kill", "--auto-kill", "postgress"]).unwrap_err().to_string();
+        assert!(error.contains("Invalid port 'postgress', expected a number or a service name (postgres, postgresql, mysql"), "{}", error);
+    }
+
+    #[test]
+    fn test_get_ports_to_monitor_common_preset() {
+        let args = Args {
+            common: true,
+            ..default_args()
+        };
+        assert_eq!(args.get_ports_to_monitor(), COMMON_DEV_PORTS.to_vec());
+
+        // --ports adds to the preset instead of replacing it
+        let args = Args {
+            common: true,
+            ports: Some(vec![7777.into(), 3000.into()]),
+            ..default_args()
+        };
+        let ports = args.get_ports_to_monitor();
+        assert!(ports.contains(&7777));
+        assert!(ports.contains(&5173));
+        assert_eq!(ports.iter().filter(|&&p| p == 3000).count(), 1);
+        assert_eq!(args.get_port_description(), "common dev ports plus specific ports: 7777, 3000");
+    }
+
+    #[test]
+    fn test_is_protected_process() {
+        let args = Args {
+            discover_all: true,
+            ..default_args()
+        };
+        assert!(args.is_protected_process(1, "init"));
+        assert!(args.is_protected_process(400, "sshd"));
+        assert!(args.is_protected_process(500, "WindowSer"));
+        assert!(!args.is_protected_process(600, "node"));
+
+        // Only active in all-ports mode, and --no-safety turns it off
+        assert!(!default_args().is_protected_process(400, "sshd"));
+        let args = Args {
+            discover_all: true,
+            no_safety: true,
+            ..default_args()
+        };
+        assert!(!args.is_protected_process(400, "sshd"));
+    }
+
     #[test]
     fn test_get_ignore_ports_set() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
             ignore_ports: Some(vec![5353, 5000, 7000]),
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         let ignore_ports = args.get_ignore_ports_set();
@@ -268,17 +1464,8 @@ mod tests {
     #[test]
     fn test_get_ignore_processes_set() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
-            ignore_ports: None,
             ignore_processes: Some(vec!["Chrome".to_string(), "ControlCe".to_string()]),
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         let ignore_processes = args.get_ignore_processes_set();
@@ -288,20 +1475,16 @@ mod tests {
     #[test]
     fn test_get_port_description_with_ignores() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
             ignore_ports: Some(vec![5353, 5000]),
             ignore_processes: Some(vec!["Chrome".to_string(), "ControlCe".to_string()]),
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert_eq!(args.get_port_description(), "port range: 2000-6000 (ignoring ports: 5353, 5000, ignoring processes: Chrome, ControlCe)");
+
+        let args = Args::parse_from(["port-kill", "--ignore-pid", "4242,4343"]);
+        assert_eq!(args.get_ignore_pids_set(), HashSet::from([4242, 4343]));
+        assert_eq!(args.get_port_description(), "port range: 2000-6000 (ignoring PIDs: 4242, 4343)");
     }
 
     #[test]
@@ -309,15 +1492,7 @@ mod tests {
         let args = Args {
             start_port: 3000,
             end_port: 3010,
-            ports: None,
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert_eq!(args.get_port_description(), "port range: 3000-3010");
@@ -326,17 +1501,8 @@ mod tests {
     #[test]
     fn test_get_port_description_specific() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: Some(vec![3000, 8000, 8080]),
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ports: Some(vec![3000.into(), 8000.into(), 8080.into()]),
+            ..default_args()
         };
 
         assert_eq!(args.get_port_description(), "specific ports: 3000, 8000, 8080");
@@ -347,15 +1513,7 @@ mod tests {
         let args = Args {
             start_port: 3000,
             end_port: 3010,
-            ports: None,
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert!(args.validate().is_ok());
@@ -366,15 +1524,7 @@ mod tests {
         let args = Args {
             start_port: 3010,
             end_port: 3000,
-            ports: None,
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert!(args.validate().is_err());
@@ -383,55 +1533,214 @@ mod tests {
     #[test]
     fn test_validation_empty_specific_ports() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
             ports: Some(vec![]),
-            ignore_ports: None,
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert!(args.validate().is_err());
     }
 
+    #[test]
+    fn test_lsof_timeout() {
+        assert_eq!(default_args().lsof_timeout, std::time::Duration::from_secs(10));
+
+        let args = Args::parse_from(["port-kill", "--lsof-timeout", "2s"]);
+        assert_eq!(args.lsof_timeout, std::time::Duration::from_secs(2));
+        assert!(args.validate().is_ok());
+
+        let args = Args::parse_from(["port-kill", "--lsof-timeout", "0"]);
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_format_template_is_validated() {
+        let args = Args::parse_from(["port-kill", "--console", "--once", "--format", "{port} {pid} {name} {cmd} {user}"]);
+        assert!(args.validate().is_ok());
+
+        let error = Args::parse_from(["port-kill", "--console", "--format", "{port} {uid}"]).validate().unwrap_err();
+        assert!(error.starts_with("Invalid --format: unknown placeholder {uid}"), "{}", error);
+        assert!(Args::try_parse_from(["port-kill", "--console", "--once", "--json", "--format", "{port}"]).is_err());
+    }
+
+    #[test]
+    fn test_max_runtime() {
+        assert_eq!(default_args().max_runtime, None);
+
+        let args = Args::parse_from(["port-kill", "--console", "--max-runtime", "1h30m"]);
+        assert_eq!(args.max_runtime, Some(std::time::Duration::from_secs(5_400)));
+        assert!(args.validate().is_ok());
+
+        assert!(Args::parse_from(["port-kill", "--max-runtime", "0"]).validate().is_err());
+        assert!(Args::parse_from(["port-kill", "--console", "--max-runtime", "30m", "--summary-on-exit"]).summary_on_exit);
+        assert!(Args::try_parse_from(["port-kill", "--max-runtime", "soon"]).is_err());
+    }
+
     #[test]
     fn test_validation_invalid_ignore_port() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
             ignore_ports: Some(vec![0]),
-            ignore_processes: None,
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
         };
 
         assert!(args.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_invalid_ignore_pid() {
+        let args = Args {
+            ignore_pids: Some(vec![4242, 0]),
+            ..default_args()
+        };
+
+        assert_eq!(args.validate(), Err("Ignore PID 0 is not valid".to_string()));
+    }
+
+    #[test]
+    fn test_ignore_path_prefixes() {
+        use std::path::Path;
+
+        let args = Args::parse_from(["port-kill", "--ignore-path-prefix", "/usr/bin,/opt/homebrew/"]);
+        assert!(args.validate().is_ok());
+        assert!(args.is_ignored_executable(Path::new("/usr/bin/node")));
+        assert!(args.is_ignored_executable(Path::new("/opt/homebrew/Cellar/node/21.0.0/bin/node")));
+        assert!(!args.is_ignored_executable(Path::new("/usr/bin2/node")));
+        assert!(!args.is_ignored_executable(Path::new("/home/me/.nvm/versions/node/v20.11.0/bin/node")));
+        assert!(!default_args().is_ignored_executable(Path::new("/usr/bin/node")));
+        assert_eq!(args.get_port_description(), "port range: 2000-6000 (ignoring executables under: /usr/bin, /opt/homebrew/)");
+
+        let relative = Args::parse_from(["port-kill", "--ignore-path-prefix", "node_modules/.bin"]);
+        assert_eq!(relative.validate(), Err("Ignore path prefix 'node_modules/.bin' must be an absolute path".to_string()));
+    }
+
     #[test]
     fn test_validation_empty_ignore_process() {
         let args = Args {
-            start_port: 2000,
-            end_port: 6000,
-            ports: None,
-            ignore_ports: None,
             ignore_processes: Some(vec!["".to_string()]),
-            console: false,
-            verbose: false,
-            docker: false,
-            show_pid: false,
-            log_level: LogLevel::Info,
-            discover_all: false,
+            ..default_args()
+        };
+
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_get_port_description_remote() {
+        let args = Args {
+            ports: Some(vec![3000.into()]),
+            remote: Some("dev@build-box".to_string()),
+            ..default_args()
+        };
+
+        assert_eq!(args.get_port_description(), "specific ports: 3000 on dev@build-box");
+        assert!(args.command_runner().is_remote());
+    }
+
+    #[test]
+    fn test_watch_process() {
+        let args = Args::parse_from(["port-kill", "--watch-process", "node,deno"]);
+        assert_eq!(args.watch_process, vec!["node", "deno"]);
+        assert_eq!(args.get_port_description(), "processes named node, deno on any port");
+        assert!(args.validate().is_ok());
+
+        assert!(Args::try_parse_from(["port-kill", "--watch-process", "node", "--discover-all"]).is_err());
+        assert!(Args::parse_from(["port-kill", "--watch-process", " "]).validate().is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        assert!(Args::parse_from(["port-kill", "--console", "--csv"]).csv);
+        assert!(Args::parse_from(["port-kill", "--once", "--csv"]).csv);
+        // Anything but rows would break the CSV
+        assert!(Args::try_parse_from(["port-kill", "--once", "--csv", "--json"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--console", "--csv", "--format", "{port}"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--console", "--csv", "--quiet"]).is_err());
+    }
+
+    #[test]
+    fn test_netns() {
+        assert!(!Args::parse_from(["port-kill", "--ports", "3000"]).netns);
+        assert!(Args::parse_from(["port-kill", "--discover-all", "--netns"]).netns);
+    }
+
+    #[test]
+    fn test_include_established() {
+        assert!(Args::parse_from(["port-kill", "--ports", "3000", "--include-established"]).include_established);
+        // Every outgoing connection has some local port, so it needs specific ports
+        assert!(Args::try_parse_from(["port-kill", "--include-established", "--discover-all"]).is_err());
+        assert!(Args::try_parse_from(["port-kill", "--include-established", "--watch-process", "node"]).is_err());
+    }
+
+    #[test]
+    fn test_socket_paths() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000", "--socket", "/tmp/app.sock,/var/run/docker.sock"]);
+        assert_eq!(args.socket, vec!["/tmp/app.sock", "/var/run/docker.sock"]);
+        assert_eq!(args.get_port_description(), "specific ports: 3000 plus sockets: /tmp/app.sock, /var/run/docker.sock");
+        assert_eq!(args.validate().is_ok(), cfg!(not(target_os = "windows")));
+
+        assert!(Args::parse_from(["port-kill", "--socket", "app.sock"]).validate().is_err());
+    }
+
+    #[test]
+    fn test_bind_filter_matches() {
+        assert!(BindFilter::Localhost.matches(Some("127.0.0.1")));
+        assert!(BindFilter::Localhost.matches(Some("[::1]")));
+        assert!(BindFilter::Localhost.matches(Some("localhost")));
+        assert!(!BindFilter::Localhost.matches(Some("*")));
+
+        assert!(BindFilter::External.matches(Some("*")));
+        assert!(BindFilter::External.matches(Some("0.0.0.0")));
+        assert!(BindFilter::External.matches(Some("[::]")));
+        assert!(BindFilter::External.matches(Some("192.168.1.20")));
+        assert!(!BindFilter::External.matches(Some("127.0.0.1")));
+
+        // Unknown bind addresses only pass the default filter
+        assert!(BindFilter::All.matches(None));
+        assert!(!BindFilter::Localhost.matches(None));
+        assert!(!BindFilter::External.matches(None));
+    }
+
+    #[test]
+    fn test_dump_before_kill() {
+        assert_eq!(Args::parse_from(["port-kill"]).kill_policy(&AppConfig::default()), KillPolicy::default());
+
+        let args = Args::parse_from(["port-kill", "--dump-before-kill"]);
+        assert_eq!(args.kill_policy(&AppConfig::default()).dump_wait, Some(std::time::Duration::from_secs(2)));
+        let args = Args::parse_from(["port-kill", "--dump-before-kill", "--dump-wait", "5s"]);
+        assert_eq!(args.dump_wait(), Some(std::time::Duration::from_secs(5)));
+        assert!(Args::try_parse_from(["port-kill", "--dump-wait", "5s"]).is_err());
+    }
+
+    #[test]
+    fn test_compact_menu() {
+        assert!(Args::parse_from(["port-kill", "--compact-menu"]).compact_menu);
+        // The console has no menu
+        assert!(Args::try_parse_from(["port-kill", "--console", "--compact-menu"]).is_err());
+    }
+
+    #[test]
+    fn test_interface() {
+        let args = Args::parse_from(["port-kill", "--ports", "3000", "--interface", "[::1]"]);
+        assert_eq!(args.interface, Some("::1".parse().unwrap()));
+        assert_eq!(args.get_port_description(), "specific ports: 3000 [interface ::1 only]");
+        assert!(Args::try_parse_from(["port-kill", "--interface", "eth0"]).is_err());
+
+        let localhost = "127.0.0.1".parse().unwrap();
+        assert!(socket_matches_interface("127.0.0.1", Some(AddressFamily::Ipv4), localhost));
+        assert!(socket_matches_interface("[::ffff:127.0.0.1]", Some(AddressFamily::Ipv6), localhost));
+        assert!(!socket_matches_interface("[::1]", Some(AddressFamily::Ipv6), localhost));
+        assert!(!socket_matches_interface("*", Some(AddressFamily::Ipv4), localhost));
+
+        // lsof shows both wildcard binds as "*"
+        assert!(socket_matches_interface("*", Some(AddressFamily::Ipv4), "0.0.0.0".parse().unwrap()));
+        assert!(!socket_matches_interface("*", Some(AddressFamily::Ipv6), "0.0.0.0".parse().unwrap()));
+        assert!(socket_matches_interface("*", Some(AddressFamily::Ipv6), "::".parse().unwrap()));
+        assert!(socket_matches_interface("[fe80::1%en0]", Some(AddressFamily::Ipv6), "fe80::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_validation_invalid_remote() {
+        let args = Args {
+            remote: Some("-oProxyCommand=evil".to_string()),
+            ..default_args()
         };
 
         assert!(args.validate().is_err());
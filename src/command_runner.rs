@@ -0,0 +1,199 @@
+use std::io::{self, Read};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a command run with a timeout is checked for completion
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Executes the external tools (lsof, ps, docker, kill) that discovery and
+/// termination rely on, either on this machine or on a remote host.
+pub trait CommandRunner: Send + Sync {
+    /// Run `program` with `args` and capture its output
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output>;
+
+    /// Like `run`, but kill the command and fail with `TimedOut` if it runs longer than `timeout`
+    fn run_with_timeout(&self, program: &str, args: &[&str], _timeout: Duration) -> io::Result<Output> {
+        self.run(program, args)
+    }
+
+    /// Human-readable description of where commands are executed
+    fn describe(&self) -> String;
+
+    /// Whether commands run on another machine (signals must then be sent via `kill`)
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+/// Runs commands directly on the local machine
+#[derive(Debug, Clone, Default)]
+pub struct LocalRunner;
+
+impl CommandRunner for LocalRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        Command::new(program).args(args).output()
+    }
+
+    fn run_with_timeout(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<Output> {
+        output_with_timeout(Command::new(program).args(args), timeout)
+    }
+
+    fn describe(&self) -> String {
+        "localhost".to_string()
+    }
+}
+
+/// Runs commands on a remote host over SSH (e.g. `user@host`)
+#[derive(Debug, Clone)]
+pub struct SshRunner {
+    target: String,
+}
+
+impl SshRunner {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+        }
+    }
+
+    /// Build the remote command line, quoting each argument for the remote shell
+    pub fn remote_command(program: &str, args: &[&str]) -> String {
+        std::iter::once(program)
+            .chain(args.iter().copied())
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn command(&self, program: &str, args: &[&str]) -> Command {
+        // BatchMode prevents ssh from blocking on a password prompt inside the monitor loop
+        let mut command = Command::new("ssh");
+        command
+            .args(["-o", "BatchMode=yes", &self.target, "--"])
+            .arg(Self::remote_command(program, args));
+        command
+    }
+}
+
+impl CommandRunner for SshRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+        self.command(program, args).output()
+    }
+
+    fn run_with_timeout(&self, program: &str, args: &[&str], timeout: Duration) -> io::Result<Output> {
+        // Killing ssh drops the connection, which also hangs up the remote command
+        output_with_timeout(&mut self.command(program, args), timeout)
+    }
+
+    fn describe(&self) -> String {
+        format!("{} (via SSH)", self.target)
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
+
+/// Run `command` to completion like `Command::output`, killing it if it outlives `timeout`
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes while waiting, so a chatty command can't stall on a full pipe buffer
+    let stdout = read_to_end_in_background(child.stdout.take());
+    let stderr = read_to_end_in_background(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("command did not finish within {:?} and was killed", timeout),
+            ));
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_to_end_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// Quote an argument for a POSIX shell, leaving simple tokens untouched
+pub fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+
+    if is_safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote_simple_tokens() {
+        assert_eq!(shell_quote("lsof"), "lsof");
+        assert_eq!(shell_quote("-sTCP:LISTEN"), "-sTCP:LISTEN");
+        assert_eq!(shell_quote(":3000,3001"), ":3000,3001");
+    }
+
+    #[test]
+    fn test_shell_quote_special_characters() {
+        assert_eq!(shell_quote("table {{.ID}}\t{{.Names}}"), "'table {{.ID}}\t{{.Names}}'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_timeout() {
+        let output = LocalRunner.run_with_timeout("echo", &["hello"], Duration::from_secs(5)).unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+
+        let started = Instant::now();
+        let error = LocalRunner.run_with_timeout("sleep", &["5"], Duration::from_millis(100)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_remote_command() {
+        assert_eq!(
+            SshRunner::remote_command("lsof", &["-ti", ":3000", "-sTCP:LISTEN"]),
+            "lsof -ti :3000 -sTCP:LISTEN"
+        );
+        assert_eq!(
+            SshRunner::remote_command("docker", &["inspect", "--format", "{{.Name}}", "abc"]),
+            "docker inspect --format '{{.Name}}' abc"
+        );
+    }
+}
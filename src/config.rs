@@ -1,15 +1,33 @@
+use crate::cli::Args;
+use crate::error::PortKillError;
+use crate::history::DEFAULT_HISTORY_SIZE;
+use crate::process_monitor::{EscalationStep, KillCooldown, KillPolicy, DEFAULT_DOCKER_RETRIES, DEFAULT_ENRICHMENT_CONCURRENCY, DEFAULT_IDLE_BACKOFF_MAX, DEFAULT_KILL_COOLDOWN, DEFAULT_MAX_KILL_WITHOUT_CONFIRM, DEFAULT_SIGKILL_RETRIES, DEFAULT_SIGKILL_RETRY_DELAY};
+use crate::tooltip::TooltipTemplate;
+use crate::types::{StatusBarInfo, StatusTier, DEFAULT_BUSY_THRESHOLD, DEFAULT_CROWDED_THRESHOLD};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Schema version written to new config files; [`Config::load`] migrates older files up to it
+pub const CONFIG_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
+    /// Schema version of the file; files from before versioning have none and count as version 0
+    #[serde(default)]
+    pub version: u32,
     pub discovery: DiscoveryConfig,
     pub ports: PortsConfig,
     pub ignore: IgnoreConfig,
     pub app: AppConfig,
+    /// Display names for ports, shown in the console and the tray menu
+    #[serde(default)]
+    pub labels: PortLabels,
+    /// Service names accepted in place of ports on the command line, e.g. `api = 4000` for `--ports api`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, u16>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -18,7 +36,7 @@ pub struct DiscoveryConfig {
     pub mode: DiscoveryMode,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DiscoveryMode {
     Range,
@@ -47,6 +65,54 @@ pub struct IgnoreConfig {
     pub ports: Vec<u16>,
     /// Process names to ignore (applies to all discovery modes)
     pub processes: Vec<String>,
+    /// Exact PIDs to ignore, e.g. one language server among others of the same name
+    #[serde(default)]
+    pub pids: Vec<i32>,
+    /// Directories whose executables are ignored, e.g. "/usr/bin" spares the system node but not ~/.nvm's
+    #[serde(default)]
+    pub path_prefixes: Vec<String>,
+}
+
+/// Port number to display name, e.g. `3000 = "Frontend"` shows port 3000 as "Frontend (3000)"
+// TOML keys are always strings, so ports go through string keys on disk
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "BTreeMap<String, String>", into = "BTreeMap<String, String>")]
+pub struct PortLabels(BTreeMap<u16, String>);
+
+impl TryFrom<BTreeMap<String, String>> for PortLabels {
+    type Error = String;
+
+    fn try_from(labels: BTreeMap<String, String>) -> std::result::Result<Self, Self::Error> {
+        labels
+            .into_iter()
+            .map(|(port, label)| match port.trim().parse::<u16>() {
+                Ok(port) => Ok((port, label)),
+                Err(_) => Err(format!("invalid port in [labels]: {:?}", port)),
+            })
+            .collect::<std::result::Result<_, _>>()
+            .map(PortLabels)
+    }
+}
+
+impl From<PortLabels> for BTreeMap<String, String> {
+    fn from(labels: PortLabels) -> Self {
+        labels.0.into_iter().map(|(port, label)| (port.to_string(), label)).collect()
+    }
+}
+
+impl PortLabels {
+    /// The label for `port`, if one is configured
+    pub fn get(&self, port: u16) -> Option<&str> {
+        self.0.get(&port).map(String::as_str).filter(|label| !label.trim().is_empty())
+    }
+
+    /// "Frontend (3000)" for a labelled port, otherwise "Port 3000"
+    pub fn display(&self, port: u16) -> String {
+        match self.get(port) {
+            Some(label) => format!("{} ({})", label, port),
+            None => format!("Port {}", port),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -61,11 +127,237 @@ pub struct AppConfig {
     pub menu_update_cooldown_seconds: u64,
     /// Maximum number of processes to show in menu (for stability)
     pub max_processes_in_menu: usize,
+    /// Status icon colors and the process counts that switch between them
+    #[serde(default)]
+    pub status_icon: StatusIconConfig,
+    /// Extra SIGKILL attempts for processes that survive the first one
+    #[serde(default = "default_kill_retries")]
+    pub kill_retries: u32,
+    /// Milliseconds to wait after each SIGKILL before checking the process again
+    #[serde(default = "default_kill_retry_delay_ms")]
+    pub kill_retry_delay_ms: u64,
+    /// Signals tried in order before SIGKILL, each followed by a wait; empty uses SIGTERM and 500ms
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub escalation: Vec<EscalationStepConfig>,
+    /// Minimum milliseconds between finished kill operations; quicker repeat clicks are ignored
+    #[serde(default = "default_kill_cooldown_ms")]
+    pub kill_cooldown_ms: u64,
+    /// Slack/Discord (or any JSON) webhook notified after each kill operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// "Kill All" operations hitting more processes than this need confirmation (console) or are refused (tray); 0 disables the check
+    #[serde(default = "default_max_kill_without_confirm")]
+    pub max_kill_without_confirm: usize,
+    /// Tray tooltip with {count}, {ports} and {names} placeholders; unset (or invalid) uses the built-in text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip_template: Option<String>,
+    /// Kills kept for the "Recently Killed" menu and --history; 0 keeps none
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    /// Vary the wait between scans by up to ±this percent, so instances on one host don't scan in lockstep; 0 disables
+    #[serde(default)]
+    pub poll_jitter_percent: u8,
+    /// Processes whose uptime and resources (--show-uptime, --show-resources) are looked up at once during a scan
+    #[serde(default = "default_enrichment_concurrency")]
+    pub enrichment_concurrency: usize,
+    /// Extra `docker ps` attempts (--docker), with a doubling delay, before a scan goes without container details
+    #[serde(default = "default_docker_retries")]
+    pub docker_retries: u32,
+    /// Longest wait between scans with --interval-backoff-when-idle, reached after a quiet spell
+    #[serde(default = "default_idle_backoff_max_seconds")]
+    pub idle_backoff_max_seconds: u64,
+    /// Prefix menu items with emoji ("🔪 Kill All Processes"); false gives plain-text labels for screen readers
+    #[serde(default = "default_use_emoji")]
+    pub use_emoji: bool,
+    /// Shell command run after each kill, with PORT_KILL_EVENT, PORT_KILL_PID, PORT_KILL_PORT and PORT_KILL_NAME set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_kill_command: Option<String>,
+    /// Shell command run for each process that starts listening on a monitored port, with the same variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_detect_command: Option<String>,
+}
+
+/// One `[[app.escalation]]` entry: send `signal` ("SIGINT", "TERM", ...), then wait `wait_ms` for the process to exit
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EscalationStepConfig {
+    pub signal: String,
+    #[serde(default)]
+    pub wait_ms: u64,
+}
+
+impl EscalationStepConfig {
+    pub fn step(&self) -> std::result::Result<EscalationStep, String> {
+        EscalationStep::new(&self.signal, std::time::Duration::from_millis(self.wait_ms))
+    }
+}
+
+fn default_kill_retries() -> u32 {
+    DEFAULT_SIGKILL_RETRIES
+}
+
+fn default_kill_retry_delay_ms() -> u64 {
+    DEFAULT_SIGKILL_RETRY_DELAY.as_millis() as u64
+}
+
+fn default_kill_cooldown_ms() -> u64 {
+    DEFAULT_KILL_COOLDOWN.as_millis() as u64
+}
+
+fn default_max_kill_without_confirm() -> usize {
+    DEFAULT_MAX_KILL_WITHOUT_CONFIRM
+}
+
+fn default_history_size() -> usize {
+    DEFAULT_HISTORY_SIZE
+}
+
+fn default_enrichment_concurrency() -> usize {
+    DEFAULT_ENRICHMENT_CONCURRENCY
+}
+
+fn default_docker_retries() -> u32 {
+    DEFAULT_DOCKER_RETRIES
+}
+
+fn default_idle_backoff_max_seconds() -> u64 {
+    DEFAULT_IDLE_BACKOFF_MAX.as_secs()
+}
+
+fn default_use_emoji() -> bool {
+    true
+}
+
+impl AppConfig {
+    /// Kill escalation and retry settings for the process monitor and menu actions
+    pub fn kill_policy(&self) -> KillPolicy {
+        // Signal names were checked by `Config::validate`
+        let escalation: Vec<EscalationStep> = self.escalation.iter().filter_map(|step| step.step().ok()).collect();
+        let default = KillPolicy::default();
+        KillPolicy {
+            escalation: if escalation.is_empty() { default.escalation } else { escalation },
+            sigkill_retries: self.kill_retries,
+            retry_delay: std::time::Duration::from_millis(self.kill_retry_delay_ms),
+            dump_wait: None,
+        }
+    }
+
+    /// Cooldown between finished kill operations, from `kill_cooldown_ms`
+    pub fn kill_cooldown(&self) -> KillCooldown {
+        KillCooldown::new(std::time::Duration::from_millis(self.kill_cooldown_ms))
+    }
+
+    /// Cap on the wait between scans with --interval-backoff-when-idle
+    pub fn idle_backoff_max(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.idle_backoff_max_seconds)
+    }
+
+    /// The parsed `tooltip_template`, or `None` for the built-in tooltip
+    pub fn tooltip_template(&self) -> Option<TooltipTemplate> {
+        TooltipTemplate::parse(self.tooltip_template.as_deref()?).ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatusIconConfig {
+    /// Process count at which the icon switches from idle to busy
+    pub busy_threshold: usize,
+    /// Process count at which the icon switches from busy to crowded
+    pub crowded_threshold: usize,
+    /// RGB color when no processes are running
+    pub idle_color: [u8; 3],
+    /// RGB color when some processes are running
+    pub busy_color: [u8; 3],
+    /// RGB color when many processes are running
+    pub crowded_color: [u8; 3],
+    /// Directory searched for the icon PNGs before the bundled assets ($PORT_KILL_ICON_DIR overrides it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_dir: Option<String>,
+}
+
+/// Environment variable that overrides `status_icon.icon_dir`
+pub const ICON_DIR_ENV: &str = "PORT_KILL_ICON_DIR";
+
+/// Where the bundled icon PNGs are looked for: a development checkout, the app bundle
+/// relative to the binary, and the installed app bundle
+pub const BUNDLED_ICON_DIRS: [&str; 3] = [
+    "assets",
+    "../Resources/assets",
+    "/Applications/PortKill.app/Contents/Resources/assets",
+];
+
+/// Icon PNGs tried for `tier`, in order: the 36px and then the 22px file, each in
+/// `custom_dir` first and then in the [`BUNDLED_ICON_DIRS`]
+pub fn icon_png_paths(custom_dir: Option<&Path>, tier: StatusTier) -> Vec<PathBuf> {
+    let bottle = if tier == StatusTier::Idle { "green-bottle" } else { "orange-bottle" };
+    let mut paths = Vec::new();
+    for size in [36, 22] {
+        let file = format!("{}-{}.png", bottle, size);
+        paths.extend(custom_dir.map(|dir| dir.join(&file)));
+        paths.extend(BUNDLED_ICON_DIRS.iter().map(|dir| Path::new(dir).join(&file)));
+    }
+    paths
+}
+
+impl Default for StatusIconConfig {
+    fn default() -> Self {
+        Self {
+            busy_threshold: DEFAULT_BUSY_THRESHOLD,
+            crowded_threshold: DEFAULT_CROWDED_THRESHOLD,
+            idle_color: [95, 249, 57],   // #5FF939, matches green-bottle.svg
+            busy_color: [255, 165, 0],   // #FFA500, matches orange-bottle.svg
+            crowded_color: [255, 59, 48], // #FF3B30
+            icon_dir: None,
+        }
+    }
+}
+
+impl StatusIconConfig {
+    /// Build the status info for a process count using the configured thresholds
+    pub fn status_for(&self, count: usize) -> StatusBarInfo {
+        StatusBarInfo::with_thresholds(count, self.busy_threshold, self.crowded_threshold)
+    }
+
+    /// RGB color for a status tier
+    pub fn color_for(&self, tier: StatusTier) -> [u8; 3] {
+        match tier {
+            StatusTier::Idle => self.idle_color,
+            StatusTier::Busy => self.busy_color,
+            StatusTier::Crowded => self.crowded_color,
+        }
+    }
+
+    /// The custom icon directory: $PORT_KILL_ICON_DIR, else `icon_dir`
+    pub fn icon_dir(&self) -> Option<PathBuf> {
+        std::env::var_os(ICON_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| self.icon_dir.as_ref().map(PathBuf::from))
+    }
+
+    /// Icon PNGs tried for `tier`, starting with the custom icon directory
+    pub fn png_paths(&self, tier: StatusTier) -> Vec<PathBuf> {
+        icon_png_paths(self.icon_dir().as_deref(), tier)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.busy_threshold == 0 {
+            return Err(anyhow::anyhow!("status_icon.busy_threshold must be at least 1"));
+        }
+        if self.crowded_threshold <= self.busy_threshold {
+            return Err(anyhow::anyhow!(
+                "status_icon.crowded_threshold ({}) must be greater than busy_threshold ({})",
+                self.crowded_threshold, self.busy_threshold
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             discovery: DiscoveryConfig {
                 mode: DiscoveryMode::Range,
             },
@@ -102,14 +394,40 @@ impl Default for Config {
                     "sharingd".to_string(),
                     "rapportd".to_string(),
                 ],
+                pids: vec![],
+                path_prefixes: vec![],
             },
-            app: AppConfig {
-                monitoring_interval_seconds: 3,
-                verbose_logging: false,
-                show_process_ids: false,
-                menu_update_cooldown_seconds: 2,
-                max_processes_in_menu: 20,
-            },
+            app: AppConfig::default(),
+            labels: PortLabels::default(),
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            monitoring_interval_seconds: 3,
+            verbose_logging: false,
+            show_process_ids: false,
+            menu_update_cooldown_seconds: 2,
+            max_processes_in_menu: 20,
+            status_icon: StatusIconConfig::default(),
+            kill_retries: default_kill_retries(),
+            kill_retry_delay_ms: default_kill_retry_delay_ms(),
+            escalation: Vec::new(),
+            kill_cooldown_ms: default_kill_cooldown_ms(),
+            webhook_url: None,
+            max_kill_without_confirm: default_max_kill_without_confirm(),
+            tooltip_template: None,
+            history_size: default_history_size(),
+            poll_jitter_percent: 0,
+            enrichment_concurrency: default_enrichment_concurrency(),
+            docker_retries: default_docker_retries(),
+            idle_backoff_max_seconds: default_idle_backoff_max_seconds(),
+            use_emoji: default_use_emoji(),
+            on_kill_command: None,
+            on_detect_command: None,
         }
     }
 }
@@ -118,7 +436,7 @@ impl Config {
     /// Load configuration from file, creating default if it doesn't exist
     pub fn load_or_create(path: &Path) -> Result<Self> {
         if path.exists() {
-            Self::load(path)
+            Ok(Self::load(path)?)
         } else {
             log::info!("Config file not found at {:?}, creating default configuration", path);
             let config = Self::default();
@@ -128,17 +446,67 @@ impl Config {
     }
 
     /// Load configuration from file
-    pub fn load(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+    pub fn load(path: &Path) -> std::result::Result<Self, PortKillError> {
+        let (mut config, content, version) = Self::read(path)?;
+        if version < CONFIG_VERSION {
+            config.save_migrated(path, &content, version);
+        }
 
-        let config: Config = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+        // A broken tooltip only costs the custom text, so it doesn't fail the whole config
+        if let Some(Err(e)) = config.app.tooltip_template.as_deref().map(TooltipTemplate::parse) {
+            log::warn!("Ignoring app.tooltip_template in {:?}: {}; using the default tooltip", path, e);
+            config.app.tooltip_template = None;
+        }
 
         log::info!("Loaded configuration from {:?}", path);
         Ok(config)
     }
 
+    /// Validate a config file without using it (--config-check): like [`load`](Self::load), but an
+    /// older file is only migrated in memory, never rewritten, and a broken tooltip_template is an error
+    pub fn check(path: &Path) -> std::result::Result<Self, PortKillError> {
+        let (config, _, _) = Self::read(path)?;
+        if let Some(Err(e)) = config.app.tooltip_template.as_deref().map(TooltipTemplate::parse) {
+            return Err(PortKillError::ConfigInvalid { path: path.to_path_buf(), reason: format!("app.tooltip_template: {}", e) });
+        }
+        Ok(config)
+    }
+
+    /// Parse, migrate and validate a config file, returning the config with the file's content and version
+    fn read(path: &Path) -> std::result::Result<(Self, String, u32), PortKillError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| PortKillError::ConfigRead { path: path.to_path_buf(), reason: e.to_string() })?;
+
+        let mut table: toml::Table = content.parse()
+            .map_err(|e: toml::de::Error| PortKillError::ConfigParse { path: path.to_path_buf(), reason: e.to_string() })?;
+
+        let version = match table.get("version") {
+            None => 0,
+            Some(value) => value.as_integer().and_then(|v| u32::try_from(v).ok()).ok_or_else(|| PortKillError::ConfigParse {
+                path: path.to_path_buf(),
+                reason: format!("version must be a non-negative integer, got {}", value),
+            })?,
+        };
+        if version > CONFIG_VERSION {
+            return Err(PortKillError::ConfigInvalid {
+                path: path.to_path_buf(),
+                reason: format!("version {} is newer than this port-kill understands (up to {}); upgrade port-kill", version, CONFIG_VERSION),
+            });
+        }
+        if version < CONFIG_VERSION {
+            migrate(&mut table, version);
+        }
+
+        let config: std::result::Result<Config, _> = toml::Value::Table(table).try_into();
+        let config = config
+            .map_err(|e| PortKillError::ConfigParse { path: path.to_path_buf(), reason: e.to_string() })?;
+
+        config.validate()
+            .map_err(|e| PortKillError::ConfigInvalid { path: path.to_path_buf(), reason: format!("{:#}", e) })?;
+
+        Ok((config, content, version))
+    }
+
     /// Save configuration to file
     pub fn save(&self, path: &Path) -> Result<()> {
         let content = toml::to_string_pretty(self)
@@ -156,6 +524,77 @@ impl Config {
         Ok(())
     }
 
+    /// Rewrite a file that was just migrated from `version`, keeping the original next to it as
+    /// `<file>.v<version>.bak` since the rewrite drops its comments. A file that can't be written
+    /// (e.g. read-only) is left as it is and migrated again on the next load.
+    fn save_migrated(&self, path: &Path, original: &str, version: u32) {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", version));
+        let result = fs::write(&backup, original)
+            .with_context(|| format!("Failed to back up config file to {:?}", backup))
+            .and_then(|_| self.save(path));
+        match result {
+            Ok(()) => log::info!(
+                "Upgraded config file {:?} from version {} to {} (original kept at {:?})",
+                path, version, CONFIG_VERSION, backup
+            ),
+            Err(e) => log::warn!("Using config file {:?} upgraded to version {} in memory only: {:#}", path, CONFIG_VERSION, e),
+        }
+    }
+
+    /// Check values that parse correctly but cannot be used
+    pub fn validate(&self) -> Result<()> {
+        for (index, range) in self.ports.ranges.iter().enumerate() {
+            if range.start == 0 || range.start > range.end {
+                return Err(anyhow::anyhow!(
+                    "ports.ranges[{}] must run from a port of at least 1 up to its end, got {}-{}",
+                    index, range.start, range.end
+                ));
+            }
+        }
+        if self.ports.specific.contains(&0) {
+            return Err(anyhow::anyhow!("ports.specific: port 0 is not valid"));
+        }
+        if let Some(ref url) = self.app.webhook_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(anyhow::anyhow!("app.webhook_url must be an http:// or https:// URL, got {:?}", url));
+            }
+        }
+        for (key, command) in [("on_kill_command", &self.app.on_kill_command), ("on_detect_command", &self.app.on_detect_command)] {
+            if command.as_deref().is_some_and(|command| command.trim().is_empty()) {
+                return Err(anyhow::anyhow!("app.{} cannot be empty; remove it to disable the hook", key));
+            }
+        }
+        for (name, port) in &self.aliases {
+            // A number or a list would be read as ports before the alias is looked up
+            if name.trim().is_empty() || name.parse::<u16>().is_ok() || name.contains(',') {
+                return Err(anyhow::anyhow!("aliases: {:?} is not a valid service name", name));
+            }
+            if *port == 0 {
+                return Err(anyhow::anyhow!("aliases.{} must be a port between 1 and 65535", name));
+            }
+        }
+        for (index, step) in self.app.escalation.iter().enumerate() {
+            step.step().map_err(|e| anyhow::anyhow!("app.escalation[{}].signal: {}", index, e))?;
+        }
+        if let Some(prefix) = self.ignore.path_prefixes.iter().find(|prefix| !Path::new(prefix).is_absolute()) {
+            return Err(anyhow::anyhow!("ignore.path_prefixes must be absolute paths, got {:?}", prefix));
+        }
+        if self.app.enrichment_concurrency == 0 {
+            return Err(anyhow::anyhow!("app.enrichment_concurrency must be at least 1"));
+        }
+        if self.app.idle_backoff_max_seconds == 0 {
+            return Err(anyhow::anyhow!("app.idle_backoff_max_seconds must be at least 1"));
+        }
+        if self.app.poll_jitter_percent > 100 {
+            return Err(anyhow::anyhow!("app.poll_jitter_percent must be at most 100, got {}", self.app.poll_jitter_percent));
+        }
+        if self.app.max_processes_in_menu == 0 {
+            return Err(anyhow::anyhow!("app.max_processes_in_menu must be at least 1"));
+        }
+        self.app.status_icon.validate()
+    }
+
     /// Get all ports to monitor based on configuration
     pub fn get_ports_to_monitor(&self) -> Vec<u16> {
         match self.discovery.mode {
@@ -174,6 +613,53 @@ impl Config {
         }
     }
 
+    /// Replace the CLI discovery mode, ports and ignore lists with this config's (used by --watch-config)
+    pub fn apply_to_args(&self, args: &mut Args) {
+        args.discover_all = self.is_discover_all();
+        args.common = false;
+        args.ports = if self.is_discover_all() {
+            None
+        } else {
+            let mut ports = self.get_ports_to_monitor();
+            ports.sort_unstable();
+            ports.dedup();
+            Some(ports.into_iter().map(Into::into).collect())
+        };
+        args.ignore_ports = Some(self.ignore.ports.clone());
+        args.ignore_processes = Some(self.ignore.processes.clone());
+        args.ignore_pids = Some(self.ignore.pids.clone());
+        args.ignore_path_prefixes = Some(self.ignore.path_prefixes.clone());
+    }
+
+    /// This config as port-kill runs it with `args` (--export-config).
+    ///
+    /// The command line decides the monitored ports and ignore lists unless --watch-config
+    /// hands them to this file, and always decides whether PIDs are shown and logs are verbose.
+    pub fn merged_with_args(mut self, args: &Args) -> Self {
+        if !args.watch_config {
+            if args.discover_all {
+                self.discovery.mode = DiscoveryMode::All;
+            } else if args.specific_ports().is_some() || args.common {
+                self.discovery.mode = DiscoveryMode::Specific;
+                self.ports.specific = args.get_ports_to_monitor();
+            } else {
+                self.discovery.mode = DiscoveryMode::Range;
+                self.ports.ranges = vec![PortRange {
+                    start: args.start_port,
+                    end: args.end_port,
+                    description: "--start-port to --end-port".to_string(),
+                }];
+            }
+            self.ignore.ports = args.ignore_ports.clone().unwrap_or_default();
+            self.ignore.processes = args.ignore_processes.clone().unwrap_or_default();
+            self.ignore.pids = args.ignore_pids.clone().unwrap_or_default();
+            self.ignore.path_prefixes = args.ignore_path_prefixes.clone().unwrap_or_default();
+        }
+        self.app.show_process_ids = args.show_pid;
+        self.app.verbose_logging = args.verbose;
+        self
+    }
+
     /// Get ports to ignore as a HashSet for efficient lookup
     pub fn get_ignore_ports_set(&self) -> HashSet<u16> {
         self.ignore.ports.iter().cloned().collect()
@@ -211,10 +697,35 @@ impl Config {
     }
 }
 
+/// Upgrade a parsed config file from schema `version` to [`CONFIG_VERSION`], one step at a time;
+/// a later schema change adds its step here (renamed keys, moved tables) and bumps the version
+fn migrate(table: &mut toml::Table, version: u32) {
+    if version < 1 {
+        // Version 0 files come from before the schema was versioned: sections and keys that were
+        // added since (or left out by hand) take their defaults, so the file loads instead of failing
+        if let Ok(toml::Value::Table(defaults)) = toml::Value::try_from(Config::default()) {
+            fill_missing(table, &defaults);
+        }
+    }
+    table.insert("version".to_string(), toml::Value::Integer(CONFIG_VERSION.into()));
+}
+
+/// Copy every key of `defaults` that `table` lacks, descending into tables present in both
+fn fill_missing(table: &mut toml::Table, defaults: &toml::Table) {
+    for (key, default) in defaults {
+        match (table.get_mut(key), default) {
+            (None, _) => {
+                table.insert(key.clone(), default.clone());
+            }
+            (Some(toml::Value::Table(existing)), toml::Value::Table(default)) => fill_missing(existing, default),
+            _ => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_default_config() {
@@ -227,6 +738,7 @@ mod tests {
     #[test]
     fn test_get_ports_to_monitor_range() {
         let config = Config {
+            version: CONFIG_VERSION,
             discovery: DiscoveryConfig { mode: DiscoveryMode::Range },
             ports: PortsConfig {
                 ranges: vec![
@@ -235,8 +747,10 @@ mod tests {
                 ],
                 specific: vec![],
             },
-            ignore: IgnoreConfig { ports: vec![], processes: vec![] },
+            ignore: IgnoreConfig { ports: vec![], processes: vec![], pids: vec![], path_prefixes: vec![] },
             app: AppConfig::default(),
+            labels: PortLabels::default(),
+            aliases: BTreeMap::new(),
         };
 
         let ports = config.get_ports_to_monitor();
@@ -246,41 +760,353 @@ mod tests {
     #[test]
     fn test_get_ports_to_monitor_specific() {
         let config = Config {
+            version: CONFIG_VERSION,
             discovery: DiscoveryConfig { mode: DiscoveryMode::Specific },
             ports: PortsConfig {
                 ranges: vec![],
                 specific: vec![3000, 8080],
             },
-            ignore: IgnoreConfig { ports: vec![], processes: vec![] },
+            ignore: IgnoreConfig { ports: vec![], processes: vec![], pids: vec![], path_prefixes: vec![] },
             app: AppConfig::default(),
+            labels: PortLabels::default(),
+            aliases: BTreeMap::new(),
         };
 
         let ports = config.get_ports_to_monitor();
         assert_eq!(ports, vec![3000, 8080]);
     }
 
+    #[test]
+    fn test_status_icon_defaults_when_missing() {
+        let app: AppConfig = toml::from_str(
+            "monitoring_interval_seconds = 3\nverbose_logging = false\nshow_process_ids = false\nmenu_update_cooldown_seconds = 2\nmax_processes_in_menu = 20\n"
+        ).unwrap();
+        assert_eq!(app.status_icon, StatusIconConfig::default());
+    }
+
+    #[test]
+    fn test_kill_policy_from_config() {
+        let app: AppConfig = toml::from_str(
+            "monitoring_interval_seconds = 3\nverbose_logging = false\nshow_process_ids = false\nmenu_update_cooldown_seconds = 2\nmax_processes_in_menu = 20\nkill_retries = 5\n"
+        ).unwrap();
+
+        let policy = app.kill_policy();
+        assert_eq!(policy.sigkill_retries, 5);
+        assert_eq!(policy.retry_delay, DEFAULT_SIGKILL_RETRY_DELAY);
+        assert_eq!(app.kill_cooldown_ms, DEFAULT_KILL_COOLDOWN.as_millis() as u64);
+        assert_eq!(app.max_kill_without_confirm, DEFAULT_MAX_KILL_WITHOUT_CONFIRM);
+        assert_eq!(app.tooltip_template, None);
+        assert_eq!(app.history_size, DEFAULT_HISTORY_SIZE);
+        assert_eq!(app.idle_backoff_max(), DEFAULT_IDLE_BACKOFF_MAX);
+        assert_eq!(app.enrichment_concurrency, DEFAULT_ENRICHMENT_CONCURRENCY);
+        assert_eq!(app.docker_retries, DEFAULT_DOCKER_RETRIES);
+        assert_eq!((app.on_kill_command, app.on_detect_command), (None, None));
+        assert_eq!(app.poll_jitter_percent, 0);
+        assert!(app.use_emoji);
+    }
+
+    #[test]
+    fn test_hook_commands_must_not_be_empty() {
+        let mut config = Config::default();
+        config.app.on_kill_command = Some("notify-send \"killed $PORT_KILL_NAME\"".to_string());
+        assert!(config.validate().is_ok());
+
+        config.app.on_detect_command = Some("  ".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("app.on_detect_command"));
+    }
+
+    #[test]
+    fn test_aliases_from_config() {
+        let mut config: Config = toml::from_str(&format!("{}\n[aliases]\napi = 4000\nmy-db = 5433\n", toml::to_string(&Config::default()).unwrap())).unwrap();
+        assert_eq!(config.aliases, BTreeMap::from([("api".to_string(), 4000), ("my-db".to_string(), 5433)]));
+        assert!(config.validate().is_ok());
+
+        config.aliases.insert("8080".to_string(), 8080);
+        assert!(config.validate().unwrap_err().to_string().contains("\"8080\" is not a valid service name"));
+        config.aliases.remove("8080");
+        config.aliases.insert("web".to_string(), 0);
+        assert!(config.validate().unwrap_err().to_string().contains("aliases.web"));
+    }
+
+    #[test]
+    fn test_poll_jitter_percent_validation() {
+        let mut config = Config::default();
+        config.app.poll_jitter_percent = 100;
+        assert!(config.validate().is_ok());
+        config.app.poll_jitter_percent = 101;
+        assert!(config.validate().unwrap_err().to_string().contains("poll_jitter_percent"));
+    }
+
+    #[test]
+    fn test_enrichment_concurrency_validation() {
+        let mut config = Config::default();
+        config.app.enrichment_concurrency = 1;
+        assert!(config.validate().is_ok());
+        config.app.enrichment_concurrency = 0;
+        assert!(config.validate().unwrap_err().to_string().contains("enrichment_concurrency"));
+    }
+
+    #[test]
+    fn test_idle_backoff_max_seconds_validation() {
+        let mut config = Config::default();
+        config.app.idle_backoff_max_seconds = 1;
+        assert!(config.validate().is_ok());
+        config.app.idle_backoff_max_seconds = 0;
+        assert!(config.validate().unwrap_err().to_string().contains("idle_backoff_max_seconds"));
+    }
+
+    #[test]
+    fn test_escalation_from_config() {
+        let app: AppConfig = toml::from_str(concat!(
+            "monitoring_interval_seconds = 3\nverbose_logging = false\nshow_process_ids = false\nmenu_update_cooldown_seconds = 2\nmax_processes_in_menu = 20\n",
+            "[[escalation]]\nsignal = \"SIGINT\"\nwait_ms = 2000\n",
+            "[[escalation]]\nsignal = \"term\"\nwait_ms = 5000\n",
+        ))
+        .unwrap();
+        let signals: Vec<(&str, u128)> = app.kill_policy().escalation.iter().map(|step| (step.signal, step.wait.as_millis())).collect();
+        assert_eq!(signals, [("SIGINT", 2000), ("SIGTERM", 5000)]);
+
+        // No ladder keeps the SIGTERM-then-SIGKILL default
+        assert_eq!(AppConfig::default().kill_policy().escalation, KillPolicy::default().escalation);
+
+        let mut config = Config::default();
+        config.app.escalation = vec![EscalationStepConfig { signal: "SIGINT".to_string(), wait_ms: 100 }, EscalationStepConfig { signal: "SIGTREM".to_string(), wait_ms: 100 }];
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("app.escalation[1].signal") && error.contains("\"SIGTREM\""), "{}", error);
+    }
+
+    #[test]
+    fn test_invalid_tooltip_template_falls_back_to_default() {
+        let path = std::env::temp_dir().join(format!("port-kill-tooltip-{}.toml", std::process::id()));
+        let mut config = Config::default();
+
+        config.app.tooltip_template = Some("{count} dev servers: {ports}".to_string());
+        config.save(&path).unwrap();
+        assert!(Config::load(&path).unwrap().app.tooltip_template().is_some());
+
+        config.app.tooltip_template = Some("{count} dev servers on {port}".to_string());
+        config.save(&path).unwrap();
+        let loaded = Config::load(&path).unwrap();
+        assert_eq!(loaded.app.tooltip_template, None);
+        assert!(loaded.app.tooltip_template().is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_errors_are_typed() {
+        let path = std::env::temp_dir().join(format!("port-kill-load-{}.toml", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(Config::load(&path), Err(PortKillError::ConfigRead { .. })));
+
+        std::fs::write(&path, "[monitoring\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(PortKillError::ConfigParse { .. })));
+
+        let mut config = Config::default();
+        config.app.max_processes_in_menu = 0;
+        config.save(&path).unwrap();
+        assert!(matches!(Config::load(&path), Err(PortKillError::ConfigInvalid { .. })));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_migrates_unversioned_config() {
+        let original = include_str!("../tests/fixtures/port-kill-v0.toml");
+        let path = std::env::temp_dir().join(format!("port-kill-migrate-{}.toml", std::process::id()));
+        let backup = std::env::temp_dir().join(format!("port-kill-migrate-{}.toml.v0.bak", std::process::id()));
+        std::fs::write(&path, original).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        // The file's own values survive; what it lacks comes from the defaults
+        assert_eq!(config.discovery.mode, DiscoveryMode::Specific);
+        assert_eq!(config.ports.specific, vec![3000, 8080]);
+        assert_eq!(config.app.menu_update_cooldown_seconds, AppConfig::default().menu_update_cooldown_seconds);
+        assert_eq!(config.app.kill_retries, DEFAULT_SIGKILL_RETRIES);
+        assert!(config.ignore.pids.is_empty());
+
+        // The upgraded file is written back, the original kept as a backup, and loading again changes nothing
+        let rewritten = std::fs::read_to_string(&path).unwrap();
+        assert!(rewritten.starts_with(&format!("version = {}\n", CONFIG_VERSION)), "{}", rewritten);
+        assert!(rewritten.contains("menu_update_cooldown_seconds = 2"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), original);
+        std::fs::remove_file(&backup).unwrap();
+        let reloaded = Config::load(&path).unwrap();
+        assert_eq!(reloaded.ports.specific, config.ports.specific);
+        assert!(!backup.exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), rewritten);
+
+        std::fs::write(&path, format!("version = {}\n{}", CONFIG_VERSION + 1, rewritten.lines().skip(1).collect::<Vec<_>>().join("\n"))).unwrap();
+        let error = Config::load(&path).unwrap_err();
+        assert!(matches!(error, PortKillError::ConfigInvalid { .. }) && error.to_string().contains("newer"), "{}", error);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_check_validates_without_rewriting() {
+        let original = include_str!("../tests/fixtures/port-kill-v0.toml");
+        let path = std::env::temp_dir().join(format!("port-kill-check-{}.toml", std::process::id()));
+        let backup = std::env::temp_dir().join(format!("port-kill-check-{}.toml.v0.bak", std::process::id()));
+        std::fs::write(&path, original).unwrap();
+        assert_eq!(Config::check(&path).unwrap().version, CONFIG_VERSION);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(!backup.exists());
+
+        let mut config = Config::default();
+        config.ports.ranges = vec![PortRange { start: 9000, end: 8000, description: "backwards".to_string() }];
+        config.save(&path).unwrap();
+        let error = Config::check(&path).unwrap_err();
+        assert!(matches!(error, PortKillError::ConfigInvalid { .. }) && error.to_string().contains("ports.ranges[0]"), "{}", error);
+
+        let mut config = Config::default();
+        config.app.tooltip_template = Some("{count} dev servers on {port}".to_string());
+        config.save(&path).unwrap();
+        assert!(matches!(Config::check(&path), Err(PortKillError::ConfigInvalid { .. })));
+        assert!(Config::load(&path).unwrap().app.tooltip_template.is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_status_icon_tiers_and_colors() {
+        let status_icon = StatusIconConfig {
+            busy_threshold: 1,
+            crowded_threshold: 4,
+            ..StatusIconConfig::default()
+        };
+
+        assert_eq!(status_icon.status_for(0).tier, StatusTier::Idle);
+        assert_eq!(status_icon.status_for(3).tier, StatusTier::Busy);
+        assert_eq!(status_icon.status_for(4).tier, StatusTier::Crowded);
+        assert_eq!(status_icon.color_for(StatusTier::Crowded), [255, 59, 48]);
+    }
+
+    #[test]
+    fn test_icon_png_paths() {
+        let bundled = icon_png_paths(None, StatusTier::Idle);
+        assert_eq!(bundled.len(), 6);
+        assert_eq!(bundled[0], Path::new("assets/green-bottle-36.png"));
+        assert_eq!(bundled[3], Path::new("assets/green-bottle-22.png"));
+
+        let custom = icon_png_paths(Some(Path::new("/home/me/icons")), StatusTier::Crowded);
+        assert_eq!(custom[0], Path::new("/home/me/icons/orange-bottle-36.png"));
+        assert_eq!(custom[1], Path::new("assets/orange-bottle-36.png"));
+        assert_eq!(custom[4], Path::new("/home/me/icons/orange-bottle-22.png"));
+        assert_eq!(custom[7], Path::new("/Applications/PortKill.app/Contents/Resources/assets/orange-bottle-22.png"));
+
+        let app: AppConfig = toml::from_str(
+            "monitoring_interval_seconds = 3\nverbose_logging = false\nshow_process_ids = false\nmenu_update_cooldown_seconds = 2\nmax_processes_in_menu = 20\n[status_icon]\nicon_dir = \"/home/me/icons\"\n"
+        ).unwrap();
+        assert_eq!(app.status_icon.icon_dir.as_deref(), Some("/home/me/icons"));
+    }
+
+    #[test]
+    fn test_status_icon_validation() {
+        let invalid = StatusIconConfig {
+            busy_threshold: 3,
+            crowded_threshold: 3,
+            ..StatusIconConfig::default()
+        };
+        assert!(invalid.validate().is_err());
+        assert!(StatusIconConfig::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.app.max_processes_in_menu = 0;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_is_discover_all() {
         let config = Config {
+            version: CONFIG_VERSION,
             discovery: DiscoveryConfig { mode: DiscoveryMode::All },
             ports: PortsConfig { ranges: vec![], specific: vec![] },
-            ignore: IgnoreConfig { ports: vec![], processes: vec![] },
+            ignore: IgnoreConfig { ports: vec![], processes: vec![], pids: vec![], path_prefixes: vec![] },
             app: AppConfig::default(),
+            labels: PortLabels::default(),
+            aliases: BTreeMap::new(),
         };
 
         assert!(config.is_discover_all());
         assert!(config.get_ports_to_monitor().is_empty());
     }
-}
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        Self {
-            monitoring_interval_seconds: 3,
-            verbose_logging: false,
-            show_process_ids: false,
-            menu_update_cooldown_seconds: 2,
-            max_processes_in_menu: 20,
-        }
+    #[test]
+    fn test_port_labels() {
+        let without_labels = toml::to_string_pretty(&Config::default()).unwrap().replace("[labels]", "");
+        let mut toml_text = without_labels.clone();
+        toml_text.push_str("\n[labels]\n3000 = \"Frontend\"\n8080 = \"API\"\n5173 = \"  \"\n");
+        let config: Config = toml::from_str(&toml_text).unwrap();
+
+        assert_eq!(config.labels.get(3000), Some("Frontend"));
+        assert_eq!(config.labels.display(8080), "API (8080)");
+        assert_eq!(config.labels.display(5173), "Port 5173");
+        assert_eq!(config.labels.display(9999), "Port 9999");
+
+        // Labels survive a save/load round trip, and older configs without [labels] still load
+        let reparsed: Config = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(reparsed.labels, config.labels);
+        let without_labels: Config = toml::from_str(&without_labels).unwrap();
+        assert_eq!(without_labels.labels, PortLabels::default());
+
+        assert!(toml::from_str::<PortLabels>("frontend = \"Frontend\"").is_err());
+    }
+
+    #[test]
+    fn test_apply_to_args() {
+        use clap::Parser;
+
+        let mut args = Args::parse_from(["port-kill", "--common", "--ignore-ports", "1234"]);
+        let mut config = Config::default();
+        config.discovery.mode = DiscoveryMode::Specific;
+        config.ports.specific = vec![8080, 3000, 8080];
+        config.apply_to_args(&mut args);
+
+        assert_eq!(args.get_ports_to_monitor(), vec![3000, 8080]);
+        assert_eq!(args.ignore_ports, Some(vec![5353, 7000]));
+        assert!(args.validate().is_ok());
+
+        config.discovery.mode = DiscoveryMode::All;
+        config.apply_to_args(&mut args);
+        assert!(args.discover_all);
+        assert!(args.get_ports_to_monitor().is_empty());
+
+        // An empty port list must be rejected before it replaces a working setup
+        config.discovery.mode = DiscoveryMode::Specific;
+        config.ports.specific.clear();
+        config.apply_to_args(&mut args);
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_merged_with_args() {
+        use clap::Parser;
+
+        let mut file = Config::default();
+        file.app.webhook_url = Some("https://hooks.example.com/x".to_string());
+
+        // The command line wins over the file for ports and ignore lists
+        let args = Args::parse_from(["port-kill", "--ports", "3000,8080", "--ignore-processes", "postgres", "--ignore-pid", "4242", "--ignore-path-prefix", "/usr/bin", "--show-pid"]);
+        let merged = file.clone().merged_with_args(&args);
+        assert_eq!(merged.discovery.mode, DiscoveryMode::Specific);
+        assert_eq!(merged.ports.specific, vec![3000, 8080]);
+        assert!(merged.ignore.ports.is_empty());
+        assert_eq!(merged.ignore.processes, vec!["postgres".to_string()]);
+        assert_eq!(merged.ignore.pids, vec![4242]);
+        assert_eq!(merged.ignore.path_prefixes, vec!["/usr/bin".to_string()]);
+        assert!(merged.app.show_process_ids);
+        assert_eq!(merged.app.webhook_url, file.app.webhook_url);
+
+        // Without --ports the default range is what gets scanned
+        let merged = file.clone().merged_with_args(&Args::parse_from(["port-kill"]));
+        assert_eq!(merged.discovery.mode, DiscoveryMode::Range);
+        assert_eq!(merged.get_ports_to_monitor(), (2000..=6000).collect::<Vec<_>>());
+
+        // --watch-config leaves ports and ignore lists to the file
+        let merged = file.clone().merged_with_args(&Args::parse_from(["port-kill", "--ports", "9000", "--watch-config"]));
+        assert_eq!(merged.discovery.mode, DiscoveryMode::Range);
+        assert_eq!(merged.ignore.ports, vec![5353, 7000]);
+
+        // The export round-trips through the file format
+        let exported = toml::to_string_pretty(&merged).unwrap();
+        assert_eq!(toml::from_str::<Config>(&exported).unwrap().get_ports_to_monitor(), merged.get_ports_to_monitor());
     }
 }
@@ -0,0 +1,157 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use crossbeam_channel::{unbounded, Receiver};
+use log::{info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Quiet period after the last file event before the config is reloaded,
+/// so an editor's burst of writes/renames on save triggers one reload
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches the config file and delivers each new, valid version of it.
+///
+/// Changes that fail to parse or validate are logged and skipped, so the
+/// previous configuration stays in effect.
+pub type ConfigWatcher = FileWatcher<Config>;
+
+/// Watches a file and delivers what `load` makes of each new version of it; versions
+/// `load` rejects are logged and skipped
+pub struct FileWatcher<T> {
+    // Dropping the watcher stops file events and ends the reload thread
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<T>,
+}
+
+impl ConfigWatcher {
+    pub fn start(path: &Path) -> Result<Self> {
+        Self::watch(path, "Config file", |path| Ok(Config::load(path)?))
+    }
+}
+
+impl<T: Send + 'static> FileWatcher<T> {
+    /// Watch `path`, calling it `what` in the log, and load each change with `load` after [`RELOAD_DEBOUNCE`]
+    pub fn watch(path: &Path, what: &'static str, load: fn(&Path) -> Result<T>) -> Result<Self> {
+        let path = path.to_path_buf();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_os_string())
+            .with_context(|| format!("Invalid {} path: {:?}", what.to_lowercase(), path))?;
+
+        let (event_sender, event_receiver) = unbounded();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            let touches_file = event.paths.iter().any(|changed| changed.file_name() == Some(file_name.as_os_str()));
+            if touches_file && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                let _ = event_sender.send(());
+            }
+        })
+        .with_context(|| format!("Failed to create {} watcher", what.to_lowercase()))?;
+
+        // Watch the directory, not the file: editors often save by replacing the file
+        watcher
+            .watch(&watch_dir(&path), RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}: {:?}", what.to_lowercase(), path))?;
+
+        let (sender, receiver) = unbounded();
+        std::thread::Builder::new()
+            .name("file-watcher".to_string())
+            .spawn(move || {
+                while event_receiver.recv().is_ok() {
+                    while event_receiver.recv_timeout(RELOAD_DEBOUNCE).is_ok() {}
+
+                    match load(&path) {
+                        Ok(loaded) => {
+                            info!("{} {:?} changed, reloading", what, path);
+                            if sender.send(loaded).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("Ignoring {} change, keeping the previous version: {:#}", what.to_lowercase(), e),
+                    }
+                }
+            })
+            .with_context(|| format!("Failed to start {} watcher thread", what.to_lowercase()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// The most recent valid version since the last call, if the file changed
+    pub fn latest(&self) -> Option<T> {
+        self.receiver.try_iter().last()
+    }
+}
+
+fn watch_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    fn wait_for_config(watcher: &ConfigWatcher, timeout: Duration) -> Option<Config> {
+        let started = Instant::now();
+        while started.elapsed() < timeout {
+            if let Some(config) = watcher.latest() {
+                return Some(config);
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        None
+    }
+
+    #[test]
+    fn test_reloads_valid_changes_and_skips_invalid_ones() {
+        let dir = std::env::temp_dir().join(format!("port-kill-watch-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("port-kill.toml");
+        Config::default().save(&path).unwrap();
+
+        let watcher = ConfigWatcher::start(&path).unwrap();
+
+        fs::write(&path, "not = [valid toml").unwrap();
+        assert!(wait_for_config(&watcher, RELOAD_DEBOUNCE * 4).is_none());
+
+        let mut config = Config::default();
+        config.ignore.ports = vec![9999];
+        config.save(&path).unwrap();
+        let reloaded = wait_for_config(&watcher, Duration::from_secs(5)).expect("config was not reloaded");
+        assert_eq!(reloaded.ignore.ports, vec![9999]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_watches_any_file_with_its_loader() {
+        let dir = std::env::temp_dir().join(format!("port-kill-watch-ports-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ports.txt");
+        fs::write(&path, "3000\n").unwrap();
+
+        let watcher = FileWatcher::watch(&path, "Ports file", crate::ports_file::load).unwrap();
+        fs::write(&path, "3000\n4000-4001 # api\n").unwrap();
+        let started = Instant::now();
+        let ports = loop {
+            if let Some(ports) = watcher.latest() {
+                break ports;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "ports file was not reloaded");
+            std::thread::sleep(Duration::from_millis(50));
+        };
+        assert_eq!(ports.iter().map(ToString::to_string).collect::<Vec<_>>(), ["3000", "4000-4001"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,55 +1,331 @@
 use crate::{
-    process_monitor::ProcessMonitor,
-    types::{ProcessUpdate, StatusBarInfo},
-    cli::Args,
+    auto_kill::AutoKiller,
+    config::{Config, PortLabels},
+    config_watcher::{ConfigWatcher, FileWatcher},
+    command_runner::CommandRunner,
+    event_socket::{EventPublisher, EventSocket},
+    exit_code::ExitCode,
+    history::{KillHistory, DEFAULT_HISTORY_SIZE},
+    hooks::Hooks,
+    port_scanner::{LsofScanner, PortScanner},
+    process_monitor::{excluded_with_scanner, filter_ignored_processes, get_processes_on_ports, listeners_by_port, process_command_line, process_started_at, wait_for_port_open, KillLimitExceeded, KillPolicy, MonitorHandle, ProcessMonitor, DEFAULT_DOCKER_RETRIES, DEFAULT_ENRICHMENT_CONCURRENCY, DEFAULT_IDLE_BACKOFF_MAX, MONITORING_INTERVAL, WAIT_POLL_INTERVAL},
+    process_tree::{render_tree, ProcessTable},
+    row_format::{csv_row, RowTemplate, CSV_HEADER},
+    project_group::{group_by_dir, group_label, project_dirs, working_dirs},
+    service_manager,
+    session_summary::{unix_now, KillReport, SessionStats},
+    shutdown,
+    tooltip::TooltipTemplate,
+    types::{docker_process_count, format_bytes, format_uptime, process_count, ProcessDiff, ProcessInfo, ProcessUpdate, StatusBarInfo},
+    webhook::Webhook,
+    cli::{Args, GroupBy, PortRange},
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossbeam_channel::{bounded, Receiver};
-use log::{error, info};
+use log::{error, info, warn};
+use owo_colors::OwoColorize;
+use std::io::IsTerminal;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How long shutdown waits for the monitor to finish its current scan
+pub const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Decide whether console output should be colorized
+pub fn should_use_color(no_color_flag: bool, no_color_env: Option<std::ffi::OsString>, is_tty: bool) -> bool {
+    // https://no-color.org: any non-empty NO_COLOR value disables color
+    let env_disabled = no_color_env.is_some_and(|value| !value.is_empty());
+    !no_color_flag && !env_disabled && is_tty
+}
+
+/// ANSI styling for console output that degrades to plain text when disabled
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    enabled: bool,
+}
+
+impl Palette {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn from_args(args: &Args) -> Self {
+        Self::new(should_use_color(args.no_color, std::env::var_os("NO_COLOR"), std::io::stdout().is_terminal()))
+    }
+
+    pub fn port(&self, port: u16) -> String {
+        if self.enabled {
+            port.cyan().bold().to_string()
+        } else {
+            port.to_string()
+        }
+    }
+
+    pub fn name(&self, name: &str) -> String {
+        if self.enabled {
+            name.bold().to_string()
+        } else {
+            name.to_string()
+        }
+    }
+
+    pub fn dim(&self, text: &str) -> String {
+        if self.enabled {
+            text.dimmed().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Container markers are blue, so Docker rows stand out from native ones
+    pub fn docker(&self, text: &str) -> String {
+        if self.enabled {
+            text.blue().bold().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Status text is green when nothing is running, yellow otherwise
+    pub fn status(&self, count: usize, text: &str) -> String {
+        if !self.enabled {
+            text.to_string()
+        } else if count == 0 {
+            text.green().to_string()
+        } else {
+            text.yellow().to_string()
+        }
+    }
+}
 
 pub struct ConsolePortKillApp {
     process_monitor: Arc<Mutex<ProcessMonitor>>,
+    monitor_handle: MonitorHandle,
     update_receiver: Receiver<ProcessUpdate>,
     args: Args,
+    palette: Palette,
+    /// Last filtered snapshot, used to report changes in quiet mode
+    last_processes: HashMap<u16, Vec<ProcessInfo>>,
+    /// Last unfiltered update, re-filtered when the ignore lists are reloaded
+    last_update: Option<ProcessUpdate>,
+    config_watcher: Option<ConfigWatcher>,
+    /// The --ports-file, watched along with the config file under --watch-config
+    ports_file_watcher: Option<FileWatcher<Vec<PortRange>>>,
+    /// Port display names from the config's [labels]
+    labels: PortLabels,
+    /// Notified of --auto-kill kills, from the config's webhook_url
+    webhook: Option<Webhook>,
+    /// `on_kill_command` and `on_detect_command` from the config
+    hooks: Option<Hooks>,
+    /// Clients of the --event-socket, once it is bound
+    events: Option<EventPublisher>,
+    /// `history_size` from the config, for the --auto-kill kills
+    history_size: usize,
+    /// Kill escalation and retries from the config, for the --auto-kill kills
+    kill_policy: KillPolicy,
+    /// Runs `ps` for --tree on the same host as the monitor
+    runner: Arc<dyn CommandRunner>,
+    /// --format, replacing the usual process rows
+    row_template: Option<RowTemplate>,
+    /// Kills counted for --summary-on-exit
+    stats: Option<SessionStats>,
 }
 
 impl ConsolePortKillApp {
-    pub fn new(args: Args) -> Result<Self> {
+    pub fn new(mut args: Args) -> Result<Self> {
         // Create channels for communication
         let (update_sender, update_receiver) = bounded(100);
 
+        // With --watch-config the config file decides what is monitored and ignored
+        let config_path = PathBuf::from(&args.config);
+        let (config_watcher, config) = if args.watch_config {
+            let config = Config::load(&config_path)?;
+            config.apply_to_args(&mut args);
+            args.validate().map_err(|e| anyhow::anyhow!("Invalid config file {:?}: {}", config_path, e))?;
+            (Some(ConfigWatcher::start(&config_path)?), Some(config))
+        } else {
+            (None, load_optional_config(&config_path))
+        };
+        let ports_file_watcher = match args.ports_file {
+            Some(ref ports_file) if args.watch_config => Some(FileWatcher::watch(Path::new(ports_file), "Ports file", crate::ports_file::load)?),
+            _ => None,
+        };
+        let labels = config.as_ref().map(|config| config.labels.clone()).unwrap_or_default();
+        let webhook = config.as_ref().and_then(|config| Webhook::from_config(&config.app));
+        let hooks = config.as_ref().and_then(|config| Hooks::from_config(&config.app));
+        let history_size = config.as_ref().map_or(DEFAULT_HISTORY_SIZE, |config| config.app.history_size);
+        let kill_policy = args.kill_policy(&config.as_ref().map(|config| config.app.clone()).unwrap_or_default());
+
         // Create process monitor with configurable ports
-        let process_monitor = Arc::new(Mutex::new(ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), args.docker, args.discover_all)?));
+        let runner = args.command_runner();
+        #[cfg(not(target_os = "windows"))]
+        LsofScanner::new(runner.clone()).with_timeout(args.lsof_timeout).check_available()?;
+        let mut process_monitor = ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), args.docker, args.discover_all, runner.clone())?;
+        process_monitor.set_watch_processes(args.watch_process.clone());
+        process_monitor.set_socket_paths(args.socket.clone());
+        process_monitor.set_include_established(args.include_established);
+        process_monitor.set_interface(args.interface);
+        process_monitor.set_network_namespaces(args.netns);
+        process_monitor.set_kill_policy(kill_policy.clone());
+        process_monitor.set_lsof_timeout(args.lsof_timeout);
+        process_monitor.set_collect_uptime(args.show_uptime);
+        process_monitor.set_collect_resources(args.show_resources);
+        process_monitor.set_enrichment_concurrency(config.as_ref().map_or(DEFAULT_ENRICHMENT_CONCURRENCY, |config| config.app.enrichment_concurrency));
+        process_monitor.set_docker_retries(config.as_ref().map_or(DEFAULT_DOCKER_RETRIES, |config| config.app.docker_retries));
+        process_monitor.set_poll_jitter_percent(config.as_ref().map_or(0, |config| config.app.poll_jitter_percent));
+        process_monitor.set_idle_backoff(args.interval_backoff_when_idle.then(|| config.as_ref().map_or(DEFAULT_IDLE_BACKOFF_MAX, |config| config.app.idle_backoff_max())));
+        let monitor_handle = process_monitor.handle();
+        let process_monitor = Arc::new(Mutex::new(process_monitor));
+
+        let palette = Palette::from_args(&args);
+        let row_template = args.format.as_deref().map(RowTemplate::parse).transpose().context("Invalid --format")?;
+        let stats = (args.summary_on_exit || args.report.is_some()).then(SessionStats::default);
 
         Ok(Self {
             process_monitor,
+            monitor_handle,
             update_receiver,
             args,
+            palette,
+            last_processes: HashMap::new(),
+            last_update: None,
+            config_watcher,
+            ports_file_watcher,
+            labels,
+            webhook,
+            hooks,
+            events: None,
+            history_size,
+            kill_policy,
+            runner,
+            row_template,
+            stats,
         })
     }
 
     pub async fn run(mut self) -> Result<()> {
+        let started = std::time::Instant::now();
+        let started_at = unix_now();
         info!("Starting Console Port Kill application...");
-        println!("🚀 Port Kill Console Monitor Started!");
-        println!("📡 Monitoring {} every 2 seconds...", self.args.get_port_description());
-        println!("💡 Press Ctrl+C to quit");
-        println!("");
+        self.announce("🚀 Port Kill Console Monitor Started!");
+        self.announce(&format!("📡 Monitoring {} every 2 seconds...", self.args.get_port_description()));
+        self.announce("💡 Press Ctrl+C to quit");
+        if let Some(max_runtime) = self.args.max_runtime {
+            self.announce(&format!("⏱️  Stopping on its own after {} (--max-runtime)", format_uptime(max_runtime)));
+        }
+        self.announce("");
+        if self.args.csv {
+            println!("{}", CSV_HEADER);
+        }
+
+        // Serve --metrics-port for the lifetime of the monitor
+        let metrics_task = match self.args.metrics_port {
+            Some(port) => {
+                let listener = crate::metrics::bind(port).await?;
+                self.announce(&format!("📈 Prometheus metrics on http://127.0.0.1:{}/metrics", port));
+                Some(tokio::spawn(crate::metrics::serve(listener)))
+            }
+            None => None,
+        };
+
+        // Stream changes and kills to --event-socket clients until shutdown removes the socket
+        let event_socket = match self.args.event_socket {
+            Some(ref path) => {
+                let socket = EventSocket::bind(Path::new(path))?;
+                self.announce(&format!("📣 Streaming events on {}", socket.path().display()));
+                self.events = Some(socket.publisher());
+                Some(socket)
+            }
+            None => None,
+        };
+
+        // Kill whatever shows up on the --auto-kill ports, once per monitoring cycle
+        let auto_kill_task = self.args.auto_kill.clone().map(|ports| {
+            println!("🎯 Auto-killing any process on ports {}{}",
+                     ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "),
+                     if self.args.dry_run { " (dry run, nothing will be killed)" } else { "" });
+            let mut auto_killer = AutoKiller::new(ports, &self.args, self.kill_policy.clone(), self.webhook.clone())
+                .with_history(KillHistory::session(self.history_size))
+                .with_hooks(self.hooks.clone())
+                .with_events(self.events.clone())
+                .with_stats(self.stats.clone());
+            tokio::spawn(async move {
+                loop {
+                    auto_killer.run_cycle();
+                    tokio::time::sleep(MONITORING_INTERVAL).await;
+                }
+            })
+        });
+
+        // `port-kill --refresh` (SIGUSR1) makes the monitor rescan right away
+        #[cfg(not(target_os = "windows"))]
+        {
+            let monitor_handle = self.monitor_handle.clone();
+            if let Err(e) = crate::refresh::on_signal(move || monitor_handle.refresh()) {
+                warn!("Failed to install refresh signal handler: {}", e);
+            }
+        }
 
         // Start process monitoring in background
         let monitor = self.process_monitor.clone();
-        tokio::spawn(async move {
+        let monitor_task = tokio::spawn(async move {
             if let Err(e) = monitor.lock().await.start_monitoring().await {
                 error!("Process monitoring failed: {}", e);
             }
         });
 
-        // Handle updates in the main thread
-        self.handle_console_updates().await;
+        // Handle updates in the main thread until Ctrl+C, SIGTERM or --max-runtime
+        let max_runtime = self.args.max_runtime;
+        let runtime_elapsed = async {
+            match max_runtime {
+                Some(max_runtime) => tokio::time::sleep(max_runtime).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = self.handle_console_updates() => {}
+            signal = shutdown::wait_for_signal() => {
+                info!("Received {}, shutting down...", signal);
+            }
+            _ = runtime_elapsed => {
+                info!("Reached --max-runtime, shutting down...");
+                self.announce(&format!("⏱️  Reached --max-runtime of {}", format_uptime(max_runtime.unwrap_or_default())));
+            }
+        }
+
+        // Let the monitor finish its current scan instead of killing it mid-lsof
+        self.monitor_handle.stop();
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, monitor_task).await.is_err() {
+            warn!("Process monitor did not stop within {:?}", SHUTDOWN_TIMEOUT);
+        }
+
+        if let Some(auto_kill_task) = auto_kill_task {
+            auto_kill_task.abort();
+        }
+        if let Some(metrics_task) = metrics_task {
+            metrics_task.abort();
+        }
+        drop(event_socket);
+
+        let pending = self.update_receiver.try_iter().count();
+        if pending > 0 {
+            info!("Discarded {} pending process update(s) on shutdown", pending);
+        }
+
+        // Every way out of the select above ends up here, so each one gets the summary and report
+        if let Some(stats) = self.stats.as_ref().filter(|_| self.args.summary_on_exit && !self.args.quiet) {
+            self.announce(&stats.report(started.elapsed()));
+        }
+        let report = match (&self.args.report, &self.stats) {
+            (Some(path), Some(stats)) => KillReport::new(&self.args, started_at, stats.kills()).write(path).map(|()| self.announce(&format!("📝 Report written to {}", path.display()))),
+            _ => Ok(()),
+        };
+        self.announce("👋 Port Kill Console Monitor stopped");
+        shutdown::flush_logs();
 
-        Ok(())
+        report
     }
 
     async fn handle_console_updates(&mut self) {
@@ -58,39 +334,24 @@ impl ConsolePortKillApp {
         loop {
             // Check for process updates
             if let Ok(update) = self.update_receiver.try_recv() {
-                // Filter out ignored processes
-                let filtered_processes = self.filter_ignored_processes(&update.processes);
-                let filtered_count = filtered_processes.len();
-                
-                // Update status
-                let status_info = StatusBarInfo::from_process_count(filtered_count);
-                
-                // Print status to console
-                println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
-                
-                if filtered_count > 0 {
-                    println!("📋 Detected Processes (after filtering ignored):");
-                    for (port, process_info) in &filtered_processes {
-                        if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
-                            println!("   • Port {}: {} - {} [Docker: {}]", 
-                                    port, process_info.name, process_info.command, container_name);
-                        } else if self.args.show_pid {
-                            println!("   • Port {}: {} (PID {}) - {}", 
-                                    port, process_info.name, process_info.pid, process_info.command);
-                        } else {
-                            println!("   • Port {}: {} - {}", 
-                                    port, process_info.name, process_info.command);
-                        }
+                self.show_update(&update);
+                self.last_update = Some(update);
+            }
+
+            // Apply config file changes (--watch-config)
+            if let Some(config) = self.config_watcher.as_ref().and_then(ConfigWatcher::latest) {
+                if self.apply_config(&config) {
+                    // Ignore lists may have changed even if the monitored processes didn't
+                    if let Some(update) = self.last_update.take() {
+                        self.show_update(&update);
+                        self.last_update = Some(update);
                     }
                 }
-                
-                // Show ignored processes if any
-                let ignored_count = update.processes.len() - filtered_count;
-                if ignored_count > 0 {
-                    println!("🚫 Ignored {} process(es) based on user configuration", ignored_count);
-                }
-                
-                println!("");
+            }
+
+            // Apply --ports-file changes (--watch-config)
+            if let Some(ports) = self.ports_file_watcher.as_ref().and_then(FileWatcher::latest) {
+                self.apply_ports_file(ports);
             }
 
             // Sleep briefly to avoid busy waiting
@@ -98,25 +359,684 @@ impl ConsolePortKillApp {
         }
     }
 
-    fn filter_ignored_processes(&self, processes: &HashMap<u16, crate::types::ProcessInfo>) -> HashMap<u16, crate::types::ProcessInfo> {
-        let mut filtered = HashMap::new();
-        
-        // Get ignore sets for efficient lookup
-        let ignore_ports = self.args.get_ignore_ports_set();
-        let ignore_processes = self.args.get_ignore_processes_set();
-        
-        for (port, process_info) in processes {
-            // Check if this process should be ignored
-            let should_ignore = ignore_ports.contains(port) || ignore_processes.contains(&process_info.name);
-            
-            if !should_ignore {
-                filtered.insert(*port, process_info.clone());
-            } else {
-                info!("Console: Ignoring process {} (PID {}) on port {} (ignored by user configuration)", 
-                      process_info.name, process_info.pid, port);
+    fn show_update(&mut self, update: &ProcessUpdate) {
+        // Filter out ignored processes
+        let mut filtered_processes = filter_ignored_processes(&update.processes, &self.args);
+        // --show-range narrows what is printed, not what is monitored
+        filtered_processes.retain(|port, _| self.args.is_port_shown(*port));
+        let changes = ProcessDiff::between(&self.last_processes, &filtered_processes);
+        if let Some(hooks) = &self.hooks {
+            hooks.on_detect(&changes.added);
+        }
+        if let Some(events) = &self.events {
+            events.changes(&changes);
+        }
+
+        if self.args.quiet {
+            self.print_changes(&changes);
+        } else {
+            self.print_status(update, &filtered_processes);
+        }
+
+        self.last_processes = filtered_processes;
+    }
+
+    /// Switch to a reloaded config's ports and ignore lists, unless they don't make a valid setup
+    fn apply_config(&mut self, config: &Config) -> bool {
+        let mut args = self.args.clone();
+        config.apply_to_args(&mut args);
+        if let Err(e) = args.validate() {
+            warn!("Ignoring config change, keeping the previous configuration: {}", e);
+            return false;
+        }
+
+        self.monitor_handle.retarget(args.get_ports_to_monitor(), args.discover_all);
+        self.args = args;
+        self.labels = config.labels.clone();
+        self.announce(&format!("🔁 Config reloaded, now monitoring {}", self.args.get_port_description()));
+        true
+    }
+
+    /// Switch to the entries of a changed --ports-file, unless it no longer lists any port
+    fn apply_ports_file(&mut self, ports: Vec<PortRange>) {
+        if ports.is_empty() {
+            warn!("Ignoring ports file change, keeping the previous ports: no ports listed in {}", self.args.ports_file.as_deref().unwrap_or_default());
+            return;
+        }
+
+        self.args.file_ports = ports;
+        self.monitor_handle.retarget(self.args.get_ports_to_monitor(), self.args.discover_all);
+        self.announce(&format!("🔁 Ports file reloaded, now monitoring {}", self.args.get_port_description()));
+    }
+
+    /// Print a status message, on stderr with --csv so stdout stays valid CSV
+    fn announce(&self, message: &str) {
+        if self.args.csv {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    fn print_status(&self, update: &ProcessUpdate, filtered_processes: &HashMap<u16, Vec<ProcessInfo>>) {
+        let filtered_count = process_count(filtered_processes);
+
+        // --csv: only the rows of this scan, under the header printed at startup
+        if self.args.csv {
+            let mut sorted_processes: Vec<&ProcessInfo> = filtered_processes.values().flatten().collect();
+            self.args.sort.sort(&mut sorted_processes);
+            for process_info in sorted_processes {
+                println!("{}", csv_row(process_info));
+            }
+            return;
+        }
+
+        // Update status
+        let status_info = StatusBarInfo::from_process_count(filtered_count).with_docker_count(docker_process_count(filtered_processes));
+
+        // Print status to console
+        let palette = self.palette;
+        println!("🔄 Port Status: {}", palette.status(filtered_count, &format!("{} - {}", status_info.text, status_info.tooltip)));
+
+        if filtered_count > 0 {
+            println!("📋 Detected Processes (after filtering ignored):");
+            let mut sorted_processes: Vec<&ProcessInfo> = filtered_processes.values().flatten().collect();
+            self.args.sort.sort(&mut sorted_processes);
+            let process_table = self.process_table();
+
+            for (header, group) in listing_groups(&self.args, self.runner.as_ref(), sorted_processes) {
+                if let Some(header) = header {
+                    println!("📁 {}", header);
+                }
+                for process_info in group {
+                    if let Some(ref template) = self.row_template {
+                        println!("{}", template.render(process_info));
+                    } else {
+                        let details = format!("{}{}", self.uptime_suffix(process_info), self.resources_suffix(process_info));
+                        println!("{}{}", process_line(process_info, &self.args, palette, &self.labels), details);
+                    }
+
+                    if let Some(ref table) = process_table {
+                        for line in render_tree(&table.descendants(process_info.pid)) {
+                            println!("     {}", palette.dim(&line));
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(summary) = process_type_summary(filtered_processes.values().flatten()) {
+            println!("{}", summary);
+        }
+
+        // Show ignored processes if any
+        let shown_count: usize = update.processes.iter().filter(|(port, _)| self.args.is_port_shown(**port)).map(|(_, on_port)| on_port.len()).sum();
+        let ignored_count = shown_count - filtered_count;
+        if ignored_count > 0 {
+            println!("🚫 Ignored {} process(es) based on user configuration", ignored_count);
+        }
+
+        let hidden_count = update.count - shown_count;
+        if let (Some(range), true) = (self.args.show_range, hidden_count > 0) {
+            println!("👁  Hiding {} process(es) outside --show-range {}", hidden_count, range);
+        }
+
+        println!();
+    }
+
+    /// One process table snapshot per status print when --tree is set
+    fn process_table(&self) -> Option<ProcessTable> {
+        if !self.args.tree {
+            return None;
+        }
+        match ProcessTable::snapshot(self.runner.as_ref()) {
+            Ok(table) => Some(table),
+            Err(e) => {
+                warn!("Failed to read the process tree: {:#}", e);
+                None
+            }
+        }
+    }
+
+    /// " (up 5m)" when --show-uptime is set and the start time is known
+    fn uptime_suffix(&self, process_info: &ProcessInfo) -> String {
+        match process_info.uptime() {
+            Some(uptime) if self.args.show_uptime => self.palette.dim(&format!(" (up {})", format_uptime(uptime))),
+            _ => String::new(),
+        }
+    }
+
+    /// " [CPU 12.5%, MEM 150.0 MB]" when --show-resources is set and usage is known
+    fn resources_suffix(&self, process_info: &ProcessInfo) -> String {
+        match (process_info.cpu_percent, process_info.memory_bytes) {
+            (Some(cpu_percent), Some(memory_bytes)) if self.args.show_resources => {
+                self.palette.dim(&format!(" [CPU {:.1}%, MEM {}]", cpu_percent, format_bytes(memory_bytes)))
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Print only what changed since the last snapshot (quiet mode)
+    fn print_changes(&self, changes: &ProcessDiff) {
+        for change in changes.describe() {
+            println!("🔔 {}", change);
+        }
+    }
+}
+
+/// Print the current processes once and return, without starting the monitor (--once)
+pub fn print_snapshot(args: &Args) -> Result<()> {
+    #[cfg(not(target_os = "windows"))]
+    LsofScanner::new(args.command_runner()).with_timeout(args.lsof_timeout).check_available()?;
+
+    let (_, mut processes) = get_processes_on_ports(&args.get_ports_to_monitor(), args);
+    processes.retain(|port, _| args.is_port_shown(*port));
+    let mut sorted_processes: Vec<&ProcessInfo> = processes.values().flatten().collect();
+    args.sort.sort(&mut sorted_processes);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&sorted_processes)?);
+        return Ok(());
+    }
+    if args.csv {
+        println!("{}", CSV_HEADER);
+        for process_info in sorted_processes {
+            println!("{}", csv_row(process_info));
+        }
+        return Ok(());
+    }
+
+    // --format prints only the rows (and --group-by headers), ready for awk or cut
+    let row_template = args.format.as_deref().map(RowTemplate::parse).transpose().context("Invalid --format")?;
+    if row_template.is_none() && sorted_processes.is_empty() {
+        println!("📋 No processes found on {}", args.get_port_description());
+        return Ok(());
+    }
+
+    let palette = Palette::from_args(args);
+    let labels = load_labels(&PathBuf::from(&args.config));
+    if row_template.is_none() {
+        println!("📋 {} process(es) on {}:", sorted_processes.len(), args.get_port_description());
+    }
+    let runner = args.command_runner();
+    for (header, group) in listing_groups(args, runner.as_ref(), sorted_processes) {
+        if let Some(header) = header {
+            println!("📁 {}", header);
+        }
+        for process_info in group {
+            match row_template {
+                Some(ref template) => println!("{}", template.render(process_info)),
+                None => println!("{}", process_line(process_info, args, palette, &labels)),
+            }
+        }
+    }
+    if row_template.is_none() {
+        if let Some(summary) = process_type_summary(processes.values().flatten()) {
+            println!("{}", summary);
+        }
+    }
+    Ok(())
+}
+
+/// `sorted` under one header per project directory with --group-by dir, otherwise as a single headerless group
+pub fn listing_groups<'a>(args: &Args, runner: &dyn CommandRunner, sorted: Vec<&'a ProcessInfo>) -> Vec<(Option<String>, Vec<&'a ProcessInfo>)> {
+    match args.group_by {
+        None => vec![(None, sorted)],
+        Some(GroupBy::Dir) => {
+            // Project markers are looked up on the local disk, so --remote groups by working directory only
+            let dirs = project_dirs(runner, &sorted, args.remote.is_none());
+            group_by_dir(&sorted, &dirs)
+                .into_iter()
+                .map(|(dir, members)| (Some(group_label(dir.as_deref())), members))
+                .collect()
+        }
+    }
+}
+
+/// Scan once and print one line in --status-format, without emoji or colors (--status-line)
+pub fn print_status_line(args: &Args) -> Result<()> {
+    let template = TooltipTemplate::parse(&args.status_format).context("Invalid --status-format")?.with_separator(",");
+
+    #[cfg(not(target_os = "windows"))]
+    LsofScanner::new(args.command_runner()).with_timeout(args.lsof_timeout).check_available()?;
+
+    let (_, mut processes) = get_processes_on_ports(&args.get_ports_to_monitor(), args);
+    processes.retain(|port, _| args.is_port_shown(*port));
+    println!("{}", template.render(&processes));
+    Ok(())
+}
+
+/// Block until something listens on --wait-open, then print it (--wait-open); fails on timeout
+pub fn wait_open(args: &Args, port: u16) -> Result<ExitCode> {
+    let scanner = LsofScanner::new(args.command_runner()).with_timeout(args.lsof_timeout).with_interface(args.interface).with_network_namespaces(args.netns);
+    #[cfg(not(target_os = "windows"))]
+    scanner.check_available()?;
+
+    if !args.json {
+        println!("⏳ Waiting up to {:?} for a listener on port {}...", args.wait_timeout, port);
+    }
+    let listeners = wait_for_port_open(&scanner, port, args.wait_timeout, WAIT_POLL_INTERVAL)?;
+    if listeners.is_empty() {
+        eprintln!("Error: Nothing listening on port {} after {:?}", port, args.wait_timeout);
+        return Ok(ExitCode::Failure);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&listeners)?);
+    } else {
+        let labels = load_labels(&PathBuf::from(&args.config));
+        println!("✅ {} is open:", labels.display(port));
+        for process_info in &listeners {
+            println!("{}", process_line(process_info, args, Palette::from_args(args), &labels));
+        }
+    }
+    Ok(ExitCode::Clean)
+}
+
+/// A process holding a --who port, with the details the listing leaves out
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PortHolder {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    /// Command with its arguments, e.g. "node node_modules/.bin/vite --port 3000"
+    pub command_line: Option<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// Print what holds `port` and the command to kill it (--who); [`ExitCode::NothingFound`] if it's free.
+///
+/// Ignore lists don't apply: an ignored process still holds the port.
+pub fn who(args: &Args, port: u16) -> Result<ExitCode> {
+    let runner = args.command_runner();
+    let scanner = LsofScanner::from_args(args);
+    #[cfg(not(target_os = "windows"))]
+    scanner.check_available()?;
+
+    let listeners: Vec<ProcessInfo> = listeners_by_port(scanner.scan(&[port])?).into_values().flatten().collect();
+    let pids: Vec<i32> = listeners.iter().map(|process_info| process_info.pid).collect();
+    let mut cwds = working_dirs(runner.as_ref(), &pids).unwrap_or_else(|e| {
+        warn!("Failed to read working directories: {:#}", e);
+        HashMap::new()
+    });
+    let holders: Vec<PortHolder> = listeners
+        .into_iter()
+        .map(|mut process_info| {
+            process_info.started_at = process_started_at(runner.as_ref(), process_info.pid);
+            process_info.managed_by = service_manager::detect(runner.as_ref(), process_info.pid);
+            PortHolder {
+                command_line: process_command_line(runner.as_ref(), process_info.pid),
+                cwd: cwds.remove(&process_info.pid),
+                process: process_info,
             }
+        })
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&holders)?);
+    } else if holders.is_empty() {
+        println!("✅ Nothing is listening on port {}", port);
+    } else {
+        let palette = Palette::from_args(args);
+        for holder in &holders {
+            print!("{}", who_report(holder, port, palette));
+        }
+    }
+    Ok(if holders.is_empty() { ExitCode::NothingFound } else { ExitCode::Clean })
+}
+
+/// The --who block for one holder of `port`
+pub fn who_report(holder: &PortHolder, port: u16, palette: Palette) -> String {
+    let process_info = &holder.process;
+    let unknown = || "unknown".to_string();
+    let mut report = format!("🔎 Port {} is held by {} (PID {})\n", palette.port(port), palette.name(&process_info.name), process_info.pid);
+    report.push_str(&format!("   Command:   {}\n", holder.command_line.clone().unwrap_or_else(|| process_info.command.clone())));
+    report.push_str(&format!("   Directory: {}\n", holder.cwd.as_ref().map(|cwd| cwd.display().to_string()).unwrap_or_else(unknown)));
+    report.push_str(&format!("   Uptime:    {}\n", process_info.uptime().map(format_uptime).unwrap_or_else(unknown)));
+    if let Some(ref state) = process_info.state {
+        report.push_str(&format!("   State:     {} (not listening)\n", state));
+    }
+    if let Some(ref owner) = process_info.owner {
+        report.push_str(&format!("   User:      {}\n", owner));
+    }
+    if let Some(ref container_name) = process_info.container_name {
+        report.push_str(&format!("   Docker:    {}\n", container_name));
+    }
+    if let Some(ref manager) = process_info.managed_by {
+        // A kill alone doesn't free the port for long
+        report.push_str(&format!("   Managed:   {}, restarted when killed\n", manager));
+        report.push_str(&format!("   Stop it:   {}\n", palette.dim(&manager.stop_command())));
+    }
+    report.push_str(&format!("   Kill it:   {}\n", palette.dim(&format!("port-kill-console --kill-pid {}", process_info.pid))));
+    report
+}
+
+/// Print the processes the filters leave out on the monitored ports, each with the filter that
+/// does (--list-ignored); [`ExitCode::NothingFound`] if nothing is filtered out
+pub fn list_ignored(args: &Args) -> Result<ExitCode> {
+    let scanner = LsofScanner::from_args(args);
+    #[cfg(not(target_os = "windows"))]
+    scanner.check_available()?;
+
+    let excluded = excluded_with_scanner(&scanner, &args.get_ports_to_monitor(), args)?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&excluded)?);
+    } else if excluded.is_empty() {
+        println!("✅ Nothing on {} is filtered out", args.get_port_description());
+    } else {
+        let palette = Palette::from_args(args);
+        let labels = load_labels(&PathBuf::from(&args.config));
+        println!("🙈 {} process(es) filtered out on {}:", excluded.len(), args.get_port_description());
+        for excluded in &excluded {
+            println!("{}", process_line(&excluded.process, args, palette, &labels));
+            println!("     {}", palette.dim(&format!("↳ {}", excluded.reason)));
+        }
+    }
+    Ok(if excluded.is_empty() { ExitCode::NothingFound } else { ExitCode::Clean })
+}
+
+/// One "   • Port 3000 [IPv4]: node - node" listing line, without uptime/resource details
+pub fn process_line(process_info: &ProcessInfo, args: &Args, palette: Palette, labels: &PortLabels) -> String {
+    // "Frontend (3000)" for a labelled port, otherwise "Port 3000"
+    let mut port = match (&process_info.socket_path, labels.get(process_info.port)) {
+        (Some(path), _) => format!("Socket {}", path),
+        (None, Some(label)) => format!("{} ({})", label, palette.port(process_info.port)),
+        (None, None) => format!("Port {}", palette.port(process_info.port)),
+    };
+    if let Some(family) = process_info.family_label() {
+        port.push_str(&format!(" [{}]", family));
+    }
+    // --include-established: the process holds the port without listening on it
+    if let Some(ref state) = process_info.state {
+        port.push_str(&format!(" ({}, not listening)", state));
+    }
+
+    let mut details = match process_info.owner {
+        Some(ref owner) if args.show_user => format!(" [user: {}]", owner),
+        _ => String::new(),
+    };
+    // Killing it only frees the port until the service manager restarts it
+    if let Some(ref manager) = process_info.managed_by {
+        details.push_str(&format!(" ♻️ [{}]", manager));
+    }
+    // --netns: the socket is only reachable inside this namespace
+    if let Some(ref namespace) = process_info.network_namespace {
+        details.push_str(&format!(" [netns: {}]", namespace));
+    }
+
+    if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
+        format!("   • {}: {} - {} {}{}",
+                port, palette.name(&process_info.name), palette.dim(&process_info.command), palette.docker(&format!("🐳 [Docker: {}]", container_name)), details)
+    } else if args.show_pid {
+        format!("   • {}: {} (PID {}) - {}{}",
+                port, palette.name(&process_info.name), process_info.pid, palette.dim(&process_info.command), details)
+    } else {
+        format!("   • {}: {} - {}{}",
+                port, palette.name(&process_info.name), palette.dim(&process_info.command), details)
+    }
+}
+
+/// "🐳 2 in Docker, 3 native" under a listing with containers in it; None when everything runs natively
+pub fn process_type_summary<'a>(processes: impl IntoIterator<Item = &'a ProcessInfo>) -> Option<String> {
+    let (docker, native): (Vec<&ProcessInfo>, Vec<&ProcessInfo>) = processes.into_iter().partition(|process_info| process_info.container_id.is_some());
+    if docker.is_empty() {
+        return None;
+    }
+    Some(format!("🐳 {} in Docker, {} native", docker.len(), native.len()))
+}
+
+/// Port labels from the config file, if there is one; a broken file only costs the labels
+pub fn load_labels(config_path: &Path) -> PortLabels {
+    load_optional_config(config_path).map(|config| config.labels).unwrap_or_default()
+}
+
+/// Print the configuration port-kill runs with and return (--export-config).
+///
+/// A missing --config file means the built-in defaults; one that can't be loaded is an error,
+/// since the point is to see what is actually in effect.
+pub fn export_config(args: &Args) -> Result<()> {
+    let config_path = PathBuf::from(&args.config);
+    let config = if config_path.exists() { Config::load(&config_path)? } else { Config::default() };
+    let config = config.merged_with_args(args);
+
+    let exported = if args.json {
+        serde_json::to_string_pretty(&config)?
+    } else if args.yaml {
+        serde_yaml::to_string(&config).context("Failed to serialize config as YAML")?
+    } else {
+        toml::to_string_pretty(&config).context("Failed to serialize config")?
+    };
+    println!("{}", exported.trim_end());
+    Ok(())
+}
+
+/// Validate a config file for --config-check, printing "OK" or the reason it can't be used.
+///
+/// Unlike a normal start, a missing file is an error and an older file is not rewritten.
+pub fn check_config(path: &Path) -> ExitCode {
+    match Config::check(path) {
+        Ok(_) => {
+            println!("OK: {}", path.display());
+            ExitCode::Clean
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::Failure
+        }
+    }
+}
+
+/// Show the processes of a bulk kill above max_kill_without_confirm and ask whether to go ahead.
+///
+/// Without a terminal on stdin there is nobody to ask, so the kill is refused.
+pub fn confirm_bulk_kill(processes: &[ProcessInfo], exceeded: KillLimitExceeded, args: &Args) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    let labels = load_labels(&PathBuf::from(&args.config));
+    println!("⚠️  {} process(es) would be killed, more than max_kill_without_confirm ({}):", exceeded.count, exceeded.limit);
+    for process_info in processes {
+        println!("{}", process_line(process_info, args, Palette::from_args(args), &labels));
+    }
+    print!("Kill all {}? [y/N]: ", exceeded.count);
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut answer = String::new();
+    std::io::BufRead::read_line(&mut std::io::stdin().lock(), &mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// The config file for optional extras (labels, webhook, kill escalation) outside --watch-config, if it loads
+pub fn load_optional_config(config_path: &Path) -> Option<Config> {
+    if !config_path.exists() {
+        return None;
+    }
+    match Config::load(config_path) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            warn!("Ignoring config file extras (labels, webhook, kill escalation), it could not be loaded: {:#}", e);
+            None
+        }
+    }
+}
+
+/// The kill escalation and retries of the --config file (the defaults without one), with --dump-before-kill applied
+pub fn load_kill_policy(args: &Args) -> KillPolicy {
+    args.kill_policy(&load_optional_config(Path::new(&args.config)).map(|config| config.app).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use crate::service_manager::ServiceManager;
+
+    #[test]
+    fn test_should_use_color() {
+        assert!(should_use_color(false, None, true));
+        assert!(!should_use_color(true, None, true));
+        assert!(!should_use_color(false, None, false));
+        assert!(!should_use_color(false, Some("1".into()), true));
+        // An empty NO_COLOR does not disable color
+        assert!(should_use_color(false, Some("".into()), true));
+    }
+
+    #[test]
+    fn test_palette_plain_when_disabled() {
+        let palette = Palette::new(false);
+        assert_eq!(palette.port(3000), "3000");
+        assert_eq!(palette.status(2, "2 running"), "2 running");
+    }
+
+    #[test]
+    fn test_palette_status_colors() {
+        let palette = Palette::new(true);
+        assert!(palette.status(0, "idle").starts_with("\u{1b}[32m"));
+        assert!(palette.status(3, "busy").starts_with("\u{1b}[33m"));
+    }
+
+    /// A remote host where every `kill` succeeds; the process is gone afterwards unless `alive`
+    #[cfg(unix)]
+    #[derive(Default)]
+    struct KillRecorder {
+        commands: std::sync::Mutex<Vec<String>>,
+        alive: bool,
+    }
+
+    #[cfg(unix)]
+    impl KillRecorder {
+        fn kills(&self) -> Vec<String> {
+            self.commands.lock().unwrap().iter().filter(|command| command.starts_with("kill ")).cloned().collect()
+        }
+    }
+
+    #[cfg(unix)]
+    impl crate::command_runner::CommandRunner for KillRecorder {
+        fn run(&self, program: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
+            use std::os::unix::process::ExitStatusExt;
+            self.commands.lock().unwrap().push(format!("{} {}", program, args.join(" ")));
+            let code = if program == "ps" && !self.alive { 1 } else { 0 };
+            Ok(std::process::Output { status: std::process::ExitStatus::from_raw(code << 8), stdout: Vec::new(), stderr: Vec::new() })
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
         }
-        
-        filtered
+
+        fn is_remote(&self) -> bool {
+            true
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_console_kills_use_the_configured_escalation() {
+        let path = std::env::temp_dir().join(format!("port-kill-escalation-{}.toml", std::process::id()));
+        let mut config = Config::default();
+        config.app.escalation = vec![crate::config::EscalationStepConfig { signal: "SIGINT".to_string(), wait_ms: 0 }];
+        config.save(&path).unwrap();
+        let args = Args::parse_from(["port-kill", "--config", path.to_str().unwrap()]);
+
+        let policy = load_kill_policy(&args);
+        let _ = std::fs::remove_file(&path);
+        let runner = KillRecorder::default();
+        let process = ProcessInfo { pid: 4242, port: 3000, name: "node".to_string(), ..Default::default() };
+        let outcomes = crate::process_monitor::kill_processes(&[process], &runner, &policy);
+
+        assert!(outcomes[0].success);
+        assert_eq!(runner.kills(), ["kill -s INT 4242"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_console_kills_use_the_configured_retries() {
+        let path = std::env::temp_dir().join(format!("port-kill-retries-{}.toml", std::process::id()));
+        let mut config = Config::default();
+        config.app.escalation = vec![crate::config::EscalationStepConfig { signal: "SIGTERM".to_string(), wait_ms: 0 }];
+        config.app.kill_retries = 2;
+        config.app.kill_retry_delay_ms = 0;
+        config.save(&path).unwrap();
+        let args = Args::parse_from(["port-kill", "--config", path.to_str().unwrap()]);
+
+        let policy = load_kill_policy(&args);
+        let _ = std::fs::remove_file(&path);
+        let runner = KillRecorder { alive: true, ..KillRecorder::default() };
+        let process = ProcessInfo { pid: 4242, port: 3000, name: "node".to_string(), ..Default::default() };
+        let outcomes = crate::process_monitor::kill_processes(&[process], &runner, &policy);
+
+        // SIGTERM, then the first SIGKILL and the two configured retries
+        assert!(!outcomes[0].success);
+        assert_eq!(runner.kills(), ["kill -s TERM 4242", "kill -s KILL 4242", "kill -s KILL 4242", "kill -s KILL 4242"]);
+    }
+
+    #[test]
+    fn test_who_report() {
+        let process = ProcessInfo {
+            pid: 4242,
+            port: 3000,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            owner: Some("me".to_string()),
+            ..Default::default()
+        };
+        let holder = PortHolder { process, command_line: Some("node server.js --port 3000".to_string()), cwd: Some(PathBuf::from("/home/me/shop")) };
+
+        assert_eq!(
+            who_report(&holder, 3000, Palette::new(false)),
+            "🔎 Port 3000 is held by node (PID 4242)\n   Command:   node server.js --port 3000\n   Directory: /home/me/shop\n   Uptime:    unknown\n   User:      me\n   Kill it:   port-kill-console --kill-pid 4242\n"
+        );
+
+        let json = serde_json::to_value(&holder).unwrap();
+        assert_eq!((json["pid"].as_i64(), json["cwd"].as_str()), (Some(4242), Some("/home/me/shop")));
+    }
+
+    #[test]
+    fn test_service_managed_process() {
+        let process = ProcessInfo {
+            pid: 812,
+            port: 80,
+            command: "nginx".to_string(),
+            name: "nginx".to_string(),
+            managed_by: Some(ServiceManager::Systemd { unit: "nginx.service".to_string(), user: false }),
+            ..Default::default()
+        };
+        let args = Args::parse_from(["port-kill"]);
+        assert_eq!(process_line(&process, &args, Palette::new(false), &PortLabels::default()), "   • Port 80: nginx - nginx ♻️ [systemd: nginx.service]");
+
+        let holder = PortHolder { process, command_line: None, cwd: None };
+        assert!(who_report(&holder, 80, Palette::new(false))
+            .contains("   Managed:   systemd: nginx.service, restarted when killed\n   Stop it:   systemctl stop nginx.service\n   Kill it:"));
+
+        let json = serde_json::to_value(&holder).unwrap();
+        assert_eq!(json["managed_by"], serde_json::json!({ "manager": "systemd", "unit": "nginx.service", "user": false }));
+    }
+
+    #[test]
+    fn test_docker_rows_and_summary() {
+        let native = ProcessInfo {
+            pid: 4242,
+            port: 3000,
+            command: "node".to_string(),
+            name: "node".to_string(),
+            ..Default::default()
+        };
+        let container = ProcessInfo {
+            pid: 5151,
+            port: 5432,
+            command: "docker-proxy".to_string(),
+            name: "docker-proxy".to_string(),
+            container_id: Some("3f2a9c".to_string()),
+            container_name: Some("shop-db".to_string()),
+            ..native.clone()
+        };
+        let args = Args::parse_from(["port-kill"]);
+        let labels = PortLabels::default();
+
+        assert_eq!(process_line(&native, &args, Palette::new(false), &labels), "   • Port 3000: node - node");
+        assert_eq!(process_line(&container, &args, Palette::new(false), &labels), "   • Port 5432: docker-proxy - docker-proxy 🐳 [Docker: shop-db]");
+        assert!(process_line(&container, &args, Palette::new(true), &labels).contains("\u{1b}[1m\u{1b}[34m🐳 [Docker: shop-db]"));
+
+        assert_eq!(process_type_summary([&native]), None);
+        assert_eq!(process_type_summary([&native, &container, &container]), Some("🐳 2 in Docker, 1 native".to_string()));
     }
 }
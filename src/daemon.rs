@@ -0,0 +1,206 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long `--stop` waits for the daemon to exit after SIGTERM
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default PID file location: $XDG_RUNTIME_DIR/port-kill.pid, falling back to the temp dir
+pub fn default_pid_file() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join("port-kill.pid")
+}
+
+/// Read the PID stored in a PID file, if the file exists and holds a valid PID
+pub fn read_pid_file(path: &Path) -> Option<i32> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.trim().parse::<i32>().ok())
+        .filter(|pid| *pid > 0)
+}
+
+/// Check whether a process with the given PID is alive
+pub fn is_pid_running(pid: i32) -> bool {
+    #[cfg(not(target_os = "windows"))]
+    {
+        use nix::errno::Errno;
+        use nix::sys::signal::kill;
+        use nix::unistd::Pid;
+
+        // Signal 0 only checks for existence; EPERM means it exists but belongs to someone else
+        match kill(Pid::from_raw(pid), None) {
+            Ok(_) => true,
+            Err(Errno::EPERM) => true,
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid)])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+            .unwrap_or(false)
+    }
+}
+
+/// Return the PID of a live daemon recorded in `path`, removing the file if it is stale
+pub fn running_daemon_pid(path: &Path) -> Option<i32> {
+    let pid = read_pid_file(path)?;
+
+    if is_pid_running(pid) {
+        Some(pid)
+    } else {
+        warn!("Removing stale PID file {:?} (PID {} is not running)", path, pid);
+        let _ = fs::remove_file(path);
+        None
+    }
+}
+
+/// Record the current process in the PID file, refusing if another daemon is alive
+pub fn write_pid_file(path: &Path) -> Result<()> {
+    if let Some(pid) = running_daemon_pid(path) {
+        return Err(anyhow::anyhow!("port-kill daemon is already running (PID {}, PID file {:?})", pid, path));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create PID file directory: {:?}", parent))?;
+    }
+
+    fs::write(path, format!("{}\n", std::process::id()))
+        .with_context(|| format!("Failed to write PID file: {:?}", path))?;
+
+    info!("Wrote PID file {:?}", path);
+    Ok(())
+}
+
+/// Detach from the terminal and continue in the background, recording the daemon PID
+///
+/// Must be called before any threads (including the tokio runtime) are started.
+#[cfg(not(target_os = "windows"))]
+pub fn daemonize(pid_file: &Path) -> Result<()> {
+    use nix::unistd::{dup2, fork, setsid, ForkResult};
+    use std::os::unix::io::AsRawFd;
+
+    // Check before forking so the user sees the error on their terminal
+    if let Some(pid) = running_daemon_pid(pid_file) {
+        return Err(anyhow::anyhow!("port-kill daemon is already running (PID {})", pid));
+    }
+
+    // Safety: called before any other threads exist, so the child is a faithful copy
+    match unsafe { fork() }.context("Failed to fork daemon process")? {
+        ForkResult::Parent { child } => {
+            println!("🚀 Port Kill daemon started (PID {}, PID file {:?})", child, pid_file);
+            std::process::exit(0);
+        }
+        ForkResult::Child => {}
+    }
+
+    // Become a session leader so the daemon loses its controlling terminal
+    setsid().context("Failed to create a new session")?;
+
+    // Point stdio at /dev/null; the working directory is kept so relative config/log paths resolve
+    let dev_null = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/null")
+        .context("Failed to open /dev/null")?;
+    for fd in 0..=2 {
+        dup2(dev_null.as_raw_fd(), fd).context("Failed to redirect standard streams")?;
+    }
+
+    write_pid_file(pid_file)
+}
+
+#[cfg(target_os = "windows")]
+pub fn daemonize(_pid_file: &Path) -> Result<()> {
+    Err(anyhow::anyhow!("--daemon is not supported on Windows"))
+}
+
+/// Terminate the daemon recorded in the PID file and clean the file up
+pub fn stop(pid_file: &Path) -> Result<i32> {
+    let pid = match read_pid_file(pid_file) {
+        Some(pid) => pid,
+        None => return Err(anyhow::anyhow!("No running daemon found (no PID file at {:?})", pid_file)),
+    };
+
+    if !is_pid_running(pid) {
+        let _ = fs::remove_file(pid_file);
+        return Err(anyhow::anyhow!("Daemon (PID {}) is not running; removed stale PID file {:?}", pid, pid_file));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        use nix::sys::signal::{kill, Signal};
+        use nix::unistd::Pid;
+
+        kill(Pid::from_raw(pid), Signal::SIGTERM)
+            .map_err(|e| anyhow::anyhow!("Failed to send SIGTERM to daemon (PID {}): {}", pid, e))?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .output()
+            .context("Failed to execute taskkill command")?;
+    }
+
+    let started = Instant::now();
+    while is_pid_running(pid) {
+        if started.elapsed() >= STOP_TIMEOUT {
+            return Err(anyhow::anyhow!("Daemon (PID {}) did not exit within {:?}", pid, STOP_TIMEOUT));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let _ = fs::remove_file(pid_file);
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_pid_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("port-kill-{}-{}.pid", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_read_pid_file() {
+        let path = temp_pid_file("read");
+        fs::write(&path, "4242\n").unwrap();
+        assert_eq!(read_pid_file(&path), Some(4242));
+
+        fs::write(&path, "not a pid").unwrap();
+        assert_eq!(read_pid_file(&path), None);
+
+        let _ = fs::remove_file(&path);
+        assert_eq!(read_pid_file(&path), None);
+    }
+
+    #[test]
+    fn test_stale_pid_file_is_removed() {
+        let path = temp_pid_file("stale");
+        fs::write(&path, format!("{}\n", i32::MAX)).unwrap();
+
+        assert_eq!(running_daemon_pid(&path), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_write_pid_file_refuses_live_daemon() {
+        let path = temp_pid_file("live");
+        fs::write(&path, format!("{}\n", std::process::id())).unwrap();
+
+        assert!(write_pid_file(&path).is_err());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::{
+    cli::Args,
+    command_runner::CommandRunner,
+    config::Config,
+    exit_code::ExitCode,
+};
+#[cfg(not(target_os = "windows"))]
+use crate::port_scanner::{LsofScanner, PortScanner};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Outcome of one --doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but some processes may be missed
+    Warn,
+    /// port-kill can't work until this is fixed
+    Fail,
+}
+
+impl CheckStatus {
+    fn icon(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+        }
+    }
+}
+
+/// One line of the --doctor checklist
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a warning or failure
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Pass, detail: detail.into(), hint: None }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Warn, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name, status: CheckStatus::Fail, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+/// Run every check that applies to `args`, in checklist order
+pub fn run_checks(args: &Args, runner: Arc<dyn CommandRunner>) -> Vec<Check> {
+    let mut checks = vec![check_backend(args, runner.clone())];
+    #[cfg(not(target_os = "windows"))]
+    checks.push(check_permissions(runner.as_ref()));
+    if args.docker {
+        checks.push(check_docker(runner.as_ref()));
+    }
+    checks.push(check_config(Path::new(&args.config)));
+    #[cfg(target_os = "macos")]
+    if !args.console && args.remote.is_none() {
+        checks.push(check_gui_session(&crate::command_runner::LocalRunner));
+    }
+    checks
+}
+
+/// Print the --doctor checklist; fails if any check failed
+pub fn run(args: &Args) -> ExitCode {
+    let runner = args.command_runner();
+    println!("🩺 Checking port-kill prerequisites on {}...", runner.describe());
+
+    let checks = run_checks(args, runner);
+    for check in &checks {
+        println!("  {} {}: {}", check.status.icon(), check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("     → {}", hint);
+        }
+    }
+
+    let failed = checks.iter().filter(|check| check.status == CheckStatus::Fail).count();
+    let warned = checks.iter().filter(|check| check.status == CheckStatus::Warn).count();
+    if failed > 0 {
+        println!("{} check(s) failed, {} warning(s)", failed, warned);
+        ExitCode::Failure
+    } else {
+        println!("All checks passed{}", if warned > 0 { format!(" ({} warning(s))", warned) } else { String::new() });
+        ExitCode::Clean
+    }
+}
+
+/// lsof must be installed and able to list sockets
+#[cfg(not(target_os = "windows"))]
+fn check_backend(args: &Args, runner: Arc<dyn CommandRunner>) -> Check {
+    const NAME: &str = "lsof";
+    let scanner = LsofScanner::new(runner).with_timeout(args.lsof_timeout);
+    if let Err(e) = scanner.check_available() {
+        return Check::fail(NAME, format!("{:#}", e), "Run --doctor again once lsof is installed");
+    }
+    match scanner.scan(&[]) {
+        Ok(found) => Check::pass(NAME, format!("installed, {} listening socket(s) visible", found.len())),
+        Err(e) => Check::fail(NAME, format!("installed but the scan failed: {:#}", e), "Run `lsof -i -sTCP:LISTEN -P -n` by hand to see the error"),
+    }
+}
+
+/// netstat is what discovers listeners on Windows
+#[cfg(target_os = "windows")]
+fn check_backend(_args: &Args, runner: Arc<dyn CommandRunner>) -> Check {
+    const NAME: &str = "netstat";
+    match runner.run("netstat", &["-ano"]) {
+        Ok(output) if output.status.success() => Check::pass(NAME, "installed and runnable"),
+        Ok(output) => Check::fail(NAME, format!("exited with {}", output.status), "Run `netstat -ano` by hand to see the error"),
+        Err(e) => Check::fail(NAME, format!("could not be started: {}", e), "netstat ships with Windows; check that System32 is on PATH"),
+    }
+}
+
+/// Without root, lsof only sees (and port-kill can only kill) the current user's processes
+#[cfg(not(target_os = "windows"))]
+fn check_permissions(runner: &dyn CommandRunner) -> Check {
+    const NAME: &str = "Permissions";
+    let uid = runner
+        .run("id", &["-u"])
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse::<u32>().ok());
+
+    match uid {
+        Some(0) => Check::pass(NAME, "running as root, processes of every user are visible"),
+        Some(uid) => Check::warn(
+            NAME,
+            format!("running as uid {}, processes owned by other users are hidden and can't be killed", uid),
+            "Run with sudo to see and kill every process",
+        ),
+        None => Check::warn(NAME, "could not determine the current user (`id -u` failed)", "Processes owned by other users may be hidden; run with sudo to be sure"),
+    }
+}
+
+/// --docker needs the docker CLI and a reachable daemon
+fn check_docker(runner: &dyn CommandRunner) -> Check {
+    const NAME: &str = "Docker";
+    match runner.run("docker", &["version", "--format", "{{.Server.Version}}"]) {
+        Ok(output) if output.status.success() => {
+            Check::pass(NAME, format!("daemon {} reachable", String::from_utf8_lossy(&output.stdout).trim()))
+        }
+        Ok(output) => Check::fail(
+            NAME,
+            format!("docker CLI found, but the daemon is not reachable: {}", String::from_utf8_lossy(&output.stderr).trim()),
+            "Start Docker (Docker Desktop, or `sudo systemctl start docker`) and make sure your user may access it",
+        ),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            Check::fail(NAME, "docker CLI not found", "Install Docker, or run without --docker")
+        }
+        Err(e) => Check::fail(NAME, format!("docker could not be started: {}", e), "Check the docker installation, or run without --docker"),
+    }
+}
+
+/// The --config file must parse; a missing one just means the defaults are used
+fn check_config(path: &Path) -> Check {
+    const NAME: &str = "Config";
+    if !path.exists() {
+        return Check::pass(NAME, format!("{:?} not found, built-in defaults are used", path));
+    }
+    match Config::check(path) {
+        Ok(_) => Check::pass(NAME, format!("{:?} is valid", path)),
+        Err(e) => Check::fail(NAME, format!("{:#}", e), "Fix the file, or move it away to fall back to the defaults"),
+    }
+}
+
+/// The status bar icon needs a logged-in GUI (Aqua) session, not just an SSH login
+#[cfg(target_os = "macos")]
+fn check_gui_session(runner: &dyn CommandRunner) -> Check {
+    const NAME: &str = "Status bar";
+    match runner.run("launchctl", &["managername"]) {
+        Ok(output) if String::from_utf8_lossy(&output.stdout).trim() == "Aqua" => Check::pass(NAME, "GUI session available for the status bar icon"),
+        _ => Check::warn(
+            NAME,
+            "no GUI login session, the status bar icon can't be shown",
+            "Start port-kill from the desktop session, or use --console",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Output;
+
+    /// Answers every program with a fixed result
+    struct ScriptedRunner(fn(&str) -> io::Result<Output>);
+
+    impl CommandRunner for ScriptedRunner {
+        fn run(&self, program: &str, _args: &[&str]) -> io::Result<Output> {
+            (self.0)(program)
+        }
+
+        fn describe(&self) -> String {
+            "doctor test host".to_string()
+        }
+    }
+
+    #[cfg(unix)]
+    fn output(code: i32, stdout: &str, stderr: &str) -> io::Result<Output> {
+        use std::os::unix::process::ExitStatusExt;
+        Ok(Output { status: std::process::ExitStatus::from_raw(code << 8), stdout: stdout.into(), stderr: stderr.into() })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_permissions() {
+        assert_eq!(check_permissions(&ScriptedRunner(|_| output(0, "0\n", ""))).status, CheckStatus::Pass);
+
+        let check = check_permissions(&ScriptedRunner(|_| output(0, "1000\n", "")));
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.detail.contains("uid 1000"));
+
+        assert_eq!(check_permissions(&ScriptedRunner(|_| output(1, "", ""))).status, CheckStatus::Warn);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_docker() {
+        let check = check_docker(&ScriptedRunner(|_| output(0, "27.1.1\n", "")));
+        assert_eq!((check.status, check.detail.as_str()), (CheckStatus::Pass, "daemon 27.1.1 reachable"));
+
+        let check = check_docker(&ScriptedRunner(|_| output(1, "", "Cannot connect to the Docker daemon\n")));
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.ends_with("Cannot connect to the Docker daemon"));
+
+        let check = check_docker(&ScriptedRunner(|_| Err(io::Error::from(io::ErrorKind::NotFound))));
+        assert_eq!((check.status, check.detail.as_str()), (CheckStatus::Fail, "docker CLI not found"));
+    }
+
+    #[test]
+    fn test_check_config() {
+        let path = std::env::temp_dir().join(format!("port-kill-doctor-{}.toml", std::process::id()));
+        assert_eq!(check_config(&path).status, CheckStatus::Pass);
+
+        std::fs::write(&path, "[monitoring\n").unwrap();
+        let check = check_config(&path);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.contains("Failed to parse config file"));
+
+        Config::default().save(&path).unwrap();
+        assert_eq!(check_config(&path).status, CheckStatus::Pass);
+        let _ = std::fs::remove_file(&path);
+    }
+}
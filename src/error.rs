@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Failures of the port-kill library that callers may want to tell apart.
+///
+/// The binaries report errors through anyhow, which wraps these; get one back from an
+/// `anyhow::Error` with `error.downcast_ref::<PortKillError>()`.
+#[derive(Debug, Error)]
+pub enum PortKillError {
+    /// lsof isn't installed on the host, or on the --remote host
+    #[error("lsof is not installed on {host} (install it with e.g. `apt install lsof`, `dnf install lsof` or `apk add lsof`)")]
+    LsofNotFound { host: String },
+    /// An lsof scan ran longer than --lsof-timeout and was killed
+    #[error("lsof did not finish within {timeout:?} on {host} and was killed (raise it with --lsof-timeout)")]
+    LsofTimeout { host: String, timeout: Duration },
+    #[error("No process with PID {pid} is running")]
+    ProcessNotFound { pid: i32 },
+    /// The process belongs to another user (or is protected by the OS): EPERM, so the kill stops at the first signal
+    #[error("Permission denied to kill PID {pid}; it is owned by another user. Try running with elevated privileges (e.g. sudo), or use --only-user to leave other users' processes out")]
+    PermissionDenied { pid: i32 },
+    /// The process survived the kill, or the signal could not be sent
+    #[error("{reason}")]
+    KillFailed { pid: i32, reason: String },
+    #[error("Failed to read config file {path:?}: {reason}")]
+    ConfigRead { path: PathBuf, reason: String },
+    /// The config file is not valid TOML, or doesn't have the expected tables and keys
+    #[error("Failed to parse config file {path:?}: {reason}")]
+    ConfigParse { path: PathBuf, reason: String },
+    /// The config file parsed, but a value is out of range
+    #[error("Invalid config file {path:?}: {reason}")]
+    ConfigInvalid { path: PathBuf, reason: String },
+}
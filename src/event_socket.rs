@@ -0,0 +1,256 @@
+use crate::types::{ProcessDiff, ProcessInfo};
+use crate::webhook::KillOutcome;
+use anyhow::Result;
+use log::{info, warn};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A subscriber that doesn't read its events for this long is disconnected
+pub const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// One line on the --event-socket stream, e.g. `{"event":"added","pid":4242,"port":3000,...}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A process started listening on a monitored port
+    Added(ProcessInfo),
+    /// A process stopped listening on a monitored port
+    Removed(ProcessInfo),
+    /// A process was killed
+    Killed(KillOutcome),
+    /// A kill failed
+    KillFailed(KillOutcome),
+}
+
+impl Event {
+    pub fn from_outcome(outcome: &KillOutcome) -> Self {
+        if outcome.success {
+            Event::Killed(outcome.clone())
+        } else {
+            Event::KillFailed(outcome.clone())
+        }
+    }
+
+    /// The event as one line of JSON, newline included
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+#[derive(Default)]
+struct Subscribers {
+    clients: Vec<Box<dyn Write + Send>>,
+    /// Processes currently listed, replayed as `added` events to each new client
+    listed: Vec<ProcessInfo>,
+}
+
+/// Sends events to every connected --event-socket client; cheap to clone
+#[derive(Clone, Default)]
+pub struct EventPublisher {
+    subscribers: Arc<Mutex<Subscribers>>,
+}
+
+impl EventPublisher {
+    /// Start sending events to `client`, beginning with an `added` event for each listed process
+    pub fn subscribe(&self, mut client: Box<dyn Write + Send>) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        let snapshot: String = subscribers.listed.iter().map(|process_info| Event::Added(process_info.clone()).to_line()).collect();
+        if client.write_all(snapshot.as_bytes()).and_then(|()| client.flush()).is_ok() {
+            subscribers.clients.push(client);
+        }
+    }
+
+    /// Publish the processes that appeared and disappeared since the last scan
+    pub fn changes(&self, changes: &ProcessDiff) {
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.listed.retain(|listed| !changes.removed.iter().any(|removed| removed.port == listed.port && removed.pid == listed.pid));
+            subscribers.listed.extend(changes.added.iter().cloned());
+            subscribers.listed.sort_by_key(|process_info| (process_info.port, process_info.pid));
+        }
+
+        let events: Vec<Event> = changes
+            .removed
+            .iter()
+            .cloned()
+            .map(Event::Removed)
+            .chain(changes.added.iter().cloned().map(Event::Added))
+            .collect();
+        self.publish(&events);
+    }
+
+    /// Publish the outcome of each kill
+    pub fn kills(&self, outcomes: &[KillOutcome]) {
+        let events: Vec<Event> = outcomes.iter().map(Event::from_outcome).collect();
+        self.publish(&events);
+    }
+
+    /// Write `events` to every client, dropping the ones that disconnected or stopped reading
+    pub fn publish(&self, events: &[Event]) {
+        if events.is_empty() {
+            return;
+        }
+        let lines: String = events.iter().map(Event::to_line).collect();
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        let before = subscribers.clients.len();
+        subscribers.clients.retain_mut(|client| client.write_all(lines.as_bytes()).and_then(|()| client.flush()).is_ok());
+        let dropped = before - subscribers.clients.len();
+        if dropped > 0 {
+            info!("Dropped {} event socket client(s) that disconnected or stopped reading", dropped);
+        }
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.subscribers.lock().map_or(0, |subscribers| subscribers.clients.len())
+    }
+}
+
+/// A Unix domain socket at --event-socket streaming [`Event`]s as newline-delimited JSON.
+///
+/// Clients are accepted on a background thread. The socket file is removed when this is dropped.
+pub struct EventSocket {
+    path: PathBuf,
+    publisher: EventPublisher,
+    stopped: Arc<AtomicBool>,
+}
+
+impl EventSocket {
+    /// Listen on `path`, replacing a socket file left behind by a port-kill that crashed
+    #[cfg(not(target_os = "windows"))]
+    pub fn bind(path: &Path) -> Result<Self> {
+        use anyhow::Context;
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                anyhow::bail!("{:?} already exists and is not a socket", path);
+            }
+            if UnixStream::connect(path).is_ok() {
+                anyhow::bail!("{:?} is already in use by another process", path);
+            }
+            std::fs::remove_file(path).with_context(|| format!("Failed to remove the stale socket {:?}", path))?;
+        }
+
+        let listener = UnixListener::bind(path).with_context(|| format!("Failed to bind the event socket {:?}", path))?;
+        let publisher = EventPublisher::default();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let (accept_publisher, accept_stopped) = (publisher.clone(), stopped.clone());
+        std::thread::Builder::new()
+            .name("event-socket".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if accept_stopped.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)) {
+                                warn!("Failed to set up event socket client: {}", e);
+                                continue;
+                            }
+                            info!("Event socket client connected");
+                            accept_publisher.subscribe(Box::new(stream));
+                        }
+                        Err(e) => warn!("Failed to accept event socket client: {}", e),
+                    }
+                }
+            })
+            .context("Failed to start the event socket thread")?;
+
+        Ok(Self { path: path.to_path_buf(), publisher, stopped })
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn bind(_path: &Path) -> Result<Self> {
+        Err(anyhow::anyhow!("--event-socket is not supported on Windows"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn publisher(&self) -> EventPublisher {
+        self.publisher.clone()
+    }
+}
+
+impl Drop for EventSocket {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // Wake the accept thread so it sees the stop flag
+        #[cfg(not(target_os = "windows"))]
+        let _ = std::os::unix::net::UnixStream::connect(&self.path);
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            warn!("Failed to remove the event socket {:?}: {}", self.path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_lines() {
+        let added: serde_json::Value = serde_json::from_str(&Event::Added(ProcessInfo::fixture(3000, 10, "node")).to_line()).unwrap();
+        assert_eq!((added["event"].as_str(), added["port"].as_u64(), added["pid"].as_i64()), (Some("added"), Some(3000), Some(10)));
+
+        let failed = KillOutcome::new(10, Some(3000), Some("node"), &Err(crate::error::PortKillError::PermissionDenied { pid: 10 }));
+        let line = Event::from_outcome(&failed).to_line();
+        assert!(line.starts_with("{\"event\":\"kill_failed\",") && line.ends_with("}\n"), "{}", line);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_event_socket_streams_to_every_client() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!("port-kill-events-{}.sock", std::process::id()));
+        let socket = EventSocket::bind(&path).unwrap();
+        let publisher = socket.publisher();
+        publisher.changes(&ProcessDiff { added: vec![ProcessInfo::fixture(3000, 10, "node")], removed: Vec::new() });
+
+        let connect = || {
+            let stream = UnixStream::connect(&path).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            BufReader::new(stream)
+        };
+        let (mut first, mut second) = (connect(), connect());
+        let started = std::time::Instant::now();
+        while publisher.client_count() < 2 {
+            assert!(started.elapsed() < Duration::from_secs(5), "clients were never accepted");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        publisher.changes(&ProcessDiff { added: Vec::new(), removed: vec![ProcessInfo::fixture(3000, 10, "node")] });
+        for client in [&mut first, &mut second] {
+            let events: Vec<String> = (0..2)
+                .map(|_| {
+                    let mut line = String::new();
+                    client.read_line(&mut line).unwrap();
+                    serde_json::from_str::<serde_json::Value>(&line).unwrap()["event"].as_str().unwrap().to_string()
+                })
+                .collect();
+            // The listed process is replayed on connect, then the change follows
+            assert_eq!(events, ["added", "removed"]);
+        }
+
+        // A second port-kill can't take over a live socket, and the file goes away on drop
+        assert!(EventSocket::bind(&path).is_err());
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,78 @@
+/// Exit codes of the console binary, so scripts can tell outcomes apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Monitoring stopped cleanly, or every matched process was killed
+    Clean = 0,
+    /// Some kills failed, or another error occurred (invalid arguments, daemon errors)
+    Failure = 1,
+    /// Kill mode (--kill-name, --kill-pid) found nothing to kill, or the --who port is free
+    NothingFound = 2,
+    /// lsof is not installed, locally or on the --remote host
+    BackendUnavailable = 3,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Exit code for a kill run that matched `matched` processes and killed `killed` of them
+    pub fn from_kill_result(matched: usize, killed: usize) -> Self {
+        if matched == 0 {
+            ExitCode::NothingFound
+        } else if killed < matched {
+            ExitCode::Failure
+        } else {
+            ExitCode::Clean
+        }
+    }
+
+    /// Exit code for an error, telling a missing lsof apart from other failures
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        if crate::port_scanner::is_lsof_missing(error) {
+            ExitCode::BackendUnavailable
+        } else {
+            ExitCode::Failure
+        }
+    }
+
+    /// Flush logs and exit the process with this code
+    pub fn exit(self) -> ! {
+        crate::shutdown::flush_logs();
+        std::process::exit(self.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+    use std::io;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(ExitCode::Clean.code(), 0);
+        assert_eq!(ExitCode::Failure.code(), 1);
+        assert_eq!(ExitCode::NothingFound.code(), 2);
+        assert_eq!(ExitCode::BackendUnavailable.code(), 3);
+    }
+
+    #[test]
+    fn test_from_kill_result() {
+        assert_eq!(ExitCode::from_kill_result(3, 3), ExitCode::Clean);
+        assert_eq!(ExitCode::from_kill_result(3, 2), ExitCode::Failure);
+        assert_eq!(ExitCode::from_kill_result(1, 0), ExitCode::Failure);
+        assert_eq!(ExitCode::from_kill_result(0, 0), ExitCode::NothingFound);
+    }
+
+    #[test]
+    fn test_from_error() {
+        let missing: anyhow::Result<()> = Err(io::Error::from(io::ErrorKind::NotFound)).context("Failed to execute lsof command");
+        assert_eq!(ExitCode::from_error(&missing.unwrap_err()), ExitCode::BackendUnavailable);
+
+        let denied: anyhow::Result<()> = Err(io::Error::from(io::ErrorKind::PermissionDenied)).context("Failed to execute lsof command");
+        assert_eq!(ExitCode::from_error(&denied.unwrap_err()), ExitCode::Failure);
+
+        assert_eq!(ExitCode::from_error(&anyhow::anyhow!("Daemon is not running")), ExitCode::Failure);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::webhook::KillOutcome;
+use anyhow::{Context, Result};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Kills remembered by default (`history_size` in the config)
+pub const DEFAULT_HISTORY_SIZE: usize = 20;
+
+/// Default history file: $XDG_RUNTIME_DIR/port-kill-history.json, falling back to the temp dir.
+///
+/// The runtime dir is emptied at logout, so the history lasts for the login session.
+pub fn default_history_file() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join("port-kill-history.json")
+}
+
+/// One process port-kill tried to kill
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KillRecord {
+    pub pid: i32,
+    pub port: Option<u16>,
+    pub name: Option<String>,
+    pub success: bool,
+    /// Unix timestamp in seconds
+    pub killed_at: u64,
+}
+
+impl KillRecord {
+    pub fn from_outcome(outcome: &KillOutcome, killed_at: u64) -> Self {
+        Self { pid: outcome.pid, port: outcome.port, name: outcome.name.clone(), success: outcome.success, killed_at }
+    }
+
+    /// "node:3000 @ 12:04" in local time, marked when the kill failed
+    pub fn label(&self) -> String {
+        let name = self.name.clone().unwrap_or_else(|| format!("PID {}", self.pid));
+        let target = match self.port {
+            Some(port) => format!("{}:{}", name, port),
+            None => name,
+        };
+        let failed = if self.success { "" } else { " (failed)" };
+        format!("{} @ {}{}", target, clock_time(self.killed_at), failed)
+    }
+}
+
+/// "HH:MM" of a Unix timestamp in the local time zone
+#[cfg(not(target_os = "windows"))]
+fn clock_time(timestamp: u64) -> String {
+    use nix::libc;
+
+    let time = timestamp as libc::time_t;
+    // SAFETY: localtime_r only writes to the tm we hand it
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return utc_clock_time(timestamp);
+    }
+    format!("{:02}:{:02}", tm.tm_hour, tm.tm_min)
+}
+
+#[cfg(target_os = "windows")]
+fn clock_time(timestamp: u64) -> String {
+    utc_clock_time(timestamp)
+}
+
+fn utc_clock_time(timestamp: u64) -> String {
+    format!("{:02}:{:02} UTC", timestamp / 3600 % 24, timestamp / 60 % 60)
+}
+
+/// The last `capacity` kills, oldest first, mirrored to a file so other port-kill
+/// processes (the tray, the console, `--history`) share one session history
+#[derive(Debug)]
+pub struct KillHistory {
+    records: VecDeque<KillRecord>,
+    capacity: usize,
+    path: Option<PathBuf>,
+}
+
+impl KillHistory {
+    /// Start from the kills already in `path`, if any; a `capacity` of 0 keeps no history
+    pub fn new(capacity: usize, path: Option<PathBuf>) -> Self {
+        let mut history = Self { records: VecDeque::new(), capacity, path };
+        history.reload();
+        history
+    }
+
+    /// History shared by every port-kill of this login session, in [`default_history_file`]
+    pub fn session(capacity: usize) -> Self {
+        Self::new(capacity, Some(default_history_file()))
+    }
+
+    /// Remember `outcomes`, dropping the oldest kills beyond the capacity
+    pub fn record(&mut self, outcomes: &[KillOutcome]) {
+        if self.capacity == 0 || outcomes.is_empty() {
+            return;
+        }
+        // Another process may have recorded kills since we last looked
+        self.reload();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+        self.records.extend(outcomes.iter().map(|outcome| KillRecord::from_outcome(outcome, now)));
+        while self.records.len() > self.capacity {
+            self.records.pop_front();
+        }
+
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, self.records.make_contiguous()) {
+                warn!("Failed to save the kill history: {:#}", e);
+            }
+        }
+    }
+
+    /// Recorded kills, newest first
+    pub fn recent(&self) -> impl Iterator<Item = &KillRecord> {
+        self.records.iter().rev()
+    }
+
+    fn reload(&mut self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match read(path) {
+            Ok(records) => {
+                let skip = records.len().saturating_sub(self.capacity);
+                self.records = records.into_iter().skip(skip).collect();
+            }
+            Err(e) => warn!("Ignoring the kill history: {:#}", e),
+        }
+    }
+}
+
+/// The kills recorded in `path`, oldest first; none if it doesn't exist
+pub fn read(path: &Path) -> Result<Vec<KillRecord>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {:?}", path))
+}
+
+/// Print the kills of this session, newest first (--history)
+pub fn print(json: bool) -> Result<()> {
+    let mut records = read(&default_history_file())?;
+    records.reverse();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    } else if records.is_empty() {
+        println!("🕘 No processes killed this session");
+    } else {
+        println!("🕘 Recently killed (newest first):");
+        for record in &records {
+            println!("   • {} (PID {})", record.label(), record.pid);
+        }
+    }
+    Ok(())
+}
+
+fn save(path: &Path, records: &[KillRecord]) -> Result<()> {
+    let content = serde_json::to_string(records).context("Failed to serialize the kill history")?;
+    fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PortKillError;
+
+    #[test]
+    fn test_history_keeps_the_newest_kills() {
+        let path = std::env::temp_dir().join(format!("port-kill-history-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut history = KillHistory::new(3, Some(path.clone()));
+        history.record(&[KillOutcome::new(10, Some(3000), Some("node"), &Ok(()))]);
+        history.record(&[
+            KillOutcome::new(11, Some(5173), Some("vite"), &Ok(())),
+            KillOutcome::new(12, None, None, &Err(PortKillError::PermissionDenied { pid: 12 })),
+            KillOutcome::new(13, Some(8080), Some("java"), &Ok(())),
+        ]);
+        assert_eq!(history.recent().map(|record| record.pid).collect::<Vec<_>>(), vec![13, 12, 11]);
+
+        // Another process sees the same history through the file
+        let other = KillHistory::new(2, Some(path.clone()));
+        assert_eq!(other.recent().map(|record| record.pid).collect::<Vec<_>>(), vec![13, 12]);
+        assert_eq!(read(&path).unwrap().len(), 3);
+
+        let mut disabled = KillHistory::new(0, None);
+        disabled.record(&[KillOutcome::new(10, Some(3000), Some("node"), &Ok(()))]);
+        assert_eq!(disabled.recent().count(), 0);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_label() {
+        let record = KillRecord { pid: 10, port: Some(3000), name: Some("node".to_string()), success: true, killed_at: 0 };
+        let label = record.label();
+        let time = label.strip_prefix("node:3000 @ ").unwrap_or_else(|| panic!("unexpected label {:?}", label));
+        assert_eq!(time.as_bytes()[2], b':', "{}", label);
+
+        let failed = KillRecord { pid: 12, port: None, name: None, success: false, killed_at: 0 };
+        assert!(failed.label().starts_with("PID 12 @ "));
+        assert!(failed.label().ends_with(" (failed)"));
+        assert_eq!(utc_clock_time(12 * 3600 + 4 * 60 + 59), "12:04 UTC");
+    }
+}
@@ -0,0 +1,208 @@
+use crate::config::AppConfig;
+use crate::types::ProcessInfo;
+use crate::webhook::KillOutcome;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How long a hook command may run before it is killed
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const HOOK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// What a hook run is about, passed as PORT_KILL_EVENT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A process was killed (`on_kill_command`)
+    Kill,
+    /// A kill failed (`on_kill_command`)
+    KillFailed,
+    /// A process started listening on a monitored port (`on_detect_command`)
+    Detect,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::Kill => "kill",
+            HookEvent::KillFailed => "kill_failed",
+            HookEvent::Detect => "detect",
+        }
+    }
+}
+
+/// One run of a hook command, for one process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookRun {
+    pub event: HookEvent,
+    pub pid: i32,
+    pub port: Option<u16>,
+    pub name: Option<String>,
+}
+
+impl HookRun {
+    pub fn from_outcome(outcome: &KillOutcome) -> Self {
+        let event = if outcome.success { HookEvent::Kill } else { HookEvent::KillFailed };
+        Self { event, pid: outcome.pid, port: outcome.port, name: outcome.name.clone() }
+    }
+
+    pub fn from_detected(process_info: &ProcessInfo) -> Self {
+        Self { event: HookEvent::Detect, pid: process_info.pid, port: Some(process_info.port), name: Some(process_info.name.clone()) }
+    }
+
+    /// The PORT_KILL_* variables of this run; an unknown port or name is passed as an empty string
+    pub fn env(&self) -> [(&'static str, String); 4] {
+        [
+            ("PORT_KILL_EVENT", self.event.as_str().to_string()),
+            ("PORT_KILL_PID", self.pid.to_string()),
+            ("PORT_KILL_PORT", self.port.map(|port| port.to_string()).unwrap_or_default()),
+            ("PORT_KILL_NAME", self.name.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// User commands run on kill and detect events, from `[app] on_kill_command` and `on_detect_command`
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    on_kill: Option<String>,
+    on_detect: Option<String>,
+    timeout: Duration,
+}
+
+impl Hooks {
+    pub fn new(on_kill: Option<String>, on_detect: Option<String>) -> Self {
+        Self { on_kill, on_detect, timeout: HOOK_TIMEOUT }
+    }
+
+    /// The hooks from the config, if either command is set
+    pub fn from_config(app: &AppConfig) -> Option<Self> {
+        (app.on_kill_command.is_some() || app.on_detect_command.is_some())
+            .then(|| Self::new(app.on_kill_command.clone(), app.on_detect_command.clone()))
+    }
+
+    /// Kill hook commands still running after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run `on_kill_command` once per outcome, in the background.
+    ///
+    /// One-shot commands can join the handle so the process doesn't exit first.
+    pub fn on_kill(&self, outcomes: &[KillOutcome]) -> Option<JoinHandle<()>> {
+        let runs = outcomes.iter().map(HookRun::from_outcome).collect();
+        self.spawn(self.on_kill.as_deref()?, runs)
+    }
+
+    /// Run `on_detect_command` once per newly detected process, in the background
+    pub fn on_detect(&self, processes: &[ProcessInfo]) -> Option<JoinHandle<()>> {
+        let runs = processes.iter().map(HookRun::from_detected).collect();
+        self.spawn(self.on_detect.as_deref()?, runs)
+    }
+
+    fn spawn(&self, command: &str, runs: Vec<HookRun>) -> Option<JoinHandle<()>> {
+        if runs.is_empty() {
+            return None;
+        }
+
+        let command = command.to_string();
+        let timeout = self.timeout;
+        let spawned = std::thread::Builder::new()
+            .name("hook".to_string())
+            .spawn(move || {
+                for run in runs {
+                    match run_hook(&command, &run, timeout) {
+                        Ok(status) if status.success() => info!("Ran {} hook for PID {}", run.event.as_str(), run.pid),
+                        Ok(status) => warn!("{} hook for PID {} exited with {}", run.event.as_str(), run.pid, status),
+                        Err(e) => warn!("{} hook for PID {} failed: {:#}", run.event.as_str(), run.pid, e),
+                    }
+                }
+            });
+
+        match spawned {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                warn!("Failed to start hook: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Run `command` through the shell with the environment of `run`, killing it after `timeout`.
+///
+/// The hook gets no stdin and its output is discarded, so it can't mix into the console listing.
+pub fn run_hook(command: &str, run: &HookRun, timeout: Duration) -> Result<ExitStatus> {
+    #[cfg(not(target_os = "windows"))]
+    let mut shell = Command::new("sh");
+    #[cfg(not(target_os = "windows"))]
+    shell.args(["-c", command]);
+    #[cfg(target_os = "windows")]
+    let mut shell = Command::new("cmd");
+    #[cfg(target_os = "windows")]
+    shell.args(["/C", command]);
+
+    let mut child = shell
+        .envs(run.env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to start {:?}", command))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("{:?} did not finish within {:?} and was killed", command, timeout);
+        }
+        std::thread::sleep(HOOK_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PortKillError;
+
+    #[test]
+    fn test_hook_env() {
+        let killed = HookRun::from_outcome(&KillOutcome::new(10, Some(3000), Some("node"), &Ok(())));
+        assert_eq!(
+            killed.env(),
+            [
+                ("PORT_KILL_EVENT", "kill".to_string()),
+                ("PORT_KILL_PID", "10".to_string()),
+                ("PORT_KILL_PORT", "3000".to_string()),
+                ("PORT_KILL_NAME", "node".to_string()),
+            ]
+        );
+
+        let failed = HookRun::from_outcome(&KillOutcome::new(20, None, None, &Err(PortKillError::PermissionDenied { pid: 20 })));
+        assert_eq!(failed.event, HookEvent::KillFailed);
+        assert_eq!((failed.env()[2].1.as_str(), failed.env()[3].1.as_str()), ("", ""));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_run_hook() {
+        let out = std::env::temp_dir().join(format!("port-kill-hook-{}.txt", std::process::id()));
+        let run = HookRun { event: HookEvent::Detect, pid: 42, port: Some(5173), name: Some("vite".to_string()) };
+
+        let command = format!("echo \"$PORT_KILL_EVENT $PORT_KILL_PORT $PORT_KILL_PID $PORT_KILL_NAME\" > {}", out.display());
+        assert!(run_hook(&command, &run, Duration::from_secs(5)).unwrap().success());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "detect 5173 42 vite\n");
+        let _ = std::fs::remove_file(&out);
+
+        // A hung hook is killed instead of blocking its caller
+        let started = Instant::now();
+        assert!(run_hook("sleep 30", &run, Duration::from_millis(200)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!run_hook("exit 3", &run, Duration::from_secs(5)).unwrap().success());
+    }
+}
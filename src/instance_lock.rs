@@ -0,0 +1,114 @@
+use crate::daemon::{is_pid_running, read_pid_file};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Default lock file location: $XDG_RUNTIME_DIR/port-kill.lock, falling back to the temp dir
+pub fn default_lock_file() -> PathBuf {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    dir.join("port-kill.lock")
+}
+
+/// Keeps a second status bar instance from fighting the first over the same ports.
+///
+/// The lock file holds the owner's PID and is removed when the lock is dropped. A lock
+/// left behind by a crashed instance is detected by its dead PID and taken over.
+#[derive(Debug)]
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    /// Take the lock at `path`, failing with the other instance's PID if it is still running
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create lock file directory: {:?}", parent))?;
+        }
+
+        // Two attempts: the second one follows removing a stale lock
+        for _ in 0..2 {
+            match fs::OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", std::process::id())
+                        .with_context(|| format!("Failed to write lock file: {:?}", path))?;
+                    info!("Acquired instance lock {:?}", path);
+                    return Ok(Self { path: path.to_path_buf() });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to create lock file: {:?}", path)),
+            }
+
+            match read_pid_file(path) {
+                Some(pid) if is_pid_running(pid) => {
+                    return Err(anyhow::anyhow!(
+                        "port-kill is already running (PID {}); quit it first or remove the lock file {:?}",
+                        pid, path
+                    ));
+                }
+                Some(pid) => warn!("Removing stale lock file {:?} (PID {} is not running)", path, pid),
+                // Unreadable or empty: left by an instance that died while writing it
+                None => warn!("Removing invalid lock file {:?}", path),
+            }
+            let _ = fs::remove_file(path);
+        }
+
+        Err(anyhow::anyhow!("Failed to acquire lock file {:?}: another instance keeps recreating it", path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // Only remove the file if it is still ours
+        if read_pid_file(&self.path) == Some(std::process::id() as i32) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_lock_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("port-kill-{}-{}.lock", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_second_acquire_is_refused_until_released() {
+        let path = temp_lock_file("held");
+        let _ = fs::remove_file(&path);
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(read_pid_file(lock.path()), Some(std::process::id() as i32));
+
+        let error = InstanceLock::acquire(&path).unwrap_err();
+        assert!(error.to_string().contains("already running"));
+
+        drop(lock);
+        assert!(!path.exists());
+        drop(InstanceLock::acquire(&path).unwrap());
+    }
+
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let path = temp_lock_file("stale");
+        fs::write(&path, format!("{}\n", i32::MAX)).unwrap();
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        assert_eq!(read_pid_file(&path), Some(std::process::id() as i32));
+        drop(lock);
+
+        fs::write(&path, "").unwrap();
+        drop(InstanceLock::acquire(&path).unwrap());
+        assert!(!path.exists());
+    }
+}
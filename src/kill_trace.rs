@@ -0,0 +1,85 @@
+use crate::error::PortKillError;
+use crate::process_monitor::KillPolicy;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// Log target of the --trace-kill timeline, e.g. `RUST_LOG=kill_trace=trace` enables it without the flag
+pub const KILL_TRACE_TARGET: &str = "kill_trace";
+
+/// Timeline of one kill for --trace-kill: each step is logged with the time since the kill started,
+/// so a report shows exactly where a process resisted
+pub struct KillTrace {
+    pid: i32,
+    started: Instant,
+}
+
+impl KillTrace {
+    /// Start the timeline for `pid`, logging the plan `policy` will follow
+    pub fn start(pid: i32, policy: &KillPolicy) -> Self {
+        let trace = Self { pid, started: Instant::now() };
+        trace.step(format_args!("kill started: {}", describe_policy(policy)));
+        trace
+    }
+
+    pub fn step(&self, message: impl Display) {
+        log::trace!(target: KILL_TRACE_TARGET, "{}", trace_line(self.pid, self.started.elapsed(), message));
+    }
+
+    /// Log the outcome of sending `signal` and pass it on
+    pub fn signal_sent<T>(&self, signal: &str, result: Result<T, PortKillError>) -> Result<T, PortKillError> {
+        match result {
+            Ok(_) => self.step(format_args!("{} sent", signal)),
+            Err(ref e) => self.step(format_args!("{} not sent: {}", signal, e)),
+        }
+        result
+    }
+
+    /// Log a liveness check and pass its result on
+    pub fn liveness(&self, alive: bool) -> bool {
+        self.step(if alive { "liveness check: still running" } else { "liveness check: gone" });
+        alive
+    }
+
+    /// Log the final state of the kill
+    pub fn finish(&self, result: &Result<(), PortKillError>) {
+        match result {
+            Ok(()) => self.step("final state: terminated"),
+            Err(e) => self.step(format_args!("final state: failed ({})", e)),
+        }
+    }
+}
+
+/// "PID 4242 +0.503s: SIGTERM sent"
+pub fn trace_line(pid: i32, elapsed: Duration, message: impl Display) -> String {
+    format!("PID {} +{:.3}s: {}", pid, elapsed.as_secs_f64(), message)
+}
+
+/// "SIGTERM (wait 500ms), then SIGKILL up to 4 times, 100ms apart"
+fn describe_policy(policy: &KillPolicy) -> String {
+    let dump = policy.dump_wait.map(|wait| format!("SIGQUIT for a dump (wait {:?})", wait));
+    let steps: Vec<String> = dump.into_iter().chain(policy.escalation.iter().map(|step| format!("{} (wait {:?})", step.signal, step.wait))).collect();
+    let sigkill = format!("SIGKILL up to {} time(s), {:?} apart", policy.sigkill_retries + 1, policy.retry_delay);
+    if steps.is_empty() {
+        sigkill
+    } else {
+        format!("{}, then {}", steps.join(", "), sigkill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_line_and_policy() {
+        assert_eq!(trace_line(4242, Duration::from_millis(503), "SIGTERM sent"), "PID 4242 +0.503s: SIGTERM sent");
+        assert_eq!(describe_policy(&KillPolicy::default()), "SIGTERM (wait 500ms), then SIGKILL up to 4 time(s), 250ms apart");
+
+        let policy = KillPolicy { escalation: Vec::new(), sigkill_retries: 0, retry_delay: Duration::from_millis(50), dump_wait: None };
+        assert_eq!(describe_policy(&policy), "SIGKILL up to 1 time(s), 50ms apart");
+        assert_eq!(
+            describe_policy(&policy.with_dump(Some(Duration::from_secs(2)))),
+            "SIGQUIT for a dump (wait 2s), then SIGKILL up to 1 time(s), 50ms apart"
+        );
+    }
+}
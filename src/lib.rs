@@ -1,8 +1,40 @@
+pub mod auto_kill;
+pub mod badge;
 pub mod cli;
+pub mod command_runner;
 pub mod config;
+pub mod config_watcher;
 pub mod console_app;
+pub mod daemon;
+pub mod doctor;
+pub mod error;
+pub mod event_socket;
+pub mod exit_code;
+pub mod history;
+pub mod hooks;
+pub mod instance_lock;
+pub mod kill_trace;
+pub mod logging;
+pub mod metrics;
+pub mod netns;
+pub mod picker;
+pub mod port_aliases;
+pub mod port_scanner;
+pub mod ports_file;
 pub mod process_monitor;
+pub mod process_tree;
+pub mod project_group;
+pub mod project_ports;
+pub mod refresh;
+pub mod respawn;
+pub mod row_format;
+pub mod rpc;
+pub mod service_manager;
+pub mod session_summary;
+pub mod shutdown;
+pub mod tooltip;
 pub mod types;
+pub mod webhook;
 
 // macOS-specific modules (only compiled on macOS)
 #[cfg(target_os = "macos")]
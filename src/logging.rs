@@ -0,0 +1,161 @@
+use crate::cli::{Args, LogFormat};
+use crate::kill_trace::KILL_TRACE_TARGET;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Rotate the log file once it grows beyond this size
+pub const LOG_FILE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Initialize env_logger from RUST_LOG, honoring --log-file and --log-format
+pub fn init(args: &Args) -> Result<()> {
+    let mut builder = env_logger::Builder::from_default_env();
+
+    if args.log_format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = format_json_record(
+                &buf.timestamp().to_string(),
+                record.level(),
+                record.target(),
+                &record.args().to_string(),
+            );
+            writeln!(buf, "{}", line)
+        });
+    }
+
+    // --trace-kill shows the kill timeline whatever the log level
+    if args.trace_kill {
+        builder.filter_module(KILL_TRACE_TARGET, log::LevelFilter::Trace);
+    }
+
+    if let Some(ref path) = args.log_file {
+        let file = RotatingFile::open(PathBuf::from(path), LOG_FILE_MAX_BYTES)
+            .with_context(|| format!("Failed to open log file: {}", path))?;
+        builder.target(env_logger::Target::Pipe(Box::new(TeeWriter { file })));
+    }
+
+    builder.try_init().context("Failed to initialize logger")?;
+    Ok(())
+}
+
+/// Render a single log record as one line of JSON
+pub fn format_json_record(timestamp: &str, level: log::Level, target: &str, message: &str) -> String {
+    serde_json::json!({
+        "timestamp": timestamp,
+        "level": level.to_string(),
+        "target": target,
+        "message": message,
+    })
+    .to_string()
+}
+
+/// Writes every log line to stderr and to the log file
+struct TeeWriter {
+    file: RotatingFile,
+}
+
+impl Write for TeeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // A broken stderr (e.g. detached daemon) must not stop file logging
+        let _ = io::stderr().write_all(buf);
+        self.file.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let _ = io::stderr().flush();
+        self.file.flush()
+    }
+}
+
+/// Append-only log file that moves itself to `<path>.1` once it exceeds `max_bytes`
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            max_bytes,
+            file,
+            written,
+        })
+    }
+
+    fn rotated_path(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        PathBuf::from(rotated)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        fs::rename(&self.path, self.rotated_path())?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_json_record() {
+        let line = format_json_record("2024-01-01T00:00:00Z", log::Level::Warn, "port_kill", "Port 3000 \"busy\"");
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "port_kill");
+        assert_eq!(value["message"], "Port 3000 \"busy\"");
+        assert_eq!(value["timestamp"], "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_rotating_file_rotates_when_full() {
+        let dir = std::env::temp_dir().join(format!("port-kill-log-test-{}", std::process::id()));
+        let path = dir.join("port-kill.log");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut file = RotatingFile::open(path.clone(), 16).unwrap();
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("port-kill.log.1")).unwrap(), "first line\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second line\n");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -9,8 +9,33 @@ use clap::Parser;
 
 #[cfg(target_os = "macos")]
 fn main() -> Result<()> {
-    // Parse command-line arguments
-    let args = Args::parse();
+    // Parse command-line arguments, with the config's [aliases] as port names
+    port_kill::port_aliases::register_from_args(std::env::args_os());
+    let mut args = Args::parse();
+
+    // Validate a config file and exit, without creating or rewriting it
+    if let Some(path) = args.config_check_path() {
+        port_kill::console_app::check_config(std::path::Path::new(&path)).exit();
+    }
+
+    // Check prerequisites and exit, before a default config file gets created
+    if args.doctor {
+        port_kill::doctor::run(&args).exit();
+    }
+
+    // Make the running status bar app rescan now and exit
+    if args.refresh {
+        match port_kill::refresh::send(&port_kill::instance_lock::default_lock_file(), &args.get_pid_file()) {
+            Ok(pid) => {
+                println!("🔄 Asked port-kill (PID {}) to refresh", pid);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
     // Load configuration file
     let config_path = std::path::Path::new(&args.config);
@@ -23,11 +48,17 @@ fn main() -> Result<()> {
     };
 
     // Validate arguments (CLI args can override config)
-    if let Err(e) = args.validate() {
+    if let Err(e) = args.validate().and_then(|()| args.resolve_mine()).and_then(|()| args.resolve_project_ports()).and_then(|()| args.resolve_ports_file()) {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 
+    // The status bar app always manages local processes
+    if args.remote.is_some() {
+        eprintln!("Error: --remote is only supported in console mode (use --console)");
+        std::process::exit(1);
+    }
+
     // Set up logging level based on log_level argument
     let log_level = if args.verbose {
         // Verbose flag overrides log_level for backward compatibility
@@ -38,7 +69,19 @@ fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    // A second status bar instance would double-kill everything the first one kills
+    let instance_lock = match port_kill::instance_lock::InstanceLock::acquire(&port_kill::instance_lock::default_lock_file()) {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     info!("Starting Port Kill application...");
     info!("Monitoring: {}", config.get_monitoring_description());
@@ -46,8 +89,10 @@ fn main() -> Result<()> {
     // Create and run the application
     let app = PortKillApp::new(args, config)?;
     app.run()?;
+    drop(instance_lock);
 
     info!("Port Kill application stopped");
+    port_kill::shutdown::flush_logs();
     Ok(())
 }
 
@@ -1,36 +1,378 @@
 use anyhow::Result;
 use log::info;
-use port_kill::{console_app::ConsolePortKillApp, cli::Args};
+use port_kill::{console_app::ConsolePortKillApp, cli::Args, exit_code::ExitCode, port_scanner::{LingeringScanner, LsofScanner, NetstatScanner}, process_monitor::{check_kill_limit, kill_process, kill_processes, wait_for_ports_free, KillPolicy, SingleKill, StartTimePick, WAIT_POLL_INTERVAL}, webhook::{KillOutcome, Webhook}, history::KillHistory, hooks::Hooks, respawn::RESPAWN_DETECTION_WINDOW, session_summary::{unix_now, KillReport, ReportedKill}, types::format_uptime};
 use clap::Parser;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let args = Args::parse();
+fn main() -> Result<()> {
+    // Start of the run for --report
+    let started_at = unix_now();
+
+    // The config's [aliases] are resolved while parsing the port arguments
+    port_kill::port_aliases::register_from_args(std::env::args_os());
+
+    // Parse command-line arguments (usage errors exit with 1, not clap's 2, which means "nothing found")
+    let mut args = Args::try_parse().unwrap_or_else(|e| {
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() { ExitCode::Failure.code() } else { ExitCode::Clean.code() });
+    });
     
+    // Validate a config file and exit, before anything else reads it
+    if let Some(path) = args.config_check_path() {
+        port_kill::console_app::check_config(std::path::Path::new(&path)).exit();
+    }
+
     // Validate arguments
-    if let Err(e) = args.validate() {
+    if let Err(e) = args.validate().and_then(|()| args.resolve_mine()).and_then(|()| args.resolve_project_ports()).and_then(|()| args.resolve_ports_file()) {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        ExitCode::Failure.exit();
+    }
+
+    // Stop a running daemon and exit
+    if args.stop {
+        match port_kill::daemon::stop(&args.get_pid_file()) {
+            Ok(pid) => {
+                println!("✅ Stopped Port Kill daemon (PID {})", pid);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::Failure.exit();
+            }
+        }
+    }
+
+    // Make the running status bar app or daemon rescan now and exit
+    if args.refresh {
+        match port_kill::refresh::send(&port_kill::instance_lock::default_lock_file(), &args.get_pid_file()) {
+            Ok(pid) => {
+                println!("🔄 Asked port-kill (PID {}) to refresh", pid);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::Failure.exit();
+            }
+        }
+    }
+
+    // Detach before the tokio runtime spawns its worker threads
+    if args.daemon {
+        if let Err(e) = port_kill::daemon::daemonize(&args.get_pid_file()) {
+            eprintln!("Error: {}", e);
+            ExitCode::Failure.exit();
+        }
     }
 
     // Set up logging level based on verbose flag
     if args.verbose {
         std::env::set_var("RUST_LOG", "debug");
     } else if std::env::var("RUST_LOG").is_err() {
-        std::env::set_var("RUST_LOG", "info");
+        // --status-line output ends up in prompts and status bars, so only errors are logged
+        std::env::set_var("RUST_LOG", if args.status_line { "error" } else { "info" });
     }
 
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {}", e);
+        ExitCode::Failure.exit();
+    }
     
+    // Check prerequisites and exit
+    if args.doctor {
+        port_kill::doctor::run(&args).exit();
+    }
+
+    // Print the processes killed this session and exit
+    if args.history {
+        if let Err(e) = port_kill::history::print(args.json) {
+            eprintln!("Error: {:#}", e);
+            ExitCode::Failure.exit();
+        }
+        ExitCode::Clean.exit();
+    }
+
+    // Print the effective configuration and exit
+    if args.export_config {
+        if let Err(e) = port_kill::console_app::export_config(&args) {
+            eprintln!("Error: {:#}", e);
+            ExitCode::Failure.exit();
+        }
+        ExitCode::Clean.exit();
+    }
+
+    // Kill processes by name and exit
+    if !args.kill_name.is_empty() {
+        let ports = args.get_ports_to_monitor();
+        let matches = match port_kill::process_monitor::find_processes_by_name(&ports, &args) {
+            Ok(matches) => matches,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        };
+
+        // A misconfigured port range can match far more than intended; ask before killing it all
+        let app_config = port_kill::console_app::load_optional_config(std::path::Path::new(&args.config))
+            .map(|config| config.app)
+            .unwrap_or_default();
+        if let Err(exceeded) = check_kill_limit(matches.len(), app_config.max_kill_without_confirm) {
+            let confirmed = args.yes || port_kill::console_app::confirm_bulk_kill(&matches, exceeded, &args).unwrap_or(false);
+            if !confirmed {
+                eprintln!("Error: {}; confirm on a terminal, pass --yes or raise max_kill_without_confirm", exceeded);
+                ExitCode::Failure.exit();
+            }
+        }
+
+        let policy = args.kill_policy(&app_config);
+        let mut outcomes = kill_processes(&matches, args.command_runner().as_ref(), &policy);
+        let matched = outcomes.len();
+        let killed = outcomes.iter().filter(|outcome| outcome.success).count();
+        println!("🔪 {} process(es) named {} matched on {}, {} killed",
+                 matched, args.kill_name.join(", "), args.get_port_description(), killed);
+        outcomes.extend(watch_respawns(&args, &outcomes, &policy));
+        report_kills(&args, outcomes, started_at);
+        wait_free(&args, ExitCode::from_kill_result(matched, killed)).exit();
+    }
+
+    // Kill the given PIDs and exit
+    if !args.kill_pid.is_empty() {
+        let policy = port_kill::console_app::load_kill_policy(&args);
+        let mut outcomes = Vec::new();
+        let mut would_kill = 0;
+        for &pid in &args.kill_pid {
+            match port_kill::process_monitor::kill_single_process(pid, &args, &policy) {
+                Ok(SingleKill::Killed) => {
+                    println!("🔪 Killed PID {}", pid);
+                    outcomes.push(KillOutcome::new(pid, None, None, &Ok(())));
+                }
+                Ok(SingleKill::Ignored) => println!("🛡 PID {} is ignored (--ignore-processes, --ignore-ports or --only-user), not killed", pid),
+                Ok(SingleKill::DryRun) => {
+                    println!("🧪 Dry run: would kill PID {}", pid);
+                    would_kill += 1;
+                }
+                Err(e) => {
+                    eprintln!("Error: Failed to kill PID {}: {}", pid, e);
+                    outcomes.push(KillOutcome::new(pid, None, None, &Err(e)));
+                }
+            }
+        }
+        let matched = outcomes.len() + would_kill;
+        let killed = outcomes.iter().filter(|outcome| outcome.success).count() + would_kill;
+        report_kills(&args, outcomes, started_at);
+        wait_free(&args, ExitCode::from_kill_result(matched, killed)).exit();
+    }
+
+    // Kill the longest-running or the most recently started process and exit
+    if let Some(pick) = StartTimePick::from_args(&args) {
+        let ports = args.get_ports_to_monitor();
+        let chosen = match port_kill::process_monitor::find_process_by_start_time(&ports, &args, pick) {
+            Ok(chosen) => chosen,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        };
+        let Some(chosen) = chosen else {
+            println!("📋 No process with a known start time on {}, nothing killed", args.get_port_description());
+            ExitCode::NothingFound.exit();
+        };
+
+        let running_for = format_uptime(std::time::Duration::from_secs(unix_now().saturating_sub(chosen.started_at.unwrap_or_default())));
+        println!("🎯 The {} process on {} is {} (PID {}) on port {}, running for {}",
+                 pick.label(), args.get_port_description(), chosen.name, chosen.pid, chosen.port, running_for);
+        if args.dry_run {
+            println!("🧪 Dry run: would kill PID {}", chosen.pid);
+            wait_free(&args, ExitCode::Clean).exit();
+        }
+        let outcomes = kill_processes(std::slice::from_ref(&chosen), args.command_runner().as_ref(), &port_kill::console_app::load_kill_policy(&args));
+        let killed = outcomes.iter().filter(|outcome| outcome.success).count();
+        match outcomes.first() {
+            Some(outcome) if outcome.success => println!("🔪 Killed PID {}", chosen.pid),
+            Some(outcome) => eprintln!("Error: Failed to kill PID {}: {}", chosen.pid, outcome.error.as_deref().unwrap_or("unknown error")),
+            None => {}
+        }
+        report_kills(&args, outcomes, started_at);
+        wait_free(&args, ExitCode::from_kill_result(1, killed)).exit();
+    }
+
+    // Pick a process to kill interactively and exit
+    if args.pick {
+        match port_kill::picker::run(&args) {
+            Ok(code) => code.exit(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        }
+    }
+
+    // Wait for a listener to appear and exit
+    if let Some(port) = args.wait_open {
+        match port_kill::console_app::wait_open(&args, port) {
+            Ok(code) => code.exit(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        }
+    }
+
+    // Show what holds a port and exit
+    if let Some(port) = args.who {
+        match port_kill::console_app::who(&args, port) {
+            Ok(code) => code.exit(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        }
+    }
+
+    // Show what the filters leave out and exit
+    if args.list_ignored {
+        match port_kill::console_app::list_ignored(&args) {
+            Ok(code) => code.exit(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        }
+    }
+
+    // Answer JSON-RPC on stdio until the editor closes it
+    if args.stdio_rpc {
+        match port_kill::rpc::run(&args) {
+            Ok(code) => code.exit(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from_error(&e).exit();
+            }
+        }
+    }
+
+    // Print one compact status line and exit
+    if args.status_line {
+        if let Err(e) = port_kill::console_app::print_status_line(&args) {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from_error(&e).exit();
+        }
+        ExitCode::Clean.exit();
+    }
+
+    // Print a single snapshot and exit
+    if args.once {
+        if let Err(e) = port_kill::console_app::print_snapshot(&args) {
+            eprintln!("Error: {}", e);
+            ExitCode::from_error(&e).exit();
+        }
+        ExitCode::Clean.exit();
+    }
+
     info!("Starting Console Port Kill application...");
     info!("Monitoring: {}", args.get_port_description());
 
     // Create and run the console application
-    let app = ConsolePortKillApp::new(args)?;
-    app.run().await?;
+    let daemon_pid_file = args.daemon.then(|| args.get_pid_file());
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(async {
+        let app = ConsolePortKillApp::new(args)?;
+        app.run().await
+    });
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        ExitCode::from_error(&e).exit();
+    }
+
+    // The daemon owns its PID file, so a SIGTERM'd daemon cleans up after itself
+    if let Some(pid_file) = daemon_pid_file {
+        let _ = std::fs::remove_file(pid_file);
+    }
 
     info!("Console Port Kill application stopped");
+    port_kill::shutdown::flush_logs();
     Ok(())
 }
+
+/// With --wait-free, block until its ports are released; a timeout turns `code` into a failure
+fn wait_free(args: &Args, code: ExitCode) -> ExitCode {
+    if args.wait_free.is_empty() || args.dry_run {
+        return code;
+    }
+
+    let ports: Vec<String> = args.wait_free.iter().map(u16::to_string).collect();
+    println!("⏳ Waiting up to {:?} for port(s) {} to be free...", args.wait_timeout, ports.join(", "));
+    let scanner = LsofScanner::new(args.command_runner()).with_timeout(args.lsof_timeout);
+    let lingering = args.wait_lingering.then(|| NetstatScanner::new(args.command_runner(), args.lsof_timeout));
+    match wait_for_ports_free(&scanner, lingering.as_ref().map(|netstat| netstat as &dyn LingeringScanner), &args.wait_free, args.wait_timeout, WAIT_POLL_INTERVAL) {
+        Ok(taken) if taken.is_empty() => {
+            println!("✅ Port(s) {} free", ports.join(", "));
+            code
+        }
+        Ok(taken) => {
+            let taken: Vec<String> = taken.iter().map(u16::to_string).collect();
+            eprintln!("Error: Port(s) {} still in use after {:?}", taken.join(", "), args.wait_timeout);
+            if args.wait_lingering {
+                eprintln!("   TIME_WAIT sockets are held by the kernel for up to 2 minutes after close, even with their process gone");
+            }
+            ExitCode::Failure
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from_error(&e)
+        }
+    }
+}
+
+/// After --kill-name, warn about processes that take the freed ports right away and, with
+/// --kill-respawns, kill them too; returns those kills
+fn watch_respawns(args: &Args, killed: &[KillOutcome], policy: &KillPolicy) -> Vec<KillOutcome> {
+    let runner = args.command_runner();
+    let scanner = LsofScanner::new(runner.clone()).with_timeout(args.lsof_timeout);
+    let report = port_kill::respawn::watch(&scanner, killed, args, RESPAWN_DETECTION_WINDOW, WAIT_POLL_INTERVAL, &mut |process_info| {
+        kill_process(process_info.pid, runner.as_ref(), policy)
+    });
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Warning: Could not check for respawned processes: {}", e);
+            return Vec::new();
+        }
+    };
+
+    for process_info in &report.respawns {
+        println!("♻️  Port {} was taken again by {} (PID {}); a supervisor may be respawning it",
+                 process_info.port, process_info.name, process_info.pid);
+    }
+    if !report.respawns.is_empty() && !args.kill_respawns {
+        println!("   Pass --kill-respawns to kill respawned processes too, or stop the supervisor");
+    }
+    if !report.outcomes.is_empty() {
+        let killed = report.outcomes.iter().filter(|outcome| outcome.success).count();
+        println!("🔪 {} respawned process(es) killed", killed);
+    }
+    report.outcomes
+}
+
+/// Add a one-shot kill to the session history, write the --report and report it to the config's
+/// webhook and `on_kill_command`, waiting for both before the process exits
+fn report_kills(args: &Args, outcomes: Vec<KillOutcome>, started_at: u64) {
+    if let Some(ref path) = args.report {
+        let kills = outcomes.iter().cloned().map(ReportedKill::now).collect();
+        match KillReport::new(args, started_at, kills).write(path) {
+            Ok(()) => println!("📝 Report written to {}", path.display()),
+            Err(e) => eprintln!("Error: {:#}", e),
+        }
+    }
+
+    let config = port_kill::console_app::load_optional_config(std::path::Path::new(&args.config));
+    let history_size = config.as_ref().map_or(port_kill::history::DEFAULT_HISTORY_SIZE, |config| config.app.history_size);
+    KillHistory::session(history_size).record(&outcomes);
+    if let Some(hook) = config.as_ref().and_then(|config| Hooks::from_config(&config.app)).and_then(|hooks| hooks.on_kill(&outcomes)) {
+        let _ = hook.join();
+    }
+    if let Some(webhook) = config.as_ref().and_then(|config| Webhook::from_config(&config.app)) {
+        if let Some(delivery) = webhook.notify(outcomes) {
+            let _ = delivery.join();
+        }
+    }
+}
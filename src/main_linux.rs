@@ -4,8 +4,8 @@
 use port_kill::{
     cli::Args,
     console_app::ConsolePortKillApp,
-    types::StatusBarInfo,
-    process_monitor::{get_processes_on_ports, kill_all_processes},
+    types::{docker_process_count, StatusBarInfo},
+    process_monitor::{get_processes_on_ports, kill_all_processes, DEFAULT_MAX_KILL_WITHOUT_CONFIRM},
     app::PortKillApp,
 };
 use tray_item::TrayItem;
@@ -21,10 +21,10 @@ use std::time::Duration;
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command-line arguments
-    let args = Args::parse();
+    let mut args = Args::parse();
     
     // Validate arguments
-    if let Err(e) = args.validate() {
+    if let Err(e) = args.validate().and_then(|()| args.resolve_mine()).and_then(|()| args.resolve_project_ports()).and_then(|()| args.resolve_ports_file()) {
         eprintln!("Error: {}", e);
         process::exit(1);
     }
@@ -39,7 +39,10 @@ async fn main() -> Result<()> {
     env::set_var("RUST_LOG", log_level);
 
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
     
     info!("Starting Port Kill application on Linux...");
     info!("Monitoring: {}", args.get_port_description());
@@ -52,6 +55,15 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    // A second tray instance would double-kill everything the first one kills
+    let _instance_lock = match port_kill::instance_lock::InstanceLock::acquire(&port_kill::instance_lock::default_lock_file()) {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
     // Try to start tray mode, fallback to console if it fails
     match start_tray_mode(args.clone()).await {
         Ok(_) => {
@@ -84,15 +96,16 @@ async fn start_tray_mode(args: Args) -> Result<()> {
     
     // Clone args for the closure
     let args_clone = args.clone();
+    let kill_policy = port_kill::console_app::load_kill_policy(&args);
     
     // Set up tray icon click handler
     tray.add_menu_item("Kill All Processes", move || {
         info!("Kill All Processes clicked");
         let result = if args_clone.discover_all {
-            PortKillApp::kill_all_discovered_processes(&args_clone)
+            PortKillApp::kill_all_discovered_processes(&args_clone, &kill_policy, DEFAULT_MAX_KILL_WITHOUT_CONFIRM)
         } else {
             let ports_to_kill = args_clone.get_ports_to_monitor();
-            kill_all_processes(&ports_to_kill, &args_clone)
+            kill_all_processes(&ports_to_kill, &args_clone, &kill_policy, DEFAULT_MAX_KILL_WITHOUT_CONFIRM)
         };
         
         if let Err(e) = result {
@@ -121,7 +134,7 @@ async fn start_tray_mode(args: Args) -> Result<()> {
         };
         
         // Update status
-        let status_info = StatusBarInfo::from_process_count(process_count);
+        let status_info = StatusBarInfo::from_process_count(process_count).with_docker_count(docker_process_count(&processes));
         println!("🔄 Port Status: {} - {}", status_info.text, status_info.tooltip);
         
         // Print detected processes
@@ -130,6 +143,8 @@ async fn start_tray_mode(args: Args) -> Result<()> {
             for (port, process_info) in &processes {
                 if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
                     println!("   • Port {}: {} [Docker: {}]", port, process_info.name, container_name);
+                } else if let Some(ref namespace) = process_info.network_namespace {
+                    println!("   • Port {}: {} [netns: {}]", port, process_info.name, namespace);
                 } else if args.show_pid {
                     println!("   • Port {}: {} (PID {})", port, process_info.name, process_info.pid);
                 } else {
@@ -34,7 +34,10 @@ async fn main() -> Result<()> {
     std::env::set_var("RUST_LOG", log_level);
     
     // Initialize logging
-    env_logger::init();
+    if let Err(e) = port_kill::logging::init(&args) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
     
     info!("Starting Port Kill application on Windows...");
     info!("Monitoring: {}", args.get_port_description());
@@ -216,7 +219,10 @@ fn get_processes_on_ports(ports: &[u16], args: &Args) -> (usize, HashMap<u16, Pr
                                     let process_name = get_process_name_by_pid(pid).unwrap_or_else(|| "unknown".to_string());
                                     
                                     // Check if this process should be ignored
-                                    let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&process_name);
+                                    let bind_address = parts[1].rsplit_once(':').map(|(address, _)| address.to_string());
+                                    let should_ignore = ignore_ports.contains(&port)
+                                        || ignore_processes.contains(&process_name)
+                                        || !args.bind_filter.matches(bind_address.as_deref());
                                     
                                     if !should_ignore {
                                         processes.insert(port, ProcessInfo {
@@ -226,6 +232,10 @@ fn get_processes_on_ports(ports: &[u16], args: &Args) -> (usize, HashMap<u16, Pr
                                             name: process_name,
                                             container_id: None,
                                             container_name: None,
+                                            bind_address,
+                                            started_at: None,
+                                            cpu_percent: None,
+                                            memory_bytes: None,
                                         });
                                     } else {
                                         info!("Ignoring process {} (PID {}) on port {} (ignored by user configuration)", process_name, pid, port);
@@ -0,0 +1,204 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Upper bounds (in seconds) of the lsof scan duration histogram buckets
+pub const SCAN_DURATION_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Counters and gauges exposed on --metrics-port in the Prometheus text format
+#[derive(Debug)]
+pub struct Metrics {
+    listening_ports: AtomicU64,
+    processes_killed: AtomicU64,
+    kill_failures: AtomicU64,
+    /// Cumulative bucket counts, one per `SCAN_DURATION_BUCKETS` entry
+    scan_buckets: [AtomicU64; SCAN_DURATION_BUCKETS.len()],
+    scan_count: AtomicU64,
+    scan_sum_micros: AtomicU64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            listening_ports: AtomicU64::new(0),
+            processes_killed: AtomicU64::new(0),
+            kill_failures: AtomicU64::new(0),
+            scan_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            scan_count: AtomicU64::new(0),
+            scan_sum_micros: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    /// Number of monitored ports that currently have a listener
+    pub fn set_listening_ports(&self, count: usize) {
+        self.listening_ports.store(count as u64, Ordering::Relaxed);
+    }
+
+    /// Count a finished kill attempt
+    pub fn record_kill(&self, succeeded: bool) {
+        let counter = if succeeded { &self.processes_killed } else { &self.kill_failures };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Add one port scan to the duration histogram
+    pub fn observe_scan(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bucket, upper_bound) in self.scan_buckets.iter().zip(SCAN_DURATION_BUCKETS) {
+            if seconds <= upper_bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.scan_count.fetch_add(1, Ordering::Relaxed);
+        self.scan_sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Render every metric in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# HELP port_kill_listening_ports Monitored ports that currently have a listening process");
+        let _ = writeln!(out, "# TYPE port_kill_listening_ports gauge");
+        let _ = writeln!(out, "port_kill_listening_ports {}", self.listening_ports.load(Ordering::Relaxed));
+
+        let _ = writeln!(out, "# HELP port_kill_processes_killed_total Processes killed successfully");
+        let _ = writeln!(out, "# TYPE port_kill_processes_killed_total counter");
+        let _ = writeln!(out, "port_kill_processes_killed_total {}", self.processes_killed.load(Ordering::Relaxed));
+
+        let _ = writeln!(out, "# HELP port_kill_kill_failures_total Kill attempts that failed");
+        let _ = writeln!(out, "# TYPE port_kill_kill_failures_total counter");
+        let _ = writeln!(out, "port_kill_kill_failures_total {}", self.kill_failures.load(Ordering::Relaxed));
+
+        let _ = writeln!(out, "# HELP port_kill_scan_duration_seconds Time taken by each lsof port scan");
+        let _ = writeln!(out, "# TYPE port_kill_scan_duration_seconds histogram");
+        for (bucket, upper_bound) in self.scan_buckets.iter().zip(SCAN_DURATION_BUCKETS) {
+            let _ = writeln!(out, "port_kill_scan_duration_seconds_bucket{{le=\"{}\"}} {}", upper_bound, bucket.load(Ordering::Relaxed));
+        }
+        let count = self.scan_count.load(Ordering::Relaxed);
+        let _ = writeln!(out, "port_kill_scan_duration_seconds_bucket{{le=\"+Inf\"}} {}", count);
+        let _ = writeln!(out, "port_kill_scan_duration_seconds_sum {}", self.scan_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0);
+        let _ = writeln!(out, "port_kill_scan_duration_seconds_count {}", count);
+
+        out
+    }
+}
+
+/// The process-wide metrics, updated by the monitor loop and the kill functions
+pub fn global() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+/// Bind the metrics endpoint on localhost (port 0 picks a free port)
+pub async fn bind(port: u16) -> Result<TcpListener> {
+    TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint on 127.0.0.1:{}", port))
+}
+
+/// Answer scrapes of `GET /metrics` until the task is dropped
+pub async fn serve(listener: TcpListener) {
+    if let Ok(address) = listener.local_addr() {
+        info!("Serving Prometheus metrics on http://{}/metrics", address);
+    }
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream).await {
+                        warn!("Metrics request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept metrics connection: {}", e),
+        }
+    }
+}
+
+/// Minimal HTTP/1.1: one request per connection, only the request line is looked at
+async fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 8192 {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let response = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => http_response("200 OK", "text/plain; version=0.0.4", &global().render()),
+        (Some("GET"), Some(_)) => http_response("404 Not Found", "text/plain", "Not found, metrics are served on /metrics\n"),
+        _ => http_response("405 Method Not Allowed", "text/plain", "Only GET is supported\n"),
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, content_type, body.len(), body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.set_listening_ports(3);
+        metrics.record_kill(true);
+        metrics.record_kill(true);
+        metrics.record_kill(false);
+        metrics.observe_scan(Duration::from_millis(30));
+        metrics.observe_scan(Duration::from_secs(20));
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE port_kill_listening_ports gauge\nport_kill_listening_ports 3\n"));
+        assert!(text.contains("port_kill_processes_killed_total 2\n"));
+        assert!(text.contains("port_kill_kill_failures_total 1\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_bucket{le=\"0.025\"} 0\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_bucket{le=\"0.05\"} 1\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_bucket{le=\"10\"} 1\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_bucket{le=\"+Inf\"} 2\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_sum 20.03\n"));
+        assert!(text.contains("port_kill_scan_duration_seconds_count 2\n"));
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_over_http() {
+        let listener = bind(0).await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(listener));
+
+        let get = |path: &'static str| async move {
+            let mut stream = TcpStream::connect(address).await.unwrap();
+            stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).await.unwrap();
+            response
+        };
+
+        let response = get("/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(response.contains("# TYPE port_kill_scan_duration_seconds histogram"));
+
+        assert!(get("/").await.starts_with("HTTP/1.1 404 Not Found\r\n"));
+        server.abort();
+    }
+}
@@ -0,0 +1,96 @@
+use crate::command_runner::CommandRunner;
+use anyhow::{Context, Result};
+
+/// A network namespace other than port-kill's own (Linux), such as a container's or one made
+/// with `ip netns add`. lsof only resolves the sockets of the namespace it runs in, so listeners
+/// in here are invisible to a plain scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetNamespace {
+    /// Inode of the namespace, as in `net:[4026532500]`
+    pub inode: u64,
+    /// A process inside it, whose `/proc/<pid>/ns/net` is entered to scan it
+    pub pid: i32,
+    /// Name given with `ip netns add`, if any
+    pub name: Option<String>,
+}
+
+impl NetNamespace {
+    /// "blue" for a namespace created with `ip netns add blue`, otherwise "net:[4026532500]"
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| format!("net:[{}]", self.inode))
+    }
+
+    /// The namespace file `nsenter --net=` enters
+    pub fn path(&self) -> String {
+        format!("/proc/{}/ns/net", self.pid)
+    }
+}
+
+/// The network namespaces other than the current one, from `lsns -t net` (which reads
+/// `/proc/<pid>/ns/net` of every process and the `ip netns` mounts under /run/netns).
+/// Without root only the namespaces of the user's own processes are listed.
+pub fn list(runner: &dyn CommandRunner) -> Result<Vec<NetNamespace>> {
+    let output = runner
+        .run("lsns", &["--type", "net", "--noheadings", "--raw", "--output", "NS,PID,NSFS"])
+        .context("Failed to execute lsns command")?;
+    if !output.status.success() {
+        anyhow::bail!("lsns failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    let current = current_namespace(runner);
+    Ok(parse_lsns(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|namespace| Some(namespace.inode) != current)
+        .collect())
+}
+
+/// Inode of the namespace commands run in, from `readlink /proc/self/ns/net`
+fn current_namespace(runner: &dyn CommandRunner) -> Option<u64> {
+    let output = runner.run("readlink", &["/proc/self/ns/net"]).ok()?;
+    parse_namespace_link(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+/// The inode in a namespace link ("net:[4026531840]")
+pub fn parse_namespace_link(link: &str) -> Option<u64> {
+    link.strip_prefix("net:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// `lsns --raw --output NS,PID,NSFS` lines ("4026532500 1234 /run/netns/blue", the NSFS column
+/// empty for unnamed namespaces); lsns repeats a namespace once per nsfs mount
+pub fn parse_lsns(stdout: &str) -> Vec<NetNamespace> {
+    let mut namespaces: Vec<NetNamespace> = Vec::new();
+    for line in stdout.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(Ok(inode)), Some(Ok(pid))) = (parts.next().map(str::parse::<u64>), parts.next().map(str::parse::<i32>)) else {
+            continue;
+        };
+        let name = parts
+            .next()
+            .and_then(|nsfs| nsfs.strip_prefix("/run/netns/").or_else(|| nsfs.strip_prefix("/var/run/netns/")))
+            .map(str::to_string);
+        match namespaces.iter_mut().find(|namespace| namespace.inode == inode) {
+            Some(namespace) => {
+                namespace.name = namespace.name.take().or(name);
+            }
+            None => namespaces.push(NetNamespace { inode, pid, name }),
+        }
+    }
+    namespaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lsns() {
+        let namespaces = parse_lsns("4026531840 1 \n4026532500 2211 /run/netns/blue\n4026532612 3498 \n4026532500 2211 /var/run/netns/blue\nlsns: garbage\n");
+        assert_eq!(namespaces.len(), 3);
+        assert_eq!(namespaces[1], NetNamespace { inode: 4026532500, pid: 2211, name: Some("blue".to_string()) });
+        assert_eq!(namespaces[1].label(), "blue");
+        assert_eq!(namespaces[2].label(), "net:[4026532612]");
+        assert_eq!(namespaces[2].path(), "/proc/3498/ns/net");
+
+        assert_eq!(parse_namespace_link("net:[4026531840]"), Some(4026531840));
+        assert_eq!(parse_namespace_link("mnt:[4026531840]"), None);
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    cli::Args,
+    console_app::{load_optional_config, print_snapshot, process_line, Palette},
+    exit_code::ExitCode,
+    history::{KillHistory, DEFAULT_HISTORY_SIZE},
+    hooks::Hooks,
+    port_scanner::LsofScanner,
+    process_monitor::{get_processes_on_ports, kill_process},
+    types::ProcessInfo,
+    webhook::{KillOutcome, Webhook},
+};
+use anyhow::Result;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// How many of the best matches are offered for each query
+pub const MAX_CANDIDATES: usize = 5;
+
+/// Score how well `query` matches `text`, or `None` if it doesn't.
+///
+/// Every query character must appear in `text` in order (case-insensitive). Matches
+/// at the start, consecutive matches and exact matches score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let query: Vec<char> = query.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    if query.is_empty() {
+        return None;
+    }
+
+    let mut score = 0;
+    let mut matched = 0;
+    let mut previous: Option<usize> = None;
+    for (index, c) in text.iter().enumerate() {
+        if matched == query.len() {
+            break;
+        }
+        if *c != query[matched] {
+            continue;
+        }
+
+        score += 1;
+        if index == 0 {
+            score += 3;
+        }
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 2;
+        }
+        previous = Some(index);
+        matched += 1;
+    }
+
+    if matched < query.len() {
+        return None;
+    }
+    if text.len() == query.len() {
+        score += 5;
+    }
+    Some(score)
+}
+
+/// Processes matching `query` on their name, port or command, best first (ties by port)
+pub fn rank<'a>(query: &str, processes: &[&'a ProcessInfo]) -> Vec<&'a ProcessInfo> {
+    let mut scored: Vec<(u32, &ProcessInfo)> = processes
+        .iter()
+        .filter_map(|process_info| {
+            [
+                fuzzy_score(query, &process_info.name),
+                fuzzy_score(query, &process_info.port.to_string()),
+                fuzzy_score(query, &process_info.command),
+            ]
+            .into_iter()
+            .flatten()
+            .max()
+            .map(|score| (score, *process_info))
+        })
+        .collect();
+
+    scored.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.port.cmp(&b.port)));
+    scored.into_iter().map(|(_, process_info)| process_info).collect()
+}
+
+/// Interactively pick a process by fuzzy search and kill it (--pick).
+///
+/// Without a terminal on stdin there is nobody to ask, so this prints the listing instead.
+pub fn run(args: &Args) -> Result<ExitCode> {
+    if !io::stdin().is_terminal() {
+        print_snapshot(args)?;
+        return Ok(ExitCode::Clean);
+    }
+
+    let runner = args.command_runner();
+    #[cfg(not(target_os = "windows"))]
+    LsofScanner::new(runner.clone()).with_timeout(args.lsof_timeout).check_available()?;
+
+    let (_, processes) = get_processes_on_ports(&args.get_ports_to_monitor(), args);
+    let mut processes: Vec<&ProcessInfo> = processes.values().flatten().filter(|process_info| args.is_port_shown(process_info.port)).collect();
+    if processes.is_empty() {
+        println!("📋 No processes found on {}", args.get_port_description());
+        return Ok(ExitCode::NothingFound);
+    }
+    args.sort.sort(&mut processes);
+
+    let palette = Palette::from_args(args);
+    let config = load_optional_config(&PathBuf::from(&args.config));
+    let labels = config.as_ref().map(|config| config.labels.clone()).unwrap_or_default();
+    let webhook = config.as_ref().and_then(|config| Webhook::from_config(&config.app));
+    let hooks = config.as_ref().and_then(|config| Hooks::from_config(&config.app));
+    let mut history = KillHistory::session(config.as_ref().map_or(DEFAULT_HISTORY_SIZE, |config| config.app.history_size));
+    let policy = args.kill_policy(&config.as_ref().map(|config| config.app.clone()).unwrap_or_default());
+    println!("📋 {} process(es) on {}:", processes.len(), args.get_port_description());
+    for process_info in &processes {
+        println!("{}", process_line(process_info, args, palette, &labels));
+    }
+
+    let mut input = io::stdin().lock();
+    loop {
+        let query = match prompt(&mut input, "🔎 Name or port to kill (empty to quit): ")? {
+            Some(query) if !query.is_empty() => query,
+            _ => return Ok(ExitCode::Clean),
+        };
+
+        let candidates: Vec<&ProcessInfo> = rank(&query, &processes).into_iter().take(MAX_CANDIDATES).collect();
+        if candidates.is_empty() {
+            println!("No process matches {:?}", query);
+            continue;
+        }
+
+        for (index, process_info) in candidates.iter().enumerate() {
+            println!("  {}) {}", index + 1, process_line(process_info, args, palette, &labels).trim_start().trim_start_matches("• "));
+        }
+
+        let Some(choice) = prompt(&mut input, &format!("Kill which? [1-{}, Enter = 1, anything else to search again]: ", candidates.len()))? else {
+            return Ok(ExitCode::Clean);
+        };
+        let index = match choice.parse::<usize>() {
+            _ if choice.is_empty() => 0,
+            Ok(number) if (1..=candidates.len()).contains(&number) => number - 1,
+            _ => continue,
+        };
+
+        let chosen = candidates[index];
+        let result = kill_process(chosen.pid, runner.as_ref(), &policy);
+        let outcomes = vec![KillOutcome::new(chosen.pid, Some(chosen.port), Some(&chosen.name), &result)];
+        history.record(&outcomes);
+        if let Some(hook) = hooks.as_ref().and_then(|hooks| hooks.on_kill(&outcomes)) {
+            let _ = hook.join();
+        }
+        if let Some(delivery) = webhook.as_ref().and_then(|webhook| webhook.notify(outcomes)) {
+            let _ = delivery.join();
+        }
+        return match result {
+            Ok(()) => {
+                println!("🔪 Killed {} (PID {}) on {}", chosen.name, chosen.pid, labels.display(chosen.port));
+                Ok(ExitCode::Clean)
+            }
+            Err(e) => {
+                eprintln!("Error: Failed to kill {} (PID {}): {}", chosen.name, chosen.pid, e);
+                Ok(ExitCode::Failure)
+            }
+        };
+    }
+}
+
+/// Print `message` and read one trimmed line; `None` at end of input (Ctrl+D)
+fn prompt(input: &mut impl BufRead, message: &str) -> Result<Option<String>> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("xyz", "node"), None);
+        assert_eq!(fuzzy_score("", "node"), None);
+        assert_eq!(fuzzy_score("edon", "node"), None);
+        assert!(fuzzy_score("nd", "node").is_some());
+        assert!(fuzzy_score("NODE", "node").is_some());
+
+        // Exact beats prefix, prefix beats a scattered match
+        assert!(fuzzy_score("node", "node") > fuzzy_score("node", "nodemon"));
+        assert!(fuzzy_score("node", "nodemon") > fuzzy_score("node", "n_o_d_e"));
+        assert!(fuzzy_score("py", "python3") > fuzzy_score("py", "happy"));
+    }
+
+    #[test]
+    fn test_rank() {
+        let node = ProcessInfo::fixture(3000, 10, "node");
+        let nodemon = ProcessInfo::fixture(3001, 11, "nodemon");
+        let python = ProcessInfo::fixture(8000, 20, "python3");
+        let processes = vec![&python, &nodemon, &node];
+
+        let ranked = rank("node", &processes);
+        assert_eq!(ranked.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![10, 11]);
+
+        assert_eq!(rank("8000", &processes)[0].pid, 20);
+        assert_eq!(rank("30", &processes).iter().map(|p| p.port).collect::<Vec<_>>(), vec![3000, 3001]);
+        assert!(rank("ruby", &processes).is_empty());
+    }
+}
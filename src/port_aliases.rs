@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Default ports of common services, usable wherever a port is expected (`--ports postgres,redis`)
+pub const BUILTIN_PORT_ALIASES: &[(&str, u16)] = &[
+    ("postgres", 5432),
+    ("postgresql", 5432),
+    ("mysql", 3306),
+    ("mariadb", 3306),
+    ("redis", 6379),
+    ("mongodb", 27017),
+    ("mongo", 27017),
+    ("memcached", 11211),
+    ("elasticsearch", 9200),
+    ("rabbitmq", 5672),
+    ("kafka", 9092),
+    ("zookeeper", 2181),
+    ("vite", 5173),
+    ("jupyter", 8888),
+];
+
+/// The config file's `[aliases]`, registered before the command line is parsed
+static CONFIG_ALIASES: OnceLock<BTreeMap<String, u16>> = OnceLock::new();
+
+/// The port for service `name` (case-insensitive), looking at `aliases` before the built-in ones
+pub fn resolve(name: &str, aliases: &BTreeMap<String, u16>) -> Option<u16> {
+    let name = name.trim();
+    aliases
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map(|(_, port)| *port)
+        .or_else(|| BUILTIN_PORT_ALIASES.iter().find(|(alias, _)| alias.eq_ignore_ascii_case(name)).map(|(_, port)| *port))
+}
+
+/// The port for service `name`, including the registered config aliases
+pub fn lookup(name: &str) -> Option<u16> {
+    resolve(name, CONFIG_ALIASES.get().unwrap_or(&BTreeMap::new()))
+}
+
+/// Every service name [`lookup`] knows, for error messages, e.g. "api, postgres, postgresql, ..."
+pub fn known_names() -> String {
+    let mut names: Vec<&str> = CONFIG_ALIASES.get().into_iter().flatten().map(|(alias, _)| alias.as_str()).collect();
+    names.extend(BUILTIN_PORT_ALIASES.iter().map(|(alias, _)| *alias));
+    names.join(", ")
+}
+
+/// Make the `[aliases]` of the --config file named in `args` (a raw command line) usable by the
+/// port arguments. Runs before clap, which resolves them while parsing; a config file that is
+/// missing or can't be loaded only leaves the built-in names.
+pub fn register_from_args(args: impl IntoIterator<Item = OsString>) {
+    let config_path = config_arg(args).unwrap_or_else(|| "port-kill.toml".into());
+    let path = Path::new(&config_path);
+    if !path.exists() {
+        return;
+    }
+    if let Ok(config) = crate::config::Config::load(path) {
+        let _ = CONFIG_ALIASES.set(config.aliases);
+    }
+}
+
+/// The value of --config in a raw command line ("--config x.toml" or "--config=x.toml")
+fn config_arg(args: impl IntoIterator<Item = OsString>) -> Option<OsString> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--config" {
+            return args.next();
+        }
+        if let Some(value) = arg.to_str().and_then(|arg| arg.strip_prefix("--config=")) {
+            return Some(value.into());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let aliases = BTreeMap::from([("api".to_string(), 4000), ("redis".to_string(), 6380)]);
+        assert_eq!(resolve("postgres", &aliases), Some(5432));
+        assert_eq!(resolve("MongoDB", &aliases), Some(27017));
+        assert_eq!(resolve("api", &aliases), Some(4000));
+        // The config's aliases win over built-in ones
+        assert_eq!(resolve("redis", &aliases), Some(6380));
+        assert_eq!(resolve("redis", &BTreeMap::new()), Some(6379));
+        assert_eq!(resolve("postgress", &aliases), None);
+    }
+
+    #[test]
+    fn test_config_arg() {
+        let args = |args: &[&str]| config_arg(args.iter().map(OsString::from));
+        assert_eq!(args(&["port-kill", "--ports", "postgres", "--config", "dev.toml"]), Some("dev.toml".into()));
+        assert_eq!(args(&["port-kill", "--config=dev.toml"]), Some("dev.toml".into()));
+        assert_eq!(args(&["port-kill", "--ports", "3000"]), None);
+    }
+}
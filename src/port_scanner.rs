@@ -0,0 +1,777 @@
+use crate::cli::{socket_matches_interface, Args};
+use crate::command_runner::CommandRunner;
+use crate::error::PortKillError;
+use crate::types::{AddressFamily, ProcessInfo};
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::io;
+use std::net::IpAddr;
+use std::process::Output;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// How long one lsof call may run before it is killed (overridden by --lsof-timeout)
+pub const DEFAULT_LSOF_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// More scattered ports than this are found with one scan of every listener, filtered in memory.
+///
+/// lsof refuses more than 100 addresses per `-i` option, and listing 100 ports already
+/// takes as long as a full listener scan (about 8ms each with lsof 4.95 on Linux); longer
+/// lists only get slower (about 14ms for 2000 ports, 35ms for 5000).
+pub const FULL_SCAN_THRESHOLD: usize = 100;
+
+/// Finds the processes listening on a set of ports.
+///
+/// Discovery goes through this trait so filtering and counting can be tested
+/// with a fake scanner instead of a real `lsof`.
+pub trait PortScanner: Send + Sync {
+    /// Return every listener found on `ports` (a port may appear more than once).
+    /// An empty `ports` slice means every listening port on the system.
+    fn scan(&self, ports: &[u16]) -> Result<Vec<ProcessInfo>>;
+}
+
+/// Scans ports with `lsof -i :<ports> -sTCP:LISTEN -P -n` (no port filter for all-port discovery).
+///
+/// With process names set (--watch-process) the ports are ignored and every listening
+/// socket of those processes is returned instead. Holders of the Unix socket paths (--socket)
+/// are returned in addition, with port 0. With established sockets included
+/// (--include-established), TCP sockets in any state whose local port is scanned are returned too.
+/// With an interface set (--interface), only sockets bound to that local address are returned.
+/// With network namespaces included (--netns), lsof also runs inside every other network namespace.
+pub struct LsofScanner {
+    runner: Arc<dyn CommandRunner>,
+    timeout: Duration,
+    process_names: Vec<String>,
+    socket_paths: Vec<String>,
+    include_established: bool,
+    interface: Option<IpAddr>,
+    network_namespaces: bool,
+}
+
+impl LsofScanner {
+    pub fn new(runner: Arc<dyn CommandRunner>) -> Self {
+        Self { runner, timeout: DEFAULT_LSOF_TIMEOUT, process_names: Vec::new(), socket_paths: Vec::new(), include_established: false, interface: None, network_namespaces: false }
+    }
+
+    /// Scanner for the runner, --lsof-timeout, --watch-process, --socket, --include-established, --interface and --netns given on the command line
+    pub fn from_args(args: &Args) -> Self {
+        Self::new(args.command_runner())
+            .with_timeout(args.lsof_timeout)
+            .with_process_names(&args.watch_process)
+            .with_socket_paths(&args.socket)
+            .with_established(args.include_established)
+            .with_interface(args.interface)
+            .with_network_namespaces(args.netns)
+    }
+
+    /// Kill lsof if it runs longer than `timeout` (it can hang on stale network mounts)
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Find processes whose name starts with one of `names` on any port, instead of scanning ports
+    pub fn with_process_names(mut self, names: &[String]) -> Self {
+        self.process_names = names.to_vec();
+        self
+    }
+
+    /// Also report the processes holding the Unix sockets at `paths` on every scan
+    pub fn with_socket_paths(mut self, paths: &[String]) -> Self {
+        self.socket_paths = paths.to_vec();
+        self
+    }
+
+    /// Also report TCP sockets on the scanned ports that aren't listening (ESTABLISHED, CLOSE_WAIT, ...),
+    /// which keep a port busy just the same
+    pub fn with_established(mut self, include_established: bool) -> Self {
+        self.include_established = include_established;
+        self
+    }
+
+    /// Only report sockets bound to `interface`. Matched per socket, before a process's IPv4 and
+    /// IPv6 sockets on a port are merged, so a dual-stack listener only matches on the address it uses.
+    pub fn with_interface(mut self, interface: Option<IpAddr>) -> Self {
+        self.interface = interface;
+        self
+    }
+
+    /// Also scan inside the other network namespaces (containers, `ip netns`), tagging what is found
+    /// there with its namespace. Linux only, and entering another namespace takes root.
+    pub fn with_network_namespaces(mut self, network_namespaces: bool) -> Self {
+        self.network_namespaces = network_namespaces;
+        self
+    }
+
+    /// lsof socket selection for `port_filter` (e.g. ":3000,8080", or "" for every port):
+    /// TCP in any state with established sockets included, otherwise listeners only
+    fn socket_filter(&self, port_filter: &str) -> Vec<String> {
+        match (self.include_established, port_filter.is_empty()) {
+            (true, _) => vec![format!("-iTCP{}", port_filter)],
+            (false, true) => vec!["-i".to_string(), "-sTCP:LISTEN".to_string()],
+            (false, false) => vec!["-i".to_string(), port_filter.to_string(), "-sTCP:LISTEN".to_string()],
+        }
+    }
+
+    /// Build the lsof port selector: a range for many contiguous ports, otherwise a list
+    fn port_selector(ports: &[u16]) -> String {
+        if ports.len() <= 10 || !is_contiguous(ports) {
+            // For small or scattered port sets, list them individually
+            ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
+        } else {
+            // For large ranges, use range format
+            format!("{}-{}", ports.first().unwrap_or(&0), ports.last().unwrap_or(&0))
+        }
+    }
+
+    /// Whether `ports` is better served by scanning every listener and filtering (see [`FULL_SCAN_THRESHOLD`])
+    fn needs_full_scan(ports: &[u16]) -> bool {
+        ports.len() > FULL_SCAN_THRESHOLD && !is_contiguous(ports)
+    }
+
+    /// Fail early when lsof isn't installed, instead of reporting no listeners on every scan
+    pub fn check_available(&self) -> Result<()> {
+        self.spawn_lsof(&["-v"]).map(|_| ())
+    }
+
+    /// Run lsof, unless it was already found missing on this host.
+    ///
+    /// The first time lsof turns out to be missing it is logged once; later scans fail
+    /// straight away instead of spawning the missing binary again every cycle.
+    fn run_lsof(&self, args: &[&str]) -> Result<Output> {
+        let host = self.runner.describe();
+        let missing_hosts = missing_lsof_hosts();
+        if missing_hosts.lock().is_ok_and(|hosts| hosts.contains(&host)) {
+            return Err(missing_lsof_error(&host));
+        }
+
+        let result = self.spawn_lsof(args);
+        if let Err(ref e) = result {
+            if is_lsof_missing(e) && missing_hosts.lock().is_ok_and(|mut hosts| hosts.insert(host)) {
+                log::error!("{:#}; no listening processes can be found until it is installed and port-kill is restarted", e);
+            }
+        }
+        result
+    }
+
+    fn spawn_lsof(&self, args: &[&str]) -> Result<Output> {
+        let output = match self.runner.run_with_timeout("lsof", args, self.timeout) {
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                return Err(PortKillError::LsofTimeout { host: self.runner.describe(), timeout: self.timeout }.into());
+            }
+            // Over SSH this would be ssh itself missing, which the generic context describes better
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !self.runner.is_remote() => {
+                return Err(missing_lsof_error(&self.runner.describe()));
+            }
+            output => output.context("Failed to execute lsof command")?,
+        };
+
+        // A shell (e.g. over SSH) reports a missing command as exit status 127
+        if output.status.code() == Some(127) {
+            return Err(missing_lsof_error(&self.runner.describe()));
+        }
+
+        Ok(output)
+    }
+}
+
+/// Whether `ports` is an ascending run without gaps, e.g. 3000, 3001, 3002
+fn is_contiguous(ports: &[u16]) -> bool {
+    ports.windows(2).all(|pair| pair[0].checked_add(1) == Some(pair[1]))
+}
+
+/// Hosts (by [`CommandRunner::describe`]) on which lsof was found missing
+fn missing_lsof_hosts() -> &'static Mutex<HashSet<String>> {
+    static HOSTS: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    HOSTS.get_or_init(Mutex::default)
+}
+
+fn missing_lsof_error(host: &str) -> anyhow::Error {
+    PortKillError::LsofNotFound { host: host.to_string() }.into()
+}
+
+/// Whether `error` means lsof isn't installed (locally or on the --remote host)
+pub fn is_lsof_missing(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(cause.downcast_ref::<PortKillError>(), Some(PortKillError::LsofNotFound { .. }))
+            || cause.downcast_ref::<io::Error>().is_some_and(|io_error| io_error.kind() == io::ErrorKind::NotFound)
+    })
+}
+
+impl PortScanner for LsofScanner {
+    fn scan(&self, ports: &[u16]) -> Result<Vec<ProcessInfo>> {
+        let full_scan = self.process_names.is_empty() && Self::needs_full_scan(ports);
+        let port_filter = if ports.is_empty() || full_scan || !self.process_names.is_empty() {
+            String::new()
+        } else {
+            format!(":{}", Self::port_selector(ports))
+        };
+        let socket_filter = self.socket_filter(&port_filter);
+        let mut lsof_args = if self.process_names.is_empty() { Vec::new() } else { process_name_filter(&self.process_names) };
+        lsof_args.extend(socket_filter.iter().map(String::as_str));
+        lsof_args.extend(["-P", "-n"]);
+        let output = self.run_lsof(&lsof_args)?;
+
+        // lsof exits non-zero when nothing matches, so parse whatever it printed
+        let mut found = parse_lsof_listeners(&String::from_utf8_lossy(&output.stdout));
+        if self.network_namespaces {
+            found.extend(self.scan_namespaces(&lsof_args));
+        }
+        // lsof matches the remote port of a connection too, but only the local port keeps it busy here
+        if full_scan || (self.include_established && !ports.is_empty()) {
+            let wanted: HashSet<u16> = ports.iter().copied().collect();
+            found.retain(|process_info| wanted.contains(&process_info.port));
+        }
+        if let Some(interface) = self.interface {
+            found.retain(|process_info| {
+                process_info.bind_address.as_deref().is_some_and(|address| {
+                    socket_matches_interface(address, process_info.address_families.first().copied(), interface)
+                })
+            });
+        }
+        found.extend(self.scan_sockets(&self.socket_paths)?);
+        Ok(found)
+    }
+}
+
+impl LsofScanner {
+    /// Run lsof with `lsof_args` inside each other network namespace (`nsenter --net=/proc/<pid>/ns/net`).
+    ///
+    /// A namespace that can't be entered is skipped, so a scan without root still reports the
+    /// listeners of port-kill's own namespace; the first failure is logged as a warning.
+    fn scan_namespaces(&self, lsof_args: &[&str]) -> Vec<ProcessInfo> {
+        static NSENTER_WARNED: AtomicBool = AtomicBool::new(false);
+        let namespaces = match crate::netns::list(self.runner.as_ref()) {
+            Ok(namespaces) => namespaces,
+            Err(e) => {
+                if !NSENTER_WARNED.swap(true, Ordering::Relaxed) {
+                    log::warn!("{:#}; only scanning the current network namespace", e);
+                }
+                return Vec::new();
+            }
+        };
+
+        let mut found = Vec::new();
+        for namespace in namespaces {
+            let enter = format!("--net={}", namespace.path());
+            let mut nsenter_args = vec![enter.as_str(), "lsof"];
+            nsenter_args.extend(lsof_args);
+            let output = match self.runner.run_with_timeout("nsenter", &nsenter_args, self.timeout) {
+                Ok(output) => output,
+                Err(e) => {
+                    if !NSENTER_WARNED.swap(true, Ordering::Relaxed) {
+                        log::warn!("Failed to execute nsenter ({}); only scanning the current network namespace", e);
+                    }
+                    return found;
+                }
+            };
+            // lsof exits non-zero when nothing matches, so only nsenter's own errors mean the namespace was skipped
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("nsenter:") {
+                if !NSENTER_WARNED.swap(true, Ordering::Relaxed) {
+                    log::warn!("Can't enter network namespace {} ({}); run as root to scan inside namespaces", namespace.label(), stderr.trim());
+                }
+                continue;
+            }
+
+            let label = namespace.label();
+            found.extend(parse_lsof_listeners(&String::from_utf8_lossy(&output.stdout)).into_iter().map(|mut process_info| {
+                process_info.network_namespace = Some(label.clone());
+                process_info
+            }));
+        }
+        found
+    }
+
+    /// Find the processes holding the Unix sockets at `paths` (`lsof -U -a -- <paths>`), with port 0
+    pub fn scan_sockets(&self, paths: &[String]) -> Result<Vec<ProcessInfo>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut lsof_args = vec!["-U", "-a", "-P", "-n", "--"];
+        lsof_args.extend(paths.iter().map(String::as_str));
+        let output = self.run_lsof(&lsof_args)?;
+
+        // lsof exits non-zero when a path doesn't exist or nobody holds it
+        let mut seen = HashSet::new();
+        Ok(parse_lsof_unix_sockets(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|process_info| process_info.socket_path.as_ref().is_some_and(|path| paths.contains(path)))
+            .filter(|process_info| seen.insert((process_info.pid, process_info.socket_path.clone())))
+            .collect())
+    }
+}
+
+/// lsof selection for processes whose name starts with any of `names` (`-a -c <name>...`);
+/// `-a` ANDs it with the `-i` socket filter that follows, so other open files are skipped
+fn process_name_filter(names: &[String]) -> Vec<&str> {
+    let mut lsof_args = vec!["-a"];
+    for name in names {
+        lsof_args.extend(["-c", name.as_str()]);
+    }
+    lsof_args
+}
+
+/// Parse `lsof -P -n` output into one `ProcessInfo` per socket, with the TCP state of
+/// sockets that aren't listening (e.g. "127.0.0.1:3000->127.0.0.1:51234 (ESTABLISHED)")
+pub fn parse_lsof_listeners(stdout: &str) -> Vec<ProcessInfo> {
+    let mut processes = Vec::new();
+
+    for line in stdout.lines().skip(1) { // Skip header
+        // Example line: "node    1234 user   23u  IPv4 0x1234  0t0  TCP 127.0.0.1:3000 (LISTEN)"
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() >= 9 {
+            let local_address = parts[8].split("->").next().unwrap_or(parts[8]);
+            let state = parts.get(9).map(|state| state.trim_start_matches('(').trim_end_matches(')')).filter(|state| *state != "LISTEN");
+            if let (Ok(pid), Some((bind_address, port))) = (parts[1].parse::<i32>(), parse_lsof_address(local_address)) {
+                processes.push(ProcessInfo {
+                    pid,
+                    port,
+                    command: parts[0].to_string(),
+                    name: parts[0].to_string(),
+                    bind_address: Some(bind_address),
+                    address_families: AddressFamily::from_lsof_type(parts[4]).into_iter().collect(),
+                    owner: Some(parts[2].to_string()),
+                    state: state.map(str::to_string),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    processes
+}
+
+/// Parse `lsof -U` output into one `ProcessInfo` per Unix socket with a path (port 0)
+pub fn parse_lsof_unix_sockets(stdout: &str) -> Vec<ProcessInfo> {
+    let mut processes = Vec::new();
+
+    for line in stdout.lines().skip(1) { // Skip header
+        // Example line: "python3 1234 user 3u unix 0x1234 0t0 81376 /tmp/app.sock type=STREAM (LISTEN)";
+        // macOS leaves the NODE column empty for Unix sockets
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 8 || parts[4] != "unix" {
+            continue;
+        }
+        let name_start = if parts[7].bytes().all(|b| b.is_ascii_digit()) { 8 } else { 7 };
+        let (Ok(pid), Some(path)) = (parts[1].parse::<i32>(), parse_lsof_socket_path(&parts[name_start..].join(" "))) else {
+            continue;
+        };
+        processes.push(ProcessInfo {
+            pid,
+            port: 0,
+            command: parts[0].to_string(),
+            name: parts[0].to_string(),
+            owner: Some(parts[2].to_string()),
+            socket_path: Some(path),
+            ..Default::default()
+        });
+    }
+
+    processes
+}
+
+/// The socket path in an lsof unix NAME column ("/tmp/app.sock type=STREAM (LISTEN)"); `None` for
+/// unnamed sockets ("type=STREAM", or "->0x1234" on macOS)
+pub fn parse_lsof_socket_path(name: &str) -> Option<String> {
+    let path = name.split(" type=").next()?;
+    let path = path.strip_suffix(" (LISTEN)").unwrap_or(path).trim();
+    path.starts_with('/').then(|| path.to_string())
+}
+
+/// Split an lsof NAME column ("127.0.0.1:3000", "*:3000", "[::1]:3000") into bind address and port
+fn parse_lsof_address(name: &str) -> Option<(String, u16)> {
+    let (address, port) = name.rsplit_once(':')?;
+    Some((address.to_string(), port.parse().ok()?))
+}
+
+/// TCP states in which a closed connection still occupies its local port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LingeringState {
+    /// Closed by this side; the kernel holds the port for up to two minutes (60s on Linux),
+    /// whether or not the process that owned it is still alive
+    TimeWait,
+    /// Closed by the peer but not yet by the process holding it
+    CloseWait,
+}
+
+impl LingeringState {
+    /// The state as netstat prints it
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LingeringState::TimeWait => "TIME_WAIT",
+            LingeringState::CloseWait => "CLOSE_WAIT",
+        }
+    }
+}
+
+/// A TCP socket lingering on a local port after its connection was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LingeringSocket {
+    pub port: u16,
+    pub state: LingeringState,
+}
+
+/// Finds sockets lingering in TIME_WAIT or CLOSE_WAIT on a set of ports.
+///
+/// Unlike listeners these often belong to no process at all, so lsof can't see them.
+pub trait LingeringScanner: Send + Sync {
+    fn scan_lingering(&self, ports: &[u16]) -> Result<Vec<LingeringSocket>>;
+}
+
+/// Lists lingering sockets with `netstat -an`, which prints every TCP socket in the same
+/// columns on Linux, macOS and Windows
+pub struct NetstatScanner {
+    runner: Arc<dyn CommandRunner>,
+    timeout: Duration,
+}
+
+impl NetstatScanner {
+    pub fn new(runner: Arc<dyn CommandRunner>, timeout: Duration) -> Self {
+        Self { runner, timeout }
+    }
+}
+
+impl LingeringScanner for NetstatScanner {
+    fn scan_lingering(&self, ports: &[u16]) -> Result<Vec<LingeringSocket>> {
+        let output = self
+            .runner
+            .run_with_timeout("netstat", &["-an"], self.timeout)
+            .with_context(|| format!("Failed to run netstat on {} (needed to see TIME_WAIT and CLOSE_WAIT sockets)", self.runner.describe()))?;
+        Ok(parse_netstat_lingering(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|socket| ports.contains(&socket.port))
+            .collect())
+    }
+}
+
+/// Parse `netstat -an` output into the TCP sockets in TIME_WAIT or CLOSE_WAIT, by local port
+pub fn parse_netstat_lingering(stdout: &str) -> Vec<LingeringSocket> {
+    let mut sockets = Vec::new();
+
+    for line in stdout.lines() {
+        // Linux: "tcp  0  0 127.0.0.1:3000  127.0.0.1:54321  TIME_WAIT"
+        // macOS: "tcp4 0  0 127.0.0.1.3000  127.0.0.1.54321  TIME_WAIT"
+        // Windows: "TCP  127.0.0.1:3000  127.0.0.1:54321  TIME_WAIT"
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 || !parts[0].to_ascii_lowercase().starts_with("tcp") {
+            continue;
+        }
+        let state = match parts[parts.len() - 1] {
+            "TIME_WAIT" => LingeringState::TimeWait,
+            "CLOSE_WAIT" => LingeringState::CloseWait,
+            _ => continue,
+        };
+        // The local address comes right before the foreign one; macOS separates the port with a dot
+        let local = parts[parts.len() - 3];
+        if let Some(Ok(port)) = local.rsplit([':', '.']).next().map(str::parse::<u16>) {
+            sockets.push(LingeringSocket { port, state });
+        }
+    }
+
+    sockets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_netstat_lingering() {
+        let linux = "\
+Active Internet connections (servers and established)
+Proto Recv-Q Send-Q Local Address           Foreign Address         State
+tcp        0      0 0.0.0.0:3000            0.0.0.0:*               LISTEN
+tcp        0      0 127.0.0.1:3000          127.0.0.1:46058         TIME_WAIT
+tcp6       0      0 ::1:8080                ::1:51234               CLOSE_WAIT
+tcp        0      0 127.0.0.1:46060         127.0.0.1:3000          TIME_WAIT
+udp        0      0 0.0.0.0:5353            0.0.0.0:*
+";
+        assert_eq!(
+            parse_netstat_lingering(linux),
+            vec![
+                LingeringSocket { port: 3000, state: LingeringState::TimeWait },
+                LingeringSocket { port: 8080, state: LingeringState::CloseWait },
+                LingeringSocket { port: 46060, state: LingeringState::TimeWait },
+            ]
+        );
+
+        let macos = "tcp4       0      0  127.0.0.1.5173         127.0.0.1.60123        TIME_WAIT\n";
+        assert_eq!(parse_netstat_lingering(macos), vec![LingeringSocket { port: 5173, state: LingeringState::TimeWait }]);
+        let windows = "  TCP    127.0.0.1:3000         127.0.0.1:50000        TIME_WAIT\n";
+        assert_eq!(parse_netstat_lingering(windows), vec![LingeringSocket { port: 3000, state: LingeringState::TimeWait }]);
+    }
+
+    #[test]
+    fn test_parse_lsof_address() {
+        assert_eq!(parse_lsof_address("127.0.0.1:3000"), Some(("127.0.0.1".to_string(), 3000)));
+        assert_eq!(parse_lsof_address("*:8080"), Some(("*".to_string(), 8080)));
+        assert_eq!(parse_lsof_address("[::1]:5173"), Some(("[::1]".to_string(), 5173)));
+        assert_eq!(parse_lsof_address("(LISTEN)"), None);
+        assert_eq!(parse_lsof_address("*:http"), None);
+    }
+
+    #[test]
+    fn test_parse_lsof_unix_sockets() {
+        let stdout = "\
+COMMAND   PID USER   FD   TYPE             DEVICE SIZE/OFF  NODE NAME
+python3 19603 dev     3u  unix 0x000000008c392bd3      0t0 81376 /tmp/app.sock type=STREAM (LISTEN)
+python3 19603 dev     4u  unix 0x00000000837ab768      0t0   925 type=STREAM (CONNECTED)
+postgres  812 pg      5u  unix 0x1a2b3c4d5e6f7a8b      0t0       /tmp/.s.PGSQL.5432
+Docker    640 dev    12u  unix 0x1a2b3c4d5e6f7a8c      0t0       ->0x1a2b3c4d5e6f7a8d
+";
+        let processes = parse_lsof_unix_sockets(stdout);
+
+        assert_eq!(
+            processes.iter().map(|p| (p.pid, p.port, p.socket_path.as_deref())).collect::<Vec<_>>(),
+            vec![(19603, 0, Some("/tmp/app.sock")), (812, 0, Some("/tmp/.s.PGSQL.5432"))]
+        );
+        assert_eq!(processes[1].owner.as_deref(), Some("pg"));
+        assert_eq!(parse_lsof_socket_path("/tmp/with space.sock type=STREAM (LISTEN)").as_deref(), Some("/tmp/with space.sock"));
+    }
+
+    #[test]
+    fn test_parse_lsof_listeners() {
+        let stdout = "\
+COMMAND   PID USER   FD   TYPE DEVICE SIZE/OFF NODE NAME
+node     1234 dev    23u  IPv4 0x1234      0t0  TCP 127.0.0.1:3000 (LISTEN)
+node     1234 dev    24u  IPv6 0x5678      0t0  TCP [::1]:3000 (LISTEN)
+python3  5678 dev     3u  IPv4 0x9abc      0t0  TCP *:8000 (LISTEN)
+";
+        let processes = parse_lsof_listeners(stdout);
+
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[0].pid, 1234);
+        assert_eq!(processes[0].bind_address.as_deref(), Some("127.0.0.1"));
+        assert_eq!(processes[0].address_families, vec![AddressFamily::Ipv4]);
+        assert_eq!(processes[0].owner.as_deref(), Some("dev"));
+        assert_eq!(processes[1].address_families, vec![AddressFamily::Ipv6]);
+        assert_eq!(processes[2].name, "python3");
+        assert_eq!(processes[2].port, 8000);
+    }
+
+    #[test]
+    fn test_port_selector() {
+        assert_eq!(LsofScanner::port_selector(&[3000, 8080]), "3000,8080");
+        let range: Vec<u16> = (3000..=3020).collect();
+        assert_eq!(LsofScanner::port_selector(&range), "3000-3020");
+        // Scattered ports must not widen into a range covering everything in between
+        let scattered: Vec<u16> = (0..12).map(|i| 3000 + i * 100).collect();
+        assert!(LsofScanner::port_selector(&scattered).starts_with("3000,3100,"));
+    }
+
+    /// Records the lsof arguments and answers with a fixed listing
+    struct RecordingRunner {
+        stdout: &'static str,
+        calls: std::sync::Mutex<Vec<Vec<String>>>,
+    }
+
+    impl CommandRunner for RecordingRunner {
+        fn run(&self, _program: &str, args: &[&str]) -> io::Result<Output> {
+            self.calls.lock().unwrap().push(args.iter().map(|arg| arg.to_string()).collect());
+            Ok(Output { status: Default::default(), stdout: self.stdout.as_bytes().to_vec(), stderr: Vec::new() })
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
+        }
+    }
+
+    #[test]
+    fn test_scan_by_process_name_ignores_ports() {
+        let runner = Arc::new(RecordingRunner {
+            stdout: "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:49321 (LISTEN)
+deno 102 me 20u IPv6 0x2 0t0 TCP *:61000 (LISTEN)
+",
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let scanner = LsofScanner::new(runner.clone()).with_process_names(&["node".to_string(), "deno".to_string()]);
+
+        let processes = scanner.scan(&[3000, 3001]).unwrap();
+        assert_eq!(processes.iter().map(|p| (p.port, p.name.as_str())).collect::<Vec<_>>(), vec![(49321, "node"), (61000, "deno")]);
+        assert_eq!(
+            runner.calls.lock().unwrap()[0],
+            ["-a", "-c", "node", "-c", "deno", "-i", "-sTCP:LISTEN", "-P", "-n"]
+        );
+    }
+
+    /// A host with a container namespace (pid 2211) and one port-kill may not enter (pid 3498)
+    struct NamespacedRunner;
+
+    impl CommandRunner for NamespacedRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<Output> {
+            let (stdout, stderr) = match (program, args.first().copied()) {
+                ("lsns", _) => ("4026531840 1 \n4026532500 2211 \n4026532612 3498 /run/netns/blue\n", ""),
+                ("readlink", _) => ("net:[4026531840]\n", ""),
+                ("nsenter", Some("--net=/proc/2211/ns/net")) => ("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\nnode 2300 root 20u IPv4 0x1 0t0 TCP *:3000 (LISTEN)\n", ""),
+                ("nsenter", _) => ("", "nsenter: reassociate to namespace 'ns/net' failed: Operation not permitted\n"),
+                _ => ("COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\nvite 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:3000 (LISTEN)\n", ""),
+            };
+            Ok(Output { status: Default::default(), stdout: stdout.as_bytes().to_vec(), stderr: stderr.as_bytes().to_vec() })
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
+        }
+    }
+
+    #[test]
+    fn test_scan_inside_network_namespaces() {
+        let scanner = LsofScanner::new(Arc::new(NamespacedRunner));
+        let host_only = scanner.scan(&[3000]).unwrap();
+        assert_eq!(host_only.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![101]);
+
+        let processes = LsofScanner::new(Arc::new(NamespacedRunner)).with_network_namespaces(true).scan(&[3000]).unwrap();
+        assert_eq!(
+            processes.iter().map(|p| (p.pid, p.network_namespace.as_deref())).collect::<Vec<_>>(),
+            vec![(101, None), (2300, Some("net:[4026532500]"))]
+        );
+    }
+
+    #[test]
+    fn test_large_sparse_port_set_is_filtered_in_memory() {
+        let runner = Arc::new(RecordingRunner {
+            stdout: "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:3000 (LISTEN)
+sshd 102 root 3u IPv4 0x2 0t0 TCP *:22 (LISTEN)
+vite 103 me 21u IPv6 0x3 0t0 TCP [::1]:3007 (LISTEN)
+",
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let scanner = LsofScanner::new(runner.clone());
+
+        // Every other port from 3000: too many to list, and not a range
+        let sparse: Vec<u16> = (0..FULL_SCAN_THRESHOLD as u16 + 1).map(|i| 3000 + 2 * i).collect();
+        let processes = scanner.scan(&sparse).unwrap();
+        assert_eq!(processes.iter().map(|p| (p.port, p.name.as_str())).collect::<Vec<_>>(), vec![(3000, "node")]);
+        assert_eq!(runner.calls.lock().unwrap()[0], ["-i", "-sTCP:LISTEN", "-P", "-n"]);
+
+        // Contiguous ranges and short lists still let lsof do the filtering
+        let range: Vec<u16> = (3000..4000).collect();
+        scanner.scan(&range).unwrap();
+        scanner.scan(&sparse[..FULL_SCAN_THRESHOLD]).unwrap();
+        let calls = runner.calls.lock().unwrap();
+        assert_eq!(calls[1][1], ":3000-3999");
+        assert!(calls[2][1].starts_with(":3000,3002,"));
+    }
+
+    #[test]
+    fn test_scan_includes_socket_holders() {
+        let runner = Arc::new(RecordingRunner {
+            stdout: "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:3000 (LISTEN)
+postgres 102 me 5u unix 0x2 0t0 81376 /tmp/app.sock type=STREAM (LISTEN)
+redis 103 me 6u unix 0x3 0t0 81377 /tmp/other.sock type=STREAM (LISTEN)
+",
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let scanner = LsofScanner::new(runner.clone()).with_socket_paths(&["/tmp/app.sock".to_string()]);
+
+        let processes = scanner.scan(&[3000]).unwrap();
+        assert_eq!(
+            processes.iter().map(|p| (p.port, p.name.as_str(), p.socket_path.as_deref())).collect::<Vec<_>>(),
+            vec![(3000, "node", None), (0, "postgres", Some("/tmp/app.sock"))]
+        );
+        assert_eq!(runner.calls.lock().unwrap()[1], ["-U", "-a", "-P", "-n", "--", "/tmp/app.sock"]);
+    }
+
+    #[test]
+    fn test_scan_includes_established_sockets() {
+        let runner = Arc::new(RecordingRunner {
+            stdout: "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:3000->127.0.0.1:51234 (ESTABLISHED)
+curl 102 me 5u IPv4 0x2 0t0 TCP 127.0.0.1:51234->127.0.0.1:3000 (ESTABLISHED)
+java 103 me 6u IPv6 0x3 0t0 TCP [::1]:8080->[::1]:40000 (CLOSE_WAIT)
+vite 104 me 7u IPv4 0x4 0t0 TCP *:8080 (LISTEN)
+",
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let scanner = LsofScanner::new(runner.clone()).with_established(true);
+
+        // The client connected to port 3000 doesn't hold it, so only local ports count
+        let processes = scanner.scan(&[3000, 8080]).unwrap();
+        assert_eq!(
+            processes.iter().map(|p| (p.port, p.name.as_str(), p.bind_address.as_deref(), p.state.as_deref())).collect::<Vec<_>>(),
+            vec![
+                (3000, "node", Some("127.0.0.1"), Some("ESTABLISHED")),
+                (8080, "java", Some("[::1]"), Some("CLOSE_WAIT")),
+                (8080, "vite", Some("*"), None),
+            ]
+        );
+        assert_eq!(runner.calls.lock().unwrap()[0], ["-iTCP:3000,8080", "-P", "-n"]);
+    }
+
+    #[test]
+    fn test_scan_filters_by_interface() {
+        let runner = Arc::new(RecordingRunner {
+            stdout: "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+node 101 me 20u IPv4 0x1 0t0 TCP 127.0.0.1:3000 (LISTEN)
+node 101 me 21u IPv6 0x2 0t0 TCP [::1]:3000 (LISTEN)
+java 102 me 6u IPv6 0x3 0t0 TCP *:8080 (LISTEN)
+vite 103 me 7u IPv4 0x4 0t0 TCP *:5173 (LISTEN)
+",
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let scan = |interface: &str| {
+            let scanner = LsofScanner::new(runner.clone()).with_interface(Some(interface.parse().unwrap()));
+            scanner.scan(&[]).unwrap().iter().map(|p| (p.port, p.name.clone(), p.address_families.clone())).collect::<Vec<_>>()
+        };
+
+        // Only node's IPv4 socket, so the merged listener reports IPv4 alone
+        assert_eq!(scan("127.0.0.1"), vec![(3000, "node".to_string(), vec![AddressFamily::Ipv4])]);
+        assert_eq!(scan("::1"), vec![(3000, "node".to_string(), vec![AddressFamily::Ipv6])]);
+        assert_eq!(scan("0.0.0.0"), vec![(5173, "vite".to_string(), vec![AddressFamily::Ipv4])]);
+        assert_eq!(scan("::"), vec![(8080, "java".to_string(), vec![AddressFamily::Ipv6])]);
+    }
+
+    /// Stands in for a host where lsof is missing
+    #[cfg(unix)]
+    struct MissingLsofRunner {
+        via_shell: bool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[cfg(unix)]
+    impl CommandRunner for MissingLsofRunner {
+        fn run(&self, _program: &str, _args: &[&str]) -> io::Result<Output> {
+            use std::os::unix::process::ExitStatusExt;
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            if self.via_shell {
+                Ok(Output { status: std::process::ExitStatus::from_raw(127 << 8), stdout: Vec::new(), stderr: Vec::new() })
+            } else {
+                Err(io::Error::from(io::ErrorKind::NotFound))
+            }
+        }
+
+        fn describe(&self) -> String {
+            // Unique per runner kind, since the missing-lsof memo is process-wide
+            format!("host without lsof (via shell: {})", self.via_shell)
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_missing_lsof_is_backend_unavailable() {
+        use crate::exit_code::ExitCode;
+        use std::sync::atomic::Ordering;
+
+        for via_shell in [false, true] {
+            let runner = Arc::new(MissingLsofRunner { via_shell, calls: Default::default() });
+            let scanner = LsofScanner::new(runner.clone());
+            let error = scanner.check_available().unwrap_err();
+            assert_eq!(ExitCode::from_error(&error), ExitCode::BackendUnavailable);
+            assert!(format!("{:#}", error).contains("apt install lsof"));
+            assert!(matches!(error.downcast_ref::<PortKillError>(), Some(PortKillError::LsofNotFound { .. })));
+
+            // Found missing once, later scans don't spawn it again
+            for _ in 0..3 {
+                let error = scanner.scan(&[3000]).unwrap_err();
+                assert_eq!(ExitCode::from_error(&error), ExitCode::BackendUnavailable);
+                assert!(is_lsof_missing(&error));
+            }
+            assert_eq!(runner.calls.load(Ordering::Relaxed), 2);
+        }
+    }
+}
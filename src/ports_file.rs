@@ -0,0 +1,43 @@
+use crate::cli::{parse_port_spec, PortRange};
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Read a --ports-file: one port, range or service name per line
+pub fn load(path: &Path) -> Result<Vec<PortRange>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read ports file {:?}", path))?;
+    parse(&content).map_err(|e| anyhow::anyhow!("Invalid ports file {:?}: {}", path, e))
+}
+
+/// The entries of a ports file in file order ("3000", "8000-8010", "postgres"), skipping blank
+/// lines and `#` comments, which may also end a line
+pub fn parse(content: &str) -> std::result::Result<Vec<PortRange>, String> {
+    let mut ranges = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let entry = line.split('#').next().unwrap_or_default().trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let range = parse_port_spec(entry).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        if range.contains(0) {
+            return Err(format!("line {}: port 0 is not valid", index + 1));
+        }
+        ranges.push(range);
+    }
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let ranges = parse("# Shared with the dev proxy\n3000\n\n  8000-8010  # API workers\npostgres\r\n").unwrap();
+        assert_eq!(ranges, vec![PortRange::from(3000), PortRange { start: 8000, end: 8010 }, PortRange::from(5432)]);
+        assert_eq!(parse("# nothing yet\n\n"), Ok(Vec::new()));
+
+        let error = parse("3000\nthree-thousand\n").unwrap_err();
+        assert!(error.starts_with("line 2: "), "{}", error);
+        assert_eq!(parse("0\n"), Err("line 1: port 0 is not valid".to_string()));
+    }
+}
@@ -1,61 +1,553 @@
-use crate::types::{ProcessInfo, ProcessUpdate};
+use crate::command_runner::CommandRunner;
+use crate::error::PortKillError;
+use crate::kill_trace::KillTrace;
+use crate::port_scanner::{is_lsof_missing, LingeringScanner, LsofScanner, PortScanner, DEFAULT_LSOF_TIMEOUT};
+use crate::types::{format_uptime, process_count, ProcessInfo, ProcessUpdate};
+use crate::webhook::KillOutcome;
 use anyhow::{Context, Result};
 use crossbeam_channel::Sender;
-use log::{error, info};
-#[cfg(not(target_os = "windows"))]
-use log::warn;
+use log::{error, info, warn};
 #[cfg(not(target_os = "windows"))]
 use nix::sys::signal::{kill, Signal};
 #[cfg(not(target_os = "windows"))]
 use nix::unistd::Pid;
 use std::collections::HashMap;
-use std::process::Command;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::watch;
 use tokio::time::sleep;
 
-const MONITORING_INTERVAL: Duration = Duration::from_secs(2);
+/// Pause between two scans of the monitored ports
+pub const MONITORING_INTERVAL: Duration = Duration::from_secs(2);
+/// Pause between two checks of --wait-free and --wait-open
+pub const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Default number of extra SIGKILL attempts for processes that survive the first one
+pub const DEFAULT_SIGKILL_RETRIES: u32 = 3;
+/// Default pause between SIGKILL attempts
+pub const DEFAULT_SIGKILL_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// How long a process gets to exit after the default SIGTERM
+pub const DEFAULT_SIGTERM_WAIT: Duration = Duration::from_millis(500);
+
+/// Signals an `[[app.escalation]]` step may send
+pub const ESCALATION_SIGNALS: [&str; 7] = ["SIGTERM", "SIGINT", "SIGHUP", "SIGQUIT", "SIGUSR1", "SIGUSR2", "SIGKILL"];
+
+/// One rung of the kill escalation ladder: send `signal`, then give the process `wait` to exit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscalationStep {
+    /// One of [`ESCALATION_SIGNALS`]
+    pub signal: &'static str,
+    pub wait: Duration,
+}
+
+impl EscalationStep {
+    /// The step for a signal name, with or without the "SIG" prefix and in any case ("SIGINT", "int")
+    pub fn new(signal: &str, wait: Duration) -> std::result::Result<Self, String> {
+        let upper = signal.trim().to_ascii_uppercase();
+        let name = if upper.starts_with("SIG") { upper } else { format!("SIG{}", upper) };
+        match ESCALATION_SIGNALS.into_iter().find(|known| *known == name) {
+            Some(signal) => Ok(Self { signal, wait }),
+            None => Err(format!("unknown signal {:?}; use one of {}", signal, ESCALATION_SIGNALS.join(", "))),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn signal(&self) -> Signal {
+        // Every name in ESCALATION_SIGNALS parses; SIGTERM is only a fallback for the type
+        self.signal.parse().unwrap_or(Signal::SIGTERM)
+    }
+}
+
+/// How a process is killed: the escalation ladder, then SIGKILL until it is gone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KillPolicy {
+    /// Signals sent in order before SIGKILL, stopping as soon as the process exits
+    pub escalation: Vec<EscalationStep>,
+    /// Extra SIGKILL attempts after the first one
+    pub sigkill_retries: u32,
+    /// Pause after each SIGKILL before checking whether the process is gone
+    pub retry_delay: Duration,
+    /// Send SIGQUIT first and wait this long for a thread or core dump (--dump-before-kill)
+    pub dump_wait: Option<Duration>,
+}
+
+impl KillPolicy {
+    /// This policy with a SIGQUIT dump ahead of the escalation, or without one for `None`
+    pub fn with_dump(self, dump_wait: Option<Duration>) -> Self {
+        Self { dump_wait, ..self }
+    }
+}
+
+impl Default for KillPolicy {
+    fn default() -> Self {
+        Self {
+            escalation: vec![EscalationStep { signal: "SIGTERM", wait: DEFAULT_SIGTERM_WAIT }],
+            sigkill_retries: DEFAULT_SIGKILL_RETRIES,
+            retry_delay: DEFAULT_SIGKILL_RETRY_DELAY,
+            dump_wait: None,
+        }
+    }
+}
+
+/// One signal sent on request (tray submenu), instead of the SIGTERM-then-SIGKILL escalation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KillSignal {
+    Terminate,
+    ForceKill,
+    Interrupt,
+}
+
+impl KillSignal {
+    pub const ALL: [KillSignal; 3] = [KillSignal::Terminate, KillSignal::ForceKill, KillSignal::Interrupt];
+
+    /// Menu label, e.g. "Terminate (SIGTERM)"
+    pub fn label(self) -> &'static str {
+        match self {
+            KillSignal::Terminate => "Terminate (SIGTERM)",
+            KillSignal::ForceKill => "Force Kill (SIGKILL)",
+            KillSignal::Interrupt => "Interrupt (SIGINT)",
+        }
+    }
+
+    /// Short name used in menu item IDs
+    pub fn id(self) -> &'static str {
+        match self {
+            KillSignal::Terminate => "term",
+            KillSignal::ForceKill => "kill",
+            KillSignal::Interrupt => "int",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|signal| signal.id() == id)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn signal(self) -> Signal {
+        match self {
+            KillSignal::Terminate => Signal::SIGTERM,
+            KillSignal::ForceKill => Signal::SIGKILL,
+            KillSignal::Interrupt => Signal::SIGINT,
+        }
+    }
+}
+
+/// Default minimum time between the end of one kill operation and the start of the next
+pub const DEFAULT_KILL_COOLDOWN: Duration = Duration::from_secs(1);
+
+/// Suppresses kill requests that arrive too soon after the previous kill finished,
+/// so a double or triple click on "Kill All" doesn't cascade into newly started processes
+#[derive(Debug)]
+pub struct KillCooldown {
+    cooldown: Duration,
+    last_finished: std::sync::Mutex<Option<std::time::Instant>>,
+}
+
+impl KillCooldown {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            last_finished: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Time left before another kill is allowed, or `None` if one may start now
+    pub fn remaining(&self, now: std::time::Instant) -> Option<Duration> {
+        let last_finished = (*self.last_finished.lock().ok()?)?;
+        self.cooldown
+            .checked_sub(now.saturating_duration_since(last_finished))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Record that a kill operation just completed (successfully or not)
+    pub fn finished(&self, now: std::time::Instant) {
+        if let Ok(mut last_finished) = self.last_finished.lock() {
+            *last_finished = Some(now);
+        }
+    }
+}
+
+/// Default number of processes one "Kill All" may kill without confirmation
+pub const DEFAULT_MAX_KILL_WITHOUT_CONFIRM: usize = 20;
+
+/// A bulk kill that would hit more processes than `max_kill_without_confirm` allows,
+/// usually a sign of a misconfigured port range or discovery mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KillLimitExceeded {
+    pub count: usize,
+    pub limit: usize,
+}
+
+impl std::fmt::Display for KillLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Refusing to kill {} processes at once without confirmation (max_kill_without_confirm is {})",
+               self.count, self.limit)
+    }
+}
+
+impl std::error::Error for KillLimitExceeded {}
+
+/// Check a bulk kill of `count` processes against `limit`; a limit of 0 disables the check
+pub fn check_kill_limit(count: usize, limit: usize) -> std::result::Result<(), KillLimitExceeded> {
+    if limit > 0 && count > limit {
+        Err(KillLimitExceeded { count, limit })
+    } else {
+        Ok(())
+    }
+}
+
+/// Processes a batch kill ("Kill All", --kill-name) signals at the same time
+pub const KILL_BATCH_CONCURRENCY: usize = 8;
+/// Time after which a batch kill stops starting new kills (kills under way run to the end of their escalation)
+pub const KILL_BATCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Kill `pids` with `kill` on up to `concurrency` threads, returning one result per PID in order.
+///
+/// Kills that haven't started when `timeout` runs out are not attempted and come back as
+/// [`PortKillError::KillFailed`]; kills already under way still finish their SIGTERM/SIGKILL sequence.
+/// A kill that panics is reported as failed for its PID and doesn't take the rest of the batch down.
+pub fn kill_batch(
+    pids: &[i32],
+    concurrency: usize,
+    timeout: Duration,
+    kill: &(dyn Fn(i32) -> std::result::Result<(), PortKillError> + Sync),
+) -> Vec<std::result::Result<(), PortKillError>> {
+    let deadline = std::time::Instant::now() + timeout;
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let results = std::sync::Mutex::new(pids.iter().map(|_| None).collect::<Vec<_>>());
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, pids.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let Some(&pid) = pids.get(index) else {
+                    break;
+                };
+                let result = if std::time::Instant::now() < deadline {
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| kill(pid)))
+                        .unwrap_or_else(|_| Err(PortKillError::KillFailed { pid, reason: "The kill panicked".to_string() }))
+                } else {
+                    Err(PortKillError::KillFailed { pid, reason: format!("Not attempted: the batch kill timed out after {:?}", timeout) })
+                };
+                results.lock().unwrap_or_else(|poisoned| poisoned.into_inner())[index] = Some(result);
+            });
+        }
+    });
+
+    let results = results.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner());
+    results.into_iter().map(|result| result.expect("every PID is claimed by a worker")).collect()
+}
+
+/// Default `enrichment_concurrency`: threads looking up per-process details during a scan
+pub const DEFAULT_ENRICHMENT_CONCURRENCY: usize = 8;
+
+/// Run `enrich` on every process on up to `concurrency` threads, so per-process lookups
+/// (one `ps` call each for --show-uptime and --show-resources) don't make a wide scan crawl
+pub fn enrich_processes(processes: &mut HashMap<u16, Vec<ProcessInfo>>, concurrency: usize, enrich: &(dyn Fn(&mut ProcessInfo) + Sync)) {
+    let mut listeners: Vec<&mut ProcessInfo> = processes.values_mut().flatten().collect();
+    if listeners.is_empty() {
+        return;
+    }
+    let threads = concurrency.clamp(1, listeners.len());
+    let queue = std::sync::Mutex::new(listeners.iter_mut());
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let Some(process_info) = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).next() else {
+                    break;
+                };
+                enrich(process_info);
+            });
+        }
+    });
+}
+
+/// Default `docker_retries`: extra `docker ps` attempts before a scan goes without container details
+pub const DEFAULT_DOCKER_RETRIES: u32 = 2;
+
+/// Wait before the first `docker ps` retry; doubled for each further one
+pub const DOCKER_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// IDs of the running containers (`docker ps`), retried up to `retries` times with a doubling
+/// `delay`, since a restarting daemon or a busy Docker Desktop VM often answers a moment later
+pub async fn running_containers(runner: &dyn CommandRunner, retries: u32, delay: Duration) -> Result<Vec<String>> {
+    let mut attempt = 0;
+    loop {
+        let reason = match runner.run("docker", &["ps", "--format", "{{.ID}}"]) {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::trim).filter(|id| !id.is_empty()).map(str::to_string).collect());
+            }
+            Ok(output) => match String::from_utf8_lossy(&output.stderr).trim() {
+                "" => output.status.to_string(),
+                stderr => stderr.to_string(),
+            },
+            // Not installed: no retry will change that
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(anyhow::anyhow!("docker ps failed: docker CLI not found")),
+            Err(e) => e.to_string(),
+        };
+        attempt += 1;
+        if attempt > retries {
+            return Err(anyhow::anyhow!("docker ps failed after {} attempt(s): {}", attempt, reason));
+        }
+        sleep(delay.saturating_mul(1 << (attempt - 1).min(16))).await;
+    }
+}
+
+/// `interval` moved by up to ±`percent`% according to `sample` in [-1, 1], so instances started
+/// together drift apart instead of all running lsof at the same moment
+pub fn jittered_interval(interval: Duration, percent: u8, sample: f64) -> Duration {
+    let factor = 1.0 + sample.clamp(-1.0, 1.0) * f64::from(percent.min(100)) / 100.0;
+    interval.mul_f64(factor)
+}
+
+/// A random number in [-1, 1] for [`jittered_interval`]; the std hasher is randomly seeded,
+/// which is plenty for spreading scans and saves a dependency
+pub fn jitter_sample() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    bits as f64 / u64::MAX as f64 * 2.0 - 1.0
+}
+
+/// Scans in a row without a change before [`IdleBackoff`] starts stretching the interval
+pub const IDLE_CYCLES_BEFORE_BACKOFF: u32 = 3;
+
+/// Default cap for [`IdleBackoff`] (`idle_backoff_max_seconds`)
+pub const DEFAULT_IDLE_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Stretches the wait between scans while nothing changes (--interval-backoff-when-idle):
+/// after [`IDLE_CYCLES_BEFORE_BACKOFF`] quiet scans it doubles with each further one, up to `max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleBackoff {
+    max: Duration,
+    idle_cycles: u32,
+}
+
+impl IdleBackoff {
+    pub fn new(max: Duration) -> Self {
+        Self { max, idle_cycles: 0 }
+    }
+
+    /// Count a finished scan; a change drops straight back to the base interval
+    pub fn record(&mut self, changed: bool) {
+        self.idle_cycles = if changed { 0 } else { self.idle_cycles.saturating_add(1) };
+    }
+
+    /// Back to the base interval, e.g. after --refresh or a retarget
+    pub fn reset(&mut self) {
+        self.idle_cycles = 0;
+    }
+
+    /// The wait before the next scan for a `base` interval; never below `base`, even if `max` is
+    pub fn interval(&self, base: Duration) -> Duration {
+        let doublings = self.idle_cycles.saturating_sub(IDLE_CYCLES_BEFORE_BACKOFF - 1).min(16);
+        base.saturating_mul(1 << doublings).min(self.max).max(base)
+    }
+}
+
+/// What a [`ProcessMonitor`] scans: a set of ports, or every listening port
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MonitorTargets {
+    ports: Vec<u16>,
+    discover_all: bool,
+}
+
+/// Controls a running [`ProcessMonitor`] loop from another task
+#[derive(Clone)]
+pub struct MonitorHandle {
+    stop_sender: Arc<watch::Sender<bool>>,
+    targets_sender: Arc<watch::Sender<MonitorTargets>>,
+    refresh_sender: Arc<watch::Sender<()>>,
+}
+
+impl MonitorHandle {
+    /// Ask the loop to exit once its current scan finishes
+    pub fn stop(&self) {
+        self.stop_sender.send_replace(true);
+    }
+
+    /// Switch the monitored ports (e.g. after a config reload); takes effect with an immediate rescan
+    pub fn retarget(&self, ports: Vec<u16>, discover_all: bool) {
+        self.targets_sender.send_replace(MonitorTargets { ports, discover_all });
+    }
+
+    /// Rescan right away instead of waiting out the monitoring interval (`--refresh`)
+    pub fn refresh(&self) {
+        self.refresh_sender.send_replace(());
+    }
+}
 
 pub struct ProcessMonitor {
     update_sender: Sender<ProcessUpdate>,
-    current_processes: HashMap<u16, ProcessInfo>,
+    current_processes: HashMap<u16, Vec<ProcessInfo>>,
     ports_to_monitor: Vec<u16>,
     docker_enabled: bool,
     discover_all: bool,
+    watch_processes: Vec<String>,
+    socket_paths: Vec<String>,
+    include_established: bool,
+    interface: Option<IpAddr>,
+    network_namespaces: bool,
+    runner: Arc<dyn CommandRunner>,
+    kill_policy: KillPolicy,
+    lsof_timeout: Duration,
+    collect_uptime: bool,
+    collect_resources: bool,
+    enrichment_concurrency: usize,
+    poll_jitter_percent: u8,
+    idle_backoff: Option<IdleBackoff>,
+    docker_retries: u32,
+    /// Whether the last `docker ps` failed, so a persistent failure is only warned about once
+    docker_failing: AtomicBool,
+    stop_sender: Arc<watch::Sender<bool>>,
+    targets_sender: Arc<watch::Sender<MonitorTargets>>,
+    refresh_sender: Arc<watch::Sender<()>>,
 }
 
 impl ProcessMonitor {
-    pub fn new(update_sender: Sender<ProcessUpdate>, ports_to_monitor: Vec<u16>, docker_enabled: bool, discover_all: bool) -> Result<Self> {
+    pub fn new(update_sender: Sender<ProcessUpdate>, ports_to_monitor: Vec<u16>, docker_enabled: bool, discover_all: bool, runner: Arc<dyn CommandRunner>) -> Result<Self> {
+        let targets = MonitorTargets { ports: ports_to_monitor.clone(), discover_all };
         Ok(Self {
             update_sender,
             current_processes: HashMap::new(),
             ports_to_monitor,
             docker_enabled,
             discover_all,
+            watch_processes: Vec::new(),
+            socket_paths: Vec::new(),
+            include_established: false,
+            interface: None,
+            network_namespaces: false,
+            runner,
+            kill_policy: KillPolicy::default(),
+            lsof_timeout: DEFAULT_LSOF_TIMEOUT,
+            collect_uptime: false,
+            collect_resources: false,
+            enrichment_concurrency: DEFAULT_ENRICHMENT_CONCURRENCY,
+            poll_jitter_percent: 0,
+            idle_backoff: None,
+            docker_retries: DEFAULT_DOCKER_RETRIES,
+            docker_failing: AtomicBool::new(false),
+            stop_sender: Arc::new(watch::channel(false).0),
+            targets_sender: Arc::new(watch::channel(targets).0),
+            refresh_sender: Arc::new(watch::channel(()).0),
         })
     }
 
+    /// Handle for stopping, retargeting or refreshing `start_monitoring` from another task (e.g. on Ctrl+C)
+    pub fn handle(&self) -> MonitorHandle {
+        MonitorHandle {
+            stop_sender: self.stop_sender.clone(),
+            targets_sender: self.targets_sender.clone(),
+            refresh_sender: self.refresh_sender.clone(),
+        }
+    }
+
+    /// Override how stubborn processes are retried with SIGKILL
+    pub fn set_kill_policy(&mut self, kill_policy: KillPolicy) {
+        self.kill_policy = kill_policy;
+    }
+
+    /// Monitor processes whose name starts with one of `names` on any port, instead of the ports
+    pub fn set_watch_processes(&mut self, names: Vec<String>) {
+        self.watch_processes = names;
+    }
+
+    /// Also monitor the processes holding these Unix sockets, listed under port 0
+    pub fn set_socket_paths(&mut self, paths: Vec<String>) {
+        self.socket_paths = paths;
+    }
+
+    /// Also report processes holding the monitored ports without listening on them (--include-established)
+    pub fn set_include_established(&mut self, include_established: bool) {
+        self.include_established = include_established;
+    }
+
+    /// Only report sockets bound to this local address (--interface)
+    pub fn set_interface(&mut self, interface: Option<IpAddr>) {
+        self.interface = interface;
+    }
+
+    /// Also scan inside the other network namespaces, e.g. of containers (--netns)
+    pub fn set_network_namespaces(&mut self, network_namespaces: bool) {
+        self.network_namespaces = network_namespaces;
+    }
+
+    /// Kill lsof scans that run longer than this instead of letting the loop hang
+    pub fn set_lsof_timeout(&mut self, lsof_timeout: Duration) {
+        self.lsof_timeout = lsof_timeout;
+    }
+
+    /// Look up each process's start time on every scan (one extra `ps` call per process)
+    pub fn set_collect_uptime(&mut self, collect_uptime: bool) {
+        self.collect_uptime = collect_uptime;
+    }
+
+    /// Look up each process's CPU and memory usage on every scan (one extra `ps` call per process)
+    pub fn set_collect_resources(&mut self, collect_resources: bool) {
+        self.collect_resources = collect_resources;
+    }
+
+    /// Look up uptime and resources of up to this many processes at once (`enrichment_concurrency`)
+    pub fn set_enrichment_concurrency(&mut self, concurrency: usize) {
+        self.enrichment_concurrency = concurrency;
+    }
+
+    /// Retry a failing `docker ps` this many times per scan (`docker_retries`)
+    pub fn set_docker_retries(&mut self, retries: u32) {
+        self.docker_retries = retries;
+    }
+
+    /// Vary each wait between scans by up to ±`percent`% (`poll_jitter_percent`); 0 keeps the interval fixed
+    pub fn set_poll_jitter_percent(&mut self, percent: u8) {
+        self.poll_jitter_percent = percent;
+    }
+
+    /// Slow down scanning up to `max` while nothing changes (--interval-backoff-when-idle); None keeps the interval fixed
+    pub fn set_idle_backoff(&mut self, max: Option<Duration>) {
+        self.idle_backoff = max.map(IdleBackoff::new);
+    }
+
     pub async fn start_monitoring(&mut self) -> Result<()> {
-        let port_description = if self.discover_all {
-            "ALL listening processes on ANY port (auto-discovery mode)".to_string()
-        } else if self.ports_to_monitor.len() <= 10 {
-            format!("ports: {}", self.ports_to_monitor.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "))
-        } else {
-            format!("{} ports: {} to {}",
-                self.ports_to_monitor.len(),
-                self.ports_to_monitor.first().unwrap_or(&0),
-                self.ports_to_monitor.last().unwrap_or(&0))
-        };
+        info!("Starting process monitoring on {} ({})", self.port_description(), self.runner.describe());
+
+        let mut stop_receiver = self.stop_sender.subscribe();
+        let mut targets_receiver = self.targets_sender.subscribe();
+        let mut refresh_receiver = self.refresh_sender.subscribe();
+        while !*stop_receiver.borrow() {
+            let targets = targets_receiver.borrow_and_update().clone();
+            if targets.ports != self.ports_to_monitor || targets.discover_all != self.discover_all {
+                self.ports_to_monitor = targets.ports;
+                self.discover_all = targets.discover_all;
+                info!("Now monitoring {}", self.port_description());
+            }
 
-        info!("Starting process monitoring on {}", port_description);
+            let scan_started = std::time::Instant::now();
+            let scan = self.scan_processes().await;
+            let scan_duration = scan_started.elapsed();
+            crate::metrics::global().observe_scan(scan_duration);
+            if scan_duration > MONITORING_INTERVAL {
+                warn!("Scanning {} took {:.1?}, longer than the {:?} monitoring interval; monitor fewer ports to keep up",
+                      self.port_description(), scan_duration, MONITORING_INTERVAL);
+            }
 
-        loop {
-            match self.scan_processes().await {
+            let mut changed = false;
+            match scan {
                 Ok(processes) => {
-                    let update = ProcessUpdate::new(processes.clone());
+                    crate::metrics::global().set_listening_ports(processes.len());
 
-                    // Check if there are any changes
-                    if self.current_processes != processes {
+                    // Check if there are any changes (by port and PID, so refreshed details like
+                    // start times don't count as a change)
+                    let update = ProcessUpdate::from_change(&self.current_processes, processes.clone());
+                    changed = !update.diff.is_empty();
+                    if changed {
                         info!("Process update: {} processes found", update.count);
+                        for change in update.diff.describe() {
+                            info!("Process change: {}", change);
+                        }
                         self.current_processes = processes;
 
                         if let Err(e) = self.update_sender.send(update) {
@@ -63,32 +555,118 @@ impl ProcessMonitor {
                         }
                     }
                 }
+                // Already reported once by the scanner
+                Err(e) if is_lsof_missing(&e) => {}
                 Err(e) => {
                     error!("Failed to scan processes: {}", e);
                 }
             }
 
-            sleep(MONITORING_INTERVAL).await;
+            let base_interval = match self.idle_backoff.as_mut() {
+                Some(backoff) => {
+                    backoff.record(changed);
+                    backoff.interval(MONITORING_INTERVAL)
+                }
+                None => MONITORING_INTERVAL,
+            };
+            let interval = match self.poll_jitter_percent {
+                0 => base_interval,
+                percent => jittered_interval(base_interval, percent, jitter_sample()),
+            };
+            let woken_early = tokio::select! {
+                _ = sleep(interval) => false,
+                _ = stop_receiver.changed() => false,
+                _ = targets_receiver.changed() => true,
+                _ = refresh_receiver.changed() => {
+                    info!("Refresh requested, rescanning now");
+                    true
+                }
+            };
+            if woken_early {
+                if let Some(backoff) = self.idle_backoff.as_mut() {
+                    backoff.reset();
+                }
+            }
         }
+
+        info!("Process monitoring stopped");
+        Ok(())
     }
 
-    async fn scan_processes(&self) -> Result<HashMap<u16, ProcessInfo>> {
+    fn port_description(&self) -> String {
         if self.discover_all {
-            // Auto-discovery mode: find ALL listening processes on ANY port
-            self.discover_all_listening_processes().await
+            "ALL listening processes on ANY port (auto-discovery mode)".to_string()
+        } else if !self.watch_processes.is_empty() {
+            format!("processes named {} on any port", self.watch_processes.join(", "))
+        } else if self.ports_to_monitor.len() <= 10 {
+            format!("ports: {}", self.ports_to_monitor.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "))
+        } else {
+            format!("{} ports: {} to {}",
+                self.ports_to_monitor.len(),
+                self.ports_to_monitor.first().unwrap_or(&0),
+                self.ports_to_monitor.last().unwrap_or(&0))
+        }
+    }
+
+    async fn scan_processes(&self) -> Result<HashMap<u16, Vec<ProcessInfo>>> {
+        let mut processes = if self.discover_all || !self.watch_processes.is_empty() {
+            // Auto-discovery mode: find ALL listening processes (or all of the watched ones) on ANY port
+            self.discover_all_listening_processes().await?
         } else {
             // Traditional mode: monitor specific ports
-            self.get_processes_on_specific_ports().await
+            self.get_processes_on_specific_ports().await?
+        };
+
+        if !self.socket_paths.is_empty() {
+            let scanner = LsofScanner::new(self.runner.clone()).with_timeout(self.lsof_timeout);
+            processes.extend(listeners_by_port(scanner.scan_sockets(&self.socket_paths)?));
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        if self.docker_enabled {
+            self.add_container_info(&mut processes).await;
+        }
+        crate::service_manager::annotate(self.runner.as_ref(), &mut processes, self.enrichment_concurrency);
+
+        if self.collect_uptime || self.collect_resources {
+            let runner = self.runner.as_ref();
+            let (collect_uptime, collect_resources) = (self.collect_uptime, self.collect_resources);
+            enrich_processes(&mut processes, self.enrichment_concurrency, &|process_info| {
+                if collect_uptime {
+                    process_info.started_at = process_started_at(runner, process_info.pid);
+                }
+                if collect_resources {
+                    if let Some((cpu_percent, memory_bytes)) = process_resources(runner, process_info.pid) {
+                        process_info.cpu_percent = Some(cpu_percent);
+                        process_info.memory_bytes = Some(memory_bytes);
+                    }
+                }
+            });
         }
+
+        Ok(processes)
     }
 
     /// Get processes on specific monitored ports (traditional mode)
-    async fn get_processes_on_specific_ports(&self) -> Result<HashMap<u16, ProcessInfo>> {
+    async fn get_processes_on_specific_ports(&self) -> Result<HashMap<u16, Vec<ProcessInfo>>> {
+        #[cfg(not(target_os = "windows"))]
+        let processes = {
+            // One lsof run covers every monitored port; it already reports each listener's command
+            let scanner = LsofScanner::new(self.runner.clone())
+                .with_timeout(self.lsof_timeout)
+                .with_established(self.include_established)
+                .with_interface(self.interface)
+                .with_network_namespaces(self.network_namespaces);
+            listeners_by_port(scanner.scan(&self.ports_to_monitor)?)
+        };
+        #[cfg(target_os = "windows")]
         let mut processes = HashMap::new();
 
+        #[cfg(target_os = "windows")]
         for &port in &self.ports_to_monitor {
-            if let Ok(process_info) = self.get_process_on_port(port).await {
-                processes.insert(port, process_info);
+            // An error just means nothing is listening on the port
+            if let Ok(listeners) = self.get_processes_on_port(port).await {
+                processes.insert(port, listeners);
             }
         }
 
@@ -96,63 +674,25 @@ impl ProcessMonitor {
     }
 
     /// Discover ALL processes listening on ANY port (no more guessing!)
-    async fn discover_all_listening_processes(&self) -> Result<HashMap<u16, ProcessInfo>> {
-        let mut processes = HashMap::new();
-
+    async fn discover_all_listening_processes(&self) -> Result<HashMap<u16, Vec<ProcessInfo>>> {
         #[cfg(not(target_os = "windows"))]
-        {
-            // Use lsof to find ALL listening processes on ALL ports
-            let output = Command::new("lsof")
-                .args(&["-i", "-P", "-n", "-sTCP:LISTEN"])  // -i for internet files, -P for port numbers, -n for numeric addresses, -sTCP:LISTEN for only listening processes
-                .output()
-                .context("Failed to execute lsof command")?;
-
-            if !output.status.success() {
-                return Ok(processes);
-            }
-
-            let stdout = String::from_utf8_lossy(&output.stdout);
-
-            for line in stdout.lines().skip(1) { // Skip the header line
-                // Parse lsof output to extract listening processes
-                // Example line: "Python    1234 user   3u  IPv4 0x1234  0t0  TCP *:3000 (LISTEN)"
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 9 && (line.contains("(LISTEN)") || line.contains("*:")) {
-                    if let Some(port) = self.extract_port_from_lsof_line(line) {
-                        // Extract process info directly from lsof output for efficiency
-                        if let Ok(pid) = parts[1].parse::<i32>() {
-                            let command = parts[0].to_string();
-                            let name = parts[0].to_string();
-
-                            // Check if this is a Docker container
-                            let (container_id, container_name) = if self.docker_enabled {
-                                self.get_docker_container_info(pid).await
-                            } else {
-                                (None, None)
-                            };
-
-                            let process_info = ProcessInfo {
-                                pid,
-                                port,
-                                command,
-                                name,
-                                container_id,
-                                container_name,
-                            };
-
-                            processes.insert(port, process_info);
-                        }
-                    }
-                }
-            }
-        }
+        let processes = {
+            // Scanning with no port filter lists ALL listening processes on ALL ports
+            let scanner = LsofScanner::new(self.runner.clone())
+                .with_timeout(self.lsof_timeout)
+                .with_process_names(&self.watch_processes)
+                .with_interface(self.interface)
+                .with_network_namespaces(self.network_namespaces);
+            listeners_by_port(scanner.scan(&[])?)
+        };
+        #[cfg(target_os = "windows")]
+        let mut processes = HashMap::new();
 
         #[cfg(target_os = "windows")]
         {
             // Use netstat to find ALL listening processes on Windows
-            let output = Command::new("netstat")
-                .args(&["-ano"])  // -a for all, -n for numeric, -o for process ID
-                .output()
+            let output = self.runner
+                .run("netstat", &["-ano"])  // -a for all, -n for numeric, -o for process ID
                 .context("Failed to execute netstat command")?;
 
             if !output.status.success() {
@@ -168,37 +708,39 @@ impl ProcessMonitor {
                         // Extract port from local address (e.g., "0.0.0.0:3000")
                         if let Some(port_str) = parts[1].split(':').last() {
                             if let Ok(port) = port_str.parse::<u16>() {
-                                if let Ok(pid) = parts[4].parse::<i32>() {
-                                                                         // Get process details for Windows
-                                     if let Ok(process_info) = self.get_process_on_port(port).await {
-                                         processes.insert(port, process_info);
-                                     }
+                                // A port shows up once per listener and address family; the first line finds them all
+                                if processes.contains_key(&port) {
+                                    continue;
+                                }
+                                // Get process details for Windows
+                                if let Ok(listeners) = self.get_processes_on_port(port).await {
+                                    if !listeners.is_empty() {
+                                        processes.insert(port, listeners);
+                                    }
                                 }
                             }
                         }
                     }
                 }
             }
+
+            // netstat has no equivalent of lsof -c, so --watch-process filters by name here
+            if !self.watch_processes.is_empty() {
+                for listeners in processes.values_mut() {
+                    listeners.retain(|process_info| {
+                        let name = process_info.name.to_lowercase();
+                        self.watch_processes.iter().any(|watched| name.starts_with(&watched.to_lowercase()))
+                    });
+                }
+                processes.retain(|_, listeners| !listeners.is_empty());
+            }
         }
 
         Ok(processes)
     }
 
-    /// Extract port number from lsof output line
-    fn extract_port_from_lsof_line(&self, line: &str) -> Option<u16> {
-        // Look for patterns like "*:3000" or "localhost:8080"
-        if let Some(colon_pos) = line.rfind(':') {
-            let after_colon = &line[colon_pos + 1..];
-            // Find the end of the port number (space or parenthesis)
-            let port_end = after_colon.find(' ').unwrap_or(after_colon.len());
-            let port_str = &after_colon[..port_end];
-            port_str.parse().ok()
-        } else {
-            None
-        }
-    }
-
     /// Extract port number from netstat output line
+    #[allow(dead_code)]
     fn extract_port_from_netstat_line(&self, line: &str) -> Option<u16> {
         // Look for patterns like "0.0.0.0:3000" or "127.0.0.1:8080"
         let parts: Vec<&str> = line.split_whitespace().collect();
@@ -213,29 +755,33 @@ impl ProcessMonitor {
         None
     }
 
-    async fn get_process_on_port(&self, port: u16) -> Result<ProcessInfo> {
-        #[cfg(target_os = "windows")]
-        {
-            // Windows: Use netstat to find processes listening on the port
-            let output = Command::new("netstat")
-                .args(&["-ano"])
-                .output()
-                .context("Failed to execute netstat command")?;
+    /// Every process listening on `port` (several with SO_REUSEPORT or one per address family)
+    #[cfg(target_os = "windows")]
+    async fn get_processes_on_port(&self, port: u16) -> Result<Vec<ProcessInfo>> {
+        let mut listeners = Vec::new();
 
-            if output.status.success() {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                for line in stdout.lines() {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 5 {
-                        // Extract port from local address (e.g., "0.0.0.0:3000")
-                        if let Some(port_str) = parts[1].split(':').last() {
-                            if let Ok(found_port) = port_str.parse::<u16>() {
-                                if found_port == port {
-                                    if let Ok(pid) = parts[4].parse::<i32>() {
-                                        // Get process details
-                                        let process_info = self.get_process_details_windows(pid, port).await?;
-                                        return Ok(process_info);
+        // Windows: Use netstat to find processes listening on the port
+        let output = self.runner
+            .run("netstat", &["-ano"])
+            .context("Failed to execute netstat command")?;
+
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for line in stdout.lines() {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 5 {
+                    // Extract port from local address (e.g., "0.0.0.0:3000")
+                    if let Some(port_str) = parts[1].split(':').last() {
+                        if let Ok(found_port) = port_str.parse::<u16>() {
+                            if found_port == port {
+                                if let Ok(pid) = parts[4].parse::<i32>() {
+                                    if listeners.iter().any(|listener: &ProcessInfo| listener.pid == pid) {
+                                        continue;
                                     }
+                                    // Get process details
+                                    let mut process_info = self.get_process_details_windows(pid, port).await?;
+                                    process_info.bind_address = parts[1].rsplit_once(':').map(|(address, _)| address.to_string());
+                                    listeners.push(process_info);
                                 }
                             }
                         }
@@ -244,75 +790,18 @@ impl ProcessMonitor {
             }
         }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            // Unix-like systems: Use lsof to find processes listening on the port
-            let output = Command::new("lsof")
-                .args(&["-ti", &format!(":{}", port), "-sTCP:LISTEN"])
-                .output()
-                .context("Failed to execute lsof command")?;
-
-            if output.status.success() {
-                let output_str = String::from_utf8_lossy(&output.stdout);
-                let pid_str = output_str.trim();
-                if !pid_str.is_empty() {
-                    let pid: i32 = pid_str.parse().context("Failed to parse PID")?;
-
-                    // Get process details using ps
-                    let process_info = self.get_process_details(pid, port).await?;
-                    return Ok(process_info);
-                }
-            }
+        if listeners.is_empty() {
+            return Err(anyhow::anyhow!("No process found on port {}", port));
         }
-
-        Err(anyhow::anyhow!("No process found on port {}", port))
+        Ok(listeners)
     }
 
-    #[cfg(not(target_os = "windows"))]
-    async fn get_process_details(&self, pid: i32, port: u16) -> Result<ProcessInfo> {
-        // Get process command and name using ps
-        let output = Command::new("ps")
-            .args(&["-p", &pid.to_string(), "-o", "comm="])
-            .output()
-            .context("Failed to execute ps command")?;
-
-        let command = if output.status.success() {
-            String::from_utf8_lossy(&output.stdout).trim().to_string()
-        } else {
-            "unknown".to_string()
-        };
-
-        // Extract process name (basename of command)
-        let name = command
-            .split('/')
-            .last()
-            .unwrap_or("unknown")
-            .to_string();
-
-        // Check if this process is running in a Docker container
-        let (container_id, container_name) = if self.docker_enabled {
-            self.get_docker_container_info(pid).await
-        } else {
-            (None, None)
-        };
-
-        Ok(ProcessInfo {
-            pid,
-            port,
-            command,
-            name,
-            container_id,
-            container_name,
-        })
-    }
-
-    #[cfg(target_os = "windows")]
-    async fn get_process_details_windows(&self, pid: i32, port: u16) -> Result<ProcessInfo> {
-        // Get process name using tasklist
-        let output = Command::new("tasklist")
-            .args(&["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
-            .output()
-            .context("Failed to execute tasklist command")?;
+    #[cfg(target_os = "windows")]
+    async fn get_process_details_windows(&self, pid: i32, port: u16) -> Result<ProcessInfo> {
+        // Get process name using tasklist
+        let output = self.runner
+            .run("tasklist", &["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+            .context("Failed to execute tasklist command")?;
 
         let command = if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
@@ -327,8 +816,7 @@ impl ProcessMonitor {
                             port,
                             command: name.to_string(),
                             name: name_without_ext.to_string(),
-                            container_id: None,
-                            container_name: None,
+                            ..Default::default()
                         });
                     }
                     return Ok(ProcessInfo {
@@ -336,8 +824,7 @@ impl ProcessMonitor {
                         port,
                         command: name.to_string(),
                         name: name.to_string(),
-                        container_id: None,
-                        container_name: None,
+                        ..Default::default()
                     });
                 }
             }
@@ -362,54 +849,44 @@ impl ProcessMonitor {
             name: command,
             container_id,
             container_name,
+            ..Default::default()
         })
     }
 
+    /// Fill in the container of each process (--docker). `docker ps` runs once per scan; when it
+    /// keeps failing, the processes are still reported, just without container details.
     #[cfg(not(target_os = "windows"))]
-    async fn get_docker_container_info(&self, pid: i32) -> (Option<String>, Option<String>) {
-        // Try to find the container ID for this PID
-        let container_id = match self.find_container_id_for_pid(pid).await {
-            Ok(id) => id,
-            Err(_) => None,
-        };
-
-        // If we found a container ID, get the container name
-        let container_name = if let Some(ref id) = container_id {
-            match self.get_container_name(id).await {
-                Ok(name) => Some(name),
-                Err(_) => None,
+    async fn add_container_info(&self, processes: &mut HashMap<u16, Vec<ProcessInfo>>) {
+        let containers = match running_containers(self.runner.as_ref(), self.docker_retries, DOCKER_RETRY_DELAY).await {
+            Ok(containers) => {
+                if self.docker_failing.swap(false, Ordering::Relaxed) {
+                    info!("docker ps works again; showing container details");
+                }
+                containers
+            }
+            Err(e) => {
+                if !self.docker_failing.swap(true, Ordering::Relaxed) {
+                    warn!("{:#}; listing processes without container details until Docker responds", e);
+                }
+                return;
             }
-        } else {
-            None
         };
 
-        (container_id, container_name)
+        for process_info in processes.values_mut().flatten() {
+            let container_id = self.find_container_id_for_pid(&containers, process_info.pid).await.unwrap_or_default();
+            process_info.container_name = match container_id {
+                Some(ref id) => self.get_container_name(id).await.ok(),
+                None => None,
+            };
+            process_info.container_id = container_id;
+        }
     }
 
     #[cfg(not(target_os = "windows"))]
-    async fn find_container_id_for_pid(&self, pid: i32) -> Result<Option<String>> {
-        // Use docker ps to get all running containers
-        let output = Command::new("docker")
-            .args(&["ps", "--format", "table {{.ID}}\t{{.Names}}\t{{.Ports}}"])
-            .output()
-            .context("Failed to execute docker ps command")?;
-
-        if !output.status.success() {
-            return Ok(None);
-        }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        for line in stdout.lines().skip(1) { // Skip header
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 3 {
-                let container_id = parts[0].trim();
-                let _ports_str = parts[2].trim();
-
-                // Check if this container is using the port we're interested in
-                if self.container_has_pid(container_id, pid).await? {
-                    return Ok(Some(container_id.to_string()));
-                }
+    async fn find_container_id_for_pid(&self, containers: &[String], pid: i32) -> Result<Option<String>> {
+        for container_id in containers {
+            if self.container_has_pid(container_id, pid).await? {
+                return Ok(Some(container_id.clone()));
             }
         }
 
@@ -419,9 +896,8 @@ impl ProcessMonitor {
     #[cfg(not(target_os = "windows"))]
     async fn container_has_pid(&self, container_id: &str, pid: i32) -> Result<bool> {
         // Use docker top to get processes in the container
-        let output = Command::new("docker")
-            .args(&["top", container_id])
-            .output()
+        let output = self.runner
+            .run("docker", &["top", container_id])
             .context("Failed to execute docker top command")?;
 
         if !output.status.success() {
@@ -448,9 +924,8 @@ impl ProcessMonitor {
     #[cfg(not(target_os = "windows"))]
     async fn get_container_name(&self, container_id: &str) -> Result<String> {
         // Get container name using docker inspect
-        let output = Command::new("docker")
-            .args(&["inspect", "--format", "{{.Name}}", container_id])
-            .output()
+        let output = self.runner
+            .run("docker", &["inspect", "--format", "{{.Name}}", container_id])
             .context("Failed to execute docker inspect command")?;
 
         if output.status.success() {
@@ -463,11 +938,21 @@ impl ProcessMonitor {
     }
 
     pub async fn kill_process(&self, pid: i32) -> Result<()> {
+        let result = self.terminate_process(pid).await;
+        crate::metrics::global().record_kill(result.is_ok());
+        result
+    }
+
+    async fn terminate_process(&self, pid: i32) -> Result<()> {
         info!("Attempting to kill process {}", pid);
 
         // Check if this is a Docker container process
         if self.docker_enabled {
-            if let Some(container_id) = self.find_container_id_for_pid(pid).await? {
+            let containers = running_containers(self.runner.as_ref(), self.docker_retries, DOCKER_RETRY_DELAY).await.unwrap_or_else(|e| {
+                warn!("{:#}; killing process {} directly", e, pid);
+                Vec::new()
+            });
+            if let Some(container_id) = self.find_container_id_for_pid(&containers, pid).await? {
                 info!("Process {} is in Docker container {}, stopping container", pid, container_id);
                 return self.stop_docker_container(&container_id).await;
             }
@@ -476,9 +961,8 @@ impl ProcessMonitor {
         #[cfg(target_os = "windows")]
         {
             // Windows: Use taskkill
-            let output = Command::new("taskkill")
-                .args(&["/PID", &pid.to_string(), "/F"])
-                .output()
+            let output = self.runner
+                .run("taskkill", &["/PID", &pid.to_string(), "/F"])
                 .context("Failed to execute taskkill command")?;
 
             if output.status.success() {
@@ -492,37 +976,13 @@ impl ProcessMonitor {
 
         #[cfg(not(target_os = "windows"))]
         {
-            // Unix-like systems: Use SIGTERM then SIGKILL
-            match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-                Ok(_) => {
-                    info!("Sent SIGTERM to process {}", pid);
-
-                    // Wait a bit and check if process is still alive
-                    sleep(Duration::from_millis(500)).await;
-
-                    // Check if process is still running
-                    if self.is_process_running(pid).await {
-                        warn!("Process {} still running after SIGTERM, sending SIGKILL", pid);
-
-                        // Send SIGKILL if process is still alive
-                        match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                            Ok(_) => {
-                                info!("Sent SIGKILL to process {}", pid);
-                            }
-                            Err(e) => {
-                                error!("Failed to send SIGKILL to process {}: {}", pid, e);
-                                return Err(anyhow::anyhow!("Failed to kill process: {}", e));
-                            }
-                        }
-                    } else {
-                        info!("Process {} terminated successfully with SIGTERM", pid);
-                    }
-                }
-                Err(e) => {
-                    error!("Failed to send SIGTERM to process {}: {}", pid, e);
-                    return Err(anyhow::anyhow!("Failed to kill process: {}", e));
-                }
-            }
+            // Unix-like systems: walk the escalation ladder, then SIGKILL.
+            // The ladder sleeps between steps, so keep it off the async workers
+            let runner = self.runner.clone();
+            let kill_policy = self.kill_policy.clone();
+            tokio::task::spawn_blocking(move || escalate_until_gone(runner.as_ref(), pid, &kill_policy))
+                .await
+                .context("Kill escalation task failed")??;
         }
 
         Ok(())
@@ -532,9 +992,8 @@ impl ProcessMonitor {
         info!("Stopping Docker container: {}", container_id);
 
         // First try graceful stop
-        let stop_output = Command::new("docker")
-            .args(&["stop", container_id])
-            .output()
+        let stop_output = self.runner
+            .run("docker", &["stop", container_id])
             .context("Failed to execute docker stop command")?;
 
         if stop_output.status.success() {
@@ -544,9 +1003,8 @@ impl ProcessMonitor {
 
         // If graceful stop failed, try force remove
         info!("Graceful stop failed, force removing container: {}", container_id);
-        let remove_output = Command::new("docker")
-            .args(&["rm", "-f", container_id])
-            .output()
+        let remove_output = self.runner
+            .run("docker", &["rm", "-f", container_id])
             .context("Failed to execute docker rm command")?;
 
         if remove_output.status.success() {
@@ -558,280 +1016,1828 @@ impl ProcessMonitor {
         }
     }
 
-    pub async fn kill_all_processes(&self) -> Result<()> {
-        info!("Killing all monitored processes");
+}
 
-        let processes = self.scan_processes().await?;
-        let mut errors = Vec::new();
+/// Send a signal to a PID, locally via nix or through `kill` when the runner is remote
+#[cfg(not(target_os = "windows"))]
+fn send_signal(runner: &dyn CommandRunner, pid: i32, signal: Signal) -> std::result::Result<(), PortKillError> {
+    use nix::errno::Errno;
+
+    if !runner.is_remote() {
+        return kill(Pid::from_raw(pid), signal).map_err(|errno| match errno {
+            Errno::EPERM => PortKillError::PermissionDenied { pid },
+            Errno::ESRCH => PortKillError::ProcessNotFound { pid },
+            errno => PortKillError::KillFailed { pid, reason: errno.to_string() },
+        });
+    }
 
-        for (port, process_info) in processes {
-            info!("Killing process on port {} (PID: {})", port, process_info.pid);
-            if let Err(e) = self.kill_process(process_info.pid).await {
-                errors.push(format!("Port {} (PID {}): {}", port, process_info.pid, e));
-            }
+    // "SIGTERM" -> "TERM" for the portable `kill -s NAME` form
+    let signal_name = signal.as_str().trim_start_matches("SIG");
+    let output = runner
+        .run("kill", &["-s", signal_name, &pid.to_string()])
+        .map_err(|e| PortKillError::KillFailed { pid, reason: format!("Failed to execute remote kill command: {}", e) })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match output.status.success() {
+        true => Ok(()),
+        false if stderr.contains("Operation not permitted") => Err(PortKillError::PermissionDenied { pid }),
+        false if stderr.contains("No such process") => Err(PortKillError::ProcessNotFound { pid }),
+        false => Err(PortKillError::KillFailed { pid, reason: stderr.trim().to_string() }),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_process_running(runner: &dyn CommandRunner, pid: i32) -> bool {
+    runner
+        .run("ps", &["-p", &pid.to_string()])
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Start time of a process as a Unix timestamp, derived from its `ps` elapsed time
+#[cfg(not(target_os = "windows"))]
+pub fn process_started_at(runner: &dyn CommandRunner, pid: i32) -> Option<u64> {
+    let output = runner.run("ps", &["-p", &pid.to_string(), "-o", "etime="]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let elapsed = parse_elapsed_time(String::from_utf8_lossy(&output.stdout).trim())?;
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?.as_secs();
+    Some(now.saturating_sub(elapsed))
+}
+
+#[cfg(target_os = "windows")]
+pub fn process_started_at(_runner: &dyn CommandRunner, _pid: i32) -> Option<u64> {
+    // TODO: Query process creation time on Windows
+    None
+}
+
+/// Full command line of a process with its arguments, from `ps -o args=` (lsof only reports the name)
+#[cfg(not(target_os = "windows"))]
+pub fn process_command_line(runner: &dyn CommandRunner, pid: i32) -> Option<String> {
+    let output = runner.run("ps", &["-p", &pid.to_string(), "-o", "args="]).ok()?;
+    let command_line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !command_line.is_empty()).then_some(command_line)
+}
+
+#[cfg(target_os = "windows")]
+pub fn process_command_line(_runner: &dyn CommandRunner, _pid: i32) -> Option<String> {
+    None
+}
+
+/// Path of the executable a process runs: `/proc/<pid>/exe` where there is one, else the
+/// first `txt` file lsof reports for it, which is the executable on Linux and macOS alike
+#[cfg(not(target_os = "windows"))]
+pub fn process_executable(runner: &dyn CommandRunner, pid: i32) -> Option<std::path::PathBuf> {
+    if !runner.is_remote() {
+        if let Ok(executable) = std::fs::read_link(format!("/proc/{}/exe", pid)) {
+            return Some(strip_deleted_suffix(executable));
         }
+    }
 
-        if !errors.is_empty() {
-            let error_msg = errors.join("; ");
-            return Err(anyhow::anyhow!("Some processes failed to kill: {}", error_msg));
+    let output = runner.run("lsof", &["-a", "-p", &pid.to_string(), "-d", "txt", "-Fn"]).ok()?;
+    crate::project_group::parse_working_dirs(&String::from_utf8_lossy(&output.stdout)).remove(&pid)
+}
+
+#[cfg(target_os = "windows")]
+pub fn process_executable(_runner: &dyn CommandRunner, _pid: i32) -> Option<std::path::PathBuf> {
+    // TODO: Query the executable path on Windows
+    None
+}
+
+/// An executable replaced on disk (e.g. by a package upgrade) shows up as "/usr/bin/node (deleted)"
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn strip_deleted_suffix(executable: std::path::PathBuf) -> std::path::PathBuf {
+    match executable.to_str().and_then(|path| path.strip_suffix(" (deleted)")) {
+        Some(path) => std::path::PathBuf::from(path),
+        None => executable,
+    }
+}
+
+/// CPU percentage and resident memory (bytes) of a process, from `ps -o %cpu=,rss=`
+#[cfg(not(target_os = "windows"))]
+fn process_resources(runner: &dyn CommandRunner, pid: i32) -> Option<(f32, u64)> {
+    let output = runner.run("ps", &["-p", &pid.to_string(), "-o", "%cpu=,rss="]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    parse_resource_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+fn process_resources(_runner: &dyn CommandRunner, _pid: i32) -> Option<(f32, u64)> {
+    // TODO: Query process CPU and memory usage on Windows
+    None
+}
+
+/// Parse `ps -o %cpu=,rss=` output ("  12.5 204800", RSS in KiB) into CPU percent and bytes
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn parse_resource_usage(output: &str) -> Option<(f32, u64)> {
+    let mut fields = output.split_whitespace();
+    let cpu_percent = fields.next()?.replace(',', ".").parse::<f32>().ok()?;
+    let rss_kib = fields.next()?.parse::<u64>().ok()?;
+    Some((cpu_percent, rss_kib * 1024))
+}
+
+/// Parse `ps -o etime` output ("[[dd-]hh:]mm:ss") into seconds
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn parse_elapsed_time(etime: &str) -> Option<u64> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+
+    let mut fields = Vec::new();
+    for field in clock.split(':') {
+        fields.push(field.parse::<u64>().ok()?);
+    }
+
+    let (hours, minutes, seconds) = match fields.as_slice() {
+        [minutes, seconds] => (0, *minutes, *seconds),
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        _ => return None,
+    };
+
+    Some(((days * 24 + hours) * 60 + minutes) * 60 + seconds)
+}
+
+/// Walk `policy.escalation`, then SIGKILL (retried per `policy`) if the process outlived every step
+#[cfg(not(target_os = "windows"))]
+fn escalate_until_gone(runner: &dyn CommandRunner, pid: i32, policy: &KillPolicy) -> std::result::Result<(), PortKillError> {
+    let trace = KillTrace::start(pid, policy);
+    let exited = match policy.dump_wait {
+        Some(wait) => dump_before_kill(runner, pid, wait, &trace),
+        None => Ok(false),
+    }
+    .and_then(|exited| {
+        if exited {
+            return Ok(true);
+        }
+        escalate(
+            pid,
+            &policy.escalation,
+            |step| trace.signal_sent(step.signal, send_signal(runner, pid, step.signal())),
+            || trace.liveness(is_process_running(runner, pid)),
+            |wait| traced_sleep(&trace, wait),
+        )
+    });
+    let result = match exited {
+        Ok(true) => {
+            info!("Process {} terminated gracefully", pid);
+            Ok(())
+        }
+        Ok(false) => {
+            info!("Process {} still running, sending SIGKILL", pid);
+            trace.step("outlived every escalation step, escalating to SIGKILL");
+            sigkill_until_gone(runner, pid, policy, &trace)
         }
+        Err(e) => Err(e),
+    };
+    trace.finish(&result);
+    result
+}
 
-        info!("All processes killed successfully");
-        Ok(())
+/// --dump-before-kill: SIGQUIT makes a JVM print a thread dump and a Go program dump its goroutines
+/// (other processes may leave a core file), so the state of a hung server survives the kill.
+/// Returns whether the process exited during `wait`, or [`PortKillError::PermissionDenied`] if it can't be signalled.
+#[cfg(not(target_os = "windows"))]
+fn dump_before_kill(runner: &dyn CommandRunner, pid: i32, wait: Duration, trace: &KillTrace) -> std::result::Result<bool, PortKillError> {
+    match trace.signal_sent("SIGQUIT", send_signal(runner, pid, Signal::SIGQUIT)) {
+        Ok(()) => info!("SIGQUIT sent to PID {} for a dump; look for it in the process's output or a core file. Killing in {:?}", pid, wait),
+        Err(e @ PortKillError::PermissionDenied { .. }) => return Err(e),
+        Err(PortKillError::ProcessNotFound { .. }) => return Ok(true),
+        Err(e) => warn!("Failed to send SIGQUIT to PID {} for a dump: {}", pid, e),
     }
+    traced_sleep(trace, wait);
+    Ok(!trace.liveness(is_process_running(runner, pid)))
+}
 
-    #[cfg(not(target_os = "windows"))]
-    async fn is_process_running(&self, pid: i32) -> bool {
-        let output = Command::new("ps")
-            .args(&["-p", &pid.to_string()])
-            .output();
+#[cfg(not(target_os = "windows"))]
+fn traced_sleep(trace: &KillTrace, wait: Duration) {
+    trace.step(format_args!("waiting {:?}", wait));
+    std::thread::sleep(wait);
+}
 
-        match output {
-            Ok(output) => output.status.success(),
-            Err(_) => false,
+/// Send each step's signal and wait its `wait`, returning `true` as soon as `is_alive`
+/// reports the process gone and `false` if it outlived every step.
+///
+/// A signal refused with EPERM ends the kill with [`PortKillError::PermissionDenied`] right away:
+/// the process is still there, and no later signal (SIGKILL included) will be allowed either.
+#[cfg(not(target_os = "windows"))]
+fn escalate(
+    pid: i32,
+    steps: &[EscalationStep],
+    mut send: impl FnMut(&EscalationStep) -> std::result::Result<(), PortKillError>,
+    mut is_alive: impl FnMut() -> bool,
+    mut wait: impl FnMut(Duration),
+) -> std::result::Result<bool, PortKillError> {
+    for (index, step) in steps.iter().enumerate() {
+        match send(step) {
+            Ok(()) => info!("{} sent to PID: {} (step {}/{})", step.signal, pid, index + 1, steps.len()),
+            Err(e @ PortKillError::PermissionDenied { .. }) => return Err(e),
+            // ESRCH: it exited on its own before the signal arrived
+            Err(PortKillError::ProcessNotFound { .. }) => {
+                info!("PID {} already exited before {}", pid, step.signal);
+                return Ok(true);
+            }
+            // Don't fail yet: the liveness check tells whether it mattered
+            Err(e) => warn!("Failed to send {} to PID {}: {} (process may already be terminated)", step.signal, pid, e),
+        }
+
+        wait(step.wait);
+
+        if !is_alive() {
+            return Ok(true);
         }
     }
+    Ok(false)
 }
 
-// Platform-agnostic process management functions
-pub fn get_processes_on_ports(ports: &[u16], args: &crate::cli::Args) -> (usize, std::collections::HashMap<u16, crate::types::ProcessInfo>) {
-    // Build port range string for lsof
-    let port_range = if ports.len() <= 10 {
-        // For small number of ports, list them individually
-        ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
-    } else {
-        // For large ranges, use range format
-        format!("{}-{}", ports.first().unwrap_or(&0), ports.last().unwrap_or(&0))
-    };
+/// SIGKILL a process, retrying per `policy`, and explain why it survived if it does
+#[cfg(not(target_os = "windows"))]
+fn sigkill_until_gone(runner: &dyn CommandRunner, pid: i32, policy: &KillPolicy, trace: &KillTrace) -> std::result::Result<(), PortKillError> {
+    kill_until_dead(
+        pid,
+        policy,
+        || trace.signal_sent("SIGKILL", send_signal(runner, pid, Signal::SIGKILL)),
+        || trace.liveness(is_process_running(runner, pid)),
+        |wait| traced_sleep(trace, wait),
+    )
+    .map_err(|e| match (e, process_state_reason(runner, pid)) {
+        (PortKillError::KillFailed { pid, reason }, Some(state)) => PortKillError::KillFailed { pid, reason: format!("{}: {}", reason, state) },
+        (e, _) => e,
+    })
+}
 
-    // Use lsof to get detailed process information
-    let output = std::process::Command::new("lsof")
-        .args(&["-i", &format!(":{}", port_range), "-sTCP:LISTEN", "-P", "-n"])
-        .output();
+/// Send SIGKILL until `is_alive` reports the process gone, at most `1 + policy.sigkill_retries` times.
+///
+/// A process we may not signal at all fails with [`PortKillError::PermissionDenied`] on the first
+/// attempt, one that survives every attempt with [`PortKillError::KillFailed`].
+#[cfg(not(target_os = "windows"))]
+fn kill_until_dead(
+    pid: i32,
+    policy: &KillPolicy,
+    mut send_kill: impl FnMut() -> std::result::Result<(), PortKillError>,
+    mut is_alive: impl FnMut() -> bool,
+    mut wait: impl FnMut(Duration),
+) -> std::result::Result<(), PortKillError> {
+    let attempts = policy.sigkill_retries + 1;
+    let mut last_error = None;
+
+    for attempt in 1..=attempts {
+        match send_kill() {
+            Ok(_) => info!("SIGKILL sent to PID: {} (attempt {}/{})", pid, attempt, attempts),
+            // Retrying won't change who owns the process
+            Err(e @ PortKillError::PermissionDenied { .. }) => return Err(e),
+            Err(e) => {
+                warn!("Failed to send SIGKILL to PID {} (attempt {}/{}): {}", pid, attempt, attempts, e);
+                last_error = Some(e);
+            }
+        }
 
-    match output {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let mut processes = std::collections::HashMap::new();
+        wait(policy.retry_delay);
 
-            // Get ignore sets for efficient lookup
-            let ignore_ports = args.get_ignore_ports_set();
-            let ignore_processes = args.get_ignore_processes_set();
+        if !is_alive() {
+            return Ok(());
+        }
+    }
 
-            for line in stdout.lines().skip(1) { // Skip header
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 9 {
-                    if let (Ok(pid), Ok(port)) = (parts[1].parse::<i32>(), parts[8].split(':').last().unwrap_or("0").parse::<u16>()) {
-                        let command = parts[0].to_string();
-                        let name = parts[0].to_string();
-
-                        // Check if this process should be ignored
-                        let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
-
-                        if !should_ignore {
-                            processes.insert(port, crate::types::ProcessInfo {
-                                pid,
-                                port,
-                                command,
-                                name,
-                                container_id: None,
-                                container_name: None,
-                            });
-                        } else {
-                            log::info!("Ignoring process {} (PID {}) on port {} (ignored by user configuration)", name, pid, port);
-                        }
-                    }
-                }
+    Err(match last_error {
+        Some(e) => PortKillError::KillFailed { pid, reason: format!("Process {} is still running after {} SIGKILL attempt(s) (last error: {})", pid, attempts, e) },
+        None => PortKillError::KillFailed { pid, reason: format!("Process {} is still running after {} SIGKILL attempt(s)", pid, attempts) },
+    })
+}
+
+/// Look up why a process may be ignoring SIGKILL from its `ps` state
+#[cfg(not(target_os = "windows"))]
+fn process_state_reason(runner: &dyn CommandRunner, pid: i32) -> Option<String> {
+    let output = runner.run("ps", &["-p", &pid.to_string(), "-o", "stat="]).ok()?;
+    describe_process_state(String::from_utf8_lossy(&output.stdout).trim()).map(str::to_string)
+}
+
+/// Explain process states that delay or prevent termination
+#[cfg(not(target_os = "windows"))]
+fn describe_process_state(stat: &str) -> Option<&'static str> {
+    match stat.chars().next()? {
+        'D' => Some("process is in uninterruptible sleep (D state), usually blocked on disk or network I/O"),
+        'Z' => Some("process is a zombie; it has exited but its parent has not reaped it yet"),
+        'T' => Some("process is stopped; it will die once resumed"),
+        _ => None,
+    }
+}
+
+// Platform-agnostic process management functions
+pub fn get_processes_on_ports(ports: &[u16], args: &crate::cli::Args) -> (usize, std::collections::HashMap<u16, Vec<crate::types::ProcessInfo>>) {
+    let scanner = LsofScanner::from_args(args);
+    let (count, mut processes) = get_processes_with_scanner(&scanner, ports, args);
+    crate::service_manager::annotate(args.command_runner().as_ref(), &mut processes, DEFAULT_ENRICHMENT_CONCURRENCY);
+    (count, processes)
+}
+
+/// Scan `ports` with `scanner`, drop ignored processes and group the rest by port
+pub fn get_processes_with_scanner(scanner: &dyn PortScanner, ports: &[u16], args: &crate::cli::Args) -> (usize, std::collections::HashMap<u16, Vec<crate::types::ProcessInfo>>) {
+    match scanner.scan(ports) {
+        Ok(found) => {
+            let processes = listeners_by_port(retain_not_ignored(found, args));
+
+            (process_count(&processes), processes)
+        }
+        Err(e) => {
+            // A missing lsof was already reported once by the scanner
+            if !is_lsof_missing(&e) {
+                log::warn!("Failed to scan ports: {}", e);
             }
+            (0, HashMap::new())
+        }
+    }
+}
+
+/// A listener the filters leave out, and the first filter that does (--list-ignored)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExcludedProcess {
+    #[serde(flatten)]
+    pub process: ProcessInfo,
+    pub reason: ExclusionReason,
+}
 
-            (processes.len(), processes)
+/// Scan `ports` with `scanner` and keep only the listeners [`exclusion_reason`] leaves out, by port and PID
+pub fn excluded_with_scanner(scanner: &dyn PortScanner, ports: &[u16], args: &crate::cli::Args) -> Result<Vec<ExcludedProcess>> {
+    let mut excluded: Vec<ExcludedProcess> = listeners_by_port(scanner.scan(ports)?)
+        .into_values()
+        .flatten()
+        .filter_map(|process| {
+            let reason = exclusion_reason(args, process.pid, process.port, &process.name, process.bind_address.as_deref(), process.owner.as_deref())?;
+            Some(ExcludedProcess { process, reason })
+        })
+        .collect();
+    excluded.sort_by_key(|excluded| (excluded.process.port, excluded.process.pid));
+    Ok(excluded)
+}
+
+/// Group listening sockets by port, folding a process's IPv4 and IPv6 sockets into one entry.
+///
+/// Different processes on the same port (SO_REUSEPORT, or one per address family) each
+/// keep their own entry, ordered by PID. Unix sockets all share port 0 and keep one entry
+/// per path.
+pub fn listeners_by_port(listeners: Vec<ProcessInfo>) -> HashMap<u16, Vec<ProcessInfo>> {
+    let mut processes: HashMap<u16, Vec<ProcessInfo>> = HashMap::new();
+    for listener in listeners {
+        let on_port = processes.entry(listener.port).or_default();
+        match on_port.iter_mut().find(|existing| existing.pid == listener.pid && existing.socket_path == listener.socket_path) {
+            Some(existing) => existing.merge_families(&listener),
+            None => on_port.push(listener),
         }
-        Err(_) => (0, std::collections::HashMap::new())
     }
+    for on_port in processes.values_mut() {
+        on_port.sort_by_key(|process_info| process_info.pid);
+    }
+    processes
 }
 
-pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<()> {
-    // Build port range string for lsof
-    let port_range = if ports.len() <= 10 {
-        // For small number of ports, list them individually
-        ports.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(",")
+/// Why a listener is left out of listings and "Kill All", from [`exclusion_reason`]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "filter", rename_all = "snake_case")]
+pub enum ExclusionReason {
+    /// On the --discover-all safety list (PID 1 or a known system service)
+    Protected,
+    IgnoredPort { port: u16 },
+    IgnoredName { name: String },
+    IgnoredPid { pid: i32 },
+    /// Bound to an address --bind-filter leaves out; `None` if the address is unknown
+    BindFilter { bind_address: Option<String> },
+    /// Its executable lies under one of --ignore-path-prefix
+    IgnoredPath { executable: std::path::PathBuf },
+    /// Owned by someone other than the --only-user user
+    OtherUser { owner: Option<String> },
+}
+
+impl std::fmt::Display for ExclusionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExclusionReason::Protected => write!(f, "protected system process (--no-safety includes it)"),
+            ExclusionReason::IgnoredPort { port } => write!(f, "port {} is in the ignored ports", port),
+            ExclusionReason::IgnoredName { name } => write!(f, "name \"{}\" is in the ignored processes", name),
+            ExclusionReason::IgnoredPid { pid } => write!(f, "PID {} is in the ignored PIDs", pid),
+            ExclusionReason::BindFilter { bind_address: Some(address) } => write!(f, "listens on {}, left out by --bind-filter", address),
+            ExclusionReason::BindFilter { bind_address: None } => write!(f, "unknown listen address, left out by --bind-filter"),
+            ExclusionReason::IgnoredPath { executable } => write!(f, "executable {} is under an ignored path prefix", executable.display()),
+            ExclusionReason::OtherUser { owner } => write!(f, "owned by {}, not the --only-user user", owner.as_deref().unwrap_or("an unknown user")),
+        }
+    }
+}
+
+/// The first filter that excludes a listener: the safety list, the ignore lists (port, name,
+/// PID, path prefix), the bind filter or --only-user; `None` if it is shown
+pub fn exclusion_reason(args: &crate::cli::Args, pid: i32, port: u16, name: &str, bind_address: Option<&str>, owner: Option<&str>) -> Option<ExclusionReason> {
+    if args.is_protected_process(pid, name) {
+        Some(ExclusionReason::Protected)
+    } else if args.get_ignore_ports_set().contains(&port) {
+        Some(ExclusionReason::IgnoredPort { port })
+    } else if args.get_ignore_processes_set().contains(name) {
+        Some(ExclusionReason::IgnoredName { name: name.to_string() })
+    } else if args.get_ignore_pids_set().contains(&pid) {
+        Some(ExclusionReason::IgnoredPid { pid })
+    } else if !args.bind_filter.matches(bind_address) {
+        Some(ExclusionReason::BindFilter { bind_address: bind_address.map(str::to_string) })
+    } else if let Some(executable) = ignored_executable(args, pid) {
+        Some(ExclusionReason::IgnoredPath { executable })
+    } else if !args.matches_owner(owner) {
+        Some(ExclusionReason::OtherUser { owner: owner.map(str::to_string) })
     } else {
-        // For large ranges, use range format
-        format!("{}-{}", ports.first().unwrap_or(&0), ports.last().unwrap_or(&0))
+        None
+    }
+}
+
+/// Whether a listener is excluded by the safety list, the ignore lists or the bind filter.
+///
+/// This is the single filter behind every listing and "Kill All" path (console, tray menu
+/// and the free functions below), so they can't drift apart.
+pub fn is_process_excluded(args: &crate::cli::Args, pid: i32, port: u16, name: &str, bind_address: Option<&str>, owner: Option<&str>) -> bool {
+    match exclusion_reason(args, pid, port, name, bind_address, owner) {
+        None => false,
+        Some(ExclusionReason::Protected) => {
+            log::warn!("Skipping protected system process {} (PID {}) on port {} (use --no-safety to include it)", name, pid, port);
+            true
+        }
+        Some(ExclusionReason::OtherUser { .. }) => {
+            log::debug!("Skipping process {} (PID {}) on port {}: owned by {}, not {}",
+                        name, pid, port, owner.unwrap_or("an unknown user"), args.only_user.as_deref().unwrap_or_default());
+            true
+        }
+        Some(_) => {
+            log::info!("Ignoring process {} (PID {}) on port {} (ignored by user configuration)", name, pid, port);
+            true
+        }
+    }
+}
+
+/// Whether the executable of `pid` lies under --ignore-path-prefix; only looked up when a prefix is set
+pub fn has_ignored_executable(args: &crate::cli::Args, pid: i32) -> bool {
+    ignored_executable(args, pid).is_some()
+}
+
+/// The executable of `pid`, if it lies under --ignore-path-prefix
+fn ignored_executable(args: &crate::cli::Args, pid: i32) -> Option<std::path::PathBuf> {
+    if args.ignore_path_prefixes.as_ref().is_none_or(Vec::is_empty) {
+        return None;
+    }
+    let runner = args.command_runner();
+    process_executable(runner.as_ref(), pid).filter(|executable| args.is_ignored_executable(executable))
+}
+
+/// Whether a process may be killed under --max-age; processes whose age can't be
+/// determined are spared, since the flag exists to protect long-running daemons
+pub fn is_within_max_age(args: &crate::cli::Args, runner: &dyn CommandRunner, pid: i32, port: u16, name: &str) -> bool {
+    let Some(max_age) = args.max_age else {
+        return true;
     };
 
-    log::info!("Killing all processes on ports {}...", port_range);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+
+    match process_age(process_started_at(runner, pid), now) {
+        Some(age) if age <= max_age => true,
+        Some(age) => {
+            log::info!("Sparing {} (PID {}) on port {}: running for {}, longer than --max-age {}",
+                       name, pid, port, format_uptime(age), format_uptime(max_age));
+            false
+        }
+        None => {
+            log::warn!("Sparing {} (PID {}) on port {}: could not determine how long it has been running",
+                       name, pid, port);
+            false
+        }
+    }
+}
+
+fn process_age(started_at: Option<u64>, now: u64) -> Option<Duration> {
+    started_at.map(|started_at| Duration::from_secs(now.saturating_sub(started_at)))
+}
+
+/// Keep only the processes that pass [`is_process_excluded`], dropping ports left empty
+pub fn filter_ignored_processes(processes: &HashMap<u16, Vec<ProcessInfo>>, args: &crate::cli::Args) -> HashMap<u16, Vec<ProcessInfo>> {
+    processes
+        .iter()
+        .map(|(port, on_port)| (*port, retain_not_ignored(on_port.clone(), args)))
+        .filter(|(_, on_port)| !on_port.is_empty())
+        .collect()
+}
+
+fn is_excluded(process_info: &ProcessInfo, args: &crate::cli::Args) -> bool {
+    is_process_excluded(args, process_info.pid, process_info.port, &process_info.name, process_info.bind_address.as_deref(), process_info.owner.as_deref())
+}
+
+/// Remove processes excluded by the safety list, the ignore lists or the bind filter
+pub fn retain_not_ignored(processes: Vec<ProcessInfo>, args: &crate::cli::Args) -> Vec<ProcessInfo> {
+    processes
+        .into_iter()
+        .filter(|process_info| !is_excluded(process_info, args))
+        .collect()
+}
+
+pub fn kill_all_processes(ports: &[u16], args: &crate::cli::Args, policy: &KillPolicy, max_kill: usize) -> anyhow::Result<Vec<KillOutcome>> {
+    let runner = args.command_runner();
+    let scanner = LsofScanner::from_args(args);
+    kill_all_with_scanner(&scanner, runner.as_ref(), ports, args, policy, max_kill)
+}
+
+/// Kill every non-ignored listener on `ports`, returning one outcome per process; refuses with
+/// [`KillLimitExceeded`] above `max_kill` processes
+fn kill_all_with_scanner(scanner: &dyn PortScanner, runner: &dyn CommandRunner, ports: &[u16], args: &crate::cli::Args, policy: &KillPolicy, max_kill: usize) -> anyhow::Result<Vec<KillOutcome>> {
+    log::info!("Killing all processes on {} port(s)...", ports.len());
 
     // Get all PIDs on the monitored ports
-    let output = match std::process::Command::new("lsof")
-        .args(&["-i", &format!(":{}", port_range), "-sTCP:LISTEN", "-P", "-n"])
-        .output() {
-        Ok(output) => output,
-        Err(e) => {
-            log::error!("Failed to run lsof command: {}", e);
-            return Err(anyhow::anyhow!("Failed to run lsof: {}", e));
+    let found = scanner.scan(ports).map_err(|e| {
+        log::error!("Failed to scan ports: {}", e);
+        e
+    })?;
+
+    // A process listening on several sockets (e.g. IPv4 and IPv6) is only killed once
+    let mut to_kill: Vec<ProcessInfo> = Vec::new();
+    for process_info in retain_not_ignored(found, args) {
+        if !to_kill.iter().any(|target| target.pid == process_info.pid)
+            && is_within_max_age(args, runner, process_info.pid, process_info.port, &process_info.name)
+        {
+            to_kill.push(process_info);
         }
-    };
+    }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    if to_kill.is_empty() {
+        log::info!("No processes found to kill (all were ignored or none found)");
+        return Ok(Vec::new());
+    }
 
-    // Get ignore sets for efficient lookup
-    let ignore_ports = args.get_ignore_ports_set();
-    let ignore_processes = args.get_ignore_processes_set();
+    log::info!("Found {} processes to kill (after filtering ignored processes)", to_kill.len());
+    check_kill_limit(to_kill.len(), max_kill)?;
 
-    let mut pids_to_kill = Vec::new();
+    let outcomes = kill_processes(&to_kill, runner, policy);
+    let failed = outcomes.iter().filter(|outcome| !outcome.success).count();
+    log::info!("Finished killing all processes ({} of {} failed)", failed, outcomes.len());
+    Ok(outcomes)
+}
 
-    for line in lines {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 9 {
-            if let (Ok(pid), Ok(port)) = (parts[1].parse::<i32>(), parts[8].split(':').last().unwrap_or("0").parse::<u16>()) {
-                let name = parts[0].to_string();
+/// The listeners on `ports` whose name matches --kill-name, for [`kill_processes`]
+pub fn find_processes_by_name(ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<Vec<ProcessInfo>> {
+    let scanner = LsofScanner::from_args(args);
 
-                // Check if this process should be ignored
-                let should_ignore = ignore_ports.contains(&port) || ignore_processes.contains(&name);
+    let matches = processes_matching_kill_names(&scanner, ports, args)?;
+    log::info!("Found {} process(es) named {}", matches.len(), args.kill_name.join(", "));
+    Ok(matches)
+}
 
-                if !should_ignore {
-                    pids_to_kill.push(pid);
-                } else {
-                    log::info!("Ignoring process {} (PID {}) on port {} during kill operation (ignored by user configuration)", name, pid, port);
-                }
-            }
+/// Every process of a listing snapshot once, in port order; a process on several ports is killed for its first
+pub fn snapshot_kill_targets(processes: &HashMap<u16, Vec<ProcessInfo>>) -> Vec<ProcessInfo> {
+    let mut targets: Vec<ProcessInfo> = Vec::new();
+    let mut sorted: Vec<&ProcessInfo> = processes.values().flatten().collect();
+    sorted.sort_by_key(|process_info| (process_info.port, process_info.pid));
+    for process_info in sorted {
+        if !targets.iter().any(|target| target.pid == process_info.pid) {
+            targets.push(process_info.clone());
         }
     }
+    targets
+}
 
-    if pids_to_kill.is_empty() {
-        log::info!("No processes found to kill (all were ignored or none found)");
-        return Ok(());
+/// The most recently started process of a listing snapshot, for "Kill Newest Process"; processes
+/// whose start time is unknown are passed over
+pub fn newest_process(processes: &HashMap<u16, Vec<ProcessInfo>>) -> Option<&ProcessInfo> {
+    processes
+        .values()
+        .flatten()
+        .filter(|process_info| process_info.started_at.is_some())
+        .max_by_key(|process_info| (process_info.started_at, process_info.pid))
+}
+
+/// The earliest started process of a listing snapshot; processes whose start time is unknown are passed over
+pub fn oldest_process(processes: &HashMap<u16, Vec<ProcessInfo>>) -> Option<&ProcessInfo> {
+    processes
+        .values()
+        .flatten()
+        .filter(|process_info| process_info.started_at.is_some())
+        .min_by_key(|process_info| (process_info.started_at, process_info.pid))
+}
+
+/// Which end of the start times --kill-oldest and --kill-newest pick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartTimePick {
+    Oldest,
+    Newest,
+}
+
+impl StartTimePick {
+    pub fn from_args(args: &crate::cli::Args) -> Option<Self> {
+        if args.kill_oldest {
+            Some(Self::Oldest)
+        } else if args.kill_newest {
+            Some(Self::Newest)
+        } else {
+            None
+        }
     }
 
-    log::info!("Found {} processes to kill (after filtering ignored processes)", pids_to_kill.len());
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Oldest => "oldest",
+            Self::Newest => "newest",
+        }
+    }
+}
+
+/// The non-ignored listener on `ports` that started first or last (--kill-oldest, --kill-newest), with
+/// `started_at` filled in; `None` when no listener has a known start time within --max-age
+pub fn find_process_by_start_time(ports: &[u16], args: &crate::cli::Args, pick: StartTimePick) -> anyhow::Result<Option<ProcessInfo>> {
+    let scanner = LsofScanner::from_args(args);
+    let runner = args.command_runner();
+    process_by_start_time_with_scanner(&scanner, &|pid| process_started_at(runner.as_ref(), pid), ports, args, pick)
+}
 
-    for pid in pids_to_kill {
-        log::info!("Attempting to kill process PID: {}", pid);
-        match kill_process(pid) {
-            Ok(_) => log::info!("Successfully killed process PID: {}", pid),
-            Err(e) => log::error!("Failed to kill process {}: {}", pid, e),
+fn process_by_start_time_with_scanner(
+    scanner: &dyn PortScanner,
+    started_at: &dyn Fn(i32) -> Option<u64>,
+    ports: &[u16],
+    args: &crate::cli::Args,
+    pick: StartTimePick,
+) -> anyhow::Result<Option<ProcessInfo>> {
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or_default();
+    let mut processes = listeners_by_port(retain_not_ignored(scanner.scan(ports)?, args));
+    for process_info in processes.values_mut().flatten() {
+        process_info.started_at = started_at(process_info.pid);
+    }
+    // --max-age narrows the candidates like it does for every other kill
+    if let Some(max_age) = args.max_age {
+        for on_port in processes.values_mut() {
+            on_port.retain(|process_info| process_age(process_info.started_at, now).is_some_and(|age| age <= max_age));
         }
     }
 
-    log::info!("Finished killing all processes");
-    Ok(())
+    let chosen = match pick {
+        StartTimePick::Oldest => oldest_process(&processes),
+        StartTimePick::Newest => newest_process(&processes),
+    };
+    Ok(chosen.cloned())
 }
 
-pub fn kill_single_process(pid: i32, args: &crate::cli::Args) -> anyhow::Result<()> {
+/// Kill each of `processes`, returning the outcome of each kill
+pub fn kill_processes(processes: &[ProcessInfo], runner: &dyn CommandRunner, policy: &KillPolicy) -> Vec<KillOutcome> {
+    let pids: Vec<i32> = processes.iter().map(|process_info| process_info.pid).collect();
+    let results = kill_batch(&pids, KILL_BATCH_CONCURRENCY, KILL_BATCH_TIMEOUT, &|pid| kill_process(pid, runner, policy));
+
+    processes
+        .iter()
+        .zip(results)
+        .map(|(process_info, result)| {
+            if let Err(ref e) = result {
+                log::error!("Failed to kill process {}: {}", process_info.pid, e);
+            }
+            KillOutcome::new(process_info.pid, Some(process_info.port), Some(&process_info.name), &result)
+        })
+        .collect()
+}
+
+/// The non-ignored listeners whose name matches --kill-name, one per PID
+fn processes_matching_kill_names(scanner: &dyn PortScanner, ports: &[u16], args: &crate::cli::Args) -> anyhow::Result<Vec<ProcessInfo>> {
+    let mut matches: Vec<ProcessInfo> = Vec::new();
+    for process_info in retain_not_ignored(scanner.scan(ports)?, args) {
+        if args.matches_kill_name(&process_info.name) && !matches.iter().any(|matched| matched.pid == process_info.pid) {
+            matches.push(process_info);
+        }
+    }
+    Ok(matches)
+}
+
+/// Scan `ports` until nothing listens on them any more, for at most `timeout`.
+///
+/// With a `lingering` scanner, sockets left in TIME_WAIT or CLOSE_WAIT also keep a port taken.
+/// Ignore lists don't apply: an ignored process still holds the port. Returns the ports
+/// still taken when the timeout elapsed, so an empty list means they are all free.
+pub fn wait_for_ports_free(
+    scanner: &dyn PortScanner,
+    lingering: Option<&dyn LingeringScanner>,
+    ports: &[u16],
+    timeout: Duration,
+    poll_interval: Duration,
+) -> anyhow::Result<Vec<u16>> {
+    let mut taken = Vec::new();
+    poll_until(timeout, poll_interval, || {
+        // lsof may report other ports of a multi-socket process
+        taken = scanner.scan(ports)?.into_iter().map(|process_info| process_info.port).filter(|port| ports.contains(port)).collect();
+        if let Some(lingering) = lingering {
+            for socket in lingering.scan_lingering(ports)? {
+                log::debug!("Port {} has a socket in {}", socket.port, socket.state.as_str());
+                taken.push(socket.port);
+            }
+        }
+        taken.sort_unstable();
+        taken.dedup();
+        log::debug!("Waiting for port(s) {:?} to be released", taken);
+        Ok(taken.is_empty())
+    })?;
+    Ok(taken)
+}
+
+/// Scan `port` until something listens on it, for at most `timeout`.
+///
+/// Returns the listeners found, or an empty list if the timeout elapsed first.
+pub fn wait_for_port_open(scanner: &dyn PortScanner, port: u16, timeout: Duration, poll_interval: Duration) -> anyhow::Result<Vec<ProcessInfo>> {
+    let mut listeners = Vec::new();
+    poll_until(timeout, poll_interval, || {
+        listeners = scanner.scan(&[port])?;
+        listeners.retain(|process_info| process_info.port == port);
+        log::debug!("Waiting for a listener on port {}", port);
+        Ok(!listeners.is_empty())
+    })?;
+    Ok(listeners)
+}
+
+/// Run `done` every `poll_interval` until it returns true or `timeout` elapses; returns whether it did
+fn poll_until(timeout: Duration, poll_interval: Duration, mut done: impl FnMut() -> anyhow::Result<bool>) -> anyhow::Result<bool> {
+    let started = std::time::Instant::now();
+    loop {
+        if done()? {
+            return Ok(true);
+        }
+        let elapsed = started.elapsed();
+        if elapsed >= timeout {
+            return Ok(false);
+        }
+        std::thread::sleep(poll_interval.min(timeout - elapsed));
+    }
+}
+
+/// What `kill_single_process` did with a PID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleKill {
+    Killed,
+    /// Spared by --ignore-processes, --ignore-ports or --only-user
+    Ignored,
+    /// --dry-run: it would have been killed
+    DryRun,
+}
+
+pub fn kill_single_process(pid: i32, args: &crate::cli::Args, policy: &KillPolicy) -> std::result::Result<SingleKill, PortKillError> {
     log::info!("Killing single process PID: {}", pid);
 
+    if args.get_ignore_pids_set().contains(&pid) {
+        log::info!("Ignoring PID {} - PID is in ignore list", pid);
+        return Ok(SingleKill::Ignored);
+    }
+
+    if has_ignored_executable(args, pid) {
+        log::info!("Ignoring PID {} - its executable is under an ignored path prefix", pid);
+        return Ok(SingleKill::Ignored);
+    }
+
     // Check if this process should be ignored
     let ignore_ports = args.get_ignore_ports_set();
     let ignore_processes = args.get_ignore_processes_set();
 
     // Get process info to check if it should be ignored
-    let output = std::process::Command::new("ps")
-        .args(&["-p", &pid.to_string(), "-o", "comm="])
-        .output();
+    let runner = args.command_runner();
+    let output = runner.run("ps", &["-p", &pid.to_string(), "-o", "comm="]);
 
     if let Ok(output) = output {
         let process_name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() && process_name.is_empty() {
+            return Err(PortKillError::ProcessNotFound { pid });
+        }
 
         // Check if process name should be ignored
         if ignore_processes.contains(&process_name) {
             log::info!("Ignoring process {} (PID {}) - process name is in ignore list", process_name, pid);
-            return Ok(());
+            return Ok(SingleKill::Ignored);
+        }
+    }
+
+    if args.only_user.is_some() {
+        let owner = runner.run("ps", &["-p", &pid.to_string(), "-o", "user="])
+            .ok()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|owner| !owner.is_empty());
+        if !args.matches_owner(owner.as_deref()) {
+            log::info!("Ignoring PID {} - owned by {}, not --only-user {}", pid, owner.as_deref().unwrap_or("an unknown user"), args.only_user.as_deref().unwrap_or_default());
+            return Ok(SingleKill::Ignored);
         }
     }
 
     // Get port info to check if it should be ignored
-    let output = std::process::Command::new("lsof")
-        .args(&["-p", &pid.to_string(), "-i", "-P", "-n"])
-        .output();
+    let output = runner.run("lsof", &["-p", &pid.to_string(), "-i", "-P", "-n"]);
 
     if let Ok(output) = output {
         let stdout = String::from_utf8_lossy(&output.stdout);
         for line in stdout.lines() {
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 9 {
-                if let Ok(port) = parts[8].split(':').last().unwrap_or("0").parse::<u16>() {
+                if let Ok(port) = parts[8].rsplit(':').next().unwrap_or("0").parse::<u16>() {
                     if ignore_ports.contains(&port) {
                         log::info!("Ignoring process on port {} (PID {}) - port is in ignore list", port, pid);
-                        return Ok(());
+                        return Ok(SingleKill::Ignored);
                     }
                 }
             }
         }
     }
 
+    if args.dry_run {
+        log::info!("Dry run: would kill PID {}", pid);
+        return Ok(SingleKill::DryRun);
+    }
+
     // Process is not ignored, proceed with killing
-    kill_process(pid)
+    kill_process(pid, runner.as_ref(), policy).map(|()| SingleKill::Killed)
 }
 
-fn kill_process(pid: i32) -> anyhow::Result<()> {
-    #[cfg(not(target_os = "windows"))]
-    {
-        use nix::sys::signal::{kill, Signal};
-        use nix::unistd::Pid;
+/// Terminate a PID with the signals of `policy.escalation`, then SIGKILL (retried per `policy`) if it lingers
+#[cfg_attr(target_os = "windows", allow(unused_variables))]
+pub fn kill_process(pid: i32, runner: &dyn CommandRunner, policy: &KillPolicy) -> std::result::Result<(), PortKillError> {
+    let result = terminate_process(pid, runner, policy);
+    crate::metrics::global().record_kill(result.is_ok());
+    result
+}
 
-        log::info!("Killing process PID: {} with SIGTERM", pid);
+/// Send exactly `signal` to a PID, without waiting or escalating; whether it exits is up to the process
+pub fn signal_process(pid: i32, runner: &dyn CommandRunner, signal: KillSignal) -> std::result::Result<(), PortKillError> {
+    #[cfg(not(target_os = "windows"))]
+    let result = send_signal(runner, pid, signal.signal());
 
-        // First try SIGTERM (graceful termination)
-        match kill(Pid::from_raw(pid), Signal::SIGTERM) {
-            Ok(_) => log::info!("SIGTERM sent to PID: {}", pid),
-            Err(e) => {
-                // Don't fail immediately, just log the error and continue
-                log::warn!("Failed to send SIGTERM to PID {}: {} (process may already be terminated)", pid, e);
-            }
+    // Windows has no signals: everything but Force Kill asks the process to close
+    #[cfg(target_os = "windows")]
+    let result = {
+        let pid_arg = pid.to_string();
+        let mut taskkill_args = vec!["/PID", pid_arg.as_str()];
+        if signal == KillSignal::ForceKill {
+            taskkill_args.push("/F");
         }
+        match runner.run("taskkill", &taskkill_args) {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(PortKillError::KillFailed { pid, reason: String::from_utf8_lossy(&output.stderr).trim().to_string() }),
+            Err(e) => Err(PortKillError::KillFailed { pid, reason: format!("Failed to execute taskkill: {}", e) }),
+        }
+    };
 
-        // Wait a bit for graceful termination
-        std::thread::sleep(std::time::Duration::from_millis(500));
+    match result {
+        Ok(()) => log::info!("{} sent to PID: {}", signal.label(), pid),
+        Err(ref e) => log::warn!("Failed to send {} to PID {}: {}", signal.label(), pid, e),
+    }
+    crate::metrics::global().record_kill(result.is_ok());
+    result
+}
 
-        // Check if process is still running
-        let still_running = std::process::Command::new("ps")
-            .args(&["-p", &pid.to_string()])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false);
+fn terminate_process(pid: i32, runner: &dyn CommandRunner, policy: &KillPolicy) -> std::result::Result<(), PortKillError> {
+    // Killing only helps until the service manager restarts it
+    if let Some(manager) = crate::service_manager::detect(runner, pid) {
+        log::warn!("PID {} is managed by {} and may be restarted, taking the port again; to free the port, stop it instead: {}",
+                   pid, manager, manager.stop_command());
+    }
 
-        if still_running {
-            // Process still running, send SIGKILL
-            log::info!("Process {} still running, sending SIGKILL", pid);
-            match kill(Pid::from_raw(pid), Signal::SIGKILL) {
-                Ok(_) => log::info!("SIGKILL sent to PID: {}", pid),
-                Err(e) => {
-                    // Log error but don't fail the entire operation
-                    log::warn!("Failed to send SIGKILL to PID {}: {} (process may be protected)", pid, e);
-                }
-            }
-        } else {
-            log::info!("Process {} terminated gracefully", pid);
-        }
+    #[cfg(not(target_os = "windows"))]
+    {
+        log::info!("Killing process PID: {}", pid);
+        escalate_until_gone(runner, pid, policy)?;
     }
 
     #[cfg(target_os = "windows")]
     {
-        use std::process::Command;
-
         log::info!("Killing process PID: {} on Windows", pid);
 
         // Use taskkill to terminate the process
-        let output = Command::new("taskkill")
-            .args(&["/PID", &pid.to_string(), "/F"])
-            .output();
+        let output = runner.run("taskkill", &["/PID", &pid.to_string(), "/F"]);
 
         match output {
+            Ok(output) if output.status.success() => log::info!("Successfully killed process PID: {}", pid),
             Ok(output) => {
-                if output.status.success() {
-                    log::info!("Successfully killed process PID: {}", pid);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(if stderr.contains("Access is denied") {
+                    PortKillError::PermissionDenied { pid }
                 } else {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    log::warn!("Failed to kill process PID {}: {}", pid, stderr);
-                }
-            }
-            Err(e) => {
-                log::warn!("Failed to execute taskkill for PID {}: {}", pid, e);
+                    PortKillError::KillFailed { pid, reason: stderr.trim().to_string() }
+                });
             }
+            Err(e) => return Err(PortKillError::KillFailed { pid, reason: format!("Failed to execute taskkill: {}", e) }),
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use crate::port_scanner::{LingeringSocket, LingeringState};
+    use crate::types::AddressFamily;
+
+    /// Returns a fixed set of listeners instead of running lsof
+    struct FakeScanner(Vec<ProcessInfo>);
+
+    /// Reports its listeners until it has been scanned `released_after` times
+    struct ReleasingScanner {
+        listeners: Vec<ProcessInfo>,
+        released_after: usize,
+        scans: std::sync::atomic::AtomicUsize,
+    }
+
+    impl PortScanner for ReleasingScanner {
+        fn scan(&self, _ports: &[u16]) -> Result<Vec<ProcessInfo>> {
+            let scans = self.scans.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(if scans < self.released_after { self.listeners.clone() } else { Vec::new() })
+        }
+    }
+
+    impl PortScanner for FakeScanner {
+        fn scan(&self, ports: &[u16]) -> Result<Vec<ProcessInfo>> {
+            Ok(self.0.iter().filter(|p| ports.is_empty() || ports.contains(&p.port)).cloned().collect())
+        }
+    }
+
+    fn listener(port: u16, pid: i32, name: &str, bind_address: &str) -> ProcessInfo {
+        ProcessInfo {
+            bind_address: Some(bind_address.to_string()),
+            ..ProcessInfo::fixture(port, pid, name)
+        }
+    }
+
+    fn fake_scanner() -> FakeScanner {
+        FakeScanner(vec![
+            listener(3000, 10, "node", "127.0.0.1"),
+            listener(5353, 20, "mDNSResponder", "*"),
+            listener(8000, 30, "python3", "*"),
+            listener(8080, 40, "Chrome", "127.0.0.1"),
+        ])
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_counts_and_ignores() {
+        let args = crate::cli::Args {
+            ignore_ports: Some(vec![5353]),
+            ignore_processes: Some(vec!["Chrome".to_string()]),
+            ..crate::cli::Args::parse_from(["port-kill"])
+        };
+
+        let (count, processes) = get_processes_with_scanner(&fake_scanner(), &[3000, 5353, 8000, 8080], &args);
+
+        assert_eq!(count, 2);
+        assert_eq!(processes[&3000][0].pid, 10);
+        assert_eq!(processes[&8000][0].name, "python3");
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_shared_port() {
+        let args = crate::cli::Args::parse_from(["port-kill"]);
+        let scanner = FakeScanner(vec![
+            listener(3000, 11, "node", "*"),
+            listener(3000, 10, "node", "*"),
+        ]);
+
+        // SO_REUSEPORT workers share a port; each one is listed (and killed), not just the first
+        let (count, processes) = get_processes_with_scanner(&scanner, &[3000], &args);
+
+        assert_eq!(count, 2);
+        assert_eq!(processes[&3000].iter().map(|p| p.pid).collect::<Vec<_>>(), vec![10, 11]);
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_ignores_pids() {
+        let args = crate::cli::Args::parse_from(["port-kill", "--ignore-pid", "11"]);
+        let scanner = FakeScanner(vec![
+            listener(3000, 11, "node", "*"),
+            listener(3001, 10, "node", "*"),
+        ]);
+
+        // Only the exact PID is spared, not every process with its name
+        let (count, processes) = get_processes_with_scanner(&scanner, &[3000, 3001], &args);
+
+        assert_eq!(count, 1);
+        assert_eq!(processes[&3001][0].pid, 10);
+        assert!(!processes.contains_key(&3000));
+        assert_eq!(kill_single_process(11, &args, &KillPolicy::default()).unwrap(), SingleKill::Ignored);
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_all_ports() {
+        let args = crate::cli::Args {
+            ignore_processes: Some(vec!["mDNSResponder".to_string()]),
+            ..crate::cli::Args::parse_from(["port-kill", "--discover-all"])
+        };
+
+        let ports = args.get_ports_to_monitor();
+        assert!(ports.is_empty());
+
+        let (count, processes) = get_processes_with_scanner(&fake_scanner(), &ports, &args);
+        assert_eq!(count, 3);
+        assert!(!processes.contains_key(&5353));
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_skips_protected_in_all_ports_mode() {
+        let scanner = FakeScanner(vec![
+            listener(22, 100, "sshd", "*"),
+            listener(3000, 10, "node", "127.0.0.1"),
+        ]);
+
+        let args = crate::cli::Args::parse_from(["port-kill", "--discover-all"]);
+        let (count, processes) = get_processes_with_scanner(&scanner, &[], &args);
+        assert_eq!(count, 1);
+        assert!(processes.contains_key(&3000));
+
+        let args = crate::cli::Args::parse_from(["port-kill", "--discover-all", "--no-safety"]);
+        let (count, _) = get_processes_with_scanner(&scanner, &[], &args);
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_get_processes_with_scanner_applies_bind_filter() {
+        let args = crate::cli::Args::parse_from(["port-kill", "--bind-filter", "localhost"]);
+
+        let (count, processes) = get_processes_with_scanner(&fake_scanner(), &[3000, 8000], &args);
+
+        assert_eq!(count, 1);
+        assert!(processes.contains_key(&3000));
+    }
+
+    #[test]
+    fn test_listeners_by_port_keeps_every_process() {
+        let on_family = |port, pid, name, family| ProcessInfo {
+            address_families: vec![family],
+            ..listener(port, pid, name, "*")
+        };
+
+        // One process on both stacks becomes a single dual-stack entry
+        let processes = listeners_by_port(vec![
+            on_family(3000, 10, "node", AddressFamily::Ipv6),
+            on_family(3000, 10, "node", AddressFamily::Ipv4),
+        ]);
+        assert_eq!(processes[&3000].len(), 1);
+        assert_eq!(processes[&3000][0].address_families, vec![AddressFamily::Ipv4, AddressFamily::Ipv6]);
+
+        // Different processes sharing a port are all kept, each with its own family
+        let processes = listeners_by_port(vec![
+            on_family(8080, 30, "envoy", AddressFamily::Ipv6),
+            on_family(8080, 20, "nginx", AddressFamily::Ipv4),
+        ]);
+        assert_eq!(processes[&8080].iter().map(|p| p.pid).collect::<Vec<_>>(), vec![20, 30]);
+        assert_eq!(processes[&8080][0].address_families, vec![AddressFamily::Ipv4]);
+        assert_eq!(processes[&8080][1].address_families, vec![AddressFamily::Ipv6]);
+    }
+
+    #[test]
+    fn test_processes_matching_kill_names() {
+        let scanner = FakeScanner(vec![
+            listener(3000, 10, "node", "127.0.0.1"),
+            listener(3000, 10, "node", "[::1]"),
+            listener(5173, 11, "node", "127.0.0.1"),
+            listener(8000, 30, "python3", "*"),
+            listener(9229, 12, "node", "127.0.0.1"),
+        ]);
+        let args = crate::cli::Args {
+            ignore_ports: Some(vec![9229]),
+            ..crate::cli::Args::parse_from(["port-kill", "--kill-name", "node"])
+        };
+
+        // Each matching PID once, skipping ignored ports and other names
+        let matches = processes_matching_kill_names(&scanner, &[3000, 5173, 8000, 9229], &args).unwrap();
+        assert_eq!(matches.iter().map(|p| (p.pid, p.port)).collect::<Vec<_>>(), vec![(10, 3000), (11, 5173)]);
+    }
+
+    #[test]
+    fn test_process_by_start_time() {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        // pid 10 started an hour ago, 20 a day ago, 30 a minute ago, 40 has no known start time
+        let started_at = |pid: i32| match pid {
+            10 => Some(now - 3600),
+            20 => Some(now - 86_400),
+            30 => Some(now - 60),
+            _ => None,
+        };
+        let ports = [3000, 5353, 8000, 8080];
+        let pick = |args: &crate::cli::Args, pick| {
+            process_by_start_time_with_scanner(&fake_scanner(), &started_at, &ports, args, pick).unwrap().map(|process_info| process_info.pid)
+        };
+
+        let args = crate::cli::Args::parse_from(["port-kill", "--kill-newest"]);
+        assert_eq!(StartTimePick::from_args(&args), Some(StartTimePick::Newest));
+        assert_eq!(pick(&args, StartTimePick::Newest), Some(30));
+        assert_eq!(pick(&args, StartTimePick::Oldest), Some(20));
+
+        // Ignore lists and --max-age narrow the candidates first
+        let ignoring = crate::cli::Args { ignore_ports: Some(vec![5353, 8000]), ..args.clone() };
+        assert_eq!(pick(&ignoring, StartTimePick::Oldest), Some(10));
+        let recent = crate::cli::Args { max_age: Some(Duration::from_secs(7200)), ..args };
+        assert_eq!(pick(&recent, StartTimePick::Oldest), Some(10));
+    }
+
+    #[test]
+    fn test_filter_ignored_processes_matches_scanner_path() {
+        let args = crate::cli::Args {
+            ignore_ports: Some(vec![5353]),
+            ignore_processes: Some(vec!["Chrome".to_string()]),
+            ..crate::cli::Args::parse_from(["port-kill"])
+        };
+
+        // The console filters snapshots from the monitor; it must keep exactly what the scanner path keeps
+        let snapshot: HashMap<u16, Vec<ProcessInfo>> = fake_scanner().0.into_iter().map(|p| (p.port, vec![p])).collect();
+        let (_, scanned) = get_processes_with_scanner(&fake_scanner(), &[3000, 5353, 8000, 8080], &args);
+
+        assert_eq!(filter_ignored_processes(&snapshot, &args), scanned);
+        assert!(is_process_excluded(&args, 40, 8080, "Chrome", Some("127.0.0.1"), None));
+        assert!(!is_process_excluded(&args, 10, 3000, "node", Some("127.0.0.1"), None));
+    }
+
+    #[test]
+    fn test_excluded_with_scanner_gives_reasons() {
+        let args = crate::cli::Args {
+            ignore_ports: Some(vec![5353]),
+            ignore_processes: Some(vec!["Chrome".to_string()]),
+            ignore_pids: Some(vec![30]),
+            ..crate::cli::Args::parse_from(["port-kill", "--bind-filter", "external"])
+        };
+
+        let excluded = excluded_with_scanner(&fake_scanner(), &[3000, 5353, 8000, 8080], &args).unwrap();
+        let reasons: Vec<(i32, String)> = excluded.iter().map(|excluded| (excluded.process.pid, excluded.reason.to_string())).collect();
+        assert_eq!(reasons, vec![
+            (10, "listens on 127.0.0.1, left out by --bind-filter".to_string()),
+            (20, "port 5353 is in the ignored ports".to_string()),
+            (30, "PID 30 is in the ignored PIDs".to_string()),
+            (40, "name \"Chrome\" is in the ignored processes".to_string()),
+        ]);
+
+        let json = serde_json::to_value(&excluded[1]).unwrap();
+        assert_eq!((json["pid"].clone(), json["reason"].clone()), (serde_json::json!(20), serde_json::json!({ "filter": "ignored_port", "port": 5353 })));
+
+        // Nothing ignored, nothing listed
+        assert!(excluded_with_scanner(&fake_scanner(), &[], &crate::cli::Args::parse_from(["port-kill"])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_kill_signal_ids() {
+        for signal in KillSignal::ALL {
+            assert_eq!(KillSignal::from_id(signal.id()), Some(signal));
+        }
+        assert_eq!(KillSignal::from_id("hup"), None);
+        assert_eq!(KillSignal::ForceKill.label(), "Force Kill (SIGKILL)");
+    }
+
+    #[test]
+    fn test_wait_for_ports_free() {
+        let scanner = ReleasingScanner {
+            listeners: vec![listener(3000, 10, "node", "*"), listener(3000, 11, "node", "*"), listener(9229, 10, "node", "*")],
+            released_after: 2,
+            scans: std::sync::atomic::AtomicUsize::new(0),
+        };
+        let taken = wait_for_ports_free(&scanner, None, &[3000], Duration::from_secs(5), Duration::from_millis(1)).unwrap();
+        assert!(taken.is_empty());
+        assert_eq!(scanner.scans.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+        // A listener that never goes away is reported once the timeout elapses
+        let scanner = FakeScanner(vec![listener(3000, 10, "node", "*"), listener(8080, 12, "nginx", "*")]);
+        let taken = wait_for_ports_free(&scanner, None, &[3000, 8080, 5173], Duration::from_millis(20), Duration::from_millis(5)).unwrap();
+        assert_eq!(taken, vec![3000, 8080]);
+    }
+
+    /// Reports a TIME_WAIT socket on port 3000 until it has been scanned `expires_after` times
+    struct ExpiringTimeWait {
+        expires_after: usize,
+        scans: std::sync::atomic::AtomicUsize,
+    }
+
+    impl LingeringScanner for ExpiringTimeWait {
+        fn scan_lingering(&self, ports: &[u16]) -> Result<Vec<LingeringSocket>> {
+            let scans = self.scans.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            let socket = LingeringSocket { port: 3000, state: LingeringState::TimeWait };
+            Ok(if scans < self.expires_after && ports.contains(&socket.port) { vec![socket] } else { Vec::new() })
+        }
+    }
+
+    #[test]
+    fn test_wait_for_ports_free_counts_lingering_sockets() {
+        let scanner = FakeScanner(Vec::new());
+        let time_wait = ExpiringTimeWait { expires_after: 3, scans: std::sync::atomic::AtomicUsize::new(0) };
+
+        let taken = wait_for_ports_free(&scanner, Some(&time_wait), &[3000], Duration::from_secs(5), Duration::from_millis(1)).unwrap();
+        assert!(taken.is_empty());
+        assert_eq!(time_wait.scans.load(std::sync::atomic::Ordering::Relaxed), 4);
+
+        let time_wait = ExpiringTimeWait { expires_after: usize::MAX, scans: std::sync::atomic::AtomicUsize::new(0) };
+        let taken = wait_for_ports_free(&scanner, Some(&time_wait), &[3000, 8080], Duration::from_millis(20), Duration::from_millis(5)).unwrap();
+        assert_eq!(taken, vec![3000]);
+    }
+
+    #[test]
+    fn test_wait_for_port_open() {
+        let scanner = FakeScanner(vec![listener(3000, 10, "node", "*"), listener(9229, 10, "node", "*")]);
+        let found = wait_for_port_open(&scanner, 3000, Duration::from_secs(5), Duration::from_millis(1)).unwrap();
+        assert_eq!(found.iter().map(|process_info| (process_info.port, process_info.pid)).collect::<Vec<_>>(), vec![(3000, 10)]);
+
+        let found = wait_for_port_open(&scanner, 5173, Duration::from_millis(20), Duration::from_millis(5)).unwrap();
+        assert!(found.is_empty());
+    }
+
+    /// Counts lsof runs and reports nothing listening
+    #[derive(Default)]
+    struct CountingRunner(std::sync::atomic::AtomicUsize);
+
+    impl CommandRunner for CountingRunner {
+        fn run(&self, _program: &str, _args: &[&str]) -> std::io::Result<std::process::Output> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(std::process::Output { status: Default::default(), stdout: Vec::new(), stderr: Vec::new() })
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
+        }
+    }
+
+    /// Answers lsof with one listener and fails `docker ps` the first `failures` times
+    #[cfg(unix)]
+    struct FlakyDockerRunner {
+        failures: usize,
+        docker_calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[cfg(unix)]
+    impl CommandRunner for FlakyDockerRunner {
+        fn run(&self, program: &str, _args: &[&str]) -> std::io::Result<std::process::Output> {
+            use std::os::unix::process::ExitStatusExt;
+            let output = |code: i32, stdout: &str, stderr: &str| std::process::Output {
+                status: std::process::ExitStatus::from_raw(code << 8),
+                stdout: stdout.as_bytes().to_vec(),
+                stderr: stderr.as_bytes().to_vec(),
+            };
+            if program == "lsof" {
+                return Ok(output(0, "COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME\nnode 101 me 20u IPv4 0x1 0t0 TCP *:3000 (LISTEN)\n", ""));
+            }
+            if self.docker_calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed) < self.failures {
+                return Ok(output(1, "", "Cannot connect to the Docker daemon at unix:///var/run/docker.sock\n"));
+            }
+            Ok(output(0, "abc123\ndef456\n", ""))
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_running_containers_retries() {
+        let runner = FlakyDockerRunner { failures: 2, docker_calls: Default::default() };
+        assert_eq!(running_containers(&runner, 2, Duration::ZERO).await.unwrap(), vec!["abc123", "def456"]);
+        assert_eq!(runner.docker_calls.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+        let runner = FlakyDockerRunner { failures: usize::MAX, docker_calls: Default::default() };
+        let error = running_containers(&runner, 1, Duration::ZERO).await.unwrap_err();
+        assert_eq!(error.to_string(), "docker ps failed after 2 attempt(s): Cannot connect to the Docker daemon at unix:///var/run/docker.sock");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scan_without_docker_keeps_processes() {
+        let runner = Arc::new(FlakyDockerRunner { failures: usize::MAX, docker_calls: Default::default() });
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(10);
+        let mut monitor = ProcessMonitor::new(update_sender, Vec::new(), true, true, runner.clone()).unwrap();
+        monitor.set_docker_retries(0);
+
+        let processes = monitor.scan_processes().await.unwrap();
+        assert_eq!(processes[&3000].iter().map(|p| (p.pid, p.container_id.clone())).collect::<Vec<_>>(), vec![(101, None)]);
+        assert!(monitor.docker_failing.load(Ordering::Relaxed));
+        assert_eq!(runner.docker_calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_specific_ports_are_scanned_in_one_lsof_run() {
+        let runner = Arc::new(CountingRunner::default());
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(10);
+        let monitor = ProcessMonitor::new(update_sender, vec![3000, 5173, 8080], false, false, runner.clone()).unwrap();
+
+        assert!(monitor.scan_processes().await.unwrap().is_empty());
+        assert_eq!(runner.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rescans_before_the_interval() {
+        let runner = Arc::new(CountingRunner::default());
+        let scans = || runner.0.load(std::sync::atomic::Ordering::Relaxed);
+        let (update_sender, _update_receiver) = crossbeam_channel::bounded(10);
+        let mut monitor = ProcessMonitor::new(update_sender, vec![3000], false, false, runner.clone()).unwrap();
+        let handle = monitor.handle();
+        let monitor_task = tokio::spawn(async move { monitor.start_monitoring().await });
+
+        while scans() == 0 {
+            sleep(Duration::from_millis(5)).await;
+        }
+        let after_first_scan = scans();
+        handle.refresh();
+        tokio::time::timeout(MONITORING_INTERVAL / 2, async {
+            while scans() == after_first_scan {
+                sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("refresh should rescan before the monitoring interval elapses");
+
+        handle.stop();
+        monitor_task.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_kill_limit() {
+        assert_eq!(check_kill_limit(20, 20), Ok(()));
+        assert_eq!(check_kill_limit(21, 20), Err(KillLimitExceeded { count: 21, limit: 20 }));
+        assert_eq!(check_kill_limit(500, 0), Ok(()));
+
+        // "Kill All" refuses before a single process is signalled
+        let args = crate::cli::Args::parse_from(["port-kill", "--ports", "3000,5353,8000,8080"]);
+        let runner = CountingRunner::default();
+        let error = kill_all_with_scanner(&fake_scanner(), &runner, &[3000, 5353, 8000, 8080], &args, &KillPolicy::default(), 2).unwrap_err();
+        assert_eq!(error.downcast_ref::<KillLimitExceeded>(), Some(&KillLimitExceeded { count: 4, limit: 2 }));
+        assert_eq!(runner.0.load(std::sync::atomic::Ordering::Relaxed), 0);
+    }
+
+    /// A remote host whose `kill` is refused for PID 20; every process is gone after its signal
+    #[cfg(unix)]
+    struct RefusingRunner;
+
+    #[cfg(unix)]
+    impl CommandRunner for RefusingRunner {
+        fn run(&self, program: &str, args: &[&str]) -> std::io::Result<std::process::Output> {
+            use std::os::unix::process::ExitStatusExt;
+            let (code, stderr) = match program {
+                "kill" if args.contains(&"20") => (1, "kill: (20) - Operation not permitted\n"),
+                "ps" => (1, ""),
+                _ => (0, ""),
+            };
+            Ok(std::process::Output { status: std::process::ExitStatus::from_raw(code << 8), stdout: Vec::new(), stderr: stderr.as_bytes().to_vec() })
+        }
+
+        fn describe(&self) -> String {
+            "test host".to_string()
+        }
+
+        fn is_remote(&self) -> bool {
+            true
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kill_all_reports_each_outcome() {
+        let args = crate::cli::Args::parse_from(["port-kill", "--ports", "3000,8000,8080"]);
+        let policy = KillPolicy { escalation: vec![EscalationStep::new("SIGTERM", Duration::ZERO).unwrap()], ..KillPolicy::default() };
+        let outcomes = kill_all_with_scanner(&fake_scanner(), &RefusingRunner, &[3000, 8000, 8080], &args, &policy, 0).unwrap();
+
+        let results: Vec<(i32, Option<u16>, bool)> = outcomes.iter().map(|outcome| (outcome.pid, outcome.port, outcome.success)).collect();
+        assert_eq!(results, [(10, Some(3000), true), (30, Some(8000), true), (40, Some(8080), true)]);
+
+        let outcomes = kill_all_with_scanner(&fake_scanner(), &RefusingRunner, &[3000, 5353, 8000, 8080], &args, &policy, 0).unwrap();
+        let failed: Vec<i32> = outcomes.iter().filter(|outcome| !outcome.success).map(|outcome| outcome.pid).collect();
+        assert_eq!(failed, [20]);
+    }
+
+    #[test]
+    fn test_jittered_interval() {
+        let interval = Duration::from_secs(2);
+        assert_eq!(jittered_interval(interval, 0, 0.7), interval);
+        assert_eq!(jittered_interval(interval, 25, 1.0), Duration::from_millis(2500));
+        assert_eq!(jittered_interval(interval, 25, -1.0), Duration::from_millis(1500));
+        assert_eq!(jittered_interval(interval, 50, 0.0), interval);
+        // Out-of-range inputs are clamped rather than producing a negative or runaway interval
+        assert_eq!(jittered_interval(interval, 200, -3.0), Duration::ZERO);
+
+        for _ in 0..100 {
+            let sample = jitter_sample();
+            assert!((-1.0..=1.0).contains(&sample), "{}", sample);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_kill_targets() {
+        let processes = listeners_by_port(vec![
+            listener(8080, 12, "node", "*"),
+            listener(3000, 12, "node", "*"),
+            listener(3000, 11, "node", "*"),
+            listener(5173, 10, "vite", "127.0.0.1"),
+        ]);
+
+        let targets: Vec<(u16, i32)> = snapshot_kill_targets(&processes).iter().map(|target| (target.port, target.pid)).collect();
+        assert_eq!(targets, vec![(3000, 11), (3000, 12), (5173, 10)]);
+        assert!(snapshot_kill_targets(&HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_newest_process() {
+        let started = |mut process_info: ProcessInfo, started_at: Option<u64>| {
+            process_info.started_at = started_at;
+            process_info
+        };
+        let mut processes = listeners_by_port(vec![
+            started(listener(3000, 11, "node", "*"), Some(1_700_000_000)),
+            started(listener(5173, 10, "vite", "127.0.0.1"), Some(1_700_000_500)),
+            started(listener(8080, 12, "java", "*"), None),
+        ]);
+        assert_eq!(newest_process(&processes).map(|process_info| process_info.pid), Some(10));
+
+        // Unknown start times never win
+        processes.remove(&3000);
+        processes.remove(&5173);
+        assert_eq!(newest_process(&processes), None);
+    }
+
+    #[test]
+    fn test_kill_batch_runs_kills_in_parallel() {
+        let running = std::sync::atomic::AtomicUsize::new(0);
+        let most_running = std::sync::atomic::AtomicUsize::new(0);
+        let pids: Vec<i32> = (1..=12).collect();
+
+        let started = std::time::Instant::now();
+        let results = kill_batch(&pids, 4, Duration::from_secs(30), &|pid| {
+            let now_running = running.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            most_running.fetch_max(now_running, std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            running.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            if pid % 5 == 0 {
+                Err(PortKillError::PermissionDenied { pid })
+            } else {
+                Ok(())
+            }
+        });
+
+        // Three rounds of four instead of twelve kills in a row
+        assert!(started.elapsed() < Duration::from_millis(450), "{:?}", started.elapsed());
+        assert_eq!(most_running.load(std::sync::atomic::Ordering::SeqCst), 4);
+        let failed: Vec<i32> = pids.iter().zip(&results).filter(|(_, result)| result.is_err()).map(|(pid, _)| *pid).collect();
+        assert_eq!(failed, vec![5, 10]);
+        assert!(kill_batch(&[], 4, Duration::from_secs(1), &|_| Ok(())).is_empty());
+    }
+
+    #[test]
+    fn test_enrich_processes_in_parallel() {
+        // 50 listeners whose lookups take 5ms each, like one `ps` call per process
+        let mut processes: HashMap<u16, Vec<ProcessInfo>> = (0..50).map(|index| (3000 + index, vec![listener(3000 + index, i32::from(index) + 100, "node", "*")])).collect();
+        let enrich = |process_info: &mut ProcessInfo| {
+            std::thread::sleep(Duration::from_millis(5));
+            process_info.memory_bytes = Some(process_info.pid as u64);
+        };
+
+        let started = std::time::Instant::now();
+        enrich_processes(&mut processes, 1, &enrich);
+        let serial = started.elapsed();
+        let started = std::time::Instant::now();
+        enrich_processes(&mut processes, 10, &enrich);
+        let parallel = started.elapsed();
+
+        assert!(serial >= Duration::from_millis(250), "{:?}", serial);
+        assert!(parallel < serial / 3, "{:?} with 10 threads vs {:?} serially", parallel, serial);
+        assert!(processes.values().flatten().all(|process_info| process_info.memory_bytes == Some(process_info.pid as u64)));
+        enrich_processes(&mut HashMap::new(), 10, &enrich);
+    }
+
+    #[test]
+    fn test_kill_batch_reports_a_panicking_kill_per_pid() {
+        let results = kill_batch(&[10, 11, 12], 2, Duration::from_secs(30), &|pid| {
+            if pid == 11 {
+                panic!("kill of {} blew up", pid);
+            }
+            Ok(())
+        });
+
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[1], Err(PortKillError::KillFailed { pid: 11, .. })), "{:?}", results[1]);
+    }
+
+    #[test]
+    fn test_kill_batch_timeout_reports_unattempted_pids() {
+        let attempted = std::sync::Mutex::new(Vec::new());
+        let results = kill_batch(&[10, 11, 12], 1, Duration::from_millis(20), &|pid| {
+            attempted.lock().unwrap().push(pid);
+            std::thread::sleep(Duration::from_millis(60));
+            Ok(())
+        });
+
+        assert_eq!(*attempted.lock().unwrap(), vec![10]);
+        assert!(results[0].is_ok());
+        for result in &results[1..] {
+            let error = result.as_ref().unwrap_err();
+            assert!(matches!(error, PortKillError::KillFailed { .. }));
+            assert!(error.to_string().contains("timed out"), "{}", error);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_kill_single_process_respects_ignore_and_dry_run() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id().to_string();
+        let policy = KillPolicy::default();
+
+        let ignored = crate::cli::Args::parse_from(["port-kill", "--kill-pid", &pid, "--ignore-processes", "sleep"]);
+        assert_eq!(kill_single_process(child.id() as i32, &ignored, &policy).unwrap(), SingleKill::Ignored);
+        let dry_run = crate::cli::Args::parse_from(["port-kill", "--kill-pid", &pid, "--dry-run"]);
+        assert_eq!(kill_single_process(child.id() as i32, &dry_run, &policy).unwrap(), SingleKill::DryRun);
+        assert!(child.try_wait().unwrap().is_none());
+
+        // Reap the child as soon as it dies, or the kill would find a zombie and keep retrying
+        let pid_number = child.id() as i32;
+        let reaper = std::thread::spawn(move || child.wait());
+        let args = crate::cli::Args::parse_from(["port-kill", "--kill-pid", &pid]);
+        assert_eq!(kill_single_process(pid_number, &args, &policy).unwrap(), SingleKill::Killed);
+        assert!(reaper.join().unwrap().unwrap().code().is_none());
+        assert!(matches!(kill_single_process(pid_number, &args, &policy), Err(PortKillError::ProcessNotFound { pid }) if pid == pid_number));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_signal_process_sends_only_that_signal() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        signal_process(child.id() as i32, &crate::command_runner::LocalRunner, KillSignal::Interrupt).unwrap();
+
+        assert_eq!(child.wait().unwrap().signal(), Some(Signal::SIGINT as i32));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_dump_before_kill_sends_sigquit_first() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id() as i32;
+        let reaper = std::thread::spawn(move || child.wait());
+        let policy = KillPolicy::default().with_dump(Some(Duration::from_millis(200)));
+
+        // sleep doesn't handle SIGQUIT, so the dump signal already ends it
+        kill_process(pid, &crate::command_runner::LocalRunner, &policy).unwrap();
+        assert_eq!(reaper.join().unwrap().unwrap().signal(), Some(Signal::SIGQUIT as i32));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_kill_until_dead_retries_until_process_exits() {
+        let policy = KillPolicy { sigkill_retries: 3, retry_delay: Duration::from_millis(10), ..KillPolicy::default() };
+        let mut kills = 0;
+        let mut checks = 0;
+        let mut waited = Duration::ZERO;
+
+        // Still alive after the first two SIGKILLs, gone after the third
+        let result = kill_until_dead(
+            42,
+            &policy,
+            || {
+                kills += 1;
+                Ok(())
+            },
+            || {
+                checks += 1;
+                checks < 3
+            },
+            |delay| waited += delay,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(kills, 3);
+        assert_eq!(waited, Duration::from_millis(30));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_kill_until_dead_gives_up_with_reason() {
+        let policy = KillPolicy { sigkill_retries: 2, retry_delay: Duration::ZERO, ..KillPolicy::default() };
+        let mut kills = 0;
+
+        let result = kill_until_dead(
+            42,
+            &policy,
+            || {
+                kills += 1;
+                Err(PortKillError::KillFailed { pid: 42, reason: "EPERM".to_string() })
+            },
+            || true,
+            |_| {},
+        );
+
+        let message = result.unwrap_err().to_string();
+        assert_eq!(kills, 3);
+        assert!(message.contains("still running after 3 SIGKILL attempt(s)"), "{}", message);
+        assert!(message.contains("EPERM"), "{}", message);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_escalate_stops_once_process_exits() {
+        let steps = [
+            EscalationStep::new("SIGINT", Duration::from_millis(2000)).unwrap(),
+            EscalationStep::new("term", Duration::from_millis(3000)).unwrap(),
+            EscalationStep::new("KILL", Duration::ZERO).unwrap(),
+        ];
+        let mut sent = Vec::new();
+        let mut waited = Duration::ZERO;
+        let mut checks = 0;
+
+        // Ignores SIGINT, exits on SIGTERM; SIGKILL is never needed
+        let exited = escalate(
+            42,
+            &steps,
+            |step| {
+                sent.push(step.signal);
+                Ok(())
+            },
+            || {
+                checks += 1;
+                checks < 2
+            },
+            |delay| waited += delay,
+        );
+
+        assert!(exited.unwrap());
+        assert_eq!(sent, ["SIGINT", "SIGTERM"]);
+        assert_eq!(waited, Duration::from_millis(5000));
+
+        // A process that outlives every step is left to the SIGKILL retries, even if a signal failed
+        let mut sends = 0;
+        let survived = escalate(
+            42,
+            &steps,
+            |_| {
+                sends += 1;
+                Err(PortKillError::KillFailed { pid: 42, reason: "EINVAL".to_string() })
+            },
+            || true,
+            |_| {},
+        );
+        assert!(!survived.unwrap());
+        assert_eq!(sends, 3);
+
+        // ESRCH: it exited before the signal, so there's nothing to wait for
+        let mut waited = Duration::ZERO;
+        let gone = escalate(42, &steps, |_| Err(PortKillError::ProcessNotFound { pid: 42 }), || true, |delay| waited += delay);
+        assert!(gone.unwrap());
+        assert_eq!(waited, Duration::ZERO);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_permission_denied_fails_the_kill_right_away() {
+        let steps = [EscalationStep::new("SIGTERM", Duration::from_millis(500)).unwrap()];
+        let mut sends = 0;
+        let mut waited = Duration::ZERO;
+
+        // EPERM on the first signal: no waiting, no SIGKILL, and a failure rather than a success
+        let result = escalate(
+            42,
+            &steps,
+            |_| {
+                sends += 1;
+                Err(PortKillError::PermissionDenied { pid: 42 })
+            },
+            || true,
+            |delay| waited += delay,
+        );
+        let error = result.unwrap_err();
+        assert!(matches!(error, PortKillError::PermissionDenied { pid: 42 }));
+        assert!(error.to_string().contains("elevated privileges") && error.to_string().contains("--only-user"), "{}", error);
+        assert_eq!((sends, waited), (1, Duration::ZERO));
+
+        // Without an escalation ladder SIGKILL is the first signal; it isn't retried either
+        let policy = KillPolicy { sigkill_retries: 3, retry_delay: Duration::from_millis(250), ..KillPolicy::default() };
+        let mut kills = 0;
+        let result = kill_until_dead(
+            42,
+            &policy,
+            || {
+                kills += 1;
+                Err(PortKillError::PermissionDenied { pid: 42 })
+            },
+            || true,
+            |_| panic!("waited after EPERM"),
+        );
+        assert!(matches!(result, Err(PortKillError::PermissionDenied { pid: 42 })));
+        assert_eq!(kills, 1);
+
+        let stats = crate::session_summary::SessionStats::default();
+        stats.record(&[KillOutcome::new(42, Some(80), Some("nginx"), &result)]);
+        assert_eq!(stats.failed(), 1);
+    }
+
+    #[test]
+    fn test_escalation_step_signal_names() {
+        assert_eq!(EscalationStep::new("sigquit", Duration::ZERO).unwrap().signal, "SIGQUIT");
+        assert_eq!(EscalationStep::new("HUP", Duration::ZERO).unwrap().signal, "SIGHUP");
+        let error = EscalationStep::new("SIGSTOP", Duration::ZERO).unwrap_err();
+        assert!(error.contains("\"SIGSTOP\"") && error.contains("SIGTERM, SIGINT"), "{}", error);
+        assert!(EscalationStep::new("", Duration::ZERO).is_err());
+
+        #[cfg(not(target_os = "windows"))]
+        for name in ESCALATION_SIGNALS {
+            assert_eq!(EscalationStep::new(name, Duration::ZERO).unwrap().signal().as_str(), name);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_process_executable() {
+        let executable = process_executable(&crate::command_runner::LocalRunner, std::process::id() as i32).unwrap();
+        assert_eq!(executable.canonicalize().unwrap(), std::env::current_exe().unwrap().canonicalize().unwrap());
+
+        assert_eq!(strip_deleted_suffix("/usr/bin/node (deleted)".into()), std::path::PathBuf::from("/usr/bin/node"));
+        assert_eq!(strip_deleted_suffix("/usr/bin/node".into()), std::path::PathBuf::from("/usr/bin/node"));
+    }
+
+    #[test]
+    fn test_idle_backoff() {
+        let base = Duration::from_secs(2);
+        let mut backoff = IdleBackoff::new(Duration::from_secs(30));
+        let mut intervals = Vec::new();
+        for _ in 0..8 {
+            backoff.record(false);
+            intervals.push(backoff.interval(base).as_secs());
+        }
+        assert_eq!(intervals, vec![2, 2, 4, 8, 16, 30, 30, 30]);
+
+        backoff.record(true);
+        assert_eq!(backoff.interval(base), base);
+        for _ in 0..5 {
+            backoff.record(false);
+        }
+        backoff.reset();
+        assert_eq!(backoff.interval(base), base);
+
+        // A cap below the base interval never speeds scanning up
+        let mut short = IdleBackoff::new(Duration::from_secs(1));
+        for _ in 0..10 {
+            short.record(false);
+        }
+        assert_eq!(short.interval(base), base);
+
+        let mut long_idle = IdleBackoff::new(Duration::MAX);
+        for _ in 0..1000 {
+            long_idle.record(false);
+        }
+        assert_eq!(long_idle.interval(base), base * (1 << 16));
+    }
+
+    #[test]
+    fn test_kill_cooldown_suppresses_quick_repeats() {
+        let cooldown = KillCooldown::new(Duration::from_secs(1));
+        let start = std::time::Instant::now();
+
+        assert_eq!(cooldown.remaining(start), None);
+
+        cooldown.finished(start);
+        assert_eq!(cooldown.remaining(start + Duration::from_millis(300)), Some(Duration::from_millis(700)));
+        assert_eq!(cooldown.remaining(start + Duration::from_secs(1)), None);
+        assert_eq!(cooldown.remaining(start + Duration::from_secs(5)), None);
+
+        // A zero cooldown never suppresses anything
+        let disabled = KillCooldown::new(Duration::ZERO);
+        disabled.finished(start);
+        assert_eq!(disabled.remaining(start), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_max_age_spares_old_and_unknown_processes() {
+        let args = crate::cli::Args::parse_from(["port-kill", "--max-age", "30m"]);
+        let runner = crate::command_runner::LocalRunner;
+
+        // Without --max-age every process qualifies, no lookup needed
+        assert!(is_within_max_age(&crate::cli::Args::parse_from(["port-kill"]), &runner, i32::MAX, 3000, "node"));
+        // A PID that doesn't exist has no known age and is spared
+        assert!(!is_within_max_age(&args, &runner, i32::MAX, 3000, "node"));
+        // This test process has only just started
+        assert!(is_within_max_age(&args, &runner, std::process::id() as i32, 3000, "cargo"));
+
+        assert_eq!(process_age(Some(1_000), 1_600), Some(Duration::from_secs(600)));
+        assert_eq!(process_age(None, 1_600), None);
+    }
+
+    #[test]
+    fn test_parse_resource_usage() {
+        assert_eq!(parse_resource_usage("  12.5 204800\n"), Some((12.5, 204_800 * 1024)));
+        // Some locales print a decimal comma
+        assert_eq!(parse_resource_usage("0,3 1024"), Some((0.3, 1024 * 1024)));
+        assert_eq!(parse_resource_usage(""), None);
+        assert_eq!(parse_resource_usage("12.5"), None);
+    }
+
+    #[test]
+    fn test_parse_elapsed_time() {
+        assert_eq!(parse_elapsed_time("00:03"), Some(3));
+        assert_eq!(parse_elapsed_time("12:34"), Some(12 * 60 + 34));
+        assert_eq!(parse_elapsed_time("01:00:00"), Some(3_600));
+        assert_eq!(parse_elapsed_time("10-04:00:05"), Some(10 * 86_400 + 4 * 3_600 + 5));
+        assert_eq!(parse_elapsed_time("soon"), None);
+        assert_eq!(parse_elapsed_time(""), None);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_describe_process_state() {
+        assert!(describe_process_state("D+").unwrap().contains("uninterruptible"));
+        assert!(describe_process_state("Z").unwrap().contains("zombie"));
+        assert_eq!(describe_process_state("S"), None);
+        assert_eq!(describe_process_state(""), None);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::command_runner::CommandRunner;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+
+/// A process and everything it has spawned
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessNode {
+    pub pid: i32,
+    pub name: String,
+    pub children: Vec<ProcessNode>,
+}
+
+/// Parent/child relationships of every running process, taken from one `ps` snapshot
+#[derive(Debug, Default)]
+pub struct ProcessTable {
+    children: HashMap<i32, Vec<(i32, String)>>,
+}
+
+impl ProcessTable {
+    /// Snapshot the process table with `ps -A -o pid=,ppid=,comm=`
+    pub fn snapshot(runner: &dyn CommandRunner) -> Result<Self> {
+        let output = runner
+            .run("ps", &["-A", "-o", "pid=,ppid=,comm="])
+            .with_context(|| format!("Failed to run ps on {}", runner.describe()))?;
+        if !output.status.success() {
+            anyhow::bail!("ps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Parse `pid ppid command` lines, skipping any that don't fit
+    pub fn parse(output: &str) -> Self {
+        let mut children: HashMap<i32, Vec<(i32, String)>> = HashMap::new();
+        for line in output.lines() {
+            // The command may contain spaces, so only the first two columns are split off
+            let Some((pid, rest)) = line.trim().split_once(char::is_whitespace) else { continue };
+            let (ppid, command) = rest.trim_start().split_once(char::is_whitespace).unwrap_or((rest.trim_start(), ""));
+            let (Ok(pid), Ok(ppid)) = (pid.parse::<i32>(), ppid.parse::<i32>()) else { continue };
+            // macOS reports the full executable path; keep just the name like lsof does
+            let command = command.trim();
+            let name = command.rsplit('/').next().unwrap_or(command).to_string();
+            children.entry(ppid).or_default().push((pid, name));
+        }
+        for siblings in children.values_mut() {
+            siblings.sort_by_key(|(pid, _)| *pid);
+        }
+        Self { children }
+    }
+
+    /// Every descendant of `pid`, nested under its parent
+    pub fn descendants(&self, pid: i32) -> Vec<ProcessNode> {
+        let mut visited = HashSet::from([pid]);
+        self.collect_children(pid, &mut visited)
+    }
+
+    fn collect_children(&self, pid: i32, visited: &mut HashSet<i32>) -> Vec<ProcessNode> {
+        let Some(children) = self.children.get(&pid) else { return Vec::new() };
+        let mut nodes = Vec::new();
+        for (child_pid, name) in children {
+            // Guard against PID reuse or pid == ppid entries (e.g. the macOS kernel task) looping forever
+            if !visited.insert(*child_pid) {
+                continue;
+            }
+            nodes.push(ProcessNode {
+                pid: *child_pid,
+                name: name.clone(),
+                children: self.collect_children(*child_pid, visited),
+            });
+        }
+        nodes
+    }
+}
+
+/// Render nodes as indented tree lines ("├─ 1234 node", "│  └─ 1240 esbuild")
+pub fn render_tree(nodes: &[ProcessNode]) -> Vec<String> {
+    let mut lines = Vec::new();
+    render_level(nodes, "", &mut lines);
+    lines
+}
+
+fn render_level(nodes: &[ProcessNode], prefix: &str, lines: &mut Vec<String>) {
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let branch = if last { "└─" } else { "├─" };
+        lines.push(format!("{}{} {} {}", prefix, branch, node.pid, node.name));
+        let child_prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+        render_level(&node.children, &child_prefix, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PS_OUTPUT: &str = "    1     0 /sbin/launchd
+  100     1 /usr/local/bin/npm
+  101   100 node
+  102   101 esbuild
+  103   100 sh
+  104   103 /Applications/My App.app/Contents/MacOS/helper
+  200     1 postgres
+    0     0 kernel_task
+";
+
+    #[test]
+    fn test_descendants_follow_the_whole_tree() {
+        let table = ProcessTable::parse(PS_OUTPUT);
+        let tree = table.descendants(100);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!((tree[0].pid, tree[0].name.as_str()), (101, "node"));
+        assert_eq!((tree[0].children[0].pid, tree[0].children[0].name.as_str()), (102, "esbuild"));
+        assert_eq!((tree[1].pid, tree[1].name.as_str()), (103, "sh"));
+        assert_eq!(tree[1].children[0].name, "helper");
+
+        assert!(table.descendants(200).is_empty());
+        assert!(table.descendants(999).is_empty());
+    }
+
+    #[test]
+    fn test_descendants_ignore_self_parented_processes() {
+        let table = ProcessTable::parse(PS_OUTPUT);
+        let tree = table.descendants(0);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].pid, 1);
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let table = ProcessTable::parse(PS_OUTPUT);
+        assert_eq!(
+            render_tree(&table.descendants(100)),
+            vec!["├─ 101 node", "│  └─ 102 esbuild", "└─ 103 sh", "   └─ 104 helper"]
+        );
+        assert!(render_tree(&[]).is_empty());
+    }
+}
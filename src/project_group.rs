@@ -0,0 +1,137 @@
+use crate::command_runner::CommandRunner;
+use crate::types::ProcessInfo;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Files that mark the root of a project, checked from the working directory upwards
+pub const PROJECT_MARKERS: [&str; 6] = [".git", "package.json", "Cargo.toml", "go.mod", "pyproject.toml", "Gemfile"];
+
+/// Header of the group for processes whose working directory can't be read
+pub const UNKNOWN_GROUP: &str = "unknown";
+
+/// Working directory of each of `pids` that lsof can read, from one `lsof -a -d cwd -p ...` call
+pub fn working_dirs(runner: &dyn CommandRunner, pids: &[i32]) -> Result<HashMap<i32, PathBuf>> {
+    if pids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let pid_list = pids.iter().map(i32::to_string).collect::<Vec<_>>().join(",");
+    // lsof exits non-zero when one of the PIDs is gone, so only a failure to run counts
+    let output = runner
+        .run("lsof", &["-a", "-d", "cwd", "-Fn", "-p", &pid_list])
+        .with_context(|| format!("Failed to run lsof on {}", runner.describe()))?;
+    Ok(parse_working_dirs(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `lsof -F n` output: a `p<PID>` line followed by the `n<path>` line of its cwd
+pub fn parse_working_dirs(output: &str) -> HashMap<i32, PathBuf> {
+    let mut dirs = HashMap::new();
+    let mut pid = None;
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse::<i32>().ok();
+        } else if let (Some(path), Some(pid)) = (line.strip_prefix('n'), pid) {
+            dirs.entry(pid).or_insert_with(|| PathBuf::from(path));
+        }
+    }
+    dirs
+}
+
+/// The closest ancestor of `dir` (or `dir` itself) holding a [`PROJECT_MARKERS`] file,
+/// so servers started from `app/` and `app/web/` land in the same project
+pub fn project_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| PROJECT_MARKERS.iter().any(|marker| ancestor.join(marker).exists()))
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// Split `processes` by project directory, keeping their order within each group.
+///
+/// Groups are sorted by directory, with processes of unknown directory last under `None`.
+pub fn group_by_dir<'a>(processes: &[&'a ProcessInfo], project_dirs: &HashMap<i32, PathBuf>) -> Vec<(Option<PathBuf>, Vec<&'a ProcessInfo>)> {
+    let mut groups: Vec<(Option<PathBuf>, Vec<&ProcessInfo>)> = Vec::new();
+    for process_info in processes {
+        let dir = project_dirs.get(&process_info.pid).cloned();
+        match groups.iter_mut().find(|(group_dir, _)| *group_dir == dir) {
+            Some((_, members)) => members.push(process_info),
+            None => groups.push((dir, vec![process_info])),
+        }
+    }
+    // None sorts before Some, so flip the known flag to put "unknown" last
+    groups.sort_by(|(a, _), (b, _)| (a.is_none(), a).cmp(&(b.is_none(), b)));
+    groups
+}
+
+/// Project directory of each process in `processes`; with `resolve_roots` the working
+/// directory is widened to its [`project_root`], which needs the files to be local
+pub fn project_dirs(runner: &dyn CommandRunner, processes: &[&ProcessInfo], resolve_roots: bool) -> HashMap<i32, PathBuf> {
+    let pids: Vec<i32> = processes.iter().map(|process_info| process_info.pid).collect();
+    let dirs = match working_dirs(runner, &pids) {
+        Ok(dirs) => dirs,
+        Err(e) => {
+            log::warn!("Failed to read working directories: {:#}", e);
+            return HashMap::new();
+        }
+    };
+    if !resolve_roots {
+        return dirs;
+    }
+    dirs.into_iter().map(|(pid, dir)| (pid, project_root(&dir))).collect()
+}
+
+/// Group header, e.g. "/home/me/shop" or "unknown"
+pub fn group_label(dir: Option<&Path>) -> String {
+    dir.map(|dir| dir.display().to_string()).unwrap_or_else(|| UNKNOWN_GROUP.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_working_dirs() {
+        let output = "p101\nfcwd\nn/home/me/shop\np102\nfcwd\nn/home/me/blog with spaces\nnot-a-field\n";
+        let dirs = parse_working_dirs(output);
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[&101], PathBuf::from("/home/me/shop"));
+        assert_eq!(dirs[&102], PathBuf::from("/home/me/blog with spaces"));
+    }
+
+    #[test]
+    fn test_project_root() {
+        let root = std::env::temp_dir().join(format!("port-kill-group-{}", std::process::id()));
+        let nested = root.join("web").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+
+        assert_eq!(project_root(&nested), root);
+        assert_eq!(project_root(&root), root);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_group_by_dir() {
+        let (api, web, stray, other) = (ProcessInfo::fixture(8080, 10, "node"), ProcessInfo::fixture(3000, 11, "node"), ProcessInfo::fixture(9000, 12, "node"), ProcessInfo::fixture(5173, 13, "node"));
+        let project_dirs = HashMap::from([
+            (10, PathBuf::from("/home/me/shop")),
+            (11, PathBuf::from("/home/me/shop")),
+            (13, PathBuf::from("/home/me/blog")),
+        ]);
+
+        let groups = group_by_dir(&[&api, &web, &stray, &other], &project_dirs);
+        let summary: Vec<(String, Vec<i32>)> = groups
+            .iter()
+            .map(|(dir, members)| (group_label(dir.as_deref()), members.iter().map(|process_info| process_info.pid).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/home/me/blog".to_string(), vec![13]),
+                ("/home/me/shop".to_string(), vec![10, 11]),
+                ("unknown".to_string(), vec![12]),
+            ]
+        );
+    }
+}
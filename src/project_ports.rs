@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// .env files read by --from-project, in the order dev tools usually load them
+pub const ENV_FILES: [&str; 4] = [".env", ".env.local", ".env.development", ".env.development.local"];
+/// Vite config file names read by --from-project
+pub const VITE_CONFIGS: [&str; 5] = ["vite.config.js", "vite.config.ts", "vite.config.mjs", "vite.config.mts", "vite.config.cjs"];
+
+/// A port declared by a project file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPort {
+    pub port: u16,
+    /// Where it was found, e.g. ".env (PORT)"
+    pub source: String,
+}
+
+/// Find the ports a project declares in `dir`: `PORT`/`*_PORT` in .env files, `port:` in
+/// vite.config and `--port`, `-p` or `PORT=` in package.json scripts. Sorted by port, each once.
+pub fn discover(dir: &Path) -> Result<Vec<ProjectPort>> {
+    if !dir.is_dir() {
+        return Err(anyhow::anyhow!("{:?} is not a directory", dir));
+    }
+
+    let mut found = Vec::new();
+    for file in ENV_FILES {
+        if let Some(content) = read_optional(&dir.join(file))? {
+            found.extend(env_ports(&content).into_iter().map(|(key, port)| ProjectPort { port, source: format!("{} ({})", file, key) }));
+        }
+    }
+    for file in VITE_CONFIGS {
+        if let Some(content) = read_optional(&dir.join(file))? {
+            found.extend(config_ports(&content).into_iter().map(|port| ProjectPort { port, source: file.to_string() }));
+        }
+    }
+    if let Some(content) = read_optional(&dir.join("package.json"))? {
+        let package: serde_json::Value = serde_json::from_str(&content).context("Failed to parse package.json")?;
+        if let Some(scripts) = package.get("scripts").and_then(|scripts| scripts.as_object()) {
+            for (name, script) in scripts {
+                let ports = script.as_str().map(script_ports).unwrap_or_default();
+                found.extend(ports.into_iter().map(|port| ProjectPort { port, source: format!("package.json (scripts.{})", name) }));
+            }
+        }
+    }
+
+    // Keep the first source of each port
+    found.sort_by_key(|project_port| project_port.port);
+    found.dedup_by_key(|project_port| project_port.port);
+    Ok(found)
+}
+
+/// File contents, or `None` if it doesn't exist
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    fs::read_to_string(path).map(Some).with_context(|| format!("Failed to read {:?}", path))
+}
+
+fn parse_port(value: &str) -> Option<u16> {
+    value.trim().trim_matches(|c| c == '"' || c == '\'').parse::<u16>().ok().filter(|port| *port != 0)
+}
+
+/// `PORT=3000` and `VITE_PORT="5173"` style assignments, with their keys
+pub fn env_ports(content: &str) -> Vec<(String, u16)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (key, value) = line.strip_prefix("export ").unwrap_or(line).split_once('=')?;
+            let key = key.trim();
+            if key != "PORT" && !key.ends_with("_PORT") {
+                return None;
+            }
+            // Drop a trailing comment
+            let value = value.split(" #").next().unwrap_or(value);
+            Some((key.to_string(), parse_port(value)?))
+        })
+        .collect()
+}
+
+/// `port: 5173` (or `port = 5173`) in a JavaScript/TypeScript config file
+pub fn config_ports(content: &str) -> Vec<u16> {
+    let mut ports = Vec::new();
+    for (index, _) in content.match_indices("port") {
+        // `port` must be a whole identifier, not e.g. `import` or `portal`
+        let preceded_by_ident = content[..index].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let rest = content[index + "port".len()..].trim_start();
+        let Some(rest) = rest.strip_prefix(':').or_else(|| rest.strip_prefix('=')) else {
+            continue;
+        };
+        let digits: String = rest.trim_start().chars().take_while(char::is_ascii_digit).collect();
+        if let (false, Some(port)) = (preceded_by_ident, parse_port(&digits)) {
+            ports.push(port);
+        }
+    }
+    ports
+}
+
+/// `--port 3000`, `--port=3000`, `-p 3000` and `PORT=3000` in a package.json script
+pub fn script_ports(script: &str) -> Vec<u16> {
+    let tokens: Vec<&str> = script.split_whitespace().collect();
+    let mut ports = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let value = match *token {
+            "--port" | "-p" => tokens.get(index + 1).copied(),
+            _ => token.strip_prefix("--port=").or_else(|| token.strip_prefix("PORT=")),
+        };
+        if let Some(port) = value.and_then(parse_port) {
+            ports.push(port);
+        }
+    }
+    ports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_ports() {
+        let content = "# dev settings\nPORT=3000\nexport API_PORT = \"8080\"\nDATABASE_URL=postgres://localhost:5432/app\nVITE_PORT=5173 # frontend\nSUPPORT=1\nPORT=abc\n";
+        assert_eq!(
+            env_ports(content),
+            vec![("PORT".to_string(), 3000), ("API_PORT".to_string(), 8080), ("VITE_PORT".to_string(), 5173)]
+        );
+    }
+
+    #[test]
+    fn test_config_ports() {
+        let content = "import { defineConfig } from 'vite'\nexport default defineConfig({\n  server: { port: 5173, strictPort: true },\n  preview: { port:4173 },\n  report: 12,\n})\n";
+        assert_eq!(config_ports(content), vec![5173, 4173]);
+    }
+
+    #[test]
+    fn test_script_ports() {
+        assert_eq!(script_ports("next dev -p 3001"), vec![3001]);
+        assert_eq!(script_ports("PORT=4000 node server.js"), vec![4000]);
+        assert_eq!(script_ports("vite --port=5174 --host"), vec![5174]);
+        assert!(script_ports("vite build").is_empty());
+    }
+
+    #[test]
+    fn test_discover() {
+        let dir = std::env::temp_dir().join(format!("port-kill-project-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".env"), "PORT=3000\n").unwrap();
+        fs::write(dir.join("vite.config.ts"), "export default { server: { port: 5173 } }\n").unwrap();
+        fs::write(dir.join("package.json"), r#"{"scripts": {"dev": "vite --port 5173", "api": "PORT=3000 node api.js", "storybook": "storybook dev -p 6006"}}"#).unwrap();
+
+        let found = discover(&dir).unwrap();
+        assert_eq!(found.iter().map(|project_port| project_port.port).collect::<Vec<_>>(), vec![3000, 5173, 6006]);
+        assert_eq!(found[0].source, ".env (PORT)");
+        assert_eq!(found[2].source, "package.json (scripts.storybook)");
+
+        fs::write(dir.join("package.json"), "{ not json").unwrap();
+        assert!(discover(&dir).is_err());
+        assert!(discover(&dir.join("missing")).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(not(target_os = "windows"))]
+use crate::daemon::{is_pid_running, read_pid_file};
+use anyhow::Result;
+use std::path::Path;
+
+/// Run `on_refresh` each time SIGUSR1 arrives (sent by `port-kill --refresh`), from a background thread
+#[cfg(not(target_os = "windows"))]
+pub fn on_signal(on_refresh: impl Fn() + Send + 'static) -> std::io::Result<()> {
+    use log::info;
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    // Installed before returning, so a --refresh sent right after startup isn't fatal
+    let mut sigusr1 = {
+        let _runtime = runtime.enter();
+        signal(SignalKind::user_defined1())?
+    };
+
+    std::thread::Builder::new()
+        .name("refresh-signals".to_string())
+        .spawn(move || {
+            runtime.block_on(async {
+                while sigusr1.recv().await.is_some() {
+                    info!("Received SIGUSR1, refreshing...");
+                    on_refresh();
+                }
+            });
+        })?;
+
+    Ok(())
+}
+
+/// Ask the running status bar app (`lock_file`) or console daemon (`pid_file`) to rescan now; returns its PID
+#[cfg(not(target_os = "windows"))]
+pub fn send(lock_file: &Path, pid_file: &Path) -> Result<i32> {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    let pid = [lock_file, pid_file]
+        .into_iter()
+        .filter_map(read_pid_file)
+        .find(|pid| is_pid_running(*pid))
+        .ok_or_else(|| anyhow::anyhow!("No running port-kill found (checked {:?} and {:?})", lock_file, pid_file))?;
+
+    kill(Pid::from_raw(pid), Signal::SIGUSR1)
+        .map_err(|e| anyhow::anyhow!("Failed to send SIGUSR1 to port-kill (PID {}): {}", pid, e))?;
+    Ok(pid)
+}
+
+#[cfg(target_os = "windows")]
+pub fn send(_lock_file: &Path, _pid_file: &Path) -> Result<i32> {
+    Err(anyhow::anyhow!("--refresh is not supported on Windows"))
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_send_reaches_the_signal_handler() {
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        on_signal(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        let lock_file = std::env::temp_dir().join(format!("port-kill-refresh-{}.lock", std::process::id()));
+        let missing = std::env::temp_dir().join(format!("port-kill-refresh-{}.pid", std::process::id()));
+        assert!(send(&lock_file, &missing).unwrap_err().to_string().contains("No running port-kill"));
+
+        std::fs::write(&lock_file, format!("{}\n", std::process::id())).unwrap();
+        assert_eq!(send(&lock_file, &missing).unwrap(), std::process::id() as i32);
+        let started = Instant::now();
+        while refreshes.load(Ordering::Relaxed) == 0 {
+            assert!(started.elapsed() < Duration::from_secs(5), "SIGUSR1 never arrived");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let _ = std::fs::remove_file(&lock_file);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    cli::Args,
+    error::PortKillError,
+    port_scanner::PortScanner,
+    process_monitor::is_process_excluded,
+    types::ProcessInfo,
+    webhook::KillOutcome,
+};
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long after a kill a new process on the freed port counts as a respawn
+pub const RESPAWN_DETECTION_WINDOW: Duration = Duration::from_secs(1);
+/// Respawns --kill-respawns kills on one port before giving up on it
+pub const MAX_RESPAWN_KILLS: usize = 3;
+
+/// What [`watch`] saw after a kill
+#[derive(Debug, Default)]
+pub struct RespawnReport {
+    /// Processes that took a port right after its process was killed
+    pub respawns: Vec<ProcessInfo>,
+    /// Kills of those processes with --kill-respawns
+    pub outcomes: Vec<KillOutcome>,
+}
+
+/// Re-scan the ports freed by `killed` for `window`, warning about every new process that takes one.
+///
+/// With --kill-respawns each respawn is killed through `kill` (at most [`MAX_RESPAWN_KILLS`] per
+/// port) and the window starts over, since a supervisor may respawn it again.
+pub fn watch(
+    scanner: &dyn PortScanner,
+    killed: &[KillOutcome],
+    args: &Args,
+    window: Duration,
+    poll_interval: Duration,
+    kill: &mut dyn FnMut(&ProcessInfo) -> Result<(), PortKillError>,
+) -> Result<RespawnReport> {
+    let mut ports: Vec<u16> = killed.iter().filter(|outcome| outcome.success).filter_map(|outcome| outcome.port).collect();
+    ports.sort_unstable();
+    ports.dedup();
+
+    let mut report = RespawnReport::default();
+    if ports.is_empty() {
+        return Ok(report);
+    }
+
+    let mut known_pids: HashSet<i32> = killed.iter().map(|outcome| outcome.pid).collect();
+    let mut respawn_kills: HashMap<u16, usize> = HashMap::new();
+    let mut deadline = Instant::now() + window;
+    loop {
+        for process_info in scanner.scan(&ports)? {
+            // lsof may report other ports of a multi-socket process
+            if !ports.contains(&process_info.port) || !known_pids.insert(process_info.pid) {
+                continue;
+            }
+            let (pid, port, name) = (process_info.pid, process_info.port, process_info.name.as_str());
+            if is_process_excluded(args, pid, port, name, process_info.bind_address.as_deref(), process_info.owner.as_deref()) {
+                continue;
+            }
+            warn!("Port {} was taken again by {} (PID {}) right after the kill; a supervisor may be respawning it", port, name, pid);
+            report.respawns.push(process_info.clone());
+
+            if !args.kill_respawns {
+                continue;
+            }
+            let kills = respawn_kills.entry(port).or_default();
+            if *kills >= MAX_RESPAWN_KILLS {
+                warn!("Not killing {} (PID {}) on port {}: it already respawned {} times", name, pid, port, MAX_RESPAWN_KILLS);
+                continue;
+            }
+            *kills += 1;
+
+            let result = kill(&process_info);
+            match result {
+                Ok(()) => info!("Killed respawned {} (PID {}) on port {}", name, pid, port),
+                Err(ref e) => warn!("Failed to kill respawned {} (PID {}) on port {}: {}", name, pid, port, e),
+            }
+            report.outcomes.push(KillOutcome::new(pid, Some(port), Some(name), &result));
+            deadline = Instant::now() + window;
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(report);
+        }
+        std::thread::sleep(poll_interval.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::sync::Mutex;
+
+    /// Reports a new PID on port 3000 on every scan, like a supervisor restarting it at once
+    struct RespawningScanner {
+        next_pid: Mutex<i32>,
+    }
+
+    impl PortScanner for RespawningScanner {
+        fn scan(&self, _ports: &[u16]) -> Result<Vec<ProcessInfo>> {
+            let mut next_pid = self.next_pid.lock().unwrap();
+            *next_pid += 1;
+            Ok(vec![ProcessInfo::fixture(3000, *next_pid, "node"), ProcessInfo::fixture(5173, 99, "vite")])
+        }
+    }
+
+    fn killed_node() -> Vec<KillOutcome> {
+        vec![KillOutcome::new(10, Some(3000), Some("node"), &Ok(()))]
+    }
+
+    #[test]
+    fn test_respawns_are_only_reported_by_default() {
+        let args = Args::parse_from(["port-kill", "--kill-name", "node"]);
+        let scanner = RespawningScanner { next_pid: Mutex::new(10) };
+
+        let report = watch(&scanner, &killed_node(), &args, Duration::from_millis(30), Duration::from_millis(10), &mut |_| {
+            panic!("respawns are only killed with --kill-respawns")
+        })
+        .unwrap();
+
+        assert!(!report.respawns.is_empty());
+        assert!(report.respawns.iter().all(|process_info| process_info.port == 3000 && process_info.pid > 10));
+        assert!(report.outcomes.is_empty());
+    }
+
+    #[test]
+    fn test_kill_respawns_gives_up_after_the_limit() {
+        let args = Args::parse_from(["port-kill", "--kill-name", "node", "--kill-respawns"]);
+        let scanner = RespawningScanner { next_pid: Mutex::new(10) };
+
+        let mut killed = Vec::new();
+        let report = watch(&scanner, &killed_node(), &args, Duration::from_millis(30), Duration::from_millis(10), &mut |process_info| {
+            killed.push(process_info.pid);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(killed, vec![11, 12, 13]);
+        assert_eq!(report.outcomes.len(), MAX_RESPAWN_KILLS);
+        assert!(report.respawns.len() > MAX_RESPAWN_KILLS);
+    }
+
+    #[test]
+    fn test_failed_kills_are_not_watched() {
+        let args = Args::parse_from(["port-kill", "--kill-name", "node", "--kill-respawns"]);
+        let scanner = RespawningScanner { next_pid: Mutex::new(10) };
+        let failed = vec![KillOutcome::new(10, Some(3000), Some("node"), &Err(PortKillError::PermissionDenied { pid: 10 }))];
+
+        let report = watch(&scanner, &failed, &args, Duration::from_secs(5), Duration::from_millis(10), &mut |_| panic!("nothing to watch")).unwrap();
+        assert!(report.respawns.is_empty());
+    }
+}
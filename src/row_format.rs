@@ -0,0 +1,122 @@
+use crate::tooltip::{parse_template, TemplatePart};
+use crate::types::ProcessInfo;
+use anyhow::Result;
+use std::borrow::Cow;
+
+/// Printed for a field whose value is unknown, so whitespace-separated columns stay aligned
+pub const UNKNOWN_VALUE: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Port,
+    Pid,
+    Name,
+    Command,
+    User,
+}
+
+/// The placeholders a --format template may use
+const FIELDS: [(&str, Field); 5] = [
+    ("port", Field::Port),
+    ("pid", Field::Pid),
+    ("name", Field::Name),
+    ("cmd", Field::Command),
+    ("user", Field::User),
+];
+
+/// A parsed --format template, rendered once per process row, e.g. "{port} {pid} {name}".
+///
+/// `{port}`, `{pid}`, `{name}`, `{cmd}` and `{user}` are the process fields; an unknown
+/// user is printed as "-". `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowTemplate {
+    parts: Vec<TemplatePart<Field>>,
+}
+
+impl RowTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        Ok(Self { parts: parse_template(template, &FIELDS)? })
+    }
+
+    /// The row for one process, without colors
+    pub fn render(&self, process_info: &ProcessInfo) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Field(Field::Port) => process_info.port.to_string(),
+                TemplatePart::Field(Field::Pid) => process_info.pid.to_string(),
+                TemplatePart::Field(Field::Name) => process_info.name.clone(),
+                TemplatePart::Field(Field::Command) => process_info.command.clone(),
+                TemplatePart::Field(Field::User) => process_info.owner.clone().unwrap_or_else(|| UNKNOWN_VALUE.to_string()),
+            })
+            .collect()
+    }
+}
+
+/// First line of --csv output, naming the columns of [`csv_row`]
+pub const CSV_HEADER: &str = "port,pid,name,command,user,container";
+
+/// One --csv line for a process; an unknown user or a process outside any container is an empty field
+pub fn csv_row(process_info: &ProcessInfo) -> String {
+    let container = process_info.container_name.as_deref().or(process_info.container_id.as_deref()).unwrap_or_default();
+    [
+        process_info.port.to_string().as_str(),
+        process_info.pid.to_string().as_str(),
+        &process_info.name,
+        &process_info.command,
+        process_info.owner.as_deref().unwrap_or_default(),
+        container,
+    ]
+    .into_iter()
+    .map(csv_field)
+    .collect::<Vec<_>>()
+    .join(",")
+}
+
+/// Quote a field containing a comma, quote or line break, doubling its quotes (RFC 4180)
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(owner: Option<&str>) -> ProcessInfo {
+        ProcessInfo {
+            owner: owner.map(str::to_string),
+            ..ProcessInfo::fixture(3000, 4242, "node")
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let template = RowTemplate::parse("{port}\t{pid} {name} {cmd} {user}").unwrap();
+        assert_eq!(template.render(&process(Some("me"))), "3000\t4242 node node me");
+        assert_eq!(template.render(&process(None)), "3000\t4242 node node -");
+        assert_eq!(RowTemplate::parse("{{{port}}}").unwrap().render(&process(None)), "{3000}");
+    }
+
+    #[test]
+    fn test_csv_row() {
+        assert_eq!(csv_row(&process(Some("me"))), "3000,4242,node,node,me,");
+
+        let mut quoted = process(None);
+        quoted.command = "node server.js --hosts a,b".to_string();
+        quoted.name = "say \"hi\"".to_string();
+        quoted.container_name = Some("web".to_string());
+        assert_eq!(csv_row(&quoted), "3000,4242,\"say \"\"hi\"\"\",\"node server.js --hosts a,b\",,web");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_fields() {
+        let error = RowTemplate::parse("{port} {command}").unwrap_err().to_string();
+        assert!(error.contains("{command}") && error.contains("{port}, {pid}, {name}, {cmd}, {user}"), "{}", error);
+        assert!(RowTemplate::parse("{port").is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use crate::{
+    cli::Args,
+    console_app::{load_kill_policy, SHUTDOWN_TIMEOUT},
+    exit_code::ExitCode,
+    port_scanner::LsofScanner,
+    process_monitor::{filter_ignored_processes, get_processes_on_ports, kill_process, kill_single_process, KillPolicy, ProcessMonitor, SingleKill},
+    types::{ProcessDiff, ProcessInfo},
+    webhook::KillOutcome,
+};
+use anyhow::Result;
+use crossbeam_channel::bounded;
+use log::{error, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+
+/// JSON-RPC 2.0 error codes
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// A scan or kill that failed
+pub const SERVER_ERROR: i64 = -32000;
+
+/// A JSON-RPC error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct KillPortParams {
+    port: u16,
+}
+
+#[derive(Deserialize)]
+struct KillPidParams {
+    pid: i32,
+}
+
+/// Answers the --stdio-rpc methods with the same discovery and kill functions as the console
+pub struct RpcServer {
+    args: Args,
+    policy: KillPolicy,
+}
+
+impl RpcServer {
+    pub fn new(args: Args, policy: KillPolicy) -> Self {
+        Self { args, policy }
+    }
+
+    /// Handle one line of input, returning the response to write (none for notifications)
+    pub fn handle_line(&self, line: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let request = match serde_json::from_value::<Request>(request) {
+            Ok(request) if request.jsonrpc == "2.0" => request,
+            _ => return Some(error_response(id, RpcError::new(INVALID_REQUEST, "Invalid request: expected a JSON-RPC 2.0 request object"))),
+        };
+
+        let result = self.call(&request.method, request.params);
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "listProcesses" => self.list_processes(),
+            "killPort" => self.kill_port(parse_params::<KillPortParams>(params)?.port),
+            "killPid" => self.kill_pid(parse_params::<KillPidParams>(params)?.pid),
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+
+    /// `listProcesses`: the processes the console would list right now
+    fn list_processes(&self) -> Result<Value, RpcError> {
+        let (_, processes) = get_processes_on_ports(&self.args.get_ports_to_monitor(), &self.args);
+        Ok(json!({ "processes": shown_processes(&processes, &self.args) }))
+    }
+
+    /// `killPort`: kill every non-ignored process listening on `port`
+    fn kill_port(&self, port: u16) -> Result<Value, RpcError> {
+        if port == 0 {
+            return Err(RpcError::new(INVALID_PARAMS, "Port 0 is not valid"));
+        }
+        let (_, processes) = get_processes_on_ports(&[port], &self.args);
+        let runner = self.args.command_runner();
+
+        let mut outcomes: Vec<KillOutcome> = Vec::new();
+        for process_info in processes.values().flatten().filter(|process_info| process_info.port == port) {
+            // One process may hold the port over IPv4 and IPv6
+            if outcomes.iter().any(|outcome| outcome.pid == process_info.pid) {
+                continue;
+            }
+            let result = kill_process(process_info.pid, runner.as_ref(), &self.policy);
+            outcomes.push(KillOutcome::new(process_info.pid, Some(port), Some(&process_info.name), &result));
+        }
+        info!("RPC killPort {}: {} process(es) found", port, outcomes.len());
+        Ok(json!({ "processes": outcomes }))
+    }
+
+    /// `killPid`: kill one PID unless it is ignored
+    fn kill_pid(&self, pid: i32) -> Result<Value, RpcError> {
+        if pid <= 0 {
+            return Err(RpcError::new(INVALID_PARAMS, format!("PID {} is not valid", pid)));
+        }
+        let outcome = match kill_single_process(pid, &self.args, &self.policy) {
+            Ok(SingleKill::Killed) => "killed",
+            Ok(SingleKill::Ignored) => "ignored",
+            Ok(SingleKill::DryRun) => "dryRun",
+            Err(e) => return Err(RpcError::new(SERVER_ERROR, format!("Failed to kill PID {}: {}", pid, e))),
+        };
+        Ok(json!({ "pid": pid, "outcome": outcome }))
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", e)))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": error.code, "message": error.message } })
+}
+
+/// Non-ignored processes inside --show-range, in --sort order
+fn shown_processes(processes: &HashMap<u16, Vec<ProcessInfo>>, args: &Args) -> Vec<ProcessInfo> {
+    let mut shown: Vec<&ProcessInfo> = processes.values().flatten().filter(|process_info| args.is_port_shown(process_info.port)).collect();
+    args.sort.sort(&mut shown);
+    shown.into_iter().cloned().collect()
+}
+
+/// Write one newline-delimited JSON message
+fn write_message(out: &Mutex<io::Stdout>, message: &Value) -> io::Result<()> {
+    let mut out = out.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    serde_json::to_writer(&mut *out, message)?;
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Serve JSON-RPC 2.0 on stdin/stdout, one message per line, until stdin closes (--stdio-rpc).
+///
+/// `processesChanged` notifications are pushed whenever the monitor sees the listing change.
+pub fn run(args: &Args) -> Result<ExitCode> {
+    #[cfg(not(target_os = "windows"))]
+    LsofScanner::new(args.command_runner()).with_timeout(args.lsof_timeout).check_available()?;
+
+    let out = Arc::new(Mutex::new(io::stdout()));
+    let (update_sender, update_receiver) = bounded(100);
+    let mut monitor = ProcessMonitor::new(update_sender, args.get_ports_to_monitor(), args.docker, args.discover_all, args.command_runner())?;
+    monitor.set_watch_processes(args.watch_process.clone());
+    monitor.set_socket_paths(args.socket.clone());
+    monitor.set_include_established(args.include_established);
+    monitor.set_interface(args.interface);
+    monitor.set_network_namespaces(args.netns);
+    let policy = load_kill_policy(args);
+    monitor.set_kill_policy(policy.clone());
+    monitor.set_lsof_timeout(args.lsof_timeout);
+    let monitor_handle = monitor.handle();
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let monitor_task = runtime.spawn(async move {
+        if let Err(e) = monitor.start_monitoring().await {
+            error!("Process monitoring failed: {}", e);
+        }
+    });
+
+    // Ends once the monitor, and with it the update sender, is gone
+    let notify_out = out.clone();
+    let notify_args = args.clone();
+    std::thread::spawn(move || {
+        let mut last_processes = HashMap::new();
+        for update in update_receiver {
+            let processes = filter_ignored_processes(&update.processes, &notify_args);
+            if ProcessDiff::between(&last_processes, &processes).is_empty() {
+                continue;
+            }
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "processesChanged",
+                "params": { "processes": shown_processes(&processes, &notify_args) },
+            });
+            if let Err(e) = write_message(&notify_out, &notification) {
+                warn!("Failed to write processesChanged notification: {}", e);
+            }
+            last_processes = processes;
+        }
+    });
+
+    info!("Serving JSON-RPC on stdio for {}", args.get_port_description());
+    let server = RpcServer::new(args.clone(), policy);
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line) {
+            write_message(&out, &response)?;
+        }
+    }
+
+    info!("stdin closed, stopping the JSON-RPC server");
+    monitor_handle.stop();
+    if runtime.block_on(async { tokio::time::timeout(SHUTDOWN_TIMEOUT, monitor_task).await }).is_err() {
+        warn!("Process monitor did not stop within {:?}", SHUTDOWN_TIMEOUT);
+    }
+    Ok(ExitCode::Clean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    fn server() -> RpcServer {
+        RpcServer::new(Args::parse_from(["port-kill", "--ports", "3000"]), KillPolicy::default())
+    }
+
+    fn error_code(response: &Value) -> Option<i64> {
+        response["error"]["code"].as_i64()
+    }
+
+    #[test]
+    fn test_malformed_requests() {
+        let server = server();
+
+        assert_eq!(error_code(&server.handle_line("{not json").unwrap()), Some(PARSE_ERROR));
+        assert_eq!(error_code(&server.handle_line(r#"{"id":1,"method":"listProcesses"}"#).unwrap()), Some(INVALID_REQUEST));
+
+        let response = server.handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"restartEverything"}"#).unwrap();
+        assert_eq!((response["id"].as_i64(), error_code(&response)), (Some(7), Some(METHOD_NOT_FOUND)));
+
+        // Notifications are never answered, not even with an error
+        assert_eq!(server.handle_line(r#"{"jsonrpc":"2.0","method":"restartEverything"}"#), None);
+    }
+
+    #[test]
+    fn test_kill_params_are_validated() {
+        let server = server();
+
+        for line in [
+            r#"{"jsonrpc":"2.0","id":1,"method":"killPort"}"#,
+            r#"{"jsonrpc":"2.0","id":1,"method":"killPort","params":{"port":"http"}}"#,
+            r#"{"jsonrpc":"2.0","id":1,"method":"killPort","params":{"port":0}}"#,
+            r#"{"jsonrpc":"2.0","id":1,"method":"killPid","params":{"pid":-1}}"#,
+        ] {
+            assert_eq!(error_code(&server.handle_line(line).unwrap()), Some(INVALID_PARAMS), "{}", line);
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_kill_pid_reports_missing_process() {
+        let line = format!(r#"{{"jsonrpc":"2.0","id":"a","method":"killPid","params":{{"pid":{}}}}}"#, i32::MAX);
+        let response = server().handle_line(&line).unwrap();
+
+        assert_eq!(response["id"], "a");
+        assert_eq!(error_code(&response), Some(SERVER_ERROR));
+        assert!(response["error"]["message"].as_str().unwrap().contains("No process with PID"));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::command_runner::CommandRunner;
+use crate::process_monitor::enrich_processes;
+use crate::types::ProcessInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A service manager that restarts a process when it dies, so killing it frees the port only
+/// until the restart; stopping the service is what keeps the port free
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "manager", rename_all = "lowercase")]
+pub enum ServiceManager {
+    /// A systemd service, from the process's cgroup; `user` for a unit of the user's own systemd (`systemctl --user`)
+    Systemd { unit: String, user: bool },
+    /// A launchd job of the current user, from `launchctl list`
+    Launchd { label: String },
+}
+
+impl ServiceManager {
+    /// The command that stops the service, e.g. "systemctl stop nginx.service"
+    pub fn stop_command(&self) -> String {
+        match self {
+            ServiceManager::Systemd { unit, user: false } => format!("systemctl stop {}", unit),
+            ServiceManager::Systemd { unit, user: true } => format!("systemctl --user stop {}", unit),
+            ServiceManager::Launchd { label } => format!("launchctl remove {}", label),
+        }
+    }
+}
+
+impl fmt::Display for ServiceManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceManager::Systemd { unit, .. } => write!(f, "systemd: {}", unit),
+            ServiceManager::Launchd { label } => write!(f, "launchd: {}", label),
+        }
+    }
+}
+
+/// The service manager that restarts `pid`, if it can be found out
+pub fn detect(runner: &dyn CommandRunner, pid: i32) -> Option<ServiceManager> {
+    systemd_unit(runner, pid).or_else(|| launchd_jobs(runner).remove(&pid))
+}
+
+/// Fill in `managed_by` for every process of a scan, with up to `concurrency` cgroup lookups at once.
+///
+/// Over SSH every lookup is another connection, so only local processes are annotated on each scan;
+/// a kill still checks a remote process with [`detect`].
+pub fn annotate(runner: &dyn CommandRunner, processes: &mut HashMap<u16, Vec<ProcessInfo>>, concurrency: usize) {
+    if runner.is_remote() {
+        return;
+    }
+    let launchd_jobs = launchd_jobs(runner);
+    enrich_processes(processes, concurrency, &|process_info| {
+        process_info.managed_by = systemd_unit(runner, process_info.pid)
+            .or_else(|| launchd_jobs.get(&process_info.pid).cloned());
+    });
+}
+
+/// The systemd service `pid` runs in, from `/proc/<pid>/cgroup` (Linux)
+fn systemd_unit(runner: &dyn CommandRunner, pid: i32) -> Option<ServiceManager> {
+    let path = format!("/proc/{}/cgroup", pid);
+    let cgroup = if runner.is_remote() {
+        let output = runner.run("cat", &[&path]).ok()?;
+        output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())?
+    } else if cfg!(target_os = "linux") {
+        std::fs::read_to_string(&path).ok()?
+    } else {
+        return None;
+    };
+    parse_systemd_cgroup(&cgroup)
+}
+
+/// launchd jobs of the current user by PID, from `launchctl list` (macOS); empty elsewhere
+fn launchd_jobs(runner: &dyn CommandRunner) -> HashMap<i32, ServiceManager> {
+    if !cfg!(target_os = "macos") && !runner.is_remote() {
+        return HashMap::new();
+    }
+    match runner.run("launchctl", &["list"]) {
+        Ok(output) if output.status.success() => parse_launchctl_list(&String::from_utf8_lossy(&output.stdout)),
+        _ => HashMap::new(),
+    }
+}
+
+/// The systemd service in a `/proc/<pid>/cgroup` file: the unified hierarchy ("0::/system.slice/nginx.service")
+/// or the systemd one of cgroup v1 ("1:name=systemd:/system.slice/nginx.service")
+pub fn parse_systemd_cgroup(cgroup: &str) -> Option<ServiceManager> {
+    let path = cgroup.lines().find_map(|line| {
+        let (_, rest) = line.split_once(':')?;
+        let (controllers, path) = rest.split_once(':')?;
+        (controllers.is_empty() || controllers == "name=systemd").then_some(path)
+    })?;
+    let unit = path.rsplit('/').find(|component| component.ends_with(".service"))?;
+    // user@1000.service is the user's own systemd, which also holds their terminals and desktop apps
+    if unit.starts_with("user@") {
+        return None;
+    }
+    Some(ServiceManager::Systemd { unit: unit.to_string(), user: path.contains("/user@") })
+}
+
+/// `launchctl list` output ("PID\tStatus\tLabel", "-" for jobs that aren't running) by PID
+pub fn parse_launchctl_list(stdout: &str) -> HashMap<i32, ServiceManager> {
+    stdout
+        .lines()
+        .skip(1) // Skip header
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let pid = parts.first()?.parse::<i32>().ok()?;
+            let label = parts.get(2)?;
+            Some((pid, ServiceManager::Launchd { label: label.to_string() }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_systemd_cgroup() {
+        let nginx = parse_systemd_cgroup("0::/system.slice/nginx.service\n").unwrap();
+        assert_eq!(nginx, ServiceManager::Systemd { unit: "nginx.service".to_string(), user: false });
+        assert_eq!(nginx.stop_command(), "systemctl stop nginx.service");
+        assert_eq!(nginx.to_string(), "systemd: nginx.service");
+
+        let user_unit = parse_systemd_cgroup("0::/user.slice/user-1000.slice/user@1000.service/app.slice/api.service\n").unwrap();
+        assert_eq!(user_unit.stop_command(), "systemctl --user stop api.service");
+
+        let v1 = "12:cpu,cpuacct:/system.slice/postgresql.service\n1:name=systemd:/system.slice/postgresql.service\n";
+        assert_eq!(parse_systemd_cgroup(v1), Some(ServiceManager::Systemd { unit: "postgresql.service".to_string(), user: false }));
+
+        // Terminals, login sessions and containers aren't restarted by anyone
+        assert_eq!(parse_systemd_cgroup("0::/user.slice/user-1000.slice/user@1000.service/app.slice/app-org.gnome.Terminal.slice/vte-spawn-1.scope\n"), None);
+        assert_eq!(parse_systemd_cgroup("0::/user.slice/user-1000.slice/session-3.scope\n"), None);
+        assert_eq!(parse_systemd_cgroup("0::/system.slice/docker-4f1c.scope\n"), None);
+        assert_eq!(parse_systemd_cgroup("0::/\n"), None);
+    }
+
+    #[test]
+    fn test_parse_launchctl_list() {
+        let jobs = parse_launchctl_list("PID\tStatus\tLabel\n-\t0\tcom.apple.SafariHistoryServiceAgent\n641\t0\tcom.apple.controlcenter\n812\t-9\tcom.example.api\n");
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[&812].stop_command(), "launchctl remove com.example.api");
+        assert_eq!(jobs[&641].to_string(), "launchd: com.apple.controlcenter");
+    }
+}
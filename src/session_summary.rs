@@ -0,0 +1,198 @@
+use crate::cli::Args;
+use crate::config::Config;
+use crate::types::format_uptime;
+use crate::webhook::KillOutcome;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Default)]
+struct Tally {
+    /// Successful kills per port; `None` for kills whose port is unknown
+    killed_by_port: BTreeMap<Option<u16>, usize>,
+    failed: usize,
+    /// Every kill in order, for --report
+    kills: Vec<ReportedKill>,
+}
+
+/// One kill in a --report document
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportedKill {
+    /// Unix timestamp in seconds
+    pub killed_at: u64,
+    #[serde(flatten)]
+    pub outcome: KillOutcome,
+}
+
+impl ReportedKill {
+    /// `outcome`, stamped with the current time
+    pub fn now(outcome: KillOutcome) -> Self {
+        Self { killed_at: unix_now(), outcome }
+    }
+}
+
+/// The JSON document --report writes when a run ends: when it ran, what it watched and every kill
+#[derive(Debug, Serialize)]
+pub struct KillReport {
+    /// Unix timestamps in seconds
+    pub started_at: u64,
+    pub ended_at: u64,
+    /// What was monitored, e.g. "specific ports: 3000, 8080"
+    pub monitoring: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_kill: Option<Vec<u16>>,
+    pub dry_run: bool,
+    /// The effective configuration, as --export-config prints it
+    pub config: Config,
+    pub killed: usize,
+    pub failed: usize,
+    pub kills: Vec<ReportedKill>,
+}
+
+impl KillReport {
+    /// The report of a run with `args` that started at `started_at` and ends now
+    pub fn new(args: &Args, started_at: u64, kills: Vec<ReportedKill>) -> Self {
+        // A config that doesn't load shows up as the defaults the run fell back to
+        let config = crate::console_app::load_optional_config(Path::new(&args.config)).unwrap_or_default();
+        Self {
+            started_at,
+            ended_at: unix_now(),
+            monitoring: args.get_port_description(),
+            auto_kill: args.auto_kill.clone(),
+            dry_run: args.dry_run,
+            config: config.merged_with_args(args),
+            killed: kills.iter().filter(|kill| kill.outcome.success).count(),
+            failed: kills.iter().filter(|kill| !kill.outcome.success).count(),
+            kills,
+        }
+    }
+
+    /// Write the report as pretty-printed JSON, creating missing parent directories
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json + "\n").with_context(|| format!("Failed to write report {}", path.display()))
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Kills made during one console session, printed by --summary-on-exit and written by --report; cheap to clone
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    tally: Arc<Mutex<Tally>>,
+}
+
+impl SessionStats {
+    /// Count the outcome of each kill
+    pub fn record(&self, outcomes: &[KillOutcome]) {
+        let Ok(mut tally) = self.tally.lock() else {
+            return;
+        };
+        for outcome in outcomes {
+            if outcome.success {
+                *tally.killed_by_port.entry(outcome.port).or_default() += 1;
+            } else {
+                tally.failed += 1;
+            }
+            tally.kills.push(ReportedKill::now(outcome.clone()));
+        }
+    }
+
+    /// Every kill so far, oldest first
+    pub fn kills(&self) -> Vec<ReportedKill> {
+        self.tally.lock().map(|tally| tally.kills.clone()).unwrap_or_default()
+    }
+
+    /// Processes killed so far
+    pub fn killed(&self) -> usize {
+        self.tally.lock().map_or(0, |tally| tally.killed_by_port.values().sum())
+    }
+
+    /// Kills that failed so far
+    pub fn failed(&self) -> usize {
+        self.tally.lock().map_or(0, |tally| tally.failed)
+    }
+
+    /// The report printed on exit after running for `runtime`, one line per killed port
+    pub fn report(&self, runtime: Duration) -> String {
+        let Ok(tally) = self.tally.lock() else {
+            return String::new();
+        };
+        let killed: usize = tally.killed_by_port.values().sum();
+        let mut lines = vec![
+            "📊 Session summary".to_string(),
+            format!("   Runtime: {}", format_uptime(runtime)),
+            format!("   Killed: {} process(es)", killed),
+        ];
+        lines.extend(tally.killed_by_port.iter().filter_map(|(port, count)| Some(format!("     Port {}: {}", (*port)?, count))));
+        if let Some(count) = tally.killed_by_port.get(&None) {
+            lines.push(format!("     Unknown port: {}", count));
+        }
+        lines.push(format!("   Failed: {}", tally.failed));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PortKillError;
+
+    #[test]
+    fn test_report() {
+        let stats = SessionStats::default();
+        assert_eq!(stats.report(Duration::from_secs(42)), "📊 Session summary\n   Runtime: 42s\n   Killed: 0 process(es)\n   Failed: 0");
+
+        // Clones share the tally, as the auto-killer's copy does
+        stats.clone().record(&[
+            KillOutcome::new(10, Some(5173), Some("vite"), &Ok(())),
+            KillOutcome::new(11, Some(3000), Some("node"), &Ok(())),
+            KillOutcome::new(12, Some(3000), Some("node"), &Ok(())),
+            KillOutcome::new(13, Some(3000), Some("node"), &Err(PortKillError::PermissionDenied { pid: 13 })),
+        ]);
+        stats.record(&[KillOutcome::new(14, None, None, &Ok(()))]);
+
+        assert_eq!((stats.killed(), stats.failed()), (4, 1));
+        assert_eq!(stats.kills().iter().map(|kill| kill.outcome.pid).collect::<Vec<_>>(), vec![10, 11, 12, 13, 14]);
+        assert_eq!(
+            stats.report(Duration::from_secs(3_900)),
+            "📊 Session summary\n   Runtime: 1h 5m\n   Killed: 4 process(es)\n     Port 3000: 2\n     Port 5173: 1\n     Unknown port: 1\n   Failed: 1"
+        );
+    }
+
+    #[test]
+    fn test_kill_report() {
+        use clap::Parser;
+
+        let dir = std::env::temp_dir().join(format!("port-kill-report-test-{}", std::process::id()));
+        let path = dir.join("artifacts").join("report.json");
+        let args = Args::parse_from(["port-kill", "--console", "--auto-kill", "3000", "--ports", "3000,8080", "--config", "/nonexistent/port-kill.toml"]);
+        let kills = vec![
+            ReportedKill { killed_at: 1_700_000_010, outcome: KillOutcome::new(11, Some(3000), Some("node"), &Ok(())) },
+            ReportedKill { killed_at: 1_700_000_020, outcome: KillOutcome::new(12, Some(3000), Some("node"), &Err(PortKillError::PermissionDenied { pid: 12 })) },
+        ];
+        let report = KillReport::new(&args, 1_700_000_000, kills);
+        report.write(&path).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["started_at"], 1_700_000_000);
+        assert!(json["ended_at"].as_u64().unwrap() >= 1_700_000_000);
+        assert_eq!(json["monitoring"], "specific ports: 3000, 8080");
+        assert_eq!(json["auto_kill"], serde_json::json!([3000]));
+        assert_eq!(json["config"]["ports"]["specific"], serde_json::json!([3000, 8080]));
+        assert_eq!((json["killed"].as_u64(), json["failed"].as_u64()), (Some(1), Some(1)));
+        assert_eq!(json["kills"][0], serde_json::json!({"killed_at": 1_700_000_010, "port": 3000, "name": "node", "pid": 11, "success": true, "error": null}));
+        assert_eq!(json["kills"][1]["success"], false);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,51 @@
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Wait for Ctrl+C (SIGINT) or, on Unix, SIGTERM and return the name of the signal received
+pub async fn wait_for_signal() -> &'static str {
+    #[cfg(not(target_os = "windows"))]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => "SIGINT",
+                    _ = sigterm.recv() => "SIGTERM",
+                }
+            }
+            Err(e) => {
+                warn!("Failed to install SIGTERM handler, only Ctrl+C will shut down cleanly: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
+}
+
+/// Set `requested` when a shutdown signal arrives (for callers without a tokio runtime, like the tray app)
+pub fn request_on_signal(requested: Arc<AtomicBool>) -> std::io::Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+    std::thread::Builder::new()
+        .name("shutdown-signals".to_string())
+        .spawn(move || {
+            let signal = runtime.block_on(wait_for_signal());
+            info!("Received {}, shutting down...", signal);
+            requested.store(true, Ordering::Relaxed);
+        })?;
+
+    Ok(())
+}
+
+/// Flush buffered log output (including --log-file) before the process exits
+pub fn flush_logs() {
+    log::logger().flush();
+}
@@ -0,0 +1,152 @@
+use crate::types::{process_count, ProcessInfo};
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap};
+
+/// Placeholders a tooltip template may use
+pub const PLACEHOLDERS: [&str; 3] = ["count", "ports", "names"];
+/// Separator between the items of `{ports}` and `{names}` unless changed with [`TooltipTemplate::with_separator`]
+pub const DEFAULT_SEPARATOR: &str = ", ";
+
+/// A piece of a parsed template: literal text, or the field a `{placeholder}` stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart<F> {
+    Text(String),
+    Field(F),
+}
+
+/// Split `template` into text and the `fields` its `{name}` placeholders refer to.
+///
+/// `{{` and `}}` are literal braces; an unknown placeholder or a lone brace is an error.
+pub fn parse_template<F: Copy>(template: &str, fields: &[(&str, F)]) -> Result<Vec<TemplatePart<F>>> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(anyhow::anyhow!("unclosed {{ in template {:?}", template)),
+                    }
+                }
+                let Some(&(_, field)) = fields.iter().find(|(field_name, _)| *field_name == name) else {
+                    let names: Vec<&str> = fields.iter().map(|(field_name, _)| *field_name).collect();
+                    return Err(anyhow::anyhow!(
+                        "unknown placeholder {{{}}} in template, expected one of {{{}}}",
+                        name, names.join("}, {")
+                    ));
+                };
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(TemplatePart::Field(field));
+            }
+            '}' => return Err(anyhow::anyhow!("unmatched }} in template {:?}; write }}}} for a literal brace", template)),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+
+    Ok(parts)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Count,
+    Ports,
+    Names,
+}
+
+/// [`PLACEHOLDERS`] and what each of them stands for
+const FIELDS: [(&str, Placeholder); 3] = [("count", Placeholder::Count), ("ports", Placeholder::Ports), ("names", Placeholder::Names)];
+
+/// A parsed `app.tooltip_template` or `--status-format`, e.g. "{count} dev servers: {ports}".
+///
+/// `{count}` is the number of processes, `{ports}` their ports and `{names}` their
+/// process names, both comma-separated without duplicates. `{{` and `}}` are literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TooltipTemplate {
+    segments: Vec<TemplatePart<Placeholder>>,
+    separator: String,
+}
+
+impl TooltipTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        Ok(Self { segments: parse_template(template, &FIELDS)?, separator: DEFAULT_SEPARATOR.to_string() })
+    }
+
+    /// Separate the items of `{ports}` and `{names}` with `separator`, e.g. "," for a compact line
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Fill in the placeholders for the processes currently shown
+    pub fn render(&self, processes: &HashMap<u16, Vec<ProcessInfo>>) -> String {
+        let ports: BTreeSet<u16> = processes.values().flatten().map(|process_info| process_info.port).filter(|port| *port != 0).collect();
+
+        // Names in port order, each once
+        let mut sorted: Vec<&ProcessInfo> = processes.values().flatten().collect();
+        sorted.sort_by_key(|process_info| (process_info.port, process_info.pid));
+        let mut names: Vec<&str> = Vec::new();
+        for process_info in sorted {
+            if !names.contains(&process_info.name.as_str()) {
+                names.push(&process_info.name);
+            }
+        }
+
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Field(Placeholder::Count) => process_count(processes).to_string(),
+                TemplatePart::Field(Placeholder::Ports) => ports.iter().map(u16::to_string).collect::<Vec<_>>().join(&self.separator),
+                TemplatePart::Field(Placeholder::Names) => names.join(&self.separator),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let processes: HashMap<u16, Vec<ProcessInfo>> = HashMap::from([
+            (8080, vec![ProcessInfo::fixture(8080, 12, "node")]),
+            (3000, vec![ProcessInfo::fixture(3000, 10, "node")]),
+            (5173, vec![ProcessInfo::fixture(5173, 11, "vite")]),
+        ]);
+
+        let template = TooltipTemplate::parse("{count} dev servers: {ports}").unwrap();
+        assert_eq!(template.render(&processes), "3 dev servers: 3000, 5173, 8080");
+        assert_eq!(TooltipTemplate::parse("{names} {{busy}}").unwrap().render(&processes), "node, vite {busy}");
+        assert_eq!(template.render(&HashMap::new()), "0 dev servers: ");
+
+        let compact = TooltipTemplate::parse("ports: {ports} ({count})").unwrap().with_separator(",");
+        assert_eq!(compact.render(&processes), "ports: 3000,5173,8080 (3)");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_templates() {
+        for template in ["{count", "{pids} running", "ports}"] {
+            assert!(TooltipTemplate::parse(template).is_err(), "{}", template);
+        }
+        assert!(TooltipTemplate::parse("{pids}").unwrap_err().to_string().contains("{count}, {ports}, {names}"));
+    }
+}
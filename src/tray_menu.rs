@@ -1,8 +1,11 @@
-use crate::types::{ProcessInfo, StatusBarInfo};
+use crate::badge;
+use crate::config::{PortLabels, StatusIconConfig};
+use crate::types::{menu_label, menu_overflow_label, menu_ports, process_names, ProcessInfo, StatusBarInfo, StatusTier};
+use crate::process_monitor::snapshot_kill_targets;
 use anyhow::Result;
 use crossbeam_channel::Sender;
 use image;
-use log::debug;
+use log::{debug, warn};
 use std::collections::HashMap;
 use std::path::Path;
 #[cfg(target_os = "macos")]
@@ -16,13 +19,18 @@ use tray_icon::{
 pub struct TrayMenu {
     pub icon: Icon,
     menu_sender: Sender<MenuEvent>,
-    current_processes: HashMap<u16, ProcessInfo>,
+    current_processes: HashMap<u16, Vec<ProcessInfo>>,
     show_pid: bool,
+    labels: PortLabels,
+    /// `max_processes_in_menu`: ports beyond this are summarized in one item
+    max_ports: usize,
+    /// `use_emoji`: prefix the items with emoji, or plain-text labels
+    use_emoji: bool,
 }
 
 #[cfg(target_os = "macos")]
 impl TrayMenu {
-    pub fn new(menu_sender: Sender<MenuEvent>) -> Result<Self> {
+    pub fn new(menu_sender: Sender<MenuEvent>, labels: PortLabels, max_ports: usize, use_emoji: bool) -> Result<Self> {
         // Create a simple icon (we'll use a text-based approach for now)
         let icon = Self::create_icon("0")?;
 
@@ -37,10 +45,13 @@ impl TrayMenu {
             menu_sender,
             current_processes: HashMap::new(),
             show_pid: false,
+            labels,
+            max_ports,
+            use_emoji,
         })
     }
 
-    pub fn update_menu(&mut self, processes: &HashMap<u16, ProcessInfo>, show_pid: bool) -> Result<()> {
+    pub fn update_menu(&mut self, processes: &HashMap<u16, Vec<ProcessInfo>>, show_pid: bool) -> Result<()> {
         debug!("Updating menu with {} processes", processes.len());
 
         // Update internal state
@@ -51,7 +62,7 @@ impl TrayMenu {
     }
 
     pub fn get_current_menu(&self) -> Result<Menu> {
-        Self::create_menu(&self.current_processes, self.show_pid)
+        Self::create_menu(&self.current_processes, self.show_pid, &self.labels, self.max_ports, self.use_emoji)
     }
 
     pub fn update_status(&mut self, status_info: &StatusBarInfo) -> Result<()> {
@@ -63,44 +74,49 @@ impl TrayMenu {
         Ok(())
     }
 
-    pub fn create_menu(processes: &HashMap<u16, ProcessInfo>, show_pid: bool) -> Result<Menu> {
+    pub fn create_menu(processes: &HashMap<u16, Vec<ProcessInfo>>, show_pid: bool, labels: &PortLabels, max_ports: usize, use_emoji: bool) -> Result<Menu> {
         let menu = Menu::new();
 
         // Add "Kill All Processes" item with explicit string ID
         let kill_all_item = MenuItem::with_id(
             MenuId("kill_all".to_string()),
-            "🔪 Kill All Processes",
+            &menu_label("🔪", "Kill All Processes", use_emoji),
             true,
             None,
         );
         menu.append(&kill_all_item)?;
 
+        // Kill exactly the processes listed below, including those past the overflow item
+        let snapshot_count = snapshot_kill_targets(processes).len();
+        menu.append(&MenuItem::with_id(
+            MenuId("kill_snapshot".to_string()),
+            &menu_label("🎯", &format!("Kill Monitored ({} in this snapshot)", snapshot_count), use_emoji),
+            snapshot_count > 0,
+            None,
+        ))?;
+
         // Add separator
         let separator = PredefinedMenuItem::separator();
         menu.append(&separator)?;
 
-        // Add individual process items with better organization
-        let mut process_entries: Vec<_> = processes.iter().collect();
-        // Sort by port for consistent ordering
-        process_entries.sort_by_key(|(port, _)| **port);
-
-                 for (_index, (port, process_info)) in process_entries.iter().enumerate() {
-            let menu_text = if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
-                format!(
-                    "Kill: Port {}: {} [Docker: {}]",
-                    port, process_info.name, container_name
-                )
-            } else if show_pid {
-                format!(
-                    "Kill: Port {}: {} (PID {})",
-                    port, process_info.name, process_info.pid
-                )
-            } else {
-                format!(
-                    "Kill: Port {}: {}",
-                    port, process_info.name
-                )
-            };
+        // Add individual process items, sorted by port and capped at `max_ports`
+        let (shown_ports, hidden) = menu_ports(processes, max_ports);
+
+        for (port, on_port) in shown_ports.iter().filter_map(|port| processes.get(port).map(|on_port| (port, on_port))) {
+            // One item per port; killing it kills every process sharing the port
+            let names: Vec<String> = on_port
+                .iter()
+                .map(|process_info| {
+                    if let (Some(_container_id), Some(container_name)) = (&process_info.container_id, &process_info.container_name) {
+                        format!("{} [Docker: {}]", process_info.name, container_name)
+                    } else if show_pid {
+                        format!("{} (PID {})", process_info.name, process_info.pid)
+                    } else {
+                        process_info.name.clone()
+                    }
+                })
+                .collect();
+            let menu_text = format!("Kill: {}: {}", labels.display(*port), names.join(", "));
 
             // Create process menu item with string ID for reliable mapping
             let process_item = MenuItem::with_id(
@@ -112,6 +128,16 @@ impl TrayMenu {
             menu.append(&process_item)?;
         }
 
+        // The rest are only listed in the console
+        if hidden > 0 {
+            menu.append(&MenuItem::with_id(
+                MenuId("more_processes".to_string()),
+                &menu_overflow_label(hidden),
+                false,
+                None,
+            ))?;
+        }
+
         // Add another separator if there are processes
         if !processes.is_empty() {
             let separator = PredefinedMenuItem::separator();
@@ -121,7 +147,7 @@ impl TrayMenu {
         // Add "Quit" item with explicit string ID
         let quit_item = MenuItem::with_id(
             MenuId("quit".to_string()),
-            "❌ Quit",
+            &menu_label("❌", "Quit", use_emoji),
             true,
             None,
         );
@@ -140,7 +166,7 @@ impl TrayMenu {
     }
 
     // Helper function to get menu item mapping for better debugging
-    pub fn get_menu_item_mapping(processes: &HashMap<u16, ProcessInfo>) -> HashMap<String, String> {
+    pub fn get_menu_item_mapping(processes: &HashMap<u16, Vec<ProcessInfo>>) -> HashMap<String, String> {
         let mut mapping = HashMap::new();
 
         // Kill All is always first (ID 0 or 10)
@@ -155,8 +181,8 @@ impl TrayMenu {
         let mut process_entries: Vec<_> = processes.iter().collect();
         process_entries.sort_by_key(|(port, _)| **port);
 
-                          for (index, (port, process_info)) in process_entries.iter().enumerate() {
-             let menu_text = format!("Kill: Port {}: {}", port, process_info.name);
+                          for (index, (port, on_port)) in process_entries.iter().enumerate() {
+             let menu_text = format!("Kill: Port {}: {}", port, process_names(on_port));
              let menu_id = if index == 0 { "2" } else if index == 1 { "3" } else if index == 2 { "4" } else { "5" };
             mapping.insert(menu_id.to_string(), menu_text);
         }
@@ -165,40 +191,40 @@ impl TrayMenu {
     }
 
     pub fn create_icon(text: &str) -> Result<Icon> {
+        // Parse the number to determine the status tier with the default colors
+        let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
+        let count = number.parse::<usize>().unwrap_or(0);
+
+        let status_icon = StatusIconConfig::default();
+        Self::create_status_icon(&status_icon.status_for(count), &status_icon)
+    }
+
+    /// Create the icon for a status using the configured tier colors
+    pub fn create_status_icon(status_info: &StatusBarInfo, status_icon: &StatusIconConfig) -> Result<Icon> {
         // Always use the poison bottle icon (custom PNG files are handled within create_poison_bottle_icon)
-        Self::create_poison_bottle_icon(text)
+        Self::create_poison_bottle_icon(status_info, status_icon)
     }
 
-    fn load_custom_png_icon(text: &str) -> Result<Icon> {
-        // Parse the number to determine which PNG to use
-        let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
-        let num = number.parse::<u32>().unwrap_or(0);
-
-        // Try multiple paths for PNG files (app bundle and development)
-        let png_paths = if num == 0 {
-            vec![
-                "assets/green-bottle-36.png",                                    // Development path
-                "../Resources/assets/green-bottle-36.png",                      // App bundle path
-                "/Applications/PortKill.app/Contents/Resources/assets/green-bottle-36.png", // Absolute app bundle path
-                "assets/green-bottle-22.png",                                    // Fallback to 22px
-                "../Resources/assets/green-bottle-22.png",
-                "/Applications/PortKill.app/Contents/Resources/assets/green-bottle-22.png"
-            ]
-        } else {
-            vec![
-                "assets/orange-bottle-36.png",                                   // Development path
-                "../Resources/assets/orange-bottle-36.png",                     // App bundle path
-                "/Applications/PortKill.app/Contents/Resources/assets/orange-bottle-36.png", // Absolute app bundle path
-                "assets/orange-bottle-22.png",                                   // Fallback to 22px
-                "../Resources/assets/orange-bottle-22.png",
-                "/Applications/PortKill.app/Contents/Resources/assets/orange-bottle-22.png"
-            ]
-        };
+    /// Grey icon with pause bars in place of the count, shown while monitoring is paused
+    pub fn create_paused_icon() -> Result<Icon> {
+        let size = 22;
+        let mut icon_data = Self::generate_poison_bottle_icon(StatusTier::Idle, badge::PAUSED_COLOR, "", false);
+        badge::draw_pause_bars(&mut icon_data, size, size, badge::PAUSED_COLOR);
+        Icon::from_rgba(icon_data, size as u32, size as u32).or_else(|e| {
+            warn!("Paused icon couldn't be created ({}), falling back to a plain grey icon", e);
+            Ok(Self::fallback_icon(badge::PAUSED_COLOR))
+        })
+    }
+
+    /// Load the bundled PNG for a tier as RGBA pixels with its width and height
+    fn load_custom_png_icon(tier: StatusTier, status_icon: &StatusIconConfig) -> Result<(Vec<u8>, u32, u32)> {
+        // Custom icon directory first, then the app bundle and development paths
+        let png_paths = status_icon.png_paths(tier);
 
         // Try each path until we find one that works
         for png_path in &png_paths {
-            if Path::new(png_path).exists() {
-                debug!("Loading PNG file: {}", png_path);
+            if png_path.exists() {
+                debug!("Loading PNG file: {}", png_path.display());
 
                 // Load and decode the PNG file
                 match image::open(png_path) {
@@ -208,21 +234,10 @@ impl TrayMenu {
                         let height = img.height();
 
                         debug!("PNG decoded: {}x{} pixels, {} bytes", width, height, rgba.len());
-
-                        // Create icon from RGBA data
-                        match Icon::from_rgba(rgba.into_raw(), width, height) {
-                            Ok(icon) => {
-                                debug!("Successfully created icon from PNG data");
-                                return Ok(icon);
-                            },
-                            Err(e) => {
-                                debug!("Failed to create icon from PNG data: {}", e);
-                                // Continue to next path or fallback
-                            }
-                        }
+                        return Ok((rgba.into_raw(), width, height));
                     },
                     Err(e) => {
-                        debug!("Failed to load PNG {}: {}", png_path, e);
+                        debug!("Failed to load PNG {}: {}", png_path.display(), e);
                         // Continue to next path
                     }
                 }
@@ -232,14 +247,35 @@ impl TrayMenu {
         Err(anyhow::anyhow!("PNG files not found or PNG decoding not implemented"))
     }
 
-    fn create_poison_bottle_icon(text: &str) -> Result<Icon> {
-        // Try to load custom PNG files first
-        if let Ok(icon) = Self::load_custom_png_icon(text) {
-            return Ok(icon);
+    fn create_poison_bottle_icon(status_info: &StatusBarInfo, status_icon: &StatusIconConfig) -> Result<Icon> {
+        let tier = status_info.tier;
+        let color = status_icon.color_for(tier);
+
+        // The bundled PNGs only cover the default idle/busy colors; anything else is generated
+        let has_png_asset = tier != StatusTier::Crowded && color == StatusIconConfig::default().color_for(tier);
+        let badge_text = badge::badge_text(status_info.count);
+        let has_docker = status_info.docker_count > 0;
+        if has_png_asset {
+            if let Ok((mut rgba, width, height)) = Self::load_custom_png_icon(tier, status_icon) {
+                badge::draw_badge(&mut rgba, width as usize, height as usize, &badge_text, color);
+                if has_docker {
+                    badge::draw_docker_marker(&mut rgba, width as usize, height as usize);
+                }
+                match Icon::from_rgba(rgba, width, height) {
+                    Ok(icon) => {
+                        debug!("Successfully created icon from PNG data");
+                        return Ok(icon);
+                    },
+                    Err(e) => {
+                        debug!("Failed to create icon from PNG data: {}", e);
+                        // Fall back to the generated icon
+                    }
+                }
+            }
         }
 
         // Generate poison bottle icon with status colors
-        let icon_data = Self::generate_poison_bottle_icon(text);
+        let icon_data = Self::generate_poison_bottle_icon(tier, color, &badge_text, has_docker);
 
         // Try the actual PNG dimensions first, then fallback to other sizes
         match Icon::from_rgba(icon_data.clone(), 22, 22) {
@@ -249,18 +285,27 @@ impl TrayMenu {
                 match Icon::from_rgba(icon_data.clone(), 16, 16) {
                     Ok(icon) => Ok(icon),
                     Err(_) => {
-                        // Final fallback to 32x32
-                        Icon::from_rgba(icon_data, 32, 32)
-                            .map_err(|e| anyhow::anyhow!("Failed to create poison bottle icon: {}", e))
+                        // Then 32x32, and finally a plain square so the tray always appears
+                        Icon::from_rgba(icon_data, 32, 32).or_else(|e| {
+                            warn!("Icon assets couldn't be loaded ({}), falling back to a plain status color icon", e);
+                            Ok(Self::fallback_icon(color))
+                        })
                     }
                 }
             }
         }
     }
 
-    fn generate_poison_bottle_icon(text: &str) -> Vec<u8> {
+    /// Solid status color square; a buffer of exactly size * size * 4 bytes is always a valid icon
+    fn fallback_icon(color: [u8; 3]) -> Icon {
+        let size = badge::FALLBACK_ICON_SIZE as u32;
+        Icon::from_rgba(badge::solid_icon(badge::FALLBACK_ICON_SIZE, color), size, size)
+            .expect("a square RGBA buffer of the right length is a valid icon")
+    }
+
+    fn generate_poison_bottle_icon(tier: StatusTier, color: [u8; 3], badge_text: &str, has_docker: bool) -> Vec<u8> {
         // Try to load the actual SVG files first
-        if let Ok(icon_data) = Self::load_svg_icon(text) {
+        if let Ok(icon_data) = Self::load_svg_icon(tier) {
             return icon_data;
         }
 
@@ -270,18 +315,11 @@ impl TrayMenu {
 
         debug!("Generating {}x{} RGBA bitmap = {} bytes", size, size, size * size * 4);
 
+        // Status color comes from the configured tier colors
+        let [status_r, status_g, status_b] = color;
+
         for y in 0..size {
             for x in 0..size {
-                // Parse the number from text to determine status
-                let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
-                let num = number.parse::<u32>().unwrap_or(0);
-
-                // Use the exact colors from your SVG files but with a simple, clean design
-                let (status_r, status_g, status_b) = if num == 0 {
-                    (95, 249, 57) // Green from your green bottle.svg (#5FF939)
-                } else {
-                    (255, 165, 0) // Orange from your orange bottle.svg (#FFA500)
-                };
 
                 // Create a simple, clean circle icon instead of trying to recreate the complex bottle
                 let center_x = size as f32 / 2.0;
@@ -304,15 +342,18 @@ impl TrayMenu {
             }
         }
 
+        // Render the process count on top of the circle
+        badge::draw_badge(&mut icon_data, size, size, badge_text, color);
+        // Mark the icon when some of the processes run in Docker
+        if has_docker {
+            badge::draw_docker_marker(&mut icon_data, size, size);
+        }
+
         icon_data
     }
 
-    fn load_svg_icon(text: &str) -> Result<Vec<u8>> {
-        // Parse the number to determine which SVG to use
-        let number = text.chars().filter(|c| c.is_numeric()).collect::<String>();
-        let num = number.parse::<u32>().unwrap_or(0);
-
-        let svg_path = if num == 0 {
+    fn load_svg_icon(tier: StatusTier) -> Result<Vec<u8>> {
+        let svg_path = if tier == StatusTier::Idle {
             "assets/green bottle.svg"
         } else {
             "assets/orange bottle.svg"
@@ -1,7 +1,38 @@
+use crate::service_manager::ServiceManager;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// IP version of a listening socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    Ipv4,
+    Ipv6,
+}
+
+impl AddressFamily {
+    /// Parse lsof's TYPE column ("IPv4", "IPv6")
+    pub fn from_lsof_type(socket_type: &str) -> Option<Self> {
+        match socket_type {
+            "IPv4" => Some(AddressFamily::Ipv4),
+            "IPv6" => Some(AddressFamily::Ipv6),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AddressFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressFamily::Ipv4 => write!(f, "IPv4"),
+            AddressFamily::Ipv6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct ProcessInfo {
     pub pid: i32,
     pub port: u16,
@@ -9,36 +40,306 @@ pub struct ProcessInfo {
     pub name: String,
     pub container_id: Option<String>,
     pub container_name: Option<String>,
+    /// Address the socket listens on as reported by lsof (e.g. "127.0.0.1", "*", "[::1]")
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    /// When the process started (Unix timestamp in seconds), only collected with --show-uptime
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    /// CPU usage as reported by `ps -o %cpu`, only collected with --show-resources
+    #[serde(default)]
+    pub cpu_percent: Option<f32>,
+    /// Resident memory in bytes, only collected with --show-resources
+    #[serde(default)]
+    pub memory_bytes: Option<u64>,
+    /// IP versions the process listens on for this port (both when bound on IPv4 and IPv6)
+    #[serde(default)]
+    pub address_families: Vec<AddressFamily>,
+    /// User owning the process, from lsof's USER column
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Unix socket the process listens on (--socket); such entries have no TCP port, `port` is 0
+    #[serde(default)]
+    pub socket_path: Option<String>,
+    /// TCP state of a socket that holds the port without listening on it (--include-established),
+    /// e.g. "ESTABLISHED" or "CLOSE_WAIT"; `None` for listeners
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Service manager (systemd unit, launchd job) that restarts the process when it dies, if known;
+    /// killing it frees the port only until the restart
+    #[serde(default)]
+    pub managed_by: Option<ServiceManager>,
+    /// Network namespace the socket lives in when it isn't port-kill's own (--netns), e.g. "blue"
+    /// for `ip netns add blue` or "net:[4026532500]" for a container's
+    #[serde(default)]
+    pub network_namespace: Option<String>,
+}
+
+impl ProcessInfo {
+    /// A bare listener whose command and name are both `name`, shared by the test modules
+    #[cfg(test)]
+    pub(crate) fn fixture(port: u16, pid: i32, name: &str) -> Self {
+        ProcessInfo {
+            pid,
+            port,
+            command: name.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// "IPv4", "IPv6" or "IPv4+IPv6", if the address family is known
+    pub fn family_label(&self) -> Option<String> {
+        if self.address_families.is_empty() {
+            return None;
+        }
+        Some(self.address_families.iter().map(|family| family.to_string()).collect::<Vec<_>>().join("+"))
+    }
+
+    /// Fold another socket of the same process on the same port into this entry;
+    /// the entry counts as listening if either socket listens
+    pub fn merge_families(&mut self, other: &ProcessInfo) {
+        if other.state.is_none() {
+            self.state = None;
+        }
+        for family in &other.address_families {
+            if !self.address_families.contains(family) {
+                self.address_families.push(*family);
+            }
+        }
+        self.address_families.sort();
+    }
+
+    /// "port 3000", or "socket /tmp/app.sock" for a --socket holder
+    pub fn endpoint(&self) -> String {
+        match self.socket_path {
+            Some(ref path) => format!("socket {}", path),
+            None => format!("port {}", self.port),
+        }
+    }
+
+    /// How long the process has been running, if its start time is known
+    pub fn uptime(&self) -> Option<Duration> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+        self.started_at.map(|started_at| Duration::from_secs(now.saturating_sub(started_at)))
+    }
+}
+
+/// Compact human-readable byte size: "512 B", "12.3 MB", "1.5 GB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Compact human-readable duration: "42s", "5m", "3h 12m", "10d 4h"
+pub fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Total number of processes in a port map (a port may be held by several processes)
+pub fn process_count(processes: &HashMap<u16, Vec<ProcessInfo>>) -> usize {
+    processes.values().map(Vec::len).sum()
+}
+
+/// How many of the processes run inside a Docker container
+pub fn docker_process_count(processes: &HashMap<u16, Vec<ProcessInfo>>) -> usize {
+    processes.values().flatten().filter(|process_info| process_info.container_id.is_some()).count()
+}
+
+/// Names of the processes sharing a port, e.g. "nginx, envoy"
+pub fn process_names(processes: &[ProcessInfo]) -> String {
+    processes.iter().map(|process_info| process_info.name.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+/// The ports a tray menu lists when it has room for `max_ports` of them: the lowest first,
+/// plus how many didn't fit
+pub fn menu_ports(processes: &HashMap<u16, Vec<ProcessInfo>>, max_ports: usize) -> (Vec<u16>, usize) {
+    let mut ports: Vec<u16> = processes.keys().copied().collect();
+    ports.sort_unstable();
+    let hidden = ports.len().saturating_sub(max_ports);
+    ports.truncate(max_ports);
+    (ports, hidden)
+}
+
+/// Text of the disabled menu item standing in for the ports that didn't fit
+pub fn menu_overflow_label(hidden: usize) -> String {
+    format!("... and {} more (see console)", hidden)
+}
+
+/// A menu item text with its emoji prefix ("🔪 Kill All Processes"), or the plain text
+/// ("Kill All Processes") when `use_emoji` is off
+pub fn menu_label(emoji: &str, text: &str, use_emoji: bool) -> String {
+    if use_emoji {
+        format!("{} {}", emoji, text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Checkbox in front of a port in the "Kill All Except..." submenu
+pub fn spare_marker(spared: bool, use_emoji: bool) -> &'static str {
+    match (spared, use_emoji) {
+        (true, true) => "✅",
+        (false, true) => "⬜",
+        (true, false) => "[x]",
+        (false, false) => "[ ]",
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ProcessUpdate {
-    pub processes: HashMap<u16, ProcessInfo>,
+    /// Listening processes by port, several per port with SO_REUSEPORT or per address family
+    pub processes: HashMap<u16, Vec<ProcessInfo>>,
     pub count: usize,
+    /// What changed since the previous update
+    pub diff: ProcessDiff,
 }
 
 impl ProcessUpdate {
-    pub fn new(processes: HashMap<u16, ProcessInfo>) -> Self {
-        let count = processes.len();
-        Self { processes, count }
+    pub fn new(processes: HashMap<u16, Vec<ProcessInfo>>) -> Self {
+        Self::from_change(&HashMap::new(), processes)
+    }
+
+    /// Build an update whose diff is computed against the previous snapshot
+    pub fn from_change(previous: &HashMap<u16, Vec<ProcessInfo>>, processes: HashMap<u16, Vec<ProcessInfo>>) -> Self {
+        let count = process_count(&processes);
+        let diff = ProcessDiff::between(previous, &processes);
+        Self { processes, count, diff }
     }
 
     pub fn empty() -> Self {
         Self {
             processes: HashMap::new(),
             count: 0,
+            diff: ProcessDiff::default(),
+        }
+    }
+}
+
+/// Processes that started or stopped listening between two snapshots
+///
+/// A port whose owning process changed appears in both lists.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProcessDiff {
+    /// Processes that are newly listening on a port
+    pub added: Vec<ProcessInfo>,
+    /// Processes that stopped listening on a port
+    pub removed: Vec<ProcessInfo>,
+}
+
+impl ProcessDiff {
+    pub fn between(previous: &HashMap<u16, Vec<ProcessInfo>>, current: &HashMap<u16, Vec<ProcessInfo>>) -> Self {
+        Self {
+            added: Self::missing_from(current, previous),
+            removed: Self::missing_from(previous, current),
+        }
+    }
+
+    /// Processes in `from` whose (port, PID) isn't in `other`, sorted by port then PID
+    fn missing_from(from: &HashMap<u16, Vec<ProcessInfo>>, other: &HashMap<u16, Vec<ProcessInfo>>) -> Vec<ProcessInfo> {
+        let mut missing: Vec<ProcessInfo> = from
+            .iter()
+            .flat_map(|(port, infos)| {
+                infos.iter().filter(move |info| {
+                    other.get(port).is_none_or(|others| others.iter().all(|other| other.pid != info.pid))
+                })
+            })
+            .cloned()
+            .collect();
+        missing.sort_by_key(|info| (info.port, info.pid));
+        missing
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Human-readable lines such as "port 3000 now in use by node" / "port 5000 freed"
+    pub fn describe(&self) -> Vec<String> {
+        let freed = self
+            .removed
+            .iter()
+            .map(|info| format!("{} freed (was {}, PID {})", info.endpoint(), info.name, info.pid));
+        let opened = self
+            .added
+            .iter()
+            .map(|info| format!("{} now in use by {} (PID {})", info.endpoint(), info.name, info.pid));
+
+        freed.chain(opened).collect()
+    }
+}
+
+/// Default process count at which the status turns busy (orange)
+pub const DEFAULT_BUSY_THRESHOLD: usize = 1;
+/// Default process count at which the status turns crowded (red)
+pub const DEFAULT_CROWDED_THRESHOLD: usize = 4;
+
+/// How crowded the monitored ports are, used to pick icon and console colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusTier {
+    Idle,
+    Busy,
+    Crowded,
+}
+
+impl StatusTier {
+    pub fn from_count(count: usize, busy_threshold: usize, crowded_threshold: usize) -> Self {
+        if count >= crowded_threshold {
+            StatusTier::Crowded
+        } else if count >= busy_threshold {
+            StatusTier::Busy
+        } else {
+            StatusTier::Idle
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatusTier::Idle => "idle",
+            StatusTier::Busy => "busy",
+            StatusTier::Crowded => "crowded",
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct StatusBarInfo {
+    pub count: usize,
     pub text: String,
     pub tooltip: String,
+    pub tier: StatusTier,
+    /// How many of `count` run in Docker; the icon gets a container marker when non-zero
+    pub docker_count: usize,
 }
 
 impl StatusBarInfo {
     pub fn from_process_count(count: usize) -> Self {
+        Self::with_thresholds(count, DEFAULT_BUSY_THRESHOLD, DEFAULT_CROWDED_THRESHOLD)
+    }
+
+    pub fn with_thresholds(count: usize, busy_threshold: usize, crowded_threshold: usize) -> Self {
         let text = count.to_string(); // Just show the number
 
         let tooltip = if count == 0 {
@@ -47,6 +348,193 @@ impl StatusBarInfo {
             format!("{} development process(es) running", count)
         };
 
-        Self { text, tooltip }
+        let tier = StatusTier::from_count(count, busy_threshold, crowded_threshold);
+
+        Self { count, text, tooltip, tier, docker_count: 0 }
+    }
+
+    /// Split the tooltip into native and Docker counts when any process is containerized
+    pub fn with_docker_count(mut self, docker_count: usize) -> Self {
+        self.docker_count = docker_count.min(self.count);
+        if self.docker_count > 0 {
+            self.tooltip = format!("{} ({} native, {} docker)", self.tooltip, self.count - self.docker_count, self.docker_count);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_ports() {
+        let processes: HashMap<u16, Vec<ProcessInfo>> = [8080, 3000, 5173, 3001]
+            .into_iter()
+            .map(|port| (port, vec![ProcessInfo::fixture(port, port as i32, "node")]))
+            .collect();
+
+        assert_eq!(menu_ports(&processes, 2), (vec![3000, 3001], 2));
+        assert_eq!(menu_ports(&processes, 20), (vec![3000, 3001, 5173, 8080], 0));
+        assert_eq!(menu_overflow_label(2), "... and 2 more (see console)");
+        assert_eq!(menu_label("🔪", "Kill All Processes", true), "🔪 Kill All Processes");
+        assert_eq!(menu_label("🔪", "Kill All Processes", false), "Kill All Processes");
+        assert_eq!((spare_marker(true, false), spare_marker(false, false)), ("[x]", "[ ]"));
+
+        // The port items plus the overflow item never take more than one entry beyond the limit
+        for count in 0..30u16 {
+            let processes: HashMap<u16, Vec<ProcessInfo>> = (0..count).map(|i| (3000 + i, vec![ProcessInfo::fixture(3000 + i, i as i32, "node")])).collect();
+            for max_ports in 1..25 {
+                let (shown, hidden) = menu_ports(&processes, max_ports);
+                assert!(shown.len() <= max_ports);
+                assert_eq!(shown.len() + hidden, count as usize);
+                assert!(shown.len() + usize::from(hidden > 0) <= max_ports + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_added_and_removed() {
+        let previous = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node")]), (5000, vec![ProcessInfo::fixture(5000, 20, "python")])]);
+        let current = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node")]), (8080, vec![ProcessInfo::fixture(8080, 30, "java")])]);
+
+        let diff = ProcessDiff::between(&previous, &current);
+        assert_eq!(diff.added, vec![ProcessInfo::fixture(8080, 30, "java")]);
+        assert_eq!(diff.removed, vec![ProcessInfo::fixture(5000, 20, "python")]);
+        assert_eq!(
+            diff.describe(),
+            vec!["port 5000 freed (was python, PID 20)", "port 8080 now in use by java (PID 30)"]
+        );
+    }
+
+    #[test]
+    fn test_diff_pid_change_on_same_port() {
+        let previous = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node")])]);
+        let current = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 11, "node")])]);
+
+        let diff = ProcessDiff::between(&previous, &current);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_second_process_on_shared_port() {
+        let previous = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node")])]);
+        let current = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node"), ProcessInfo::fixture(3000, 11, "node")])]);
+
+        let diff = ProcessDiff::between(&previous, &current);
+        assert_eq!(diff.added, vec![ProcessInfo::fixture(3000, 11, "node")]);
+        assert!(diff.removed.is_empty());
+
+        let update = ProcessUpdate::from_change(&previous, current);
+        assert_eq!(update.count, 2);
+    }
+
+    #[test]
+    fn test_status_tier_thresholds() {
+        assert_eq!(StatusTier::from_count(0, 1, 4), StatusTier::Idle);
+        assert_eq!(StatusTier::from_count(1, 1, 4), StatusTier::Busy);
+        assert_eq!(StatusTier::from_count(3, 1, 4), StatusTier::Busy);
+        assert_eq!(StatusTier::from_count(4, 1, 4), StatusTier::Crowded);
+        assert_eq!(StatusBarInfo::from_process_count(2).tier, StatusTier::Busy);
+    }
+
+    #[test]
+    fn test_status_bar_info_from_process_count() {
+        // (count, text, tooltip, tier) around the default thresholds of 1 (busy) and 4 (crowded)
+        let cases = [
+            (0, "0", "No development processes running", StatusTier::Idle),
+            (1, "1", "1 development process(es) running", StatusTier::Busy),
+            (3, "3", "3 development process(es) running", StatusTier::Busy),
+            (4, "4", "4 development process(es) running", StatusTier::Crowded),
+            (25, "25", "25 development process(es) running", StatusTier::Crowded),
+            (1000, "1000", "1000 development process(es) running", StatusTier::Crowded),
+        ];
+        for (count, text, tooltip, tier) in cases {
+            let status_info = StatusBarInfo::from_process_count(count);
+            assert_eq!(status_info.count, count);
+            assert_eq!((status_info.text.as_str(), status_info.tooltip.as_str(), status_info.tier), (text, tooltip, tier), "count {}", count);
+            assert_eq!(status_info.docker_count, 0);
+        }
+
+        // The count is shown in full rather than abbreviated or wrapped
+        let huge = StatusBarInfo::from_process_count(usize::MAX);
+        assert_eq!(huge.text, usize::MAX.to_string());
+        assert_eq!(huge.tier, StatusTier::Crowded);
+        // A Docker count larger than the total is clamped instead of underflowing the native count
+        assert_eq!(StatusBarInfo::from_process_count(2).with_docker_count(5).tooltip, "2 development process(es) running (0 native, 2 docker)");
+    }
+
+    #[test]
+    fn test_status_tier_custom_thresholds() {
+        // Crowded wins when the thresholds overlap, and a zero busy threshold is never idle
+        assert_eq!(StatusBarInfo::with_thresholds(2, 3, 2).tier, StatusTier::Crowded);
+        assert_eq!(StatusBarInfo::with_thresholds(0, 0, 5).tier, StatusTier::Busy);
+        assert_eq!(StatusBarInfo::with_thresholds(9, 10, 20).tier, StatusTier::Idle);
+        assert_eq!(StatusBarInfo::with_thresholds(usize::MAX, 1, usize::MAX).tier, StatusTier::Crowded);
+        assert_eq!(StatusBarInfo::with_thresholds(0, 1, 4).tooltip, "No development processes running");
+    }
+
+    #[test]
+    fn test_docker_counts_in_tooltip() {
+        let mut container = ProcessInfo::fixture(8080, 12, "nginx");
+        container.container_id = Some("abc123".to_string());
+        container.container_name = Some("web".to_string());
+        let processes: HashMap<u16, Vec<ProcessInfo>> = HashMap::from([
+            (3000, vec![ProcessInfo::fixture(3000, 10, "node")]),
+            (5173, vec![ProcessInfo::fixture(5173, 11, "vite")]),
+            (8080, vec![container]),
+        ]);
+
+        let status_info = StatusBarInfo::from_process_count(process_count(&processes)).with_docker_count(docker_process_count(&processes));
+        assert_eq!(status_info.docker_count, 1);
+        assert_eq!(status_info.tooltip, "3 development process(es) running (2 native, 1 docker)");
+
+        let status_info = StatusBarInfo::from_process_count(2).with_docker_count(0);
+        assert_eq!(status_info.tooltip, "2 development process(es) running");
+    }
+
+    #[test]
+    fn test_address_families() {
+        assert_eq!(AddressFamily::from_lsof_type("IPv4"), Some(AddressFamily::Ipv4));
+        assert_eq!(AddressFamily::from_lsof_type("IPv6"), Some(AddressFamily::Ipv6));
+        assert_eq!(AddressFamily::from_lsof_type("unix"), None);
+
+        let mut ipv6 = ProcessInfo::fixture(3000, 10, "node");
+        ipv6.address_families = vec![AddressFamily::Ipv6];
+        assert_eq!(ipv6.family_label().as_deref(), Some("IPv6"));
+        assert_eq!(ProcessInfo::fixture(3000, 10, "node").family_label(), None);
+
+        let mut ipv4 = ProcessInfo::fixture(3000, 10, "node");
+        ipv4.address_families = vec![AddressFamily::Ipv4];
+        ipv6.merge_families(&ipv4);
+        ipv6.merge_families(&ipv4);
+        assert_eq!(ipv6.family_label().as_deref(), Some("IPv4+IPv6"));
+        assert_eq!(serde_json::to_value(&ipv6).unwrap()["address_families"], serde_json::json!(["ipv4", "ipv6"]));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(2048), "2.0 KB");
+        assert_eq!(format_bytes(150 * 1024 * 1024), "150.0 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(3)), "3s");
+        assert_eq!(format_uptime(Duration::from_secs(5 * 60 + 7)), "5m");
+        assert_eq!(format_uptime(Duration::from_secs(3 * 3_600 + 12 * 60)), "3h 12m");
+        assert_eq!(format_uptime(Duration::from_secs(10 * 86_400 + 4 * 3_600)), "10d 4h");
+    }
+
+    #[test]
+    fn test_update_from_unchanged_snapshot_has_empty_diff() {
+        let snapshot = HashMap::from([(3000, vec![ProcessInfo::fixture(3000, 10, "node")])]);
+        let update = ProcessUpdate::from_change(&snapshot, snapshot.clone());
+
+        assert!(update.diff.is_empty());
+        assert_eq!(update.count, 1);
     }
 }
@@ -0,0 +1,187 @@
+use crate::config::AppConfig;
+use crate::error::PortKillError;
+use log::{info, warn};
+use serde::Serialize;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// How long a webhook delivery may take before it is abandoned
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What happened to one process during a kill operation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KillOutcome {
+    pub port: Option<u16>,
+    pub name: Option<String>,
+    pub pid: i32,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl KillOutcome {
+    pub fn new(pid: i32, port: Option<u16>, name: Option<&str>, result: &Result<(), PortKillError>) -> Self {
+        Self {
+            port,
+            name: name.map(str::to_string),
+            pid,
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+
+    /// "node (PID 1234) on port 3000"
+    fn describe(&self) -> String {
+        let name = self.name.as_deref().unwrap_or("process");
+        match self.port {
+            Some(port) => format!("{} (PID {}) on port {}", name, self.pid, port),
+            None => format!("{} (PID {})", name, self.pid),
+        }
+    }
+}
+
+/// JSON body POSTed to the webhook after a kill operation.
+///
+/// `text` (Slack) and `content` (Discord) carry the same human-readable summary;
+/// the remaining fields are for other receivers.
+#[derive(Debug, Clone, Serialize)]
+pub struct KillEvent {
+    pub text: String,
+    pub content: String,
+    pub killed: usize,
+    pub failed: usize,
+    pub processes: Vec<KillOutcome>,
+}
+
+impl KillEvent {
+    pub fn new(processes: Vec<KillOutcome>) -> Self {
+        let (succeeded, failed): (Vec<&KillOutcome>, Vec<&KillOutcome>) = processes.iter().partition(|outcome| outcome.success);
+
+        let mut summary = format!("🔪 port-kill killed {} process(es)", succeeded.len());
+        if !succeeded.is_empty() {
+            summary.push_str(&format!(": {}", succeeded.iter().map(|outcome| outcome.describe()).collect::<Vec<_>>().join(", ")));
+        }
+        if !failed.is_empty() {
+            summary.push_str(&format!(
+                "\n⚠️ Failed to kill {} process(es): {}",
+                failed.len(),
+                failed.iter().map(|outcome| outcome.describe()).collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        Self {
+            text: summary.clone(),
+            content: summary,
+            killed: succeeded.len(),
+            failed: failed.len(),
+            processes,
+        }
+    }
+}
+
+/// Posts kill events to the configured `webhook_url` (Slack, Discord or any JSON receiver)
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    url: String,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string() }
+    }
+
+    /// The webhook from `[app] webhook_url`, if one is set
+    pub fn from_config(app: &AppConfig) -> Option<Self> {
+        app.webhook_url.as_deref().map(Self::new)
+    }
+
+    /// Send the outcomes of one kill operation in the background.
+    ///
+    /// Nothing is sent when nothing was attempted. Delivery failures are logged, never
+    /// returned; one-shot commands can join the handle so the process doesn't exit first.
+    pub fn notify(&self, outcomes: Vec<KillOutcome>) -> Option<JoinHandle<()>> {
+        if outcomes.is_empty() {
+            return None;
+        }
+
+        let url = self.url.clone();
+        let event = KillEvent::new(outcomes);
+        let spawned = std::thread::Builder::new()
+            .name("webhook".to_string())
+            .spawn(move || {
+                let agent = ureq::AgentBuilder::new().timeout(WEBHOOK_TIMEOUT).build();
+                match agent.post(&url).send_json(&event) {
+                    Ok(_) => info!("Sent kill notification to webhook ({} killed, {} failed)", event.killed, event.failed),
+                    Err(e) => warn!("Failed to deliver kill notification to webhook: {}", e),
+                }
+            });
+
+        match spawned {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                warn!("Failed to start webhook delivery: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn outcomes() -> Vec<KillOutcome> {
+        vec![
+            KillOutcome::new(10, Some(3000), Some("node"), &Ok(())),
+            KillOutcome::new(20, None, None, &Err(PortKillError::KillFailed { pid: 20, reason: "Operation not permitted".to_string() })),
+        ]
+    }
+
+    #[test]
+    fn test_kill_event() {
+        let event = KillEvent::new(outcomes());
+
+        assert_eq!((event.killed, event.failed), (1, 1));
+        assert_eq!(event.text, event.content);
+        assert!(event.text.starts_with("🔪 port-kill killed 1 process(es): node (PID 10) on port 3000"));
+        assert!(event.text.contains("Failed to kill 1 process(es): process (PID 20)"));
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["processes"][0]["port"], 3000);
+        assert_eq!(json["processes"][1]["success"], false);
+        assert_eq!(json["processes"][1]["error"], "Operation not permitted");
+    }
+
+    #[test]
+    fn test_notify_posts_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let handle = Webhook::new(&url).notify(outcomes()).expect("delivery should start");
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        while !String::from_utf8_lossy(&request).contains("\"processes\"") {
+            let read = stream.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        handle.join().unwrap();
+
+        let request = String::from_utf8_lossy(&request);
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        assert!(request.to_lowercase().contains("content-type: application/json"));
+        assert!(request.contains("\"killed\":1"));
+    }
+
+    #[test]
+    fn test_notify_skips_empty_operations() {
+        assert!(Webhook::new("http://127.0.0.1:9/hook").notify(Vec::new()).is_none());
+    }
+}
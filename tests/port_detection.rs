@@ -0,0 +1,154 @@
+//! End-to-end port detection against a real listening socket.
+//!
+//! Each test binds a `TcpListener` on an ephemeral port in this process and checks that
+//! discovery finds this process on it, which exercises the lsof arguments and output
+//! parsing together. Discovery on Windows goes through netstat, so these are Unix-only.
+#![cfg(not(target_os = "windows"))]
+
+use clap::Parser;
+use port_kill::cli::Args;
+use port_kill::command_runner::LocalRunner;
+use port_kill::port_scanner::{LsofScanner, PortScanner};
+use port_kill::process_monitor::get_processes_on_ports;
+use port_kill::types::AddressFamily;
+use std::net::TcpListener;
+use std::sync::Arc;
+
+fn scanner() -> LsofScanner {
+    LsofScanner::new(Arc::new(LocalRunner))
+}
+
+/// lsof isn't installed everywhere (e.g. minimal CI containers); skip rather than fail there
+fn lsof_available() -> bool {
+    match scanner().check_available() {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("Skipping port detection test: {:#}", e);
+            false
+        }
+    }
+}
+
+fn own_pid() -> i32 {
+    std::process::id() as i32
+}
+
+#[test]
+fn test_scan_finds_own_listener() {
+    if !lsof_available() {
+        return;
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let found = scanner().scan(&[port]).unwrap();
+    let own = found
+        .iter()
+        .find(|process_info| process_info.pid == own_pid())
+        .unwrap_or_else(|| panic!("PID {} not found on port {} in {:?}", own_pid(), port, found));
+
+    assert_eq!(own.port, port);
+    assert_eq!(own.bind_address.as_deref(), Some("127.0.0.1"));
+    assert_eq!(own.address_families, vec![AddressFamily::Ipv4]);
+}
+
+#[test]
+fn test_scan_finds_own_ipv6_listener() {
+    if !lsof_available() {
+        return;
+    }
+    // Hosts without IPv6 can't bind [::1]; there is nothing to detect there
+    let Ok(listener) = TcpListener::bind("[::1]:0") else {
+        return;
+    };
+    let port = listener.local_addr().unwrap().port();
+
+    let found = scanner().scan(&[port]).unwrap();
+    let own = found.iter().find(|process_info| process_info.pid == own_pid()).expect("own IPv6 listener");
+
+    assert_eq!(own.bind_address.as_deref(), Some("[::1]"));
+    assert_eq!(own.address_families, vec![AddressFamily::Ipv6]);
+}
+
+#[test]
+fn test_discover_all_includes_own_listener() {
+    if !lsof_available() {
+        return;
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let found = scanner().scan(&[]).unwrap();
+    assert!(found.iter().any(|process_info| process_info.pid == own_pid() && process_info.port == port));
+}
+
+#[test]
+fn test_get_processes_on_ports_finds_own_listener() {
+    if !lsof_available() {
+        return;
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let args = Args::parse_from(["port-kill", "--ports", &port.to_string()]);
+
+    let (count, processes) = get_processes_on_ports(&args.get_ports_to_monitor(), &args);
+
+    assert_eq!(count, 1);
+    assert_eq!(processes[&port].iter().map(|process_info| process_info.pid).collect::<Vec<_>>(), vec![own_pid()]);
+
+    // Ignoring the port must hide it from the same path
+    let ignored = Args::parse_from(["port-kill", "--ports", &port.to_string(), "--ignore-ports", &port.to_string()]);
+    let (count, _) = get_processes_on_ports(&ignored.get_ports_to_monitor(), &ignored);
+    assert_eq!(count, 0);
+}
+
+/// More scattered ports than lsof accepts in one `-i` list around `port`, which is included
+fn sparse_ports_around(port: u16) -> Vec<u16> {
+    let mut ports: Vec<u16> = (1..=500u16).map(|i| port.wrapping_add(i * 3).max(1)).collect();
+    ports.push(port);
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+#[test]
+fn test_scan_finds_own_listener_in_large_sparse_set() {
+    if !lsof_available() {
+        return;
+    }
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let ports = sparse_ports_around(port);
+
+    let found = scanner().scan(&ports).unwrap();
+    assert!(found.iter().any(|process_info| process_info.pid == own_pid() && process_info.port == port));
+    assert!(found.iter().all(|process_info| ports.contains(&process_info.port)));
+}
+
+/// Compare the in-memory filtered full scan with lsof filtering 100 ports itself.
+/// Run with `cargo test --test port_detection -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_large_sparse_port_set() {
+    use std::time::Instant;
+
+    if !lsof_available() {
+        return;
+    }
+    const RUNS: u32 = 20;
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let ports = sparse_ports_around(listener.local_addr().unwrap().port());
+
+    let started = Instant::now();
+    for _ in 0..RUNS {
+        scanner().scan(&ports).unwrap();
+    }
+    println!("{} sparse ports, full scan filtered in memory: {:?} per scan", ports.len(), started.elapsed() / RUNS);
+
+    // lsof itself takes at most 100 addresses per -i option
+    let started = Instant::now();
+    for _ in 0..RUNS {
+        scanner().scan(&ports[..100]).unwrap();
+    }
+    println!("100 sparse ports, filtered by lsof: {:?} per scan", started.elapsed() / RUNS);
+}